@@ -0,0 +1,120 @@
+//! Benchmarks of the daylight calculation; run with `cargo bench`
+
+#[macro_use]
+extern crate criterion;
+extern crate daylight;
+extern crate time;
+
+use criterion::{black_box, Criterion};
+use daylight::{calculate_daylight, calculate_daylight_batch, calculate_daylight_checked,
+               calculate_daylight_with, daylength_by_day, daylight_grid, DaylightIter,
+               DaylightOptions, SolarCalculator};
+use time::{at_utc, Timespec};
+
+/// 2015-01-01 12:00 UTC
+const START: i64 = 1420113600;
+
+fn single_call(c: &mut Criterion) {
+    let tm = at_utc(Timespec::new(START, 0));
+    c.bench_function("single call", |b| {
+        b.iter(|| calculate_daylight(black_box(tm), black_box(52.22), black_box(5.97)))
+    });
+
+    let options = DaylightOptions::default().refined(true);
+    c.bench_function("single call refined", |b| {
+        b.iter(|| {
+            calculate_daylight_with(black_box(tm), black_box(52.22), black_box(5.97), &options)
+        })
+    });
+}
+
+fn calculator(c: &mut Criterion) {
+    // repeated queries for the same date, like a server answering for a site
+    let tm = at_utc(Timespec::new(START, 0));
+    c.bench_function("repeated free function", |b| {
+        b.iter(|| calculate_daylight(black_box(tm), 52.22, 5.97))
+    });
+    let calculator = SolarCalculator::new(52.22, 5.97);
+    c.bench_function("repeated calculator", |b| b.iter(|| calculator.daylight(black_box(tm))));
+
+    // the current time, which changes every call but stays on the same day
+    let mut second = 0;
+    c.bench_function("current time calculator", |b| {
+        b.iter(|| {
+            second = (second + 1) % 86400;
+            calculator.daylight(at_utc(Timespec::new(black_box(START - 43200 + second), 0)))
+        })
+    });
+
+    // a new date every call, which only saves the latitude
+    c.bench_function("365 days calculator", |b| {
+        b.iter(|| {
+            for day in 0..365 {
+                let tm = at_utc(Timespec::new(START + day * 86400, 0));
+                black_box(calculator.daylight(tm));
+            }
+        })
+    });
+}
+
+fn year(c: &mut Criterion) {
+    c.bench_function("365 days", |b| {
+        b.iter(|| {
+            for day in 0..365 {
+                let tm = at_utc(Timespec::new(START + day * 86400, 0));
+                black_box(calculate_daylight(tm, 52.22, 5.97));
+            }
+        })
+    });
+
+    let tm = at_utc(Timespec::new(START, 0));
+    c.bench_function("365 days iterator", |b| {
+        b.iter(|| {
+            for item in DaylightIter::new(tm, 52.22, 5.97).take(365) {
+                black_box(item);
+            }
+        })
+    });
+
+    c.bench_function("365 day lengths", |b| {
+        b.iter(|| black_box(daylength_by_day(2015, black_box(52.22), 5.97)))
+    });
+}
+
+fn batch(c: &mut Criterion) {
+    // 10000 locations spread over the globe, like weather stations
+    let tm = at_utc(Timespec::new(START, 0));
+    let coords: Vec<(f64, f64)> = (0..10000)
+        .map(|i| ((i % 170) as f64 - 85.0 + 0.37, (i % 359) as f64 - 179.0 + 0.61))
+        .collect();
+    c.bench_function("10000 locations loop", |b| {
+        b.iter(|| {
+            coords
+                .iter()
+                .map(|&(lat, long)| calculate_daylight_checked(tm, lat, long))
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("10000 locations batch", |b| {
+        b.iter(|| calculate_daylight_batch(black_box(tm), &coords))
+    });
+}
+
+fn grid(c: &mut Criterion) {
+    let tm = at_utc(Timespec::new(START, 0));
+    c.bench_function("1 degree grid", |b| {
+        b.iter(|| {
+            for lat in -90..91 {
+                for long in -180..180 {
+                    black_box(calculate_daylight(tm, lat as f64, long as f64));
+                }
+            }
+        })
+    });
+    c.bench_function("1 degree grid kernel", |b| {
+        b.iter(|| daylight_grid(black_box(tm), -90.0..=90.0, -180.0..=179.0, 1.0))
+    });
+}
+
+criterion_group!(benches, single_call, calculator, year, batch, grid);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Generates the C header of the `ffi` module with cbindgen.
+
+#[cfg(feature = "ffi")]
+extern crate cbindgen;
+
+/// Generates the header into `OUT_DIR`, where the test `header_up_to_date`
+/// compares it with the checked-in `include/daylight.h`; the build only
+/// writes to the source tree when `DAYLIGHT_UPDATE_HEADER` is set
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(Path::new(&crate_dir).join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+    let header = Path::new(&out_dir).join("daylight.h");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("unable to generate the C header")
+        .write_to_file(&header);
+    if env::var_os("DAYLIGHT_UPDATE_HEADER").is_some() {
+        fs::copy(&header, Path::new(&crate_dir).join("include").join("daylight.h"))
+            .expect("unable to update include/daylight.h");
+    }
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=DAYLIGHT_UPDATE_HEADER");
+}
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
@@ -1,62 +1,122 @@
-extern crate time;
-extern crate daylight;
-extern crate hyper;
-extern crate rustc_serialize;
-
-use time::{at, now};
-use daylight::calculate_daylight;
+//! Prints today's daylight for the location of the public IP address; run
+//! with `cargo run --example geoip --features geoip-example`.
+//!
+//! The location is looked up at ip-api.com, unless `--lat` and `--lon` are
+//! passed; like when offline.
 
-use std::io::Read;
-
-use hyper::Client;
-use hyper::header::Connection;
-use hyper::header::ConnectionOption;
-
-use rustc_serialize::json::Json;
+extern crate daylight;
+#[macro_use]
+extern crate serde;
+extern crate serde_json;
+extern crate time;
+extern crate ureq;
 
-fn main() {
-    // create client
-    let client = Client::new();
+use std::env;
+use std::process;
+use std::time::Duration;
 
-    // create request
-    let mut res = client.get("http://freegeoip.net/json/")
-        // set a header
-        .header(Connection(vec![ConnectionOption::Close]))
-        // let 'er go!
-        .send().unwrap();
+use daylight::{Coordinates, Location, next_event};
 
-    // Read the response.
-    let mut body = String::new();
-    res.read_to_string(&mut body).unwrap();
+/// Fields of the response of ip-api.com
+#[derive(Deserialize)]
+struct GeoIp {
+    status: String,
+    city: String,
+    lat: f64,
+    lon: f64,
+    timezone: String,
+    /// Seconds the local clock is ahead of UTC, including daylight saving
+    offset: i32,
+}
 
-    let data = Json::from_str(&body).unwrap();
+const GEOIP_URL: &str = "http://ip-api.com/json/?fields=status,city,lat,lon,timezone,offset";
 
-    let obj = data.as_object().unwrap();
+fn lookup() -> Result<GeoIp, String> {
+    let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(5)).build();
+    let body = agent
+        .get(GEOIP_URL)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let geoip: GeoIp = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    if geoip.status != "success" {
+        return Err(format!("lookup status {:?}", geoip.status));
+    }
+    Ok(geoip)
+}
 
-    let lat = obj.get("latitude").unwrap().as_f64().unwrap();
-    let long = obj.get("longitude").unwrap().as_f64().unwrap();
-    let city = obj.get("city").unwrap().as_string().unwrap();
-    let timezone = obj.get("time_zone").unwrap().as_string().unwrap();
-    let today = now();
+/// Returns the value of `--lat` and `--lon`, if given
+fn coordinate_flags() -> Option<(f64, f64)> {
+    let args: Vec<String> = env::args().collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+            .map(|value| {
+                value.parse::<f64>().unwrap_or_else(|_| {
+                    eprintln!("invalid {} {:?}", name, value);
+                    process::exit(2);
+                })
+            })
+    };
+    match (flag("--lat"), flag("--lon")) {
+        (Some(lat), Some(lon)) => Some((lat, lon)),
+        (None, None) => None,
+        _ => {
+            eprintln!("--lat and --lon go together");
+            process::exit(2);
+        }
+    }
+}
 
-    let daylight = calculate_daylight(today, lat, long);
+fn main() {
+    let location = match coordinate_flags() {
+        Some((lat, lon)) => {
+            Coordinates::try_new(lat, lon).map(|coordinates| Location::new("given", coordinates))
+        }
+        None => {
+            let geoip = lookup().unwrap_or_else(|e| {
+                eprintln!("location lookup failed ({}); pass --lat and --lon instead", e);
+                process::exit(1);
+            });
+            println!("Timezone:             {}", geoip.timezone);
+            Coordinates::try_new(geoip.lat, geoip.lon).map(|coordinates| {
+                Location::new(&geoip.city, coordinates).utc_offset(geoip.offset)
+            })
+        }
+    };
+    let location = location.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
 
-    let daylength_total_minutes = daylight.daylength.num_minutes();
-    let daylength_hours = daylength_total_minutes / 60;
-    let daylength_minutes = daylength_total_minutes % 60;
+    // the civil date at the location
+    let now = time::get_time();
+    let offset = time::Duration::seconds(location.get_utc_offset() as i64);
+    let daylight = location.daylight(time::at_utc(now + offset));
+    let local = daylight.to_local(location.get_utc_offset());
+    let clock = |tm: time::Tm| tm.strftime("%H:%M:%S").unwrap().to_string();
+    let daylength_minutes = daylight.daylength.num_minutes();
 
     println!("Sunrise and set times based on IP");
     println!("=================================");
+    println!("Date:                 {}", local.noon.strftime("%Y-%m-%d").unwrap());
+    println!("UTC offset:           {} s", location.get_utc_offset());
+    println!("Location:             {} ({})",
+             location.get_coordinates().to_dms_string(),
+             location.get_name());
+    println!("Declination:          {:.2}°", daylight.declination);
+    println!("Daylength:            {}:{:02}", daylength_minutes / 60, daylength_minutes % 60);
+    println!("Twilight AM:          {}", clock(local.twilight_morning));
+    println!("Sunrise:              {}", clock(local.sunrise));
+    println!("Noon:                 {}", clock(local.noon));
+    println!("Sunset:               {}", clock(local.sunset));
+    println!("Twilight PM:          {}", clock(local.twilight_evening));
+    println!("Sun altitude:         {:.2}°", daylight.sun_altitude);
 
-    println!("Date:                 {}", today.asctime());
-    println!("Timezone:             {}", timezone);
-    println!("Latitude/Longitude:   {}/{} ({})", lat, long, city);
-    println!("Declination:          {}°", daylight.declination);
-    println!("Daylength:            {}:{}", daylength_hours, daylength_minutes);
-    println!("Twilight AM:          {}", at(daylight.twilight_morning).asctime());
-    println!("Sunrise:              {}", at(daylight.sunrise).asctime());
-    println!("Noon:                 {}", at(daylight.noon).asctime());
-    println!("Sunset:               {}", at(daylight.sunset).asctime());
-    println!("Twilight PM:          {}", at(daylight.twilight_evening).asctime());
-    println!("Sun altitude:         {}°", daylight.sun_altitude)
+    let (latitude, longitude) = location.get_coordinates().into();
+    let (kind, at) = next_event(now, latitude, longitude);
+    println!("Now:                  {:?}", daylight.part_of_day(now));
+    println!("Next:                 {:?} at {}", kind, clock(time::at_utc(at + offset)));
 }
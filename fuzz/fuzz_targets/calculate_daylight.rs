@@ -0,0 +1,11 @@
+//! `calculate_daylight_checked` on any input, and `calculate_daylight` on
+//! valid input (see `daylight_fuzz::check_daylight`).
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate daylight_fuzz;
+
+fuzz_target!(|data: &[u8]| {
+    daylight_fuzz::check_daylight(&daylight_fuzz::Input::decode(data));
+});
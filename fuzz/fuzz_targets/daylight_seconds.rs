@@ -0,0 +1,11 @@
+//! `calculate_daylight_seconds` and `calculate_daylight_f32` on valid input
+//! (see `daylight_fuzz::check_seconds`).
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate daylight_fuzz;
+
+fuzz_target!(|data: &[u8]| {
+    daylight_fuzz::check_seconds(&daylight_fuzz::Input::decode(data));
+});
@@ -0,0 +1,230 @@
+//! Decoding of the fuzz input and the invariants of the calculation, shared
+//! by the fuzz targets; run with `cargo fuzz run calculate_daylight` from
+//! the `fuzz` directory (needs cargo-fuzz and a nightly toolchain).
+//!
+//! The input is the unix seconds (`i64`), the latitude and the longitude
+//! (`f64`), each little-endian, 24 bytes in all; shorter input is padded
+//! with zeros. `corpus/` holds the interesting seeds of `SEEDS`.
+
+extern crate daylight;
+extern crate time;
+
+use daylight::{calculate_daylight, calculate_daylight_checked, calculate_daylight_f32,
+               calculate_daylight_seconds, Daylight, DaylightSeconds};
+use time::{at_utc, Timespec, Tm};
+
+/// First and last second of the supported years -9999 to 9999
+const FIRST_SEC: i64 = -377_705_116_800;
+const LAST_SEC: i64 = 253_402_300_799;
+/// Longitude beyond which the checked calculation rejects the input
+const MAX_LONGITUDE: f64 = 360.0 * 366.0;
+/// Margin in seconds around the UTC date of the input within which every
+/// event falls, for a longitude within -180 to 180 degrees
+const ENVELOPE: i64 = 86_400;
+/// 2000-01-01 12:00 UTC in seconds since 1970
+const Y2000: i64 = 946_728_000;
+/// Seconds in a mean Gregorian year
+const SECS_IN_YEAR: i64 = 31_556_952;
+
+/// Seconds since 1970, latitude and longitude of a case
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Input {
+    pub secs: i64,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Interesting cases, stored in `corpus/` by name
+pub const SEEDS: &[(&str, Input)] = &[
+    ("apeldoorn", Input { secs: 1_427_457_600, latitude: 52.22, longitude: 5.97 }),
+    ("north_pole", Input { secs: 1_434_888_000, latitude: 90.0, longitude: 0.0 }),
+    ("south_pole", Input { secs: 1_434_888_000, latitude: -90.0, longitude: 0.0 }),
+    ("polar_night", Input { secs: 1_450_699_200, latitude: 78.22, longitude: 15.65 }),
+    ("date_line_east", Input { secs: 1_427_457_600, latitude: -17.7, longitude: 180.0 }),
+    ("date_line_west", Input { secs: 1_427_457_600, latitude: 51.9, longitude: -180.0 }),
+    ("epoch", Input { secs: 0, latitude: 0.0, longitude: 0.0 }),
+    ("before_epoch", Input { secs: -1, latitude: -33.87, longitude: 151.21 }),
+    ("year_2038", Input { secs: 2_147_483_647, latitude: 40.71, longitude: -74.01 }),
+    ("after_2038", Input { secs: 2_147_483_648, latitude: 40.71, longitude: -74.01 }),
+    ("first_year", Input { secs: FIRST_SEC, latitude: 30.0, longitude: 30.0 }),
+    ("last_second", Input { secs: LAST_SEC, latitude: -30.0, longitude: -30.0 }),
+    ("beyond_range", Input { secs: LAST_SEC + 1, latitude: 0.0, longitude: 0.0 }),
+    ("i64_max", Input { secs: i64::MAX, latitude: 0.0, longitude: 0.0 }),
+    ("i64_min", Input { secs: i64::MIN, latitude: 0.0, longitude: 0.0 }),
+    ("latitude_beyond_pole", Input { secs: 0, latitude: 90.000_001, longitude: 0.0 }),
+    ("latitude_nan", Input { secs: 0, latitude: f64::NAN, longitude: 0.0 }),
+    ("longitude_infinite", Input { secs: 0, latitude: 0.0, longitude: f64::INFINITY }),
+    ("longitude_huge", Input { secs: 0, latitude: 0.0, longitude: 1e300 }),
+    ("negative_zero", Input { secs: 0, latitude: -0.0, longitude: -0.0 }),
+];
+
+impl Input {
+    /// Decodes the input from the fuzzer
+    pub fn decode(data: &[u8]) -> Input {
+        let mut bytes = [0u8; 24];
+        let length = data.len().min(24);
+        bytes[..length].copy_from_slice(&data[..length]);
+        let mut word = [0u8; 8];
+        let mut next = |index: usize| {
+            word.copy_from_slice(&bytes[index * 8..index * 8 + 8]);
+            u64::from_le_bytes(word)
+        };
+        Input {
+            secs: next(0) as i64,
+            latitude: f64::from_bits(next(1)),
+            longitude: f64::from_bits(next(2)),
+        }
+    }
+
+    /// Encodes the input, like the files of the corpus
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(24);
+        bytes.extend_from_slice(&self.secs.to_le_bytes());
+        bytes.extend_from_slice(&self.latitude.to_bits().to_le_bytes());
+        bytes.extend_from_slice(&self.longitude.to_bits().to_le_bytes());
+        bytes
+    }
+
+    /// Returns whether the instant is within the supported years
+    fn in_range(&self) -> bool {
+        (FIRST_SEC..=LAST_SEC).contains(&self.secs)
+    }
+
+    /// Returns whether the calculation must succeed and meet the invariants:
+    /// a supported instant, a latitude within -90 to 90 degrees and a
+    /// longitude within -180 to 180 degrees (the checked calculation accepts
+    /// a longitude up to a year of turns, but then the events leave the date)
+    pub fn is_valid(&self) -> bool {
+        self.in_range() && (-90.0..=90.0).contains(&self.latitude) &&
+        (-180.0..=180.0).contains(&self.longitude)
+    }
+
+    /// Returns the date of the input; outside of the supported years, where
+    /// `time` can not convert the instant, January 1 of about the year
+    fn date(&self) -> Tm {
+        if self.in_range() {
+            return at_utc(Timespec::new(self.secs, 0));
+        }
+        let years = self.secs / SECS_IN_YEAR + 70;
+        // the estimate may still fall within the range near its ends
+        let years = if self.secs > LAST_SEC {
+            years.max(10_000 - 1900)
+        } else {
+            years.min(-10_000 - 1900)
+        };
+        Tm {
+            tm_year: years.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            ..at_utc(Timespec::new(0, 0))
+        }
+    }
+
+    /// Returns the envelope of the events, around the UTC date of the input
+    fn envelope(&self) -> (i64, i64) {
+        let midnight = self.secs - self.secs.rem_euclid(86_400);
+        (midnight - ENVELOPE, midnight + 86_400 + ENVELOPE)
+    }
+}
+
+/// Asserts the invariants of the events in seconds since 1970
+fn check_events(input: &Input, events: [i64; 5], daylength: i64, declination: f64) {
+    for pair in events.windows(2) {
+        assert!(pair[0] <= pair[1], "unordered events {:?} for {:?}", events, input);
+    }
+    let (start, end) = input.envelope();
+    for &event in &events {
+        assert!((start..=end).contains(&event),
+                "event {} outside of {}..{} for {:?}",
+                event,
+                start,
+                end,
+                input);
+    }
+    assert!((0..=86_400).contains(&daylength), "day length {} for {:?}", daylength, input);
+    // the obliquity of the ecliptic is linear in the days since 2000
+    let days = (input.secs - Y2000).abs() as f64 / 86_400.0;
+    let obliquity = 23.5 + 0.000_000_4 * days;
+    assert!(declination.abs() < obliquity, "declination {} for {:?}", declination, input);
+}
+
+fn check_calculated(input: &Input, daylight: &Daylight) {
+    let events = [daylight.twilight_morning.sec,
+                  daylight.sunrise.sec,
+                  daylight.noon.sec,
+                  daylight.sunset.sec,
+                  daylight.twilight_evening.sec];
+    check_events(input, events, daylight.daylength.num_seconds(), daylight.declination);
+}
+
+/// Checks `calculate_daylight_checked` on any input: an error exactly for
+/// invalid input (apart from a longitude somewhat beyond 180 degrees, which
+/// it accepts), never a panic; and `calculate_daylight` on valid input
+pub fn check_daylight(input: &Input) {
+    let date = input.date();
+    let accepted = input.in_range() && (-90.0..=90.0).contains(&input.latitude) &&
+                   input.longitude.abs() <= MAX_LONGITUDE;
+    match calculate_daylight_checked(date, input.latitude, input.longitude) {
+        Ok(daylight) => {
+            assert!(accepted, "accepted {:?}", input);
+            if input.is_valid() {
+                check_calculated(input, &daylight);
+                let unchecked = calculate_daylight(date, input.latitude, input.longitude);
+                assert_eq!(unchecked, daylight, "{:?}", input);
+            }
+        }
+        Err(error) => assert!(!accepted, "{} for {:?}", error, input),
+    }
+}
+
+/// Checks `calculate_daylight_seconds` and `calculate_daylight_f32` (which
+/// do not validate their input) on valid input
+pub fn check_seconds(input: &Input) {
+    if !input.is_valid() {
+        return;
+    }
+    let daylight: DaylightSeconds =
+        calculate_daylight_seconds(input.secs, input.latitude, input.longitude);
+    let events = [daylight.twilight_morning,
+                  daylight.sunrise,
+                  daylight.noon,
+                  daylight.sunset,
+                  daylight.twilight_evening];
+    check_events(input, events, daylight.daylength, daylight.declination);
+
+    let single = calculate_daylight_f32(input.secs, input.latitude as f32, input.longitude as f32);
+    let events = [single.twilight_morning,
+                  single.sunrise,
+                  single.noon,
+                  single.sunset,
+                  single.twilight_evening];
+    for pair in events.windows(2) {
+        assert!(pair[0] <= pair[1], "unordered events {:?} for {:?}", events, input);
+    }
+}
+
+#[test]
+fn seeds_hold_the_invariants() {
+    for &(name, input) in SEEDS {
+        let decoded = Input::decode(&input.encode());
+        assert_eq!(decoded.encode(), input.encode(), "{}", name);
+        check_daylight(&input);
+        check_seconds(&input);
+    }
+    // short input is padded
+    assert_eq!(Input::decode(&[1]),
+               Input { secs: 1, latitude: 0.0, longitude: 0.0 });
+}
+
+#[test]
+fn corpus_matches_seeds() {
+    use std::fs;
+    use std::path::Path;
+
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+    for target in &["calculate_daylight", "daylight_seconds"] {
+        for &(name, input) in SEEDS {
+            let bytes = fs::read(corpus.join(target).join(name))
+                .unwrap_or_else(|error| panic!("{}/{}: {}", target, name, error));
+            assert_eq!(bytes, input.encode(), "{}/{}", target, name);
+        }
+    }
+}
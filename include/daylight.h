@@ -0,0 +1,73 @@
+#ifndef DAYLIGHT_H
+#define DAYLIGHT_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stdint.h>
+
+/**
+ * The daylight is calculated
+ */
+#define DAYLIGHT_OK 0
+
+/**
+ * The result pointer is null
+ */
+#define DAYLIGHT_ERROR_NULL 1
+
+/**
+ * The latitude is not within -90 to 90 degrees
+ */
+#define DAYLIGHT_ERROR_LATITUDE 2
+
+/**
+ * The longitude is not a finite number
+ */
+#define DAYLIGHT_ERROR_LONGITUDE 3
+
+/**
+ * The time is outside of the supported years -9999 to 9999
+ */
+#define DAYLIGHT_ERROR_TIME 4
+
+/**
+ * The calculation failed unexpectedly
+ */
+#define DAYLIGHT_ERROR_INTERNAL 5
+
+/**
+ * Result of the daylight calculation (see `Daylight`); the events are in
+ * seconds since 1970-01-01 00:00 UTC
+ */
+typedef struct CDaylight {
+  int64_t twilight_morning;
+  int64_t sunrise;
+  int64_t noon;
+  int64_t sunset;
+  int64_t twilight_evening;
+  /**
+   * Duration of the day in seconds
+   */
+  int64_t daylength;
+  /**
+   * Declination of the sun in angle degrees
+   */
+  double declination;
+  /**
+   * Sun altitude in angle degrees
+   */
+  double sun_altitude;
+} CDaylight;
+
+/**
+ * Calculates civil twilight (am/pm) and sunrise and sunset at the instant
+ * `unix_secs` (see `calculate_daylight`) into `out`; returns `DAYLIGHT_OK`,
+ * or one of the error codes and leaves `out` untouched.
+ *
+ * # Safety
+ *
+ * `out` must be null or point to a writable `CDaylight`.
+ */
+int32_t daylight_calculate(int64_t unix_secs, double lat, double lon, struct CDaylight *out);
+
+#endif  /* DAYLIGHT_H */
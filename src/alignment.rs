@@ -0,0 +1,175 @@
+//! Dates on which the rising or setting sun lines up with a direction, like
+//! the sunset along the streets of Manhattan ("Manhattanhenge").
+
+use time::{self, Timespec, Tm};
+
+use {DaylightOptions, EventKind, Horizon, LatitudeTrig, SECS_IN_DAY, days_from_civil, daylight_at,
+     fnrange, horizon, to_degrees, to_radians};
+
+/// Sunrise or sunset of a day with the direction of the sun at that moment
+struct Alignment {
+    /// Instant of the event, `None` during the polar day and night
+    time: Option<Timespec>,
+    /// Azimuth of the sun in angle degrees; during the polar day or night
+    /// the limit towards which it moves, the north or the south
+    azimuth: f64,
+    /// Declination of the sun in angle degrees
+    declination: f64,
+}
+
+/// Rising or setting sun at a location
+struct Sun {
+    lat: LatitudeTrig,
+    longitude: f64,
+    rising: bool,
+    options: DaylightOptions<'static>,
+}
+
+impl Sun {
+    /// Returns the event of the day (at 12:00 UTC)
+    fn alignment(&self, utc: Timespec) -> Alignment {
+        let daylight = daylight_at(utc, &self.lat, self.longitude, &self.options);
+        let kind = if self.rising {
+            EventKind::Sunrise
+        } else {
+            EventKind::Sunset
+        };
+        let time = daylight.event_time(kind);
+        let azimuth = match time {
+            Some(time) => {
+                let (_, azimuth) =
+                    horizon::sun_position(time, &self.lat, self.longitude, &self.options);
+                to_degrees(azimuth)
+            }
+            None if daylight.is_polar_night() => 180.0,
+            None if self.rising => 0.0,
+            None => 360.0,
+        };
+        Alignment {
+            time,
+            azimuth,
+            declination: daylight.declination,
+        }
+    }
+}
+
+/// Returns the first day from `from` up to `to` (exclusive) for which the
+/// predicate holds, when it holds for all days after it; `to` when none
+fn first_day<F>(mut from: i64, mut to: i64, predicate: F) -> i64
+    where F: Fn(i64) -> bool
+{
+    while from < to {
+        let middle = from + (to - from) / 2;
+        if predicate(middle) {
+            to = middle;
+        } else {
+            from = middle + 1;
+        }
+    }
+    from
+}
+
+/// Returns the dates in the year on which the sun rises (`rising`) or sets
+/// within `tolerance_deg` angle degrees of the bearing (an azimuth, from the
+/// north through the east), with the moment of that sunrise or sunset; empty
+/// when the sun never rises or sets in that direction at the latitude.
+///
+/// The moment is when the centre of the sun is on the true horizon, with
+/// half of the disk above it (see `Horizon::Geometric`), the usual convention
+/// for these alignments; the position of the sun is the one at that instant
+/// (see `DaylightOptions::refined`). Each date is the day calculated
+/// at 12:00 UTC, like `calculate_daylight`.
+///
+/// The direction of the sunrise and sunset only follows the declination of
+/// the sun, which rises until the June solstice and falls until the
+/// December solstice; the days are found by bisection within each of those
+/// parts of the year, instead of calculating every day.
+pub fn alignment_dates(year: i32,
+                       latitude: f64,
+                       longitude: f64,
+                       bearing_deg: f64,
+                       rising: bool,
+                       tolerance_deg: f64)
+                       -> Vec<(Tm, Timespec)> {
+    let sun = Sun {
+        lat: LatitudeTrig::new(latitude),
+        longitude,
+        rising,
+        options: DaylightOptions::default().horizon(Horizon::Geometric).refined(true),
+    };
+    let first = days_from_civil(year as i64, 1, 1);
+    let days = days_from_civil(year as i64 + 1, 1, 1) - first;
+    let midday = |day: i64| Timespec::new((first + day) * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+    let alignment = |day: i64| sun.alignment(midday(day));
+    let bearing = to_degrees(fnrange(to_radians(bearing_deg)));
+
+    // the last days before the declination falls and rises again
+    let declination = |day: i64| alignment(day).declination;
+    let june = first_day(60, 250, |day| declination(day + 1) < declination(day));
+    let december = first_day(250, days - 1, |day| declination(day + 1) > declination(day));
+
+    let mut dates = Vec::new();
+    for &(from, to, northward) in &[(0, june + 1, true),
+                                    (june + 1, december + 1, false),
+                                    (december + 1, days, true)] {
+        // the offset from the bearing, growing through the part of the year
+        let sign = if northward == rising { -1.0 } else { 1.0 };
+        let offset = |alignment: &Alignment| sign * (alignment.azimuth - bearing);
+        let mut day = first_day(from, to, |day| offset(&alignment(day)) >= -tolerance_deg);
+        while day < to {
+            let aligned = alignment(day);
+            if offset(&aligned) > tolerance_deg {
+                break;
+            }
+            if let Some(time) = aligned.time {
+                dates.push((time::at_utc(midday(day)), time));
+            }
+            day += 1;
+        }
+    }
+    dates
+}
+
+#[test]
+fn manhattanhenge() {
+    use time::Duration;
+
+    use {Coordinates, Location};
+
+    // the streets of Manhattan run 29 degrees from the north; the sun sets
+    // along them (at 299.4 degrees) on 29 May and 12 July 2024
+    let dates = alignment_dates(2024, 40.78, -73.97, 299.0, false, 0.5);
+    let days: Vec<_> = dates.iter().map(|&(date, _)| (date.tm_mon + 1, date.tm_mday)).collect();
+    assert!(days.contains(&(5, 29)) && days.contains(&(7, 12)), "{:?}", days);
+    assert!(days.iter().all(|&(month, day)| {
+                (month == 5 && (24..=30).contains(&day)) ||
+                (month == 7 && (11..=16).contains(&day))
+            }),
+            "{:?}",
+            days);
+    let location = Location::new("Manhattan", Coordinates::try_new(40.78, -73.97).unwrap());
+    for &(date, time) in &dates {
+        // in the evening, local time
+        assert_eq!(time::at_utc(time - Duration::hours(4)).tm_mday, date.tm_mday);
+        let position = location.solar_position(time::at_utc(time));
+        assert!((position.azimuth - 299.0).abs() <= 0.51, "{:?}", position);
+        assert!(position.altitude.abs() < 0.1, "{:?}", position);
+    }
+
+    // the other way, the sunrise in January and November
+    let sunrise = alignment_dates(2024, 40.78, -73.97, 119.0, true, 0.5);
+    assert!(!sunrise.is_empty());
+    assert!(sunrise.iter().all(|&(date, _)| date.tm_mon == 0 || date.tm_mon >= 10),
+            "{:?}",
+            sunrise);
+
+    // the sun never sets in the north-west at the equator, nor in the west
+    // at the north pole
+    assert!(alignment_dates(2024, 0.0, 0.0, 299.0, false, 0.5).is_empty());
+    assert!(alignment_dates(2024, 90.0, 0.0, 270.0, false, 0.5).is_empty());
+    // the sunset due west, around the equinoxes in the southern hemisphere
+    let west = alignment_dates(2024, -33.87, 151.21, 270.0, false, 0.3);
+    assert!(west.iter().any(|&(date, _)| date.tm_mon == 2), "{:?}", west);
+    assert!(west.iter().any(|&(date, _)| date.tm_mon == 8), "{:?}", west);
+    assert!(west.iter().all(|&(date, _)| date.tm_mon == 2 || date.tm_mon == 8), "{:?}", west);
+}
@@ -0,0 +1,110 @@
+//! Angles with their unit in the type, so that radians can not be passed
+//! where degrees are expected; also available without the `std` feature.
+//!
+//! The fields of the results stay plain `f64` angle degrees; their typed
+//! accessors (like `SolarPosition::azimuth_angle`) and the newer options
+//! (like `DaylightOptions::twilight_depression`) use these types instead:
+//!
+//! ```
+//! use daylight::DaylightOptions;
+//! use daylight::angle::{Degrees, Radians};
+//!
+//! let nautical = DaylightOptions::default().twilight_depression(Degrees(12.0));
+//! assert_eq!(nautical.get_twilight_depression(), Degrees(12.0));
+//! // radians are converted
+//! let civil = DaylightOptions::default().twilight_depression(Radians(0.1));
+//! assert!((civil.get_twilight_angle() - 5.7296).abs() < 1e-4);
+//! ```
+//!
+//! A bare number does not say its unit, and does not compile:
+//!
+//! ```compile_fail
+//! use daylight::DaylightOptions;
+//!
+//! let options = DaylightOptions::default().twilight_depression(0.1);
+//! ```
+
+use {fnrange, fnrange_signed, to_degrees, to_radians};
+
+/// Angle in degrees
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Degrees(pub f64);
+
+/// Angle in radians
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Radians(pub f64);
+
+impl Degrees {
+    /// Returns the angle in radians
+    pub fn to_radians(self) -> Radians {
+        Radians(to_radians(self.0))
+    }
+
+    /// Returns the angle in the range 0 to 360 (exclusive), like an azimuth
+    pub fn normalized(self) -> Degrees {
+        self.to_radians().normalized().to_degrees()
+    }
+
+    /// Returns the angle in the range -180 (exclusive) to 180, like a
+    /// longitude
+    pub fn normalized_signed(self) -> Degrees {
+        self.to_radians().normalized_signed().to_degrees()
+    }
+}
+
+impl Radians {
+    /// Returns the angle in degrees
+    pub fn to_degrees(self) -> Degrees {
+        Degrees(to_degrees(self.0))
+    }
+
+    /// Returns the angle in the range 0 to 2*pi (exclusive)
+    pub fn normalized(self) -> Radians {
+        Radians(fnrange(self.0))
+    }
+
+    /// Returns the angle in the range -pi (exclusive) to pi
+    pub fn normalized_signed(self) -> Radians {
+        Radians(fnrange_signed(self.0))
+    }
+}
+
+impl From<Radians> for Degrees {
+    fn from(radians: Radians) -> Degrees {
+        radians.to_degrees()
+    }
+}
+
+impl From<Degrees> for Radians {
+    fn from(degrees: Degrees) -> Radians {
+        degrees.to_radians()
+    }
+}
+
+#[test]
+fn angle_round_trip() {
+    for &degrees in &[0.0, 1.0, 45.0, -23.44, 90.0, 180.0, 359.99, -720.5, 1e6] {
+        let radians = Degrees(degrees).to_radians();
+        assert!((radians.to_degrees().0 - degrees).abs() <= 1e-12 * degrees.abs().max(1.0));
+        assert_eq!(Radians::from(Degrees(degrees)), radians);
+        assert_eq!(Degrees::from(radians), radians.to_degrees());
+    }
+    assert_eq!(Degrees(180.0).to_radians(), Radians(core::f64::consts::PI));
+    assert_eq!(Radians(core::f64::consts::FRAC_PI_2).to_degrees(), Degrees(90.0));
+}
+
+#[test]
+fn angle_normalized() {
+    let close = |a: Degrees, b: f64| (a.0 - b).abs() < 1e-9;
+    assert!(close(Degrees(370.0).normalized(), 10.0));
+    assert!(close(Degrees(-10.0).normalized(), 350.0));
+    assert!(close(Degrees(-730.0).normalized(), 350.0));
+    assert!(close(Degrees(190.0).normalized_signed(), -170.0));
+    assert!(close(Degrees(-190.0).normalized_signed(), 170.0));
+    assert!(close(Degrees(180.0).normalized_signed(), 180.0));
+    assert!(close(Degrees(45.0).normalized_signed(), 45.0));
+    let radians = Radians(-0.5).normalized();
+    assert!((radians.0 - (2.0 * core::f64::consts::PI - 0.5)).abs() < 1e-12);
+    assert!((Radians(7.0).normalized_signed().0 - (7.0 - 2.0 * core::f64::consts::PI)).abs() <
+            1e-12);
+}
@@ -0,0 +1,140 @@
+//! Comparison of results within a tolerance, like between algorithms or
+//! against reference values.
+
+use time::{Duration, Timespec};
+
+use Daylight;
+
+/// Asserts that two `Daylight` values are equal within a time tolerance
+/// (`time::Duration`) and an angle tolerance (angle degrees), listing every
+/// field that differs (enabled for other crates with the `test-util`
+/// feature)
+#[cfg(any(test, feature = "test-util"))]
+#[macro_export]
+macro_rules! assert_daylight_approx_eq {
+    ($left:expr, $right:expr, $time_tolerance:expr, $angle_tolerance:expr) => {{
+        let (left, right) = (&$left, &$right);
+        let differences = left.approx_differences(right, $time_tolerance, $angle_tolerance);
+        if !differences.is_empty() {
+            panic!("daylight values differ:\n  {}\n left: {:?}\nright: {:?}",
+                   differences.join("\n  "),
+                   left,
+                   right);
+        }
+    }};
+}
+
+impl Daylight {
+    /// Returns whether the events collapsed onto a single instant, like in
+    /// the polar night when the sun does not even reach the twilight
+    fn is_collapsed(&self) -> bool {
+        let ts = self.noon;
+        self.twilight_morning == ts && self.sunrise == ts && self.sunset == ts &&
+        self.twilight_evening == ts
+    }
+
+    /// Describes each field that differs from `other` by more than the
+    /// tolerance (in angle degrees for the declination and sun altitude).
+    /// The timestamps of two collapsed results (see `approx_eq`) are not
+    /// compared.
+    pub fn approx_differences(&self,
+                              other: &Daylight,
+                              time_tolerance: Duration,
+                              angle_tolerance_deg: f64)
+                              -> Vec<String> {
+        let mut differences = Vec::new();
+        {
+            let mut time = |name: &str, delta: Duration| {
+                if delta > time_tolerance || -delta > time_tolerance {
+                    differences.push(format!("{} differs by {:.3} s",
+                                             name,
+                                             delta.num_milliseconds() as f64 / 1e3));
+                }
+            };
+            if !(self.is_collapsed() && other.is_collapsed()) {
+                let fields: [(&str, Timespec, Timespec); 5] =
+                    [("twilight_morning", self.twilight_morning, other.twilight_morning),
+                     ("sunrise", self.sunrise, other.sunrise),
+                     ("noon", self.noon, other.noon),
+                     ("sunset", self.sunset, other.sunset),
+                     ("twilight_evening", self.twilight_evening, other.twilight_evening)];
+                for &(name, left, right) in &fields {
+                    time(name, left - right);
+                }
+            }
+            time("daylength", self.daylength - other.daylength);
+        }
+        for &(name, left, right) in &[("declination", self.declination, other.declination),
+                                      ("sun_altitude", self.sun_altitude, other.sun_altitude)] {
+            let delta = left - right;
+            if delta.is_nan() || delta.abs() > angle_tolerance_deg {
+                differences.push(format!("{} differs by {} degrees", name, delta));
+            }
+        }
+        differences
+    }
+
+    /// Returns whether all events are within `time_tolerance` and the
+    /// declination and sun altitude within `angle_tolerance_deg` of `other`.
+    /// Two results of which all events collapsed onto a single instant (the
+    /// polar night without twilight) are equal regardless of that instant.
+    pub fn approx_eq(&self,
+                     other: &Daylight,
+                     time_tolerance: Duration,
+                     angle_tolerance_deg: f64)
+                     -> bool {
+        self.approx_differences(other, time_tolerance, angle_tolerance_deg).is_empty()
+    }
+}
+
+#[test]
+fn daylight_approx_eq() {
+    use time;
+    use {DaylightOptions, calculate_daylight, calculate_daylight_with};
+
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.22, 5.97);
+    let options = DaylightOptions::default().refined(true);
+    let refined = calculate_daylight_with(tm, 52.22, 5.97, &options);
+
+    assert!(daylight.approx_eq(&daylight, Duration::zero(), 0.0));
+    assert!(!daylight.approx_eq(&refined, Duration::seconds(1), 0.1));
+    // the approximated hour angle is off most for the twilight
+    assert_daylight_approx_eq!(daylight, refined, Duration::minutes(10), 0.25);
+
+    let mut later = daylight;
+    later.sunset = later.sunset + Duration::milliseconds(2500);
+    later.sun_altitude += 0.5;
+    assert_eq!(daylight.approx_differences(&later, Duration::seconds(2), 0.1),
+               vec!["sunset differs by -2.500 s".to_string(),
+                    "sun_altitude differs by -0.5 degrees".to_string()]);
+    assert!(daylight.approx_eq(&later, Duration::seconds(3), 0.5));
+    later.declination = f64::NAN;
+    assert!(!daylight.approx_eq(&later, Duration::seconds(3), 0.5));
+
+    // Longyearbyen at midwinter, collapsed onto noon and onto midnight
+    let december = time::at_utc(Timespec::new(1450699200, 0));
+    let night = calculate_daylight(december, 78.22, 15.65);
+    let mut midnight = night;
+    midnight.twilight_morning = Timespec::new(1450656000, 0);
+    midnight.sunrise = midnight.twilight_morning;
+    midnight.noon = midnight.twilight_morning;
+    midnight.sunset = midnight.twilight_morning;
+    midnight.twilight_evening = midnight.twilight_morning;
+    assert_daylight_approx_eq!(night, midnight, Duration::zero(), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "noon differs by")]
+fn daylight_approx_eq_assertion() {
+    use time;
+    use calculate_daylight;
+
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.22, 5.97);
+    assert_daylight_approx_eq!(daylight,
+                               calculate_daylight(tm, 52.22, 6.97),
+                               Duration::minutes(1),
+                               1.0);
+}
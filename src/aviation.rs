@@ -0,0 +1,71 @@
+//! Night as defined for pilots: the periods of night flying and of the night
+//! landings that count for currency.
+
+use time::{self, Duration, Timespec, Tm};
+
+use events::dark_period;
+use {calculate_daylight, tm_to_timespec};
+
+/// Nights of aviation after a date (see `night_period`); each period is
+/// `(start, end)`, empty (`start == end`, at the solar midnight) when it does
+/// not occur and the 24 hours from noon to noon when the sun does not come up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NightPeriod {
+    /// From the end of the evening civil twilight until the start of the
+    /// next morning civil twilight, the night of the logbook
+    pub civil_night: (Timespec, Timespec),
+    /// From 1 hour after sunset until 1 hour before the next sunrise, the
+    /// night of the landings for night currency
+    pub currency_night: (Timespec, Timespec),
+}
+
+/// Returns the nights of aviation that start on the day calculated at the
+/// given date (see `calculate_daylight`) and end on the next, across
+/// midnight
+pub fn night_period(date: Tm, latitude: f64, longitude: f64) -> NightPeriod {
+    let today = calculate_daylight(date, latitude, longitude);
+    let next = time::at_utc(tm_to_timespec(date) + Duration::days(1));
+    let tomorrow = calculate_daylight(next, latitude, longitude);
+    NightPeriod {
+        civil_night: dark_period(&today, &tomorrow, true, Duration::zero()),
+        currency_night: dark_period(&today, &tomorrow, false, Duration::hours(1)),
+    }
+}
+
+#[test]
+fn aviation_nights() {
+    // Teuge airfield, near Apeldoorn, 2015-03-27
+    let (lat, long) = (52.24, 6.05);
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let today = calculate_daylight(date, lat, long);
+    let tomorrow = calculate_daylight(time::at_utc(Timespec::new(1427544000, 0)), lat, long);
+    let night = night_period(date, lat, long);
+    assert_eq!(night.civil_night, (today.twilight_evening, tomorrow.twilight_morning));
+    assert_eq!(night.currency_night,
+               (today.sunset + Duration::hours(1), tomorrow.sunrise - Duration::hours(1)));
+    assert!(night.civil_night.0 < night.currency_night.0);
+    assert!(night.currency_night.1 < night.civil_night.1);
+
+    // Fairbanks in June: the twilight lasts all night, and the sun is down
+    // for just over two hours, leaving minutes for the landings
+    let june = time::at_utc(Timespec::new(1434888000, 0));
+    let night = night_period(june, 64.84, -147.72);
+    assert_eq!(night.civil_night.0, night.civil_night.1);
+    let noon = calculate_daylight(june, 64.84, -147.72).noon;
+    assert!((night.civil_night.0 - (noon + Duration::hours(12))).num_minutes().abs() < 2);
+    let currency = night.currency_night.1 - night.currency_night.0;
+    assert!(currency > Duration::zero() && currency < Duration::minutes(20), "{}", currency);
+
+    // Longyearbyen: no night in the polar day, night from noon to noon in the
+    // polar night
+    let night = night_period(june, 78.22, 15.65);
+    assert_eq!(night.civil_night.0, night.civil_night.1);
+    assert_eq!(night.currency_night.0, night.currency_night.1);
+    let december = time::at_utc(Timespec::new(1450699200, 0));
+    let night = night_period(december, 78.22, 15.65);
+    let noon = calculate_daylight(december, 78.22, 15.65).noon;
+    assert_eq!(night.civil_night.0, noon);
+    assert!((night.civil_night.1 - night.civil_night.0 - Duration::days(1)).num_minutes().abs() <
+            1);
+    assert_eq!(night.currency_night, night.civil_night);
+}
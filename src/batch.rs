@@ -0,0 +1,70 @@
+//! Daylight of many locations at the same date.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use time::Tm;
+
+use error;
+use {Daylight, DaylightError, DaylightOptions, LatitudeTrig, SunEphemeris,
+     daylight_hours_to_timespec, midnight, solar_parameters_at, tm_to_timespec};
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date for
+/// every location of `coords`, as (latitude, longitude) pairs; the results
+/// are in the order of the locations, equal to those of
+/// `calculate_daylight_checked`.
+///
+/// The position of the sun (declination and equation of time) only depends
+/// on the date, so it is calculated once for the whole batch; per location
+/// only the hour angles remain. An invalid location only fails its own
+/// result. With the `parallel` feature the locations are divided over the
+/// threads of rayon.
+pub fn calculate_daylight_batch(date: Tm,
+                                coords: &[(f64, f64)])
+                                -> Vec<Result<Daylight, DaylightError>> {
+    let options = DaylightOptions::default();
+    let utc = tm_to_timespec(date);
+    let ephemeris = SunEphemeris::from(solar_parameters_at(utc, &options));
+    let tsmidnight = midnight(utc);
+    let daylight = |&(latitude, longitude): &(f64, f64)| {
+        error::validate(&date, latitude, longitude)?;
+        let day = ephemeris.solar_day(&LatitudeTrig::new(latitude), longitude, &options);
+        Ok(day.to_daylight(daylight_hours_to_timespec(tsmidnight, day.noon),
+                           options.get_rounding()))
+    };
+
+    #[cfg(feature = "parallel")]
+    let results = coords.par_iter().map(daylight).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results = coords.iter().map(daylight).collect();
+    results
+}
+
+#[test]
+fn daylight_batch() {
+    use time::{self, Timespec};
+
+    use calculate_daylight_checked;
+
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let mut coords = Vec::new();
+    for lat in -18..19 {
+        for long in -36..36 {
+            coords.push((lat as f64 * 5.0 + 0.123, long as f64 * 5.0 + 0.456));
+        }
+    }
+    coords.push((91.0, 0.0));
+    coords.push((0.0, f64::INFINITY));
+    coords.push((52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0));
+
+    let results = calculate_daylight_batch(date, &coords);
+    assert_eq!(results.len(), coords.len());
+    for (&(latitude, longitude), result) in coords.iter().zip(results.iter()) {
+        assert_eq!(*result, calculate_daylight_checked(date, latitude, longitude));
+    }
+    assert_eq!(results[coords.len() - 3], Err(DaylightError::LatitudeOutOfRange(91.0)));
+    assert_eq!(results[coords.len() - 2],
+               Err(DaylightError::LongitudeNotFinite(f64::INFINITY)));
+    assert_eq!(results[coords.len() - 1].unwrap().sunrise.sec, 1427433767);
+
+    assert!(calculate_daylight_batch(date, &[]).is_empty());
+}
@@ -0,0 +1,152 @@
+//! Command-line interface to the daylight calculation (enabled with the
+//! `cli` feature), like:
+//!
+//! ```text
+//! daylight --lat 52.22 --lon 5.97 --date 2024-06-21 --format json
+//! daylight --coords "52°13'N 5°58'E" --days 7 --offset +02:00
+//! ```
+//!
+//! Exits with status 2 on invalid input.
+
+extern crate clap;
+extern crate daylight;
+extern crate time;
+
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+use daylight::{Coordinates, DaylightError, DaylightIter, calculate_daylight_checked,
+               calculate_daylight_local_with_offset, daily_rows, format_rows};
+use time::Tm;
+
+/// Status of invalid input, like the command-line parser
+const EXIT_INVALID: i32 = 2;
+
+fn command() -> Command {
+    Command::new("daylight")
+        .about("Calculates civil twilight, sunrise, noon and sunset")
+        .arg(Arg::new("lat")
+            .long("lat")
+            .allow_negative_numbers(true)
+            .help("Latitude in angle degrees, positive on the northern hemisphere"))
+        .arg(Arg::new("lon")
+            .long("lon")
+            .allow_negative_numbers(true)
+            .help("Longitude in angle degrees, positive east of Greenwich"))
+        .arg(Arg::new("coords")
+            .long("coords")
+            .conflicts_with_all(["lat", "lon"])
+            .help("Coordinates like `52°13'26\"N 5°57'46\"E` or `52.22, 5.97`"))
+        .arg(Arg::new("date")
+            .long("date")
+            .help("Date like 2024-06-21 [default: today in UTC]"))
+        .arg(Arg::new("days")
+            .long("days")
+            .default_value("1")
+            .value_parser(clap::value_parser!(u32).range(1..=40_000))
+            .help("Number of consecutive days"))
+        .arg(Arg::new("offset")
+            .long("offset")
+            .default_value("+00:00")
+            .allow_hyphen_values(true)
+            .help("UTC offset of the local clock for the table, like +02:00"))
+        .arg(Arg::new("format")
+            .long("format")
+            .default_value("table")
+            .value_parser(["table", "json"])
+            .help("Output as a table on the local clock, or as JSON in UTC"))
+}
+
+/// Parses an UTC offset like `+02:00`, `-09:30` or `Z` into seconds
+fn parse_offset(text: &str) -> Option<i32> {
+    if text == "Z" {
+        return Some(0);
+    }
+    let (sign, rest) = match text.chars().next() {
+        Some('+') => (1, &text[1..]),
+        Some('-') => (-1, &text[1..]),
+        _ => return None,
+    };
+    let bytes = rest.as_bytes();
+    let digits = |range: &[u8]| range.iter().all(u8::is_ascii_digit);
+    if bytes.len() != 5 || bytes[2] != b':' || !digits(&bytes[..2]) || !digits(&bytes[3..]) {
+        return None;
+    }
+    let hours: i32 = rest[..2].parse().ok()?;
+    let minutes: i32 = rest[3..].parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Returns the coordinates of the arguments, or a message for the user
+fn coordinates(matches: &ArgMatches) -> Result<Coordinates, String> {
+    if let Some(text) = matches.get_one::<String>("coords") {
+        return text.parse().map_err(|e: DaylightError| format!("invalid coordinates: {}", e));
+    }
+    let number = |name: &str| -> Result<f64, String> {
+        let text = matches
+            .get_one::<String>(name)
+            .ok_or_else(|| "either --coords or both --lat and --lon are required".to_string())?;
+        text.parse().map_err(|_| format!("invalid --{} {:?}", name, text))
+    };
+    Coordinates::try_new(number("lat")?, number("lon")?).map_err(|e| e.to_string())
+}
+
+/// Returns the date of the arguments at 12:00 UTC, or a message for the user
+fn date(matches: &ArgMatches) -> Result<Tm, String> {
+    let mut date = match matches.get_one::<String>("date") {
+        Some(text) => {
+            time::strptime(text, "%Y-%m-%d")
+                .map_err(|e| format!("invalid date {:?}: {}", text, e))?
+        }
+        None => time::now_utc(),
+    };
+    date.tm_hour = 12;
+    date.tm_min = 0;
+    date.tm_sec = 0;
+    date.tm_nsec = 0;
+    Ok(date)
+}
+
+fn run(matches: &ArgMatches) -> Result<String, String> {
+    let coordinates = coordinates(matches)?;
+    let (latitude, longitude) = coordinates.into();
+    let date = date(matches)?;
+    calculate_daylight_checked(date, latitude, longitude).map_err(|e| e.to_string())?;
+    let offset = matches.get_one::<String>("offset").unwrap();
+    let utc_offset = parse_offset(offset).ok_or_else(|| format!("invalid offset {:?}", offset))?;
+    let days = *matches.get_one::<u32>("days").unwrap();
+
+    if matches.get_one::<String>("format").unwrap() == "json" {
+        // the iterator counts the days in 64 bits, unlike `Tm::to_timespec`,
+        // which is limited to 2038 where `time_t` is 32 bits
+        let objects: Vec<String> = DaylightIter::new(date, latitude, longitude)
+            .take(days as usize)
+            .map(|(date, _)| {
+                calculate_daylight_local_with_offset(date, latitude, longitude, utc_offset)
+                    .to_json()
+            })
+            .collect();
+        if days == 1 {
+            Ok(objects[0].clone())
+        } else {
+            Ok(format!("[{}]", objects.join(",")))
+        }
+    } else {
+        let table = format_rows(&daily_rows(date, days, latitude, longitude, utc_offset));
+        Ok(format!("{}\n{}", coordinates.to_dms_string(), table.trim_end()))
+    }
+}
+
+fn main() {
+    let matches = command().get_matches();
+    match run(&matches) {
+        Ok(output) => println!("{}", output),
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(EXIT_INVALID);
+        }
+    }
+}
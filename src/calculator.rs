@@ -0,0 +1,167 @@
+//! Repeated calculations for a fixed location.
+
+use std::sync::Mutex;
+
+use time::{Timespec, Tm};
+
+use {Daylight, DaylightOptions, LatitudeTrig, SECS_IN_DAY, SolarDay, SolarPosition,
+     daylight_hours_to_timespec, horizon, refined_solar_day, sunrise_depression, tm_to_timespec,
+     to_degrees};
+
+/// Calculator for a fixed location, like the state of a server answering
+/// many queries for one site.
+///
+/// The trigonometric functions of the latitude are calculated once, and the
+/// solar day of the most recent UTC date is kept: the daylight of a date is
+/// that of its UTC date, calculated at 12:00 UTC, so all queries during a
+/// day (like for the current time) are answered from the cache. The results
+/// are equal to those of the free functions at 12:00 UTC of the date (see
+/// `calculate_daylight_ymd`). The calculator is `Send` and `Sync`; when
+/// another thread holds the cache, the day is calculated without it instead
+/// of waiting.
+pub struct SolarCalculator<'a> {
+    lat: LatitudeTrig,
+    longitude: f64,
+    options: DaylightOptions<'a>,
+    /// Days since 1970-01-01 of the cached solar day
+    cache: Mutex<Option<(i64, SolarDay)>>,
+}
+
+impl<'a> SolarCalculator<'a> {
+    /// Creates a calculator for the location, with the default options
+    pub fn new(latitude: f64, longitude: f64) -> SolarCalculator<'a> {
+        SolarCalculator {
+            lat: LatitudeTrig::new(latitude),
+            longitude,
+            options: DaylightOptions::default(),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Sets the calculation options of all calculations (see
+    /// `calculate_daylight_with`)
+    pub fn options(mut self, options: &DaylightOptions<'a>) -> SolarCalculator<'a> {
+        self.options = *options;
+        self.cache = Mutex::new(None);
+        self
+    }
+
+    /// Returns the solar day of the UTC date `days` since 1970-01-01,
+    /// calculated at its 12:00 UTC
+    fn solar_day(&self, days: i64) -> SolarDay {
+        let calculate = || {
+            let noon = Timespec::new(days * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+            refined_solar_day(noon, &self.lat, self.longitude, &self.options)
+        };
+        let mut cache = match self.cache.try_lock() {
+            Ok(cache) => cache,
+            Err(_) => return calculate(),
+        };
+        match *cache {
+            Some((cached, day)) if cached == days => day,
+            _ => {
+                let day = calculate();
+                *cache = Some((days, day));
+                day
+            }
+        }
+    }
+
+    /// Calculate civil twilight (am/pm) and sunrise and sunset of the UTC
+    /// date of the given date (see `calculate_daylight_with`, at 12:00 UTC)
+    pub fn daylight(&self, date: Tm) -> Daylight {
+        let days = tm_to_timespec(date).sec.div_euclid(SECS_IN_DAY);
+        let day = self.solar_day(days);
+        let tsnoon = daylight_hours_to_timespec(Timespec::new(days * SECS_IN_DAY, 0), day.noon);
+        day.to_daylight(tsnoon, self.options.get_rounding())
+    }
+
+    /// Returns the position of the sun at the given time (see
+    /// `Location::solar_position`)
+    pub fn position(&self, time: Tm) -> SolarPosition {
+        let (altitude, azimuth) = horizon::sun_position(tm_to_timespec(time),
+                                                        &self.lat,
+                                                        self.longitude,
+                                                        &self.options);
+        SolarPosition {
+            altitude: to_degrees(altitude),
+            azimuth: to_degrees(azimuth),
+        }
+    }
+
+    /// Returns whether the sun is up at the instant (see `is_daytime`, which
+    /// refines the calculation like this)
+    pub fn is_daytime(&self, time: Timespec) -> bool {
+        let options = self.options.refined(true);
+        let (altitude, _) = horizon::sun_position(time, &self.lat, self.longitude, &options);
+        altitude >= -sunrise_depression(&options)
+    }
+}
+
+#[test]
+fn calculator_equals_free_functions() {
+    use std::sync::Arc;
+    use std::thread;
+
+    use time;
+
+    use {Coordinates, Location, Series, calculate_daylight, calculate_daylight_precise,
+         calculate_daylight_with, is_daytime};
+
+    let (latitude, longitude) = (52.22, 5.97);
+    let calculator = SolarCalculator::new(latitude, longitude);
+    let location = Location::new("Apeldoorn", Coordinates::try_new(latitude, longitude).unwrap());
+    for hour in 0..(24 * 40) {
+        let ts = Timespec::new(1427414400 + hour * 3600, 0);
+        let tm = time::at_utc(ts);
+        // the daylight of the UTC date, at its 12:00 UTC
+        let noon = time::at_utc(Timespec::new(ts.sec - ts.sec % 86400 + 43200, 0));
+        let expected = calculate_daylight(noon, latitude, longitude);
+        assert_eq!(calculator.daylight(tm), expected);
+        // calculated once per day, also for the queries during the day
+        assert_eq!(calculator.cache.lock().unwrap().map(|(days, _)| days), Some(ts.sec / 86400));
+        assert_eq!(calculator.position(tm), location.solar_position(tm));
+        assert_eq!(calculator.is_daytime(ts), is_daytime(ts, latitude, longitude));
+    }
+
+    let refined = SolarCalculator::new(latitude, longitude)
+        .options(&DaylightOptions::default().refined(true));
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    assert_eq!(refined.daylight(tm), calculate_daylight_precise(tm, latitude, longitude));
+
+    // the options apply to all calculations
+    let options = DaylightOptions::default().series(Series::Extended).elevation(100.0);
+    let custom = SolarCalculator::new(latitude, longitude).options(&options);
+    let evening = time::at_utc(Timespec::new(1427457600 + 6 * 3600, 0));
+    assert_eq!(custom.daylight(evening),
+               calculate_daylight_with(tm, latitude, longitude, &options));
+    let (altitude, azimuth) = horizon::sun_position(tm_to_timespec(evening),
+                                                    &LatitudeTrig::new(latitude),
+                                                    longitude,
+                                                    &options);
+    assert_eq!(custom.position(evening),
+               SolarPosition {
+                   altitude: to_degrees(altitude),
+                   azimuth: to_degrees(azimuth),
+               });
+    assert!(custom.position(evening) != calculator.position(evening));
+
+    // shared between threads
+    let calculator = Arc::new(calculator);
+    let threads: Vec<_> = (0..4)
+        .map(|thread| {
+            let calculator = calculator.clone();
+            thread::spawn(move || {
+                for day in 0..100 {
+                    let tm = time::at_utc(Timespec::new(1427457600 + (day % (thread + 2)) * 86400,
+                                                        0));
+                    assert_eq!(calculator.daylight(tm),
+                               calculate_daylight(tm, latitude, longitude));
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
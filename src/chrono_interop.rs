@@ -0,0 +1,112 @@
+//! Calculation with `chrono` timestamps (enabled with the `chrono` feature).
+
+use chrono::{self, DateTime, Utc};
+use time::{self, Duration, Timespec};
+
+use {Daylight, calculate_daylight};
+
+/// Result of the daylight calculation, with `chrono` timestamps (see
+/// `Daylight`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DaylightChrono {
+    pub twilight_morning: DateTime<Utc>,
+    pub sunrise: DateTime<Utc>,
+    pub sunset: DateTime<Utc>,
+    pub twilight_evening: DateTime<Utc>,
+    pub noon: DateTime<Utc>,
+    /// Declination of the sun in angle degrees
+    pub declination: f64,
+    /// Duration of the day
+    pub daylength: chrono::Duration,
+    /// Sun altitude in angle degrees
+    pub sun_altitude: f64,
+}
+
+/// Converts a timestamp to `chrono`; panics beyond its range of about
+/// 262000 years
+fn to_chrono(ts: Timespec) -> DateTime<Utc> {
+    DateTime::from_timestamp(ts.sec, ts.nsec as u32).expect("timestamp out of range for chrono")
+}
+
+/// Converts a `chrono` timestamp; a leap second is taken as the last
+/// nanosecond of the second before it
+fn from_chrono(date: DateTime<Utc>) -> Timespec {
+    Timespec::new(date.timestamp(),
+                  date.timestamp_subsec_nanos().min(999_999_999) as i32)
+}
+
+impl From<Daylight> for DaylightChrono {
+    fn from(daylight: Daylight) -> DaylightChrono {
+        let seconds = daylight.daylength.num_seconds();
+        let nanoseconds = (daylight.daylength - Duration::seconds(seconds))
+            .num_nanoseconds()
+            .unwrap_or(0);
+        DaylightChrono {
+            twilight_morning: to_chrono(daylight.twilight_morning),
+            sunrise: to_chrono(daylight.sunrise),
+            sunset: to_chrono(daylight.sunset),
+            twilight_evening: to_chrono(daylight.twilight_evening),
+            noon: to_chrono(daylight.noon),
+            declination: daylight.declination,
+            daylength: chrono::Duration::seconds(seconds) +
+                       chrono::Duration::nanoseconds(nanoseconds),
+            sun_altitude: daylight.sun_altitude,
+        }
+    }
+}
+
+impl From<DaylightChrono> for Daylight {
+    fn from(daylight: DaylightChrono) -> Daylight {
+        let seconds = daylight.daylength.num_seconds();
+        let nanoseconds = daylight.daylength.subsec_nanos() as i64;
+        Daylight {
+            twilight_morning: from_chrono(daylight.twilight_morning),
+            sunrise: from_chrono(daylight.sunrise),
+            sunset: from_chrono(daylight.sunset),
+            twilight_evening: from_chrono(daylight.twilight_evening),
+            noon: from_chrono(daylight.noon),
+            declination: daylight.declination,
+            daylength: Duration::seconds(seconds) + Duration::nanoseconds(nanoseconds),
+            sun_altitude: daylight.sun_altitude,
+        }
+    }
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date
+/// (see `calculate_daylight`)
+pub fn calculate_daylight_chrono(date: DateTime<Utc>,
+                                 latitude: f64,
+                                 longitude: f64)
+                                 -> DaylightChrono {
+    calculate_daylight(time::at_utc(from_chrono(date)), latitude, longitude).into()
+}
+
+#[test]
+fn chrono_matches_tm() {
+    use chrono::TimeZone;
+
+    let dates = [// leap day
+                 Utc.with_ymd_and_hms(2016, 2, 29, 12, 0, 0).unwrap(),
+                 Utc.with_ymd_and_hms(2000, 2, 29, 0, 0, 0).unwrap(),
+                 // end of the year
+                 Utc.with_ymd_and_hms(2015, 12, 31, 23, 59, 59).unwrap(),
+                 Utc.with_ymd_and_hms(2016, 1, 1, 0, 0, 0).unwrap(),
+                 Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 59).unwrap()];
+    for &date in &dates {
+        for &(latitude, longitude) in &[(52.22, 5.97), (-33.86, 151.21), (78.22, 15.65)] {
+            let tm = time::at_utc(Timespec::new(date.timestamp(), 0));
+            let daylight = calculate_daylight(tm, latitude, longitude);
+            let chrono = calculate_daylight_chrono(date, latitude, longitude);
+            assert_eq!(chrono, DaylightChrono::from(daylight), "{}", date);
+            assert_eq!(Daylight::from(chrono), daylight, "{}", date);
+            assert_eq!(chrono.sunrise.timestamp(), daylight.sunrise.sec);
+            assert_eq!(chrono.daylength.num_seconds(), daylight.daylength.num_seconds());
+        }
+    }
+
+    // Apeldoorn, 2015-03-27
+    let daylight = calculate_daylight_chrono(Utc.with_ymd_and_hms(2015, 3, 27, 12, 0, 0).unwrap(),
+                                             52.22,
+                                             5.97);
+    assert_eq!(daylight.sunrise.to_rfc3339(), "2015-03-27T05:22:46+00:00");
+}
@@ -0,0 +1,206 @@
+//! Comparison of the daylight of two locations, like how much more daylight
+//! Lisbon gets than Oslo in January.
+
+use time::{self, Duration, Timespec, Tm};
+
+use {Coordinates, Daylight, DaylightOptions, EventKind, SECS_IN_DAY, days_from_civil,
+     local_daylight};
+
+/// Differences of the daylight of location `a` to that of location `b` on a
+/// date (see `compare`); each is the value of `a` less that of `b`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DayComparison {
+    /// Civil date of both days (at 00:00)
+    pub date: Tm,
+    pub daylength: Duration,
+    /// Difference of the instants of the sunrises; `None` when either day
+    /// has no sunrise
+    pub sunrise: Option<Duration>,
+    /// Difference of the times of day of the sunrises, each on the clock of
+    /// its location
+    pub sunrise_local: Option<Duration>,
+    /// Difference of the instants of the sunsets
+    pub sunset: Option<Duration>,
+    /// Difference of the times of day of the sunsets, each on the clock of
+    /// its location
+    pub sunset_local: Option<Duration>,
+    /// Difference of the lengths of the civil twilight, morning and evening
+    /// together
+    pub twilight: Duration,
+}
+
+/// Differences of the daylight of two locations over a range of dates
+#[derive(Clone, Debug, PartialEq)]
+pub struct DaylightComparison {
+    /// Each date of the range, in order
+    pub days: Vec<DayComparison>,
+    /// Sum of the differences of the day lengths
+    pub total_daylength: Duration,
+    /// Mean difference of the day lengths, zero for an empty range
+    pub mean_daylength: Duration,
+    /// Mean difference of the lengths of the civil twilight
+    pub mean_twilight: Duration,
+}
+
+/// Returns the UTC offset of the local mean solar time at the longitude
+fn solar_offset(longitude: f64) -> i32 {
+    (longitude * 240.0).round() as i32
+}
+
+/// Returns the length of the civil twilight, morning and evening together
+fn twilight_length(daylight: &Daylight) -> Duration {
+    (daylight.sunrise - daylight.twilight_morning) + (daylight.twilight_evening - daylight.sunset)
+}
+
+/// Compares the daylight of location `a` to that of location `b` on every
+/// civil date from `first` up to and including `last` (of which only the
+/// dates are used; a single date when they are equal, none when `last` is
+/// before `first`).
+///
+/// The sunrises and sunsets are compared both as instants, and as the time
+/// of day on the clock of each location: comparing instants across many
+/// degrees of longitude mostly shows the difference in longitude. The clock
+/// is the local mean solar time of each location, on which the sun is at
+/// its highest around 12:00 (see `compare_with_offsets` for the clocks of
+/// time zones). Each date is the civil date on that clock.
+pub fn compare(first: Tm, last: Tm, a: Coordinates, b: Coordinates) -> DaylightComparison {
+    let offset_a = solar_offset(a.longitude().degrees());
+    let offset_b = solar_offset(b.longitude().degrees());
+    compare_with_offsets(first, last, a, offset_a, b, offset_b)
+}
+
+/// Compares the daylight of two locations (see `compare`), with the clock of
+/// each location `utc_offset` seconds ahead of UTC (see
+/// `calculate_daylight_local_with_offset`)
+pub fn compare_with_offsets(first: Tm,
+                            last: Tm,
+                            a: Coordinates,
+                            utc_offset_a: i32,
+                            b: Coordinates,
+                            utc_offset_b: i32)
+                            -> DaylightComparison {
+    let day_number = |tm: Tm| {
+        days_from_civil(tm.tm_year as i64 + 1900, tm.tm_mon as i64 + 1, tm.tm_mday as i64)
+    };
+    let options = DaylightOptions::default();
+    let daylight = |date: Tm, location: Coordinates, utc_offset: i32| {
+        local_daylight(date,
+                       location.latitude().degrees(),
+                       location.longitude().degrees(),
+                       utc_offset,
+                       &options)
+    };
+    let time_of_day = |time: Timespec, utc_offset: i32| {
+        Duration::seconds((time.sec + utc_offset as i64).rem_euclid(SECS_IN_DAY)) +
+        Duration::nanoseconds(time.nsec as i64)
+    };
+
+    let days: Vec<_> = (day_number(first)..=day_number(last))
+        .map(|day| {
+            let date = time::at_utc(Timespec::new(day * SECS_IN_DAY, 0));
+            let (day_a, day_b) = (daylight(date, a, utc_offset_a), daylight(date, b, utc_offset_b));
+            let compare_event = |kind: EventKind| {
+                match (day_a.event_time(kind), day_b.event_time(kind)) {
+                    (Some(time_a), Some(time_b)) => {
+                        let local = time_of_day(time_a, utc_offset_a) -
+                                    time_of_day(time_b, utc_offset_b);
+                        (Some(time_a - time_b), Some(local))
+                    }
+                    _ => (None, None),
+                }
+            };
+            let (sunrise, sunrise_local) = compare_event(EventKind::Sunrise);
+            let (sunset, sunset_local) = compare_event(EventKind::Sunset);
+            DayComparison {
+                date,
+                daylength: day_a.daylength - day_b.daylength,
+                sunrise,
+                sunrise_local,
+                sunset,
+                sunset_local,
+                twilight: twilight_length(&day_a) - twilight_length(&day_b),
+            }
+        })
+        .collect();
+
+    let total_daylength = days.iter().fold(Duration::zero(), |sum, day| sum + day.daylength);
+    let total_twilight = days.iter().fold(Duration::zero(), |sum, day| sum + day.twilight);
+    let count = days.len().max(1) as i32;
+    DaylightComparison {
+        total_daylength,
+        mean_daylength: total_daylength / count,
+        mean_twilight: total_twilight / count,
+        days,
+    }
+}
+
+#[test]
+fn compare_equator_to_north() {
+    let equator = Coordinates::try_new(0.0, 0.0).unwrap();
+    let north = Coordinates::try_new(60.0, 0.0).unwrap();
+
+    // the December solstice: the day at the equator is about 6 hours longer,
+    // with the sunrise earlier and the sunset later by about 3 hours each
+    let solstice = time::at_utc(Timespec::new(1734782400, 0));
+    let comparison = compare(solstice, solstice, equator, north);
+    assert_eq!(comparison.days.len(), 1);
+    let day = comparison.days[0];
+    assert_eq!((day.date.tm_mon, day.date.tm_mday, day.date.tm_hour), (11, 21, 0));
+    assert!(day.daylength > Duration::minutes(6 * 60 + 5), "{:?}", day);
+    assert_eq!(comparison.total_daylength, day.daylength);
+    assert_eq!(comparison.mean_daylength, day.daylength);
+    let sunrise = day.sunrise_local.unwrap();
+    let sunset = day.sunset_local.unwrap();
+    assert!(sunrise < Duration::hours(-3) && sunset > Duration::hours(3), "{:?}", day);
+    // up to the rounding of the events
+    let spread = day.sunset_local.unwrap() - day.sunrise_local.unwrap();
+    assert!((spread - day.daylength).num_seconds().abs() <= 1, "{:?}", day);
+    // on the same meridian, the instants differ as much as the clock times
+    assert_eq!(day.sunrise, day.sunrise_local);
+    // the sun sets steeply at the equator
+    assert!(day.twilight < Duration::minutes(-30), "{:?}", day);
+
+    // the March equinox: nearly equal, the sun only takes longer to cross
+    // the horizon at a slant in the north
+    let equinox = time::at_utc(Timespec::new(1710936000, 0));
+    let day = compare(equinox, equinox, equator, north).days[0];
+    let daylength = day.daylength.num_minutes();
+    assert!((-20..0).contains(&daylength), "{:?}", day);
+    assert!(day.sunrise_local.unwrap().num_minutes().abs() < 10, "{:?}", day);
+}
+
+#[test]
+fn compare_across_longitudes() {
+    // at the same latitude 46 degrees apart, the instants differ by about 3
+    // hours, the clock times of the local mean solar time hardly
+    let west = Coordinates::try_new(52.22, -40.0).unwrap();
+    let east = Coordinates::try_new(52.22, 5.97).unwrap();
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let day = compare(date, date, west, east).days[0];
+    let sunrise = day.sunrise.unwrap();
+    assert!((sunrise - Duration::minutes(184)).num_minutes().abs() <= 1, "{:?}", day);
+    assert!(day.sunrise_local.unwrap().num_minutes().abs() < 1, "{:?}", day);
+    assert!(day.daylength.num_minutes().abs() < 1, "{:?}", day);
+
+    // Lisbon (UTC) and Oslo (UTC+1) in January 2024
+    let lisbon = Coordinates::try_new(38.72, -9.14).unwrap();
+    let oslo = Coordinates::try_new(59.91, 10.75).unwrap();
+    let first = time::at_utc(Timespec::new(1704067200, 0));
+    let last = time::at_utc(Timespec::new(1704067200 + 30 * 86400, 0));
+    let comparison = compare_with_offsets(first, last, lisbon, 0, oslo, 3600);
+    assert_eq!(comparison.days.len(), 31);
+    assert_eq!(comparison.days[30].date.tm_mday, 31);
+    let total = comparison.days.iter().fold(Duration::zero(), |sum, day| sum + day.daylength);
+    assert_eq!(comparison.total_daylength, total);
+    assert_eq!(comparison.mean_daylength, total / 31);
+    let hours = comparison.mean_daylength.num_minutes() as f64 / 60.0;
+    assert!((2.5..3.5).contains(&hours), "{}", hours);
+    // the sun rises earlier in Lisbon on its clock than in Oslo on its own
+    for day in &comparison.days {
+        let local = day.sunrise_local.unwrap();
+        assert!(local < Duration::zero(), "{:?}", day);
+    }
+
+    assert!(compare(last, first, lisbon, oslo).days.is_empty());
+    assert_eq!(compare(last, first, lisbon, oslo).mean_daylength, Duration::zero());
+}
@@ -0,0 +1,164 @@
+//! Search for the first date on which an event is before or after a time on
+//! the local clock, like "the first day the sun sets after 18:00".
+
+use time::{self, Duration, Timespec, Tm};
+
+use events::SEARCH_DAYS;
+use {DaylightOptions, EventKind, SECS_IN_DAY, days_from_civil, local_daylight};
+
+/// Largest number of days skipped at once
+const MAX_SKIP: i64 = 30;
+
+/// Side of the clock time the event is on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// Strictly before the clock time
+    Before,
+    /// Strictly after the clock time
+    After,
+}
+
+/// Condition on the time of an event on the local clock (see
+/// `first_date_matching`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EventCondition {
+    pub kind: EventKind,
+    pub comparison: Comparison,
+    /// Clock time, since local midnight
+    pub time_of_day: Duration,
+}
+
+impl EventCondition {
+    /// Creates the condition that the event is before or after the clock
+    /// time; panics for an invalid hour or minute
+    pub fn new(kind: EventKind, comparison: Comparison, hour: u32, minute: u32) -> EventCondition {
+        assert!(hour < 24 && minute < 60, "invalid clock time {}:{:02}", hour, minute);
+        EventCondition {
+            kind,
+            comparison,
+            time_of_day: Duration::hours(hour as i64) + Duration::minutes(minute as i64),
+        }
+    }
+}
+
+/// Returns the first civil date from the start date (of which only the date
+/// is used) up to `SEARCH_DAYS` days later, on which the event of the
+/// location matches the condition on its clock, `utc_offset` seconds ahead
+/// of UTC (see `calculate_daylight_local_with_offset`); `None` when there is
+/// none, like for a date on which the event does not occur. The date is
+/// returned at 00:00.
+///
+/// The time of day of an event drifts slowly through the year, so the search
+/// skips ahead by the margin to the clock time at three times the drift of
+/// the day (up to a month) and bisects back to the first matching day;
+/// where the event does not occur, and around the polar day and night where
+/// its drift grows fast, it proceeds a day at a time.
+pub fn first_date_matching(start: Tm,
+                           latitude: f64,
+                           longitude: f64,
+                           utc_offset: i32,
+                           condition: EventCondition)
+                           -> Option<Tm> {
+    let first = days_from_civil(start.tm_year as i64 + 1900,
+                                start.tm_mon as i64 + 1,
+                                start.tm_mday as i64);
+    let options = DaylightOptions::default();
+    let date = |day: i64| time::at_utc(Timespec::new((first + day) * SECS_IN_DAY, 0));
+    // seconds the event is on the matching side of the clock time, negative
+    // when it does not match
+    let margin_on = |day: i64| {
+        let daylight = local_daylight(date(day), latitude, longitude, utc_offset, &options);
+        daylight.event_time(condition.kind).map(|time| {
+            let time_of_day = (time.sec + utc_offset as i64).rem_euclid(SECS_IN_DAY);
+            let difference = time_of_day - condition.time_of_day.num_seconds();
+            match condition.comparison {
+                Comparison::Before => -difference,
+                Comparison::After => difference,
+            }
+        })
+    };
+    let matches = |day: i64| margin_on(day).is_some_and(|margin| margin > 0);
+
+    let mut day = 0;
+    let mut missed = -1;
+    while day <= SEARCH_DAYS {
+        match margin_on(day) {
+            Some(margin) if margin > 0 => {
+                // the first match after the last day that did not match
+                let (mut low, mut high) = (missed, day);
+                while high - low > 1 {
+                    let middle = low + (high - low) / 2;
+                    if matches(middle) {
+                        high = middle;
+                    } else {
+                        low = middle;
+                    }
+                }
+                return Some(date(high));
+            }
+            Some(margin) => {
+                missed = day;
+                let drift = margin_on(day + 1).map_or(SECS_IN_DAY, |next| (next - margin).abs());
+                let skip = -margin / (3 * drift + 60);
+                day += skip.clamp(1, MAX_SKIP).min(SEARCH_DAYS + 1 - day).max(1);
+            }
+            None => {
+                missed = day;
+                day += 1;
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn first_date_of_late_sunset() {
+    // Apeldoorn on summer time, from the start of 2024: the sun sets after
+    // 21:00 from the middle of May
+    let start = time::at_utc(Timespec::new(1704067200, 0));
+    let late = EventCondition::new(EventKind::Sunset, Comparison::After, 21, 0);
+    let date = first_date_matching(start, 52.22, 5.97, 7200, late).unwrap();
+    assert_eq!((date.tm_year + 1900, date.tm_mon + 1), (2024, 5));
+    assert_eq!((date.tm_hour, date.tm_min), (0, 0));
+    // never at 10 degrees north
+    assert_eq!(first_date_matching(start, 10.0, 5.97, 7200, late), None);
+
+    // equal to trying every day, for conditions on several events
+    let conditions = [late,
+                      EventCondition::new(EventKind::Sunrise, Comparison::Before, 6, 30),
+                      EventCondition::new(EventKind::Sunrise, Comparison::After, 8, 0),
+                      EventCondition::new(EventKind::Sunset, Comparison::Before, 17, 0),
+                      EventCondition::new(EventKind::TwilightEvening, Comparison::After, 23, 0)];
+    for &(latitude, longitude, utc_offset) in &[(52.22, 5.97, 3600), (69.65, 18.96, 3600)] {
+        for &start in &[1704067200, 1714521600, 1727740800] {
+            let start = time::at_utc(Timespec::new(start, 0));
+            for &condition in &conditions {
+                let first = days_from_civil(start.tm_year as i64 + 1900,
+                                            start.tm_mon as i64 + 1,
+                                            start.tm_mday as i64);
+                let options = DaylightOptions::default();
+                let expected = (0..=SEARCH_DAYS)
+                    .map(|day| time::at_utc(Timespec::new((first + day) * SECS_IN_DAY, 0)))
+                    .find(|&date| {
+                        let daylight =
+                            local_daylight(date, latitude, longitude, utc_offset, &options);
+                        daylight.events().any(|event| {
+                            let time_of_day = Duration::seconds((event.time.sec +
+                                                                 utc_offset as i64)
+                                .rem_euclid(SECS_IN_DAY));
+                            event.kind == condition.kind &&
+                            match condition.comparison {
+                                Comparison::Before => time_of_day < condition.time_of_day,
+                                Comparison::After => time_of_day > condition.time_of_day,
+                            }
+                        })
+                    });
+                assert_eq!(first_date_matching(start, latitude, longitude, utc_offset, condition),
+                           expected,
+                           "{} {:?}",
+                           latitude,
+                           condition);
+            }
+        }
+    }
+}
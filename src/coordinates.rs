@@ -0,0 +1,429 @@
+//! Validated latitude and longitude, so that they can not be swapped or out
+//! of range by accident.
+
+use std::convert::{TryFrom, TryInto};
+use std::str::FromStr;
+
+use time::Tm;
+
+use error::DaylightError;
+use {Daylight, DaylightOptions, calculate_daylight_with};
+
+/// Converts degrees, minutes and seconds of arc to angle degrees
+fn from_dms(degrees: u32, minutes: u32, seconds: f64) -> Result<f64, DaylightError> {
+    if minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return Err(DaylightError::InvalidAngle);
+    }
+    Ok(degrees as f64 + minutes as f64 / 60.0 + seconds / 3600.0)
+}
+
+/// Formats angle degrees as whole degrees, minutes and (rounded) seconds of
+/// arc, followed by the hemisphere
+fn to_dms(degrees: f64, positive: char, negative: char) -> String {
+    let hemisphere = if degrees < 0.0 { negative } else { positive };
+    let seconds = (degrees.abs() * 3600.0).round() as u64;
+    format!("{}°{}'{}\"{}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            hemisphere)
+}
+
+/// Part of coordinates in text
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    /// Number, and whether it is written with a sign
+    Number(f64, bool),
+    /// Hemisphere letter (N, S, E or W)
+    Hemisphere(char),
+    /// Comma or semicolon between latitude and longitude
+    Separator,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, DaylightError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() || c == '.' || c == '+' || c == '-' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_digit() || c == '.' || (number.is_empty() && (c == '+' || c == '-')) {
+                    number.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = number.parse().map_err(|_| DaylightError::InvalidCoordinates)?;
+            tokens.push(Token::Number(value, number.starts_with(['+', '-'])));
+            continue;
+        }
+        match c.to_ascii_uppercase() {
+            'N' | 'S' | 'E' | 'W' => tokens.push(Token::Hemisphere(c.to_ascii_uppercase())),
+            ',' | ';' => tokens.push(Token::Separator),
+            '°' | 'º' | '\'' | '"' | '′' | '″' => {}
+            c if c.is_whitespace() => {}
+            _ => return Err(DaylightError::InvalidCoordinates),
+        }
+        chars.next();
+    }
+    Ok(tokens)
+}
+
+/// Splits the tokens in the part of the latitude and the longitude
+fn split_tokens(tokens: &[Token]) -> Result<(&[Token], &[Token]), DaylightError> {
+    if let Some(i) = tokens.iter().position(|t| *t == Token::Separator) {
+        return Ok((&tokens[..i], &tokens[i + 1..]));
+    }
+    let letters: Vec<usize> = tokens.iter()
+        .enumerate()
+        .filter(|&(_, t)| matches!(*t, Token::Hemisphere(_)))
+        .map(|(i, _)| i)
+        .collect();
+    match (tokens.first(), letters.len()) {
+        // hemisphere in front of each angle: `N 52 13 E 5 58`
+        (Some(&Token::Hemisphere(_)), 2) => Ok(tokens.split_at(letters[1])),
+        // hemisphere after each angle: `52 13 N 5 58 E`
+        (_, 2) => Ok(tokens.split_at(letters[0] + 1)),
+        // signed decimal degrees: `-33.86 151.21`
+        (_, 0) if tokens.len() == 2 => Ok(tokens.split_at(1)),
+        _ => Err(DaylightError::InvalidCoordinates),
+    }
+}
+
+/// Parses degrees, optionally followed by minutes and seconds of arc, with
+/// the hemisphere in front or after it
+fn parse_angle(tokens: &[Token]) -> Result<(f64, Option<char>), DaylightError> {
+    let (hemisphere, numbers) = match *tokens {
+        [Token::Hemisphere(h), ref numbers @ ..] |
+        [ref numbers @ .., Token::Hemisphere(h)] => (Some(h), numbers),
+        _ => (None, tokens),
+    };
+    let mut values = Vec::new();
+    for token in numbers {
+        match *token {
+            Token::Number(_, true) if hemisphere.is_some() || numbers.len() != 1 => {
+                return Err(DaylightError::InvalidCoordinates);
+            }
+            Token::Number(value, _) => values.push(value),
+            _ => return Err(DaylightError::InvalidCoordinates),
+        }
+    }
+    match values.len() {
+        1 => return Ok((values[0], hemisphere)),
+        2 | 3 => {}
+        _ => return Err(DaylightError::InvalidCoordinates),
+    }
+    if hemisphere.is_none() {
+        return Err(DaylightError::MissingHemisphere);
+    }
+    // only the last of degrees, minutes and seconds can have a fraction
+    if values[..values.len() - 1].iter().any(|v| v.fract() != 0.0) {
+        return Err(DaylightError::InvalidCoordinates);
+    }
+    if values[1..].iter().any(|&v| v >= 60.0) {
+        return Err(DaylightError::InvalidAngle);
+    }
+    let degrees = values.iter().rev().fold(0.0, |sum, value| sum / 60.0 + value);
+    Ok((degrees, hemisphere))
+}
+
+/// Latitude in angle degrees, from -90 (south pole) to 90 (north pole)
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f64", into = "f64"))]
+pub struct Latitude(f64);
+
+impl Latitude {
+    /// Creates a latitude from angle degrees (negative for south)
+    pub fn try_new(degrees: f64) -> Result<Latitude, DaylightError> {
+        if (-90.0..=90.0).contains(&degrees) {
+            Ok(Latitude(degrees))
+        } else {
+            Err(DaylightError::LatitudeOutOfRange(degrees))
+        }
+    }
+
+    /// Creates a latitude north of the equator from degrees, minutes and
+    /// seconds of arc
+    pub fn north(degrees: u32, minutes: u32, seconds: f64) -> Result<Latitude, DaylightError> {
+        Latitude::try_new(from_dms(degrees, minutes, seconds)?)
+    }
+
+    /// Creates a latitude south of the equator from degrees, minutes and
+    /// seconds of arc
+    pub fn south(degrees: u32, minutes: u32, seconds: f64) -> Result<Latitude, DaylightError> {
+        Latitude::try_new(-from_dms(degrees, minutes, seconds)?)
+    }
+
+    /// Returns the latitude in angle degrees
+    pub fn degrees(&self) -> f64 {
+        self.0
+    }
+
+    /// Formats the latitude like `52°13'26"N`, rounded to the second of arc
+    pub fn to_dms_string(&self) -> String {
+        to_dms(self.0, 'N', 'S')
+    }
+}
+
+impl TryFrom<f64> for Latitude {
+    type Error = DaylightError;
+
+    fn try_from(degrees: f64) -> Result<Latitude, DaylightError> {
+        Latitude::try_new(degrees)
+    }
+}
+
+impl From<Latitude> for f64 {
+    fn from(latitude: Latitude) -> f64 {
+        latitude.0
+    }
+}
+
+/// Longitude in angle degrees, from -180 (west) to 180 (east)
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f64", into = "f64"))]
+pub struct Longitude(f64);
+
+impl Longitude {
+    /// Creates a longitude from angle degrees (negative for west)
+    pub fn try_new(degrees: f64) -> Result<Longitude, DaylightError> {
+        if !degrees.is_finite() {
+            Err(DaylightError::LongitudeNotFinite(degrees))
+        } else if (-180.0..=180.0).contains(&degrees) {
+            Ok(Longitude(degrees))
+        } else {
+            Err(DaylightError::LongitudeOutOfRange(degrees))
+        }
+    }
+
+    /// Creates a longitude east of Greenwich from degrees, minutes and
+    /// seconds of arc
+    pub fn east(degrees: u32, minutes: u32, seconds: f64) -> Result<Longitude, DaylightError> {
+        Longitude::try_new(from_dms(degrees, minutes, seconds)?)
+    }
+
+    /// Creates a longitude west of Greenwich from degrees, minutes and
+    /// seconds of arc
+    pub fn west(degrees: u32, minutes: u32, seconds: f64) -> Result<Longitude, DaylightError> {
+        Longitude::try_new(-from_dms(degrees, minutes, seconds)?)
+    }
+
+    /// Returns the longitude in angle degrees
+    pub fn degrees(&self) -> f64 {
+        self.0
+    }
+
+    /// Formats the longitude like `5°57'46"E`, rounded to the second of arc
+    pub fn to_dms_string(&self) -> String {
+        to_dms(self.0, 'E', 'W')
+    }
+}
+
+impl TryFrom<f64> for Longitude {
+    type Error = DaylightError;
+
+    fn try_from(degrees: f64) -> Result<Longitude, DaylightError> {
+        Longitude::try_new(degrees)
+    }
+}
+
+impl From<Longitude> for f64 {
+    fn from(longitude: Longitude) -> f64 {
+        longitude.0
+    }
+}
+
+/// Location on earth
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Coordinates {
+    latitude: Latitude,
+    longitude: Longitude,
+}
+
+impl Coordinates {
+    /// Creates the coordinates of a location
+    pub fn new(latitude: Latitude, longitude: Longitude) -> Coordinates {
+        Coordinates {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Creates the coordinates of a location from angle degrees
+    pub fn try_new(latitude: f64, longitude: f64) -> Result<Coordinates, DaylightError> {
+        Ok(Coordinates::new(Latitude::try_new(latitude)?, Longitude::try_new(longitude)?))
+    }
+
+    /// Returns the latitude
+    pub fn latitude(&self) -> Latitude {
+        self.latitude
+    }
+
+    /// Returns the longitude
+    pub fn longitude(&self) -> Longitude {
+        self.longitude
+    }
+
+    /// Formats the coordinates like `52°13'26"N 5°57'46"E`, which can be
+    /// parsed again
+    pub fn to_dms_string(&self) -> String {
+        format!("{} {}", self.latitude.to_dms_string(), self.longitude.to_dms_string())
+    }
+
+    /// Calculate civil twilight (am/pm) and sunrise and sunset at given date
+    /// (see `calculate_daylight`)
+    pub fn daylight(&self, date: Tm) -> Daylight {
+        self.daylight_with(date, &DaylightOptions::default())
+    }
+
+    /// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+    /// using the given calculation options (see `calculate_daylight_with`)
+    pub fn daylight_with(&self, date: Tm, options: &DaylightOptions) -> Daylight {
+        calculate_daylight_with(date, self.latitude.0, self.longitude.0, options)
+    }
+}
+
+impl From<Coordinates> for (f64, f64) {
+    fn from(coordinates: Coordinates) -> (f64, f64) {
+        (coordinates.latitude.0, coordinates.longitude.0)
+    }
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// at a location that converts to `Coordinates`: the coordinates themselves,
+/// or with the `geo` feature a `geo_types::Point` (of which `x` is the
+/// longitude); fails when the conversion does
+pub fn calculate_daylight_at<C>(date: Tm, location: C) -> Result<Daylight, DaylightError>
+    where C: TryInto<Coordinates>,
+          DaylightError: From<C::Error>
+{
+    Ok(location.try_into()?.daylight(date))
+}
+
+/// Parses the latitude followed by the longitude, either in degrees,
+/// minutes and seconds with the hemispheres (`52°13'26"N 5°57'46"E`,
+/// `52 13 N, 5 58 E` or `N 52.22 E 5.96`), or in decimal degrees that are
+/// negative for the south and the west (`52.22, 5.96` or `-33.86 151.21`).
+/// The hemisphere letters also allow the longitude to come first.
+impl FromStr for Coordinates {
+    type Err = DaylightError;
+
+    fn from_str(text: &str) -> Result<Coordinates, DaylightError> {
+        let tokens = tokenize(text)?;
+        let (first, second) = split_tokens(&tokens)?;
+        let (first, second) = (parse_angle(first)?, parse_angle(second)?);
+
+        let signed = |(degrees, hemisphere): (f64, Option<char>)| match hemisphere {
+            Some('S') | Some('W') => -degrees,
+            _ => degrees,
+        };
+        match (first.1, second.1) {
+            (None, None) => Coordinates::try_new(first.0, second.0),
+            (Some('N'), Some('E')) | (Some('N'), Some('W')) | (Some('S'), Some('E')) |
+            (Some('S'), Some('W')) => Coordinates::try_new(signed(first), signed(second)),
+            (Some('E'), Some('N')) | (Some('E'), Some('S')) | (Some('W'), Some('N')) |
+            (Some('W'), Some('S')) => Coordinates::try_new(signed(second), signed(first)),
+            (Some(_), Some(_)) => Err(DaylightError::InvalidCoordinates),
+            _ => Err(DaylightError::MissingHemisphere),
+        }
+    }
+}
+
+#[test]
+fn coordinates_validation() {
+    assert_eq!(Latitude::try_new(52.22).map(f64::from), Ok(52.22));
+    assert_eq!(Latitude::try_from(-90.0).map(f64::from), Ok(-90.0));
+    assert_eq!(Latitude::try_new(90.1), Err(DaylightError::LatitudeOutOfRange(90.1)));
+    assert!(Latitude::try_new(f64::NAN).is_err());
+    assert_eq!(Longitude::try_new(-180.5),
+               Err(DaylightError::LongitudeOutOfRange(-180.5)));
+    assert_eq!(Longitude::try_new(f64::INFINITY),
+               Err(DaylightError::LongitudeNotFinite(f64::INFINITY)));
+    assert!(Coordinates::try_new(5.97, 52.22).is_ok());
+    assert!(Coordinates::try_new(152.22, 5.97).is_err());
+
+    // degrees, minutes and seconds
+    let latitude = Latitude::north(52, 13, 30.0).unwrap();
+    assert!((latitude.degrees() - 52.225).abs() < 1e-12);
+    assert_eq!(Latitude::south(33, 54, 0.0).unwrap().degrees(), -33.9);
+    assert_eq!(Longitude::west(78, 30, 0.0).unwrap().degrees(), -78.5);
+    assert_eq!(Latitude::north(52, 60, 0.0), Err(DaylightError::InvalidAngle));
+    assert_eq!(Longitude::east(5, 58, 60.0), Err(DaylightError::InvalidAngle));
+    assert!(Latitude::north(90, 0, 1.0).is_err());
+    assert!(Longitude::east(180, 0, 0.0).is_ok());
+}
+
+#[test]
+fn coordinates_daylight() {
+    use time::{Timespec, at_utc};
+    use calculate_daylight;
+
+    let tm = at_utc(Timespec::new(1427457600, 0));
+    let apeldoorn = Coordinates::new(Latitude::north(52, 13, 0.0).unwrap(),
+                                     Longitude::east(5, 58, 0.0).unwrap());
+
+    let (latitude, longitude) = apeldoorn.into();
+    assert_eq!(apeldoorn.daylight(tm), calculate_daylight(tm, latitude, longitude));
+    assert_eq!(apeldoorn.daylight(tm).sunrise.sec, 1427433767);
+    assert_eq!(calculate_daylight_at(tm, apeldoorn), Ok(apeldoorn.daylight(tm)));
+}
+
+#[test]
+fn coordinates_parse() {
+    type Expected = Result<(f64, f64), DaylightError>;
+    let table: &[(&str, Expected)] = &[
+        ("52°13'26\"N 5°57'46\"E", Ok((52.0 + 13.0 / 60.0 + 26.0 / 3600.0,
+                                       5.0 + 57.0 / 60.0 + 46.0 / 3600.0))),
+        ("52 13 N, 5 58 E", Ok((52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0))),
+        ("33°51′25″S 151°12′55″E", Ok((-(33.0 + 51.0 / 60.0 + 25.0 / 3600.0),
+                                      151.0 + 12.0 / 60.0 + 55.0 / 3600.0))),
+        ("N 52 13.5 W 0 7", Ok((52.225, -7.0 / 60.0))),
+        ("78 13 30 n; 15 39 e", Ok((78.225, 15.65))),
+        ("5°58'E 52°13'N", Ok((52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0))),
+        ("52.22N 5.97E", Ok((52.22, 5.97))),
+        ("52.2237, 5.9628", Ok((52.2237, 5.9628))),
+        ("-33.8568 151.2153", Ok((-33.8568, 151.2153))),
+        ("+51.48,-0.0015", Ok((51.48, -0.0015))),
+        ("52 60 N, 5 58 E", Err(DaylightError::InvalidAngle)),
+        ("52 13 60 N, 5 58 E", Err(DaylightError::InvalidAngle)),
+        ("52 13, 5 58", Err(DaylightError::MissingHemisphere)),
+        ("52 13 N, 5 58", Err(DaylightError::MissingHemisphere)),
+        ("52.5 13 N, 5 E", Err(DaylightError::InvalidCoordinates)),
+        ("-52 N, 5 E", Err(DaylightError::InvalidCoordinates)),
+        ("52N 5N", Err(DaylightError::InvalidCoordinates)),
+        ("52.22", Err(DaylightError::InvalidCoordinates)),
+        ("52.22 5.97 3", Err(DaylightError::InvalidCoordinates)),
+        ("Apeldoorn", Err(DaylightError::InvalidCoordinates)),
+        ("", Err(DaylightError::InvalidCoordinates)),
+        ("91N 5E", Err(DaylightError::LatitudeOutOfRange(91.0))),
+        ("52.22, 181", Err(DaylightError::LongitudeOutOfRange(181.0))),
+    ];
+
+    for &(text, ref expected) in table {
+        let parsed = text.parse::<Coordinates>().map(|c| c.into());
+        match (parsed, expected) {
+            (Ok((latitude, longitude)), &Ok((expected_latitude, expected_longitude))) => {
+                assert!((latitude - expected_latitude).abs() < 1e-9 &&
+                        (longitude - expected_longitude).abs() < 1e-9,
+                        "{}: {} {}",
+                        text,
+                        latitude,
+                        longitude)
+            }
+            (parsed, expected) => assert_eq!(parsed, *expected, "{}", text),
+        }
+    }
+
+    // round trip through the formatter, to the second of arc
+    let sydney = Coordinates::try_new(-33.8568, 151.2153).unwrap();
+    assert_eq!(sydney.to_dms_string(), "33°51'24\"S 151°12'55\"E");
+    let parsed: Coordinates = sydney.to_dms_string().parse().unwrap();
+    assert!((parsed.latitude().degrees() - -33.8568).abs() < 0.5 / 3600.0);
+    assert!((parsed.longitude().degrees() - 151.2153).abs() < 0.5 / 3600.0);
+    assert_eq!(Coordinates::try_new(0.0, -0.0001).unwrap().to_dms_string(),
+               "0°0'0\"N 0°0'0\"W");
+}
@@ -0,0 +1,205 @@
+//! Moments at which the sun passes a given altitude, like when it drops
+//! below the rooftops, found on the altitude of the sun through the day.
+
+use time::{Timespec, Tm};
+
+use events::SEARCH_DAYS;
+use {DaylightOptions, LatitudeTrig, SECS_IN_DAY, daylight_hours_to_timespec, horizon, midnight,
+     solar_day, tm_to_timespec, to_radians};
+
+/// Interval in seconds at which the altitude of the sun is sampled; when the
+/// sun culminates within a few hundredths of a degree of the altitude, both
+/// crossings may fall between two samples and be missed
+const SAMPLE_INTERVAL: i64 = 600;
+
+/// Direction in which the sun passes an altitude
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CrossingDirection {
+    /// From below to above the altitude, in the morning
+    Rising,
+    /// From above to below the altitude, in the evening
+    Setting,
+}
+
+/// Altitude of the sun relative to a threshold at a location
+struct Altitude<'a> {
+    lat: LatitudeTrig,
+    longitude: f64,
+    threshold: f64,
+    options: DaylightOptions<'a>,
+}
+
+impl<'a> Altitude<'a> {
+    fn new(latitude: f64,
+           longitude: f64,
+           altitude_deg: f64,
+           options: &DaylightOptions<'a>)
+           -> Altitude<'a> {
+        Altitude {
+            lat: LatitudeTrig::new(latitude),
+            longitude,
+            threshold: to_radians(altitude_deg),
+            // like `is_daytime`, the position at the instant itself
+            options: options.refined(true),
+        }
+    }
+
+    /// Returns whether the sun is at the side of the threshold the crossing
+    /// in the direction ends on, at the second
+    fn passed(&self, secs: i64, direction: CrossingDirection) -> bool {
+        let ts = Timespec::new(secs, 0);
+        let altitude = horizon::sun_position(ts, &self.lat, self.longitude, &self.options).0;
+        (altitude >= self.threshold) == (direction == CrossingDirection::Rising)
+    }
+
+    /// Returns the first crossing in the direction after the second `from`
+    /// up to and including the second `to`. The crossing is the first
+    /// second at which the sun has passed the threshold, so that it does not
+    /// depend on where the search starts.
+    fn first_crossing(&self, from: i64, to: i64, direction: CrossingDirection) -> Option<i64> {
+        let mut before = from;
+        let mut passed = self.passed(before, direction);
+        while before < to {
+            let sample = (before + SAMPLE_INTERVAL).min(to);
+            let sample_passed = self.passed(sample, direction);
+            if !passed && sample_passed {
+                // between a second before and one after the crossing
+                let (mut below, mut above) = (before, sample);
+                while above - below > 1 {
+                    let mid = (below + above).div_euclid(2);
+                    if self.passed(mid, direction) {
+                        above = mid;
+                    } else {
+                        below = mid;
+                    }
+                }
+                return Some(above);
+            }
+            before = sample;
+            passed = sample_passed;
+        }
+        None
+    }
+}
+
+/// Returns the moments at which the centre of the sun rises above and sets
+/// below `altitude_deg` angle degrees (without refraction, like
+/// `SolarPosition::altitude`) during the solar day at the given date, from
+/// 12 hours before until 12 hours after its noon; `None` when the sun does
+/// not pass the altitude in that direction that day.
+pub fn altitude_crossings(date: Tm,
+                          latitude: f64,
+                          longitude: f64,
+                          altitude_deg: f64)
+                          -> (Option<Timespec>, Option<Timespec>) {
+    altitude_crossings_with(date, latitude, longitude, altitude_deg, &DaylightOptions::default())
+}
+
+/// Returns the moments at which the centre of the sun passes `altitude_deg`
+/// angle degrees during the solar day at the given date (see
+/// `altitude_crossings`), using the given calculation options; the position
+/// of the sun is always refined to the instant itself
+pub fn altitude_crossings_with(date: Tm,
+                               latitude: f64,
+                               longitude: f64,
+                               altitude_deg: f64,
+                               options: &DaylightOptions)
+                               -> (Option<Timespec>, Option<Timespec>) {
+    let altitude = Altitude::new(latitude, longitude, altitude_deg, options);
+    let utc = tm_to_timespec(date);
+    let day = solar_day(utc, &altitude.lat, longitude, &altitude.options);
+    let noon = daylight_hours_to_timespec(midnight(utc), day.noon).sec;
+    let (start, end) = (noon - SECS_IN_DAY / 2, noon + SECS_IN_DAY / 2);
+    let crossing = |direction| {
+        altitude.first_crossing(start, end, direction).map(|secs| Timespec::new(secs, 0))
+    };
+    (crossing(CrossingDirection::Rising), crossing(CrossingDirection::Setting))
+}
+
+/// Returns the first moment strictly after the instant at which the centre
+/// of the sun passes `altitude_deg` angle degrees in the direction (see
+/// `altitude_crossings`), today or on a later day; `None` when it does not
+/// within a year, like above the polar circle in winter.
+pub fn next_altitude_crossing(after: Timespec,
+                              latitude: f64,
+                              longitude: f64,
+                              altitude_deg: f64,
+                              direction: CrossingDirection)
+                              -> Option<Timespec> {
+    next_altitude_crossing_with(after,
+                                latitude,
+                                longitude,
+                                altitude_deg,
+                                direction,
+                                &DaylightOptions::default())
+}
+
+/// Returns the first moment strictly after the instant at which the centre
+/// of the sun passes `altitude_deg` angle degrees in the direction (see
+/// `next_altitude_crossing`), using the given calculation options (see
+/// `altitude_crossings_with`)
+pub fn next_altitude_crossing_with(after: Timespec,
+                                   latitude: f64,
+                                   longitude: f64,
+                                   altitude_deg: f64,
+                                   direction: CrossingDirection,
+                                   options: &DaylightOptions)
+                                   -> Option<Timespec> {
+    let altitude = Altitude::new(latitude, longitude, altitude_deg, options);
+    altitude.first_crossing(after.sec, after.sec + SEARCH_DAYS * SECS_IN_DAY, direction)
+        .map(|secs| Timespec::new(secs, 0))
+}
+
+#[test]
+fn altitude_crossings_of_day() {
+    use time::{self, Duration};
+
+    use calculate_daylight_precise;
+
+    // Apeldoorn, 2015-03-27: at the altitude of sunrise and sunset (half the
+    // diameter of the sun and the refraction below the horizon)
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight_precise(tm, lat, long);
+    let (rising, setting) = altitude_crossings(tm, lat, long, -(0.265 + 34.0 / 60.0));
+    assert!((rising.unwrap() - daylight.sunrise).num_seconds().abs() < 30, "{:?}", rising);
+    assert!((setting.unwrap() - daylight.sunset).num_seconds().abs() < 30, "{:?}", setting);
+
+    // the sun culminates at about 40.6 degrees
+    let (rising, setting) = altitude_crossings(tm, lat, long, 40.0);
+    assert!(setting.unwrap() - rising.unwrap() < Duration::hours(2));
+    assert_eq!(altitude_crossings(tm, lat, long, 41.0), (None, None));
+}
+
+#[test]
+fn next_altitude_crossing_across_days() {
+    use time::{self, Duration};
+
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let tomorrow = time::at_utc(Timespec::new(1427457600 + 86400, 0));
+    let (rising, setting) = altitude_crossings(tm, lat, long, 10.0);
+    let (rising, setting) = (rising.unwrap(), setting.unwrap());
+    let (next_rising, next_setting) = altitude_crossings(tomorrow, lat, long, 10.0);
+    let next = |after, direction| next_altitude_crossing(after, lat, long, 10.0, direction);
+
+    // the same day, from anywhere before the crossing
+    let midnight = Timespec::new(1427414400, 0);
+    for &after in &[midnight, midnight + Duration::seconds(4321), rising - Duration::seconds(1)] {
+        assert_eq!(next(after, CrossingDirection::Rising), Some(rising));
+    }
+    assert_eq!(next(rising, CrossingDirection::Setting), Some(setting));
+    // just past the crossing, that of the next day
+    assert_eq!(next(rising, CrossingDirection::Rising), next_rising);
+    assert_eq!(next(setting, CrossingDirection::Setting), next_setting);
+    assert!(next_rising.unwrap() - rising > Duration::hours(23));
+
+    // Longyearbyen, from November the sun reaches 10 degrees in March
+    let november = Timespec::new(1446724800, 0);
+    let crossing = next_altitude_crossing(november, 78.22, 15.65, 10.0, CrossingDirection::Rising)
+        .unwrap();
+    assert_eq!(time::at_utc(crossing).tm_mon + 1, 3);
+    // at the pole the sun never reaches 30 degrees
+    assert_eq!(next_altitude_crossing(november, 90.0, 0.0, 30.0, CrossingDirection::Setting),
+               None);
+}
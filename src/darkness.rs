@@ -0,0 +1,151 @@
+//! Hours of nautical darkness of the nights of a voyage, when the sun is
+//! more than 12 degrees below the horizon and the horizon is no longer
+//! visible at sea; and the astronomical darkness of a night, when the sun is
+//! more than 18 degrees below it and no longer brightens the sky.
+
+use time::{self, Duration, Timespec, Tm};
+
+use events::dark_period;
+use {DaylightOptions, calculate_daylight_with, midnight, tm_to_timespec};
+
+/// Depression of the sun in angle degrees at nautical dusk and dawn
+const NAUTICAL_DEPRESSION: f64 = 12.0;
+/// Depression of the sun in angle degrees at astronomical dusk and dawn
+const ASTRONOMICAL_DEPRESSION: f64 = 18.0;
+
+/// Nautical darkness of a night (see `darkness_periods`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DarknessPeriod {
+    /// Date of the evening, at 12:00 UTC
+    pub date: Tm,
+    /// Nautical dusk and the next nautical dawn, or the noon of the date or
+    /// the next when the sun stays below 12 degrees all day; `None` when the
+    /// sun does not reach 12 degrees below the horizon that night
+    pub period: Option<(Timespec, Timespec)>,
+    /// Duration of the darkness, exactly from the start to the end of the
+    /// period; zero without darkness
+    pub duration: Duration,
+}
+
+/// Returns the nautical darkness of `nights` consecutive nights, from the
+/// evening of the start date (of which only the date is used) until the
+/// morning after, across midnight; using the twilight of the calculation at
+/// 12 degrees (see `DaylightOptions::twilight_angle`).
+pub fn darkness_periods(start: Tm,
+                        nights: u32,
+                        latitude: f64,
+                        longitude: f64)
+                        -> Vec<DarknessPeriod> {
+    let options = DaylightOptions::default().twilight_angle(NAUTICAL_DEPRESSION);
+    let first = midnight(tm_to_timespec(start)) + Duration::hours(12);
+    let daylight = |night: u32| {
+        let date = time::at_utc(first + Duration::days(night as i64));
+        (date, calculate_daylight_with(date, latitude, longitude, &options))
+    };
+    let mut today = daylight(0);
+    (0..nights)
+        .map(|night| {
+            let tomorrow = daylight(night + 1);
+            let (start, end) = dark_period(&today.1, &tomorrow.1, true, Duration::zero());
+            let period = DarknessPeriod {
+                date: today.0,
+                period: if start < end { Some((start, end)) } else { None },
+                duration: end - start,
+            };
+            today = tomorrow;
+            period
+        })
+        .collect()
+}
+
+/// Returns the astronomical darkness of the night after the date (of which
+/// only the date is used): from astronomical dusk in the evening of the date
+/// until astronomical dawn in the morning of the next date, using the
+/// twilight of the calculation at 18 degrees (see
+/// `DaylightOptions::twilight_angle`). Like `darkness_periods`, it starts
+/// or ends at the noon when the sun stays below 18 degrees all day, and is
+/// `None` when the sun does not reach 18 degrees below the horizon that
+/// night, like in summer above about 48.5 degrees (see
+/// `astro_darkness_duration`).
+pub fn astro_darkness(date: Tm, latitude: f64, longitude: f64) -> Option<(Timespec, Timespec)> {
+    let options = DaylightOptions::default().twilight_angle(ASTRONOMICAL_DEPRESSION);
+    let today = midnight(tm_to_timespec(date)) + Duration::hours(12);
+    let daylight = |noon: Timespec| {
+        calculate_daylight_with(time::at_utc(noon), latitude, longitude, &options)
+    };
+    let (start, end) = dark_period(&daylight(today),
+                                   &daylight(today + Duration::days(1)),
+                                   true,
+                                   Duration::zero());
+    if start < end { Some((start, end)) } else { None }
+}
+
+/// Returns the duration of the astronomical darkness of the night after the
+/// date (see `astro_darkness`), zero without darkness
+pub fn astro_darkness_duration(date: Tm, latitude: f64, longitude: f64) -> Duration {
+    astro_darkness(date, latitude, longitude).map_or(Duration::zero(), |(start, end)| end - start)
+}
+
+#[test]
+fn darkness_seasons() {
+    // 50 degrees north in the Channel, the nights after the solstices
+    let (lat, long) = (50.0, -2.0);
+    let june = darkness_periods(time::at_utc(Timespec::new(1434888000, 0)), 3, lat, long);
+    let december = darkness_periods(time::at_utc(Timespec::new(1450699200, 0)), 3, lat, long);
+    assert_eq!(june.len(), 3);
+    for period in june.iter().chain(december.iter()) {
+        let (start, end) = period.period.unwrap();
+        assert_eq!(period.duration, end - start);
+        assert_eq!(period.duration.num_milliseconds() % 1000, 0);
+    }
+    // about 4 hours in June against 13 and a half in December
+    let hours = |periods: &[DarknessPeriod]| periods[0].duration.num_minutes() as f64 / 60.0;
+    assert!(hours(&june) > 3.0 && hours(&june) < 5.0, "{}", hours(&june));
+    assert!(hours(&december) > 13.0 && hours(&december) < 14.5, "{}", hours(&december));
+
+    // from nautical dusk of the date to nautical dawn of the next
+    let options = DaylightOptions::default().twilight_angle(NAUTICAL_DEPRESSION);
+    let evening = calculate_daylight_with(june[0].date, lat, long, &options);
+    let morning = calculate_daylight_with(june[1].date, lat, long, &options);
+    assert_eq!(june[0].period, Some((evening.twilight_evening, morning.twilight_morning)));
+    assert_eq!((june[0].date.tm_mon, june[0].date.tm_mday), (5, 21));
+
+    // no nautical darkness at 56 degrees north around the solstice
+    let north = darkness_periods(time::at_utc(Timespec::new(1434888000, 0)), 2, 56.0, 3.0);
+    assert!(north.iter().all(|night| night.period.is_none() && night.duration == Duration::zero()));
+}
+
+#[test]
+fn astro_darkness_seasons() {
+    let (lat, long) = (52.22, 5.97);
+    // none around the June solstice at 52 degrees north, about 12 hours in
+    // December
+    let june = time::at_utc(Timespec::new(1434888000, 0));
+    assert_eq!(astro_darkness(june, lat, long), None);
+    assert_eq!(astro_darkness_duration(june, lat, long), Duration::zero());
+    let december = time::at_utc(Timespec::new(1450699200, 0));
+    let (dusk, dawn) = astro_darkness(december, lat, long).unwrap();
+    let hours = astro_darkness_duration(december, lat, long).num_minutes() as f64 / 60.0;
+    assert!((11.5..13.0).contains(&hours), "{}", hours);
+    assert_eq!(astro_darkness_duration(december, lat, long), dawn - dusk);
+
+    // dusk of the date, dawn of the next date; any time of the date
+    let options = DaylightOptions::default().twilight_angle(ASTRONOMICAL_DEPRESSION);
+    let evening = calculate_daylight_with(december, lat, long, &options);
+    let next = time::at_utc(Timespec::new(1450699200 + 86400, 0));
+    let morning = calculate_daylight_with(next, lat, long, &options);
+    assert_eq!((dusk, dawn), (evening.twilight_evening, morning.twilight_morning));
+    let late = time::at_utc(Timespec::new(1450699200 + 11 * 3600, 0));
+    assert_eq!(astro_darkness(late, lat, long), Some((dusk, dawn)));
+
+    // just below 48.5 degrees, a short darkness on the solstice; above it
+    // none
+    let short = astro_darkness_duration(june, 48.0, 2.35);
+    assert!(short > Duration::zero() && short < Duration::hours(2), "{}", short);
+    assert_eq!(astro_darkness(june, 49.0, 2.35), None);
+    // at 52 degrees, until early May
+    let april = time::at_utc(Timespec::new(1430136000, 0));
+    assert!(astro_darkness(april, lat, long).is_some());
+    let may = time::at_utc(Timespec::new(1432209600, 0));
+    assert_eq!(astro_darkness(may, lat, long), None);
+}
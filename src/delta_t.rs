@@ -0,0 +1,109 @@
+//! ΔT, the difference between terrestrial time (on which the position of
+//! the sun is based) and universal time (on which the clock is based).
+//!
+//! The estimate uses the polynomial expressions by Espenak and Meeus, as
+//! published in the NASA Five Millennium Canon of Solar Eclipses:
+//! http://eclipse.gsfc.nasa.gov/SEcat5/deltatpoly.html
+
+#[cfg(not(any(feature = "std", test)))]
+use math::Float;
+
+/// Source of the ΔT value applied to the ephemeris
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[non_exhaustive]
+pub enum DeltaT {
+    /// Treat terrestrial time and universal time as identical (default)
+    #[default]
+    None,
+    /// Use the Espenak/Meeus polynomial approximation for the date
+    Estimated,
+    /// Use the given value in seconds
+    Seconds(f64),
+}
+
+impl DeltaT {
+    /// Returns the ΔT in seconds for the given number of days since 2000
+    pub fn seconds(&self, d2000: f64) -> f64 {
+        match *self {
+            DeltaT::None => 0.0,
+            DeltaT::Estimated => estimate_delta_t(2000.0 + d2000 / 365.25),
+            DeltaT::Seconds(seconds) => seconds,
+        }
+    }
+}
+
+/// Estimates ΔT in seconds for the given (decimal) year
+pub fn estimate_delta_t(year: f64) -> f64 {
+    let y = year;
+    let long_term = |y: f64| {
+        let u = (y - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u
+    };
+
+    if y < -500.0 {
+        long_term(y)
+    } else if y < 500.0 {
+        let u = y / 100.0;
+        10583.6 - 1014.41 * u + 33.78311 * u.powi(2) - 5.952053 * u.powi(3) -
+        0.1798452 * u.powi(4) + 0.022174192 * u.powi(5) + 0.0090316521 * u.powi(6)
+    } else if y < 1600.0 {
+        let u = (y - 1000.0) / 100.0;
+        1574.2 - 556.01 * u + 71.23472 * u.powi(2) + 0.319781 * u.powi(3) -
+        0.8503463 * u.powi(4) - 0.005050998 * u.powi(5) + 0.0083572073 * u.powi(6)
+    } else if y < 1700.0 {
+        let t = y - 1600.0;
+        120.0 - 0.9808 * t - 0.01532 * t.powi(2) + t.powi(3) / 7129.0
+    } else if y < 1800.0 {
+        let t = y - 1700.0;
+        8.83 + 0.1603 * t - 0.0059285 * t.powi(2) + 0.00013336 * t.powi(3) -
+        t.powi(4) / 1174000.0
+    } else if y < 1860.0 {
+        let t = y - 1800.0;
+        13.72 - 0.332447 * t + 0.0068612 * t.powi(2) + 0.0041116 * t.powi(3) -
+        0.00037436 * t.powi(4) + 0.0000121272 * t.powi(5) -
+        0.0000001699 * t.powi(6) + 0.000000000875 * t.powi(7)
+    } else if y < 1900.0 {
+        let t = y - 1860.0;
+        7.62 + 0.5737 * t - 0.251754 * t.powi(2) + 0.01680668 * t.powi(3) -
+        0.0004473624 * t.powi(4) + t.powi(5) / 233174.0
+    } else if y < 1920.0 {
+        let t = y - 1900.0;
+        -2.79 + 1.494119 * t - 0.0598939 * t.powi(2) + 0.0061966 * t.powi(3) -
+        0.000197 * t.powi(4)
+    } else if y < 1941.0 {
+        let t = y - 1920.0;
+        21.20 + 0.84493 * t - 0.076100 * t.powi(2) + 0.0020936 * t.powi(3)
+    } else if y < 1961.0 {
+        let t = y - 1950.0;
+        29.07 + 0.407 * t - t.powi(2) / 233.0 + t.powi(3) / 2547.0
+    } else if y < 1986.0 {
+        let t = y - 1975.0;
+        45.45 + 1.067 * t - t.powi(2) / 260.0 - t.powi(3) / 718.0
+    } else if y < 2005.0 {
+        let t = y - 2000.0;
+        63.86 + 0.3345 * t - 0.060374 * t.powi(2) + 0.0017275 * t.powi(3) +
+        0.000651814 * t.powi(4) + 0.00002373599 * t.powi(5)
+    } else if y < 2050.0 {
+        let t = y - 2000.0;
+        62.92 + 0.32217 * t + 0.005589 * t.powi(2)
+    } else if y < 2150.0 {
+        long_term(y) - 0.5628 * (2150.0 - y)
+    } else {
+        long_term(y)
+    }
+}
+
+#[test]
+fn estimated_delta_t() {
+    // tabulated values by Espenak and Meeus
+    assert!((estimate_delta_t(1900.0) - -2.79).abs() < 0.01);
+    assert!((estimate_delta_t(1950.0) - 29.07).abs() < 0.01);
+    assert!((estimate_delta_t(2000.0) - 63.86).abs() < 0.01);
+    assert!(estimate_delta_t(2100.0) > 120.0);
+    // the segments connect (almost) continuously
+    for &y in &[1600.0, 1700.0, 1800.0, 1860.0, 1900.0, 1920.0, 1941.0, 1961.0, 1986.0,
+                2005.0, 2050.0, 2150.0] {
+        let step = (estimate_delta_t(y) - estimate_delta_t(y - 1e-6)).abs();
+        assert!(step < 2.0, "discontinuity of {} s at {}", step, y);
+    }
+}
@@ -0,0 +1,120 @@
+//! The position of the sun as an extension point: the events are solved
+//! from the declination and the equation of time of any implementation of
+//! `SolarAlgorithm`, like an ephemeris of another crate, through
+//! `Algorithm::Custom`, which borrows it for as long as the options are used.
+//!
+//! Everything that takes the calculation options then uses it:
+//! `calculate_daylight_with` and the other calculations `_with` options,
+//! like the twilights of `dawn_dusk_with` and the altitude crossings of
+//! `altitude_crossings_with`, and `SolarCalculator`. The series of the
+//! default algorithm implement it as well.
+
+use core::panic::RefUnwindSafe;
+
+use {DaylightOptions, Series, SolarParameters, solar_parameters_days};
+
+/// Position of the sun at an instant, as far as the events need it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SunEphemeris {
+    /// Declination of the sun in angle degrees, positive to the north
+    pub declination: f64,
+    /// Equation of time in minutes, apparent minus mean solar time (see
+    /// `SolarParameters::equation_of_time`)
+    pub equation_of_time: f64,
+}
+
+/// Calculation of the position of the sun (see `Algorithm::Custom`)
+///
+/// It is shared by the threads of a calculation, like of
+/// `calculate_daylight_batch`, so it must be `Sync`; and `RefUnwindSafe`,
+/// like the rest of `DaylightOptions`.
+pub trait SolarAlgorithm: Sync + RefUnwindSafe {
+    /// Returns the position of the sun at the instant `d2000` days since
+    /// 2000-01-01 00:00 UTC (including the fraction of the day); a
+    /// difference between universal and terrestrial time is up to the
+    /// implementation, the `delta_t` option does not apply
+    fn sun_position(&self, d2000: f64) -> SunEphemeris;
+}
+
+/// The series of the default algorithm, as with the default options (so
+/// `Standard` counts the days from midnight, see `Series`)
+impl SolarAlgorithm for Series {
+    fn sun_position(&self, d2000: f64) -> SunEphemeris {
+        solar_parameters_days(d2000, &DaylightOptions::default().series(*self)).into()
+    }
+}
+
+impl From<SolarParameters> for SunEphemeris {
+    fn from(parameters: SolarParameters) -> SunEphemeris {
+        SunEphemeris {
+            declination: parameters.declination,
+            equation_of_time: parameters.equation_of_time,
+        }
+    }
+}
+
+#[test]
+fn custom_solar_algorithm() {
+    use {Algorithm, calculate_daylight_seconds_with};
+
+    // a constant position, like of an ephemeris loaded at runtime
+    struct Fixed(SunEphemeris);
+    impl SolarAlgorithm for Fixed {
+        fn sun_position(&self, _: f64) -> SunEphemeris {
+            self.0
+        }
+    }
+
+    // the sun above the equator, on the meridian of Greenwich at 12:00 UTC;
+    // Apeldoorn, 2015-03-27 12:00 UTC
+    let equinox = Fixed(SunEphemeris {
+        declination: 0.0,
+        equation_of_time: 0.0,
+    });
+    let (unix_secs, latitude) = (1427457600, 52.0 + 13.0 / 60.0);
+    let options = DaylightOptions::default().algorithm(Algorithm::Custom(&equinox));
+    let daylight = calculate_daylight_seconds_with(unix_secs, latitude, 0.0, &options);
+    assert_eq!((daylight.noon, daylight.declination), (unix_secs, 0.0));
+    let sunrise = unix_secs - 6 * 3600;
+    assert!(daylight.sunrise < sunrise && daylight.sunrise > sunrise - 600, "{:?}", daylight);
+    assert_eq!(daylight.sunset - daylight.noon, daylight.noon - daylight.sunrise);
+
+    // a series through the trait equals the series option
+    let extended = DaylightOptions::default().algorithm(Algorithm::Custom(&Series::Extended));
+    assert_eq!(calculate_daylight_seconds_with(unix_secs, latitude, 5.97, &extended),
+               calculate_daylight_seconds_with(unix_secs,
+                                               latitude,
+                                               5.97,
+                                               &DaylightOptions::default()
+                                                   .series(Series::Extended)));
+
+    // compared by reference
+    assert_eq!(options.get_algorithm(), Algorithm::Custom(&equinox));
+    assert!(options != extended && options != DaylightOptions::default());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn custom_solar_algorithm_events() {
+    use time::{self, Timespec};
+
+    use {Algorithm, CrossingDirection, altitude_crossings_with, dawn_dusk_with,
+         next_altitude_crossing_with};
+
+    // Apeldoorn, 2015-03-27 12:00 UTC: the twilights and altitude crossings
+    // through the trait equal those of the series option
+    let (date, latitude, longitude) = (time::at_utc(Timespec::new(1427457600, 0)), 52.22, 5.97);
+    let custom = DaylightOptions::default().algorithm(Algorithm::Custom(&Series::Extended));
+    let series = DaylightOptions::default().series(Series::Extended);
+    for options in &[DaylightOptions::default(), DaylightOptions::default().refined(true)] {
+        let custom = options.algorithm(Algorithm::Custom(&Series::Extended));
+        assert_eq!(dawn_dusk_with(date, latitude, longitude, &custom),
+                   dawn_dusk_with(date, latitude, longitude, &options.series(Series::Extended)));
+    }
+    assert_eq!(altitude_crossings_with(date, latitude, longitude, 10.0, &custom),
+               altitude_crossings_with(date, latitude, longitude, 10.0, &series));
+    let after = date.to_timespec();
+    let setting = CrossingDirection::Setting;
+    assert_eq!(next_altitude_crossing_with(after, latitude, longitude, -6.0, setting, &custom),
+               next_altitude_crossing_with(after, latitude, longitude, -6.0, setting, &series));
+}
@@ -0,0 +1,167 @@
+//! Validation of the input of the calculation
+
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+use time::Tm;
+
+use iso::DateParseError;
+
+/// Range of years for which the calculation is supported; far outside of it
+/// the series for the position of the sun are meaningless
+const MIN_YEAR: i64 = -9999;
+const MAX_YEAR: i64 = 9999;
+/// Longitude in angle degrees beyond which the events shift by more than a
+/// year from the date (a turn is a day), which would overflow the time
+const MAX_LONGITUDE: f64 = 360.0 * 366.0;
+
+/// Maximum number of days calculated at once, over a century
+pub const MAX_RANGE_DAYS: u32 = 40_000;
+
+/// Reason why the daylight can not be calculated
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DaylightError {
+    /// The latitude (in angle degrees) is not within -90 to 90, or not a
+    /// number
+    LatitudeOutOfRange(f64),
+    /// The longitude is infinite or not a number
+    LongitudeNotFinite(f64),
+    /// The longitude (in angle degrees) is not within -180 to 180
+    LongitudeOutOfRange(f64),
+    /// The minutes or seconds of an angle are not within 0 to 60
+    InvalidAngle,
+    /// Coordinates in degrees, minutes and seconds lack the hemisphere
+    /// (N/S, E/W) of the latitude or longitude
+    MissingHemisphere,
+    /// The text is not recognized as coordinates
+    InvalidCoordinates,
+    /// The fields of the date do not represent an existing date and time
+    InvalidDate,
+    /// The text of a date can not be parsed (see `calculate_daylight_iso`)
+    InvalidDateText(DateParseError),
+    /// The date is outside of the supported years -9999 to 9999, or the
+    /// longitude is so far beyond 180 degrees that the events would be more
+    /// than a year away from the date
+    TimeOutOfRange,
+    /// The number of days of a range exceeds `MAX_RANGE_DAYS`
+    TooManyDays(u32),
+    /// The azimuth or elevation of a point of a `HorizonMask` (in angle
+    /// degrees) is infinite or not a number
+    HorizonNotFinite(f64, f64),
+}
+
+impl fmt::Display for DaylightError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DaylightError::LatitudeOutOfRange(latitude) => {
+                write!(f, "latitude {} is out of range (-90 to 90 degrees)", latitude)
+            }
+            DaylightError::LongitudeNotFinite(longitude) => {
+                write!(f, "longitude {} is not a finite number", longitude)
+            }
+            DaylightError::LongitudeOutOfRange(longitude) => {
+                write!(f, "longitude {} is out of range (-180 to 180 degrees)", longitude)
+            }
+            DaylightError::InvalidAngle => {
+                write!(f, "minutes and seconds of an angle must be less than 60")
+            }
+            DaylightError::MissingHemisphere => {
+                write!(f, "coordinates in degrees and minutes need a hemisphere (N/S, E/W)")
+            }
+            DaylightError::InvalidCoordinates => {
+                write!(f,
+                       "expected coordinates like `52°13'26\"N 5°57'46\"E` or `52.22, 5.96`")
+            }
+            DaylightError::InvalidDate => write!(f, "date is not a valid date and time"),
+            DaylightError::InvalidDateText(error) => write!(f, "invalid date: {}", error),
+            DaylightError::TimeOutOfRange => {
+                write!(f,
+                       "date is out of the supported range (years {} to {})",
+                       MIN_YEAR,
+                       MAX_YEAR)
+            }
+            DaylightError::TooManyDays(days) => {
+                write!(f, "{} days exceed the maximum of {} days", days, MAX_RANGE_DAYS)
+            }
+            DaylightError::HorizonNotFinite(azimuth, elevation) => {
+                write!(f,
+                       "horizon point at azimuth {} and elevation {} is not finite",
+                       azimuth,
+                       elevation)
+            }
+        }
+    }
+}
+
+impl Error for DaylightError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            DaylightError::InvalidDateText(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DateParseError> for DaylightError {
+    fn from(error: DateParseError) -> DaylightError {
+        DaylightError::InvalidDateText(error)
+    }
+}
+
+/// For conversions that can not fail, like of `Coordinates` to itself (see
+/// `calculate_daylight_at`)
+impl From<Infallible> for DaylightError {
+    fn from(never: Infallible) -> DaylightError {
+        match never {}
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days of the month (0 based, like `Tm::tm_mon`)
+pub fn days_in_month(year: i64, month: i32) -> i32 {
+    match month {
+        1 => if is_leap_year(year) { 29 } else { 28 },
+        3 | 5 | 8 | 10 => 30,
+        _ => 31,
+    }
+}
+
+/// Checks a date of human conventions (month and day are 1 based)
+pub fn validate_ymd(year: i32, month: u8, day: u8) -> Result<(), DaylightError> {
+    let year = year as i64;
+    if !(1..=12).contains(&month) || day < 1 || day as i32 > days_in_month(year, month as i32 - 1) {
+        return Err(DaylightError::InvalidDate);
+    }
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return Err(DaylightError::TimeOutOfRange);
+    }
+    Ok(())
+}
+
+/// Checks the date and location of the calculation
+pub fn validate(date: &Tm, latitude: f64, longitude: f64) -> Result<(), DaylightError> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(DaylightError::LatitudeOutOfRange(latitude));
+    }
+    if !longitude.is_finite() {
+        return Err(DaylightError::LongitudeNotFinite(longitude));
+    }
+
+    let year = date.tm_year as i64 + 1900;
+    if date.tm_mon < 0 || date.tm_mon > 11 || date.tm_mday < 1 ||
+       date.tm_mday > days_in_month(year, date.tm_mon) || date.tm_hour < 0 ||
+       date.tm_hour > 23 || date.tm_min < 0 || date.tm_min > 59 ||
+       date.tm_sec < 0 || date.tm_sec > 60 || date.tm_nsec < 0 ||
+       date.tm_nsec >= 1_000_000_000 || date.tm_utcoff.abs() >= 86_400 {
+        return Err(DaylightError::InvalidDate);
+    }
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) || longitude.abs() > MAX_LONGITUDE {
+        return Err(DaylightError::TimeOutOfRange);
+    }
+    Ok(())
+}
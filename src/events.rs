@@ -0,0 +1,718 @@
+//! Events of a calculated day as a sequence in time.
+
+use std::cmp::Ordering;
+use std::vec;
+
+use time::{self, Duration, Timespec, Tm};
+
+use iter::DaylightIter;
+use {Daylight, DaylightOptions, calculate_daylight, midnight, sun_altitude_at, sunrise_depression,
+     to_degrees};
+
+/// Number of days searched for the next event; over a year, so the first
+/// sunrise after the polar night is found
+pub const SEARCH_DAYS: i64 = 370;
+
+/// Kind of a daylight event, in the order of a regular day
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EventKind {
+    /// Start of the civil twilight
+    TwilightMorning,
+    Sunrise,
+    /// Highest position of the sun
+    Noon,
+    Sunset,
+    /// End of the civil twilight
+    TwilightEvening,
+}
+
+/// Event of a calculated day; events are ordered by time (and by kind when at
+/// the same time)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SolarEvent {
+    pub kind: EventKind,
+    #[cfg_attr(feature = "serde", serde(with = "::serde_support::unix_seconds"))]
+    pub time: Timespec,
+}
+
+/// Events bounding the night divided by `night_divisions`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NightBasis {
+    /// From the sunset until the next sunrise
+    SunsetSunrise,
+    /// From the end of the evening twilight until the start of the next
+    /// morning twilight
+    DuskDawn,
+}
+
+/// Part of the day a timestamp falls in (see `Daylight::part_of_day`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DayPart {
+    Night,
+    /// From the start of the civil twilight until the sunrise
+    MorningTwilight,
+    /// From the sunrise until the sunset
+    Day,
+    /// From the sunset until the end of the civil twilight
+    EveningTwilight,
+}
+
+/// Light regime at an instant by the altitude of the sun (see
+/// `light_phase`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LightPhase {
+    /// The sun is up
+    Day,
+    /// Down to 6 degrees below the horizon
+    CivilTwilight,
+    /// Down to 12 degrees below the horizon
+    NauticalTwilight,
+    /// Down to 18 degrees below the horizon
+    AstronomicalTwilight,
+    Night,
+}
+
+/// Light regime at an instant with the altitude of the sun it is based on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightLevel {
+    pub phase: LightPhase,
+    /// Altitude of the centre of the sun in angle degrees, without refraction
+    /// (see `SolarPosition::altitude`)
+    pub altitude: f64,
+}
+
+impl Ord for SolarEvent {
+    fn cmp(&self, other: &SolarEvent) -> Ordering {
+        (self.time, self.kind).cmp(&(other.time, other.kind))
+    }
+}
+
+impl PartialOrd for SolarEvent {
+    fn partial_cmp(&self, other: &SolarEvent) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Daylight {
+    /// Returns the events that occur, ordered by time; the sunrise and sunset
+    /// are left out during the polar day and night and the twilight when the
+    /// sun does not pass the twilight depression, so the noon is always
+    /// present
+    pub fn events(&self) -> vec::IntoIter<SolarEvent> {
+        let (regular, twilight) = (!self.is_polar_day() && !self.is_polar_night(),
+                                   self.has_twilight());
+        let candidates = [(twilight, EventKind::TwilightMorning, self.twilight_morning),
+                          (regular, EventKind::Sunrise, self.sunrise),
+                          (true, EventKind::Noon, self.noon),
+                          (regular, EventKind::Sunset, self.sunset),
+                          (twilight, EventKind::TwilightEvening, self.twilight_evening)];
+        let mut events: Vec<SolarEvent> = candidates.iter()
+            .filter(|&&(occurs, _, _)| occurs)
+            .map(|&(_, kind, time)| SolarEvent { kind, time })
+            .collect();
+        events.sort();
+        events.into_iter()
+    }
+
+    /// Returns the time of the event, or `None` when it does not occur on
+    /// the day (see `events`)
+    pub(crate) fn event_time(&self, kind: EventKind) -> Option<Timespec> {
+        self.events().find(|event| event.kind == kind).map(|event| event.time)
+    }
+
+    /// Returns how far the daylight has progressed at the timestamp, from 0.0
+    /// at sunrise to 1.0 at sunset, like for the arc of the sun in a user
+    /// interface; 0.0 before the sunrise and 1.0 after the sunset. During the
+    /// polar day it runs over the 24 hours around noon, during the polar
+    /// night it is 0.0 throughout (see `night_progress` for the night).
+    pub fn progress(&self, t: Timespec) -> f64 {
+        if self.is_polar_day() {
+            fraction(t, self.noon - Duration::hours(12), self.noon + Duration::hours(12))
+        } else if self.is_polar_night() {
+            0.0
+        } else {
+            fraction(t, self.sunrise, self.sunset)
+        }
+    }
+
+    /// Returns the part of the calculated day the timestamp falls in; each
+    /// part starts at its event and ends just before the next one, so the
+    /// sunrise itself is `Day` and the sunset `EveningTwilight`. Timestamps
+    /// before the morning twilight or after the evening twilight are `Night`,
+    /// except during the polar day, which is `Day` throughout.
+    pub fn part_of_day(&self, t: Timespec) -> DayPart {
+        if self.is_polar_day() {
+            DayPart::Day
+        } else if t < self.twilight_morning || t >= self.twilight_evening {
+            DayPart::Night
+        } else if t < self.sunrise {
+            DayPart::MorningTwilight
+        } else if t < self.sunset {
+            DayPart::Day
+        } else {
+            DayPart::EveningTwilight
+        }
+    }
+}
+
+impl IntoIterator for Daylight {
+    type Item = SolarEvent;
+    type IntoIter = vec::IntoIter<SolarEvent>;
+
+    fn into_iter(self) -> vec::IntoIter<SolarEvent> {
+        self.events()
+    }
+}
+
+impl IntoIterator for &Daylight {
+    type Item = SolarEvent;
+    type IntoIter = vec::IntoIter<SolarEvent>;
+
+    fn into_iter(self) -> vec::IntoIter<SolarEvent> {
+        self.events()
+    }
+}
+
+/// Returns the first event of a kind accepted by `accept` strictly after the
+/// instant, or `None` when it does not occur within `SEARCH_DAYS`
+pub fn find_next<F>(after: Timespec, latitude: f64, longitude: f64, accept: F) -> Option<SolarEvent>
+    where F: Fn(EventKind) -> bool
+{
+    // each day is calculated at 12:00 UTC, like the solar day of the UTC
+    // date; it may be the solar day before or after the one of `after`, so
+    // the search starts a day early
+    let midday = midnight(after) + Duration::hours(12);
+    (-1..SEARCH_DAYS)
+        .flat_map(|day| {
+            calculate_daylight(time::at_utc(midday + Duration::days(day)), latitude, longitude)
+        })
+        .find(|event| accept(event.kind) && event.time > after)
+}
+
+/// Returns the last event of a kind accepted by `accept` at or before the
+/// instant, or `None` when it does not occur within `SEARCH_DAYS`
+fn find_previous<F>(at: Timespec, latitude: f64, longitude: f64, accept: F) -> Option<SolarEvent>
+    where F: Fn(EventKind) -> bool
+{
+    // like `find_next`, backwards from the day after
+    let midday = midnight(at) + Duration::hours(12);
+    (-1..SEARCH_DAYS)
+        .flat_map(|day| {
+            calculate_daylight(time::at_utc(midday - Duration::days(day)), latitude, longitude)
+                .events()
+                .rev()
+        })
+        .find(|event| accept(event.kind) && event.time <= at)
+}
+
+/// Returns the fraction of the time from `start` to `end` passed at `t`,
+/// within 0.0 to 1.0
+fn fraction(t: Timespec, start: Timespec, end: Timespec) -> f64 {
+    let passed = (t - start).num_milliseconds() as f64 / (end - start).num_milliseconds() as f64;
+    passed.clamp(0.0, 1.0)
+}
+
+/// Returns how far the night has progressed at the instant, from 0.0 at
+/// sunset to 1.0 at the next sunrise, across midnight (see
+/// `Daylight::progress`); 0.0 while the sun is up. Around the polar night it
+/// runs from the last sunset before it to the first sunrise after it; at
+/// the poles, where the sun does not rise or set within a year, it is 0.0.
+pub fn night_progress(t: Timespec, latitude: f64, longitude: f64) -> f64 {
+    let sun = |kind| kind == EventKind::Sunrise || kind == EventKind::Sunset;
+    let sunrise = |kind| kind == EventKind::Sunrise;
+    match find_previous(t, latitude, longitude, sun) {
+        Some(SolarEvent { kind: EventKind::Sunset, time: sunset }) => {
+            find_next(t, latitude, longitude, sunrise)
+                .map_or(0.0, |sunrise| fraction(t, sunset, sunrise.time))
+        }
+        _ => 0.0,
+    }
+}
+
+/// Divides the night after the given date into `parts` equal parts, like
+/// halves for the midnight or thirds for the last third of the night, and
+/// returns the `parts + 1` boundaries: from the evening event of the date
+/// to the next morning event, which is on the next day, across midnight.
+/// With two parts the middle boundary is the midpoint of the night.
+///
+/// The Vec is empty when the evening event does not occur on the date:
+/// during the polar day there is no night (and for `DuskDawn` the same when
+/// the twilight lasts all night), and during the polar night it started on
+/// an earlier date (see `night_progress`). When the sun sets into the polar
+/// night, the night runs until the first sunrise after it, weeks later; the
+/// Vec is empty when the sun does not rise again within `SEARCH_DAYS`.
+/// Panics when `parts` is 0.
+pub fn night_divisions(date: Tm,
+                       latitude: f64,
+                       longitude: f64,
+                       parts: u8,
+                       basis: NightBasis)
+                       -> Vec<Timespec> {
+    assert!(parts > 0, "the night is divided in at least one part");
+    let (evening, morning) = match basis {
+        NightBasis::SunsetSunrise => (EventKind::Sunset, EventKind::Sunrise),
+        NightBasis::DuskDawn => (EventKind::TwilightEvening, EventKind::TwilightMorning),
+    };
+    let start = match calculate_daylight(date, latitude, longitude)
+        .events()
+        .find(|event| event.kind == evening) {
+        Some(event) => event.time,
+        None => return Vec::new(),
+    };
+    let end = match find_next(start, latitude, longitude, |kind| kind == morning) {
+        Some(event) => event.time,
+        None => return Vec::new(),
+    };
+    let night = (end - start).num_milliseconds() as f64;
+    (0..=parts)
+        .map(|part| {
+            let offset = (night * part as f64 / parts as f64).round();
+            start + Duration::milliseconds(offset as i64)
+        })
+        .collect()
+}
+
+/// Returns the period from the evening event of `today` until the morning
+/// event of `tomorrow`, moved inwards by `margin`; of the sunset and sunrise,
+/// or of the twilight. Without the event, the period starts at the noon of
+/// today or ends at the noon of tomorrow when the sun stays below it, and is
+/// empty when the sun stays above it.
+pub fn dark_period(today: &Daylight,
+                   tomorrow: &Daylight,
+                   twilight: bool,
+                   margin: Duration)
+                   -> (Timespec, Timespec) {
+    let (evening, morning) = if twilight {
+        (EventKind::TwilightEvening, EventKind::TwilightMorning)
+    } else {
+        (EventKind::Sunset, EventKind::Sunrise)
+    };
+    let midnight = today.noon + (tomorrow.noon - today.noon) / 2;
+    let empty = (midnight, midnight);
+    let start = match today.event_time(evening) {
+        Some(time) => time + margin,
+        None if today.is_polar_night() => today.noon,
+        None => return empty,
+    };
+    let end = match tomorrow.event_time(morning) {
+        Some(time) => time - margin,
+        None if tomorrow.is_polar_night() => tomorrow.noon,
+        None => return empty,
+    };
+    if start < end { (start, end) } else { empty }
+}
+
+/// Returns the light regime at the instant from the altitude of the sun at
+/// that moment: `Day` when the sun is up (like `is_daytime`, at about 0.833
+/// degrees below the true horizon), and the twilights down to 6, 12 and 18
+/// degrees below it (like `is_twilight`). Unlike `Daylight::part_of_day`,
+/// this does not depend on the events of a calculated day, so it holds
+/// across midnight and through the polar day and night. The altitude is
+/// returned as well, for a margin around the thresholds.
+pub fn light_phase(t: Timespec, latitude: f64, longitude: f64) -> LightLevel {
+    let options = DaylightOptions::default().refined(true);
+    let altitude = to_degrees(sun_altitude_at(t, latitude, longitude, &options));
+    let phase = if altitude >= -to_degrees(sunrise_depression(&options)) {
+        LightPhase::Day
+    } else if altitude >= -6.0 {
+        LightPhase::CivilTwilight
+    } else if altitude >= -12.0 {
+        LightPhase::NauticalTwilight
+    } else if altitude >= -18.0 {
+        LightPhase::AstronomicalTwilight
+    } else {
+        LightPhase::Night
+    };
+    LightLevel { phase, altitude }
+}
+
+/// Returns the first event of any kind strictly after the instant, across
+/// the days; absent kinds are skipped (see `Daylight::events`), so during
+/// the polar day and night this is the next noon
+pub fn next_event(after: Timespec, latitude: f64, longitude: f64) -> (EventKind, Timespec) {
+    let event = find_next(after, latitude, longitude, |_| true)
+        .expect("the noon occurs every day");
+    (event.kind, event.time)
+}
+
+/// Endless iterator over the events strictly after an instant, in
+/// chronological order across the days (see `upcoming_events`)
+#[derive(Clone)]
+pub struct UpcomingEvents {
+    days: DaylightIter<'static>,
+    pending: vec::IntoIter<SolarEvent>,
+    last: Timespec,
+}
+
+impl Iterator for UpcomingEvents {
+    type Item = SolarEvent;
+
+    fn next(&mut self) -> Option<SolarEvent> {
+        loop {
+            // an event of a day is only taken after the last one returned, so
+            // where the calculated days overlap nothing is returned twice
+            let last = self.last;
+            if let Some(event) = self.pending.find(|event| event.time > last) {
+                self.last = event.time;
+                return Some(event);
+            }
+            let (_, daylight) = self.days.next().expect("the days never end");
+            self.pending = daylight.events();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// Returns the events strictly after the instant, day after day without end
+/// (like repeated `next_event`); absent kinds are skipped, so during the
+/// polar day and night only the noon remains. Each day is calculated once,
+/// when its first event is needed.
+pub fn upcoming_events(from: Timespec, latitude: f64, longitude: f64) -> UpcomingEvents {
+    // like `find_next`, from the day before
+    let midday = midnight(from) + Duration::hours(12) - Duration::days(1);
+    UpcomingEvents {
+        days: DaylightIter::new(time::at_utc(midday), latitude, longitude),
+        pending: Vec::new().into_iter(),
+        last: from,
+    }
+}
+
+/// Returns how long it takes from `now` until the next event of the kind,
+/// the one of the following day when `now` is the event itself; `None` when
+/// it does not occur within a year
+pub fn time_until(kind: EventKind,
+                  now: Timespec,
+                  latitude: f64,
+                  longitude: f64)
+                  -> Option<Duration> {
+    find_next(now, latitude, longitude, |k| k == kind).map(|event| event.time - now)
+}
+
+/// Returns the first sunrise strictly after the instant, skipping the days
+/// of the polar day and night; `None` when the sun does not rise within a
+/// year
+pub fn next_sunrise(after: Timespec, latitude: f64, longitude: f64) -> Option<Timespec> {
+    find_next(after, latitude, longitude, |kind| kind == EventKind::Sunrise).map(|e| e.time)
+}
+
+/// Returns the first sunset strictly after the instant, skipping the days
+/// of the polar day and night; `None` when the sun does not set within a
+/// year
+pub fn next_sunset(after: Timespec, latitude: f64, longitude: f64) -> Option<Timespec> {
+    find_next(after, latitude, longitude, |kind| kind == EventKind::Sunset).map(|e| e.time)
+}
+
+#[test]
+fn daylight_events() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let events: Vec<SolarEvent> = daylight.events().collect();
+    assert_eq!(events.iter().map(|e| (e.kind, e.time.sec)).collect::<Vec<_>>(),
+               vec![(EventKind::TwilightMorning, 1427432130),
+                    (EventKind::Sunrise, 1427433767),
+                    (EventKind::Noon, 1427456487),
+                    (EventKind::Sunset, 1427479207),
+                    (EventKind::TwilightEvening, 1427480844)]);
+    assert!(events.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!((&daylight).into_iter().collect::<Vec<_>>(), events);
+    assert_eq!(daylight.into_iter().max().map(|e| e.kind),
+               Some(EventKind::TwilightEvening));
+
+    // Longyearbyen, polar day and polar night
+    for &input in &[1434888000, 1450699200] {
+        let daylight = calculate_daylight(time::at_utc(Timespec::new(input, 0)), 78.22, 15.65);
+        let events: Vec<SolarEvent> = daylight.events().collect();
+        assert_eq!(events,
+                   vec![SolarEvent {
+                            kind: EventKind::Noon,
+                            time: daylight.noon,
+                        }]);
+    }
+
+    // events at the same time are ordered by kind
+    let time = Timespec::new(1427456487, 0);
+    assert!(SolarEvent { kind: EventKind::Sunrise, time } <
+            SolarEvent { kind: EventKind::Noon, time });
+}
+
+#[test]
+fn daylight_part_of_day() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let second = Duration::seconds(1);
+    let parts = [(daylight.twilight_morning - second, DayPart::Night),
+                 (daylight.twilight_morning, DayPart::MorningTwilight),
+                 (daylight.sunrise - second, DayPart::MorningTwilight),
+                 (daylight.sunrise, DayPart::Day),
+                 (daylight.noon, DayPart::Day),
+                 (daylight.sunset - second, DayPart::Day),
+                 (daylight.sunset, DayPart::EveningTwilight),
+                 (daylight.twilight_evening - second, DayPart::EveningTwilight),
+                 (daylight.twilight_evening, DayPart::Night)];
+    for &(t, part) in &parts {
+        assert_eq!(daylight.part_of_day(t), part, "{:?}", time::at_utc(t));
+    }
+
+    // Longyearbyen, the whole polar day and the whole polar night
+    let june = calculate_daylight(time::at_utc(Timespec::new(1434888000, 0)), 78.22, 15.65);
+    let december = calculate_daylight(time::at_utc(Timespec::new(1450699200, 0)), 78.22, 15.65);
+    for hours in -13..14 {
+        let offset = Duration::hours(hours);
+        assert_eq!(june.part_of_day(june.noon + offset), DayPart::Day);
+        assert_eq!(december.part_of_day(december.noon + offset), DayPart::Night);
+    }
+}
+
+#[test]
+fn next_sunrise_and_sunset() {
+    // Apeldoorn, 2015-03-27 12:00 UTC
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let midday = Timespec::new(1427457600, 0);
+    let sunrise = next_sunrise(midday, lat, long).unwrap();
+    let tomorrow = calculate_daylight(time::at_utc(midday + Duration::days(1)), lat, long);
+    assert_eq!(sunrise, tomorrow.sunrise);
+    assert_eq!(next_sunset(midday, lat, long), Some(Timespec::new(1427479207, 0)));
+
+    // never the event itself, but the one of the day after
+    assert_eq!(next_sunrise(sunrise - Duration::seconds(1), lat, long), Some(sunrise));
+    let following = next_sunrise(sunrise, lat, long).unwrap();
+    assert!(following - sunrise > Duration::hours(23), "{:?}", time::at_utc(following));
+    assert!(following - sunrise < Duration::hours(24), "{:?}", time::at_utc(following));
+
+    // Longyearbyen, from early November until after the polar night
+    let november = Timespec::new(1446724800, 0);
+    let sunrise = time::at_utc(next_sunrise(november, 78.22, 15.65).unwrap());
+    assert_eq!((sunrise.tm_year + 1900, sunrise.tm_mon + 1), (2016, 2));
+    assert!(sunrise.tm_mday > 10 && sunrise.tm_mday < 20, "{}", sunrise.rfc3339());
+
+    // at the pole no day has both a sunrise and a sunset
+    assert_eq!(next_sunrise(november, 90.0, 0.0), None);
+    assert_eq!(next_sunset(november, -90.0, 0.0), None);
+}
+
+#[test]
+fn next_event_sequence() {
+    // Apeldoorn, a full day from 2015-03-27 00:00 UTC, in the night between
+    // the evening twilight and the morning twilight
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let start = Timespec::new(1427414400, 0);
+    let mut t = start;
+    let mut kinds = Vec::new();
+    while t < start + Duration::days(1) {
+        let (kind, time) = next_event(t, lat, long);
+        assert!(time > t);
+        kinds.push(kind);
+        t = time;
+    }
+    assert_eq!(kinds,
+               vec![EventKind::TwilightMorning,
+                    EventKind::Sunrise,
+                    EventKind::Noon,
+                    EventKind::Sunset,
+                    EventKind::TwilightEvening,
+                    EventKind::TwilightMorning]);
+    assert_eq!(next_event(Timespec::new(1427479207, 0), lat, long),
+               (EventKind::TwilightEvening, Timespec::new(1427480844, 0)));
+
+    // Longyearbyen, only the noon in the polar night
+    let december = Timespec::new(1450699200, 0);
+    let (kind, noon) = next_event(december, 78.22, 15.65);
+    assert_eq!(kind, EventKind::Noon);
+    assert_eq!(next_event(noon, 78.22, 15.65).0, EventKind::Noon);
+}
+
+#[test]
+fn time_until_event() {
+    // Apeldoorn, 2015-03-27; in the afternoon the sunset of the same day, in
+    // the evening the one of the next day
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let afternoon = Timespec::new(1427464800, 0);
+    assert_eq!(time_until(EventKind::Sunset, afternoon, lat, long),
+               Some(Timespec::new(1427479207, 0) - afternoon));
+    let evening = Timespec::new(1427486400, 0);
+    let tomorrow = calculate_daylight(time::at_utc(Timespec::new(1427544000, 0)), lat, long);
+    assert_eq!(time_until(EventKind::Sunset, evening, lat, long),
+               Some(tomorrow.sunset - evening));
+    let sunset = Timespec::new(1427479207, 0);
+    assert_eq!(time_until(EventKind::Sunset, sunset, lat, long),
+               Some(tomorrow.sunset - sunset));
+
+    // Longyearbyen, the first sunrise after the polar night
+    let november = Timespec::new(1446724800, 0);
+    let wait = time_until(EventKind::Sunrise, november, 78.22, 15.65).unwrap();
+    assert_eq!(Some(november + wait), next_sunrise(november, 78.22, 15.65));
+    assert!(wait > Duration::days(90) && wait < Duration::days(110), "{}", wait);
+    assert_eq!(time_until(EventKind::Sunrise, november, 90.0, 0.0), None);
+}
+
+#[test]
+fn upcoming_events_sequence() {
+    use std::collections::HashSet;
+
+    // Apeldoorn, 1000 events from 2015-03-27 00:00 UTC, as repeated
+    // `next_event`
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let start = Timespec::new(1427414400, 0);
+    let events: Vec<SolarEvent> = upcoming_events(start, lat, long).take(1000).collect();
+    assert!(events[0].time > start);
+    assert!(events.windows(2).all(|pair| pair[0].time < pair[1].time));
+    assert_eq!(events[0].kind, EventKind::TwilightMorning);
+    assert_eq!(events[999].kind, EventKind::TwilightEvening);
+    let mut t = start;
+    for event in &events[..50] {
+        assert_eq!((event.kind, event.time), next_event(t, lat, long));
+        t = event.time;
+    }
+
+    // Longyearbyen, from October 2015 into the polar night: the sunrise and
+    // sunset stop, then the twilight, but nothing is returned twice
+    let october = Timespec::new(1443657600, 0);
+    let events: Vec<SolarEvent> = upcoming_events(october, 78.22, 15.65).take(200).collect();
+    assert!(events.windows(2).all(|pair| pair[0].time < pair[1].time));
+    assert_eq!(events.iter().cloned().collect::<HashSet<_>>().len(), events.len());
+    let last_of = |kind| events.iter().rposition(|event| event.kind == kind).unwrap();
+    assert!(last_of(EventKind::Sunrise) < last_of(EventKind::TwilightMorning));
+    assert_eq!(events[199].kind, EventKind::Noon);
+    assert_eq!(events[190..].iter().filter(|event| event.kind != EventKind::Noon).count(), 0);
+}
+
+#[test]
+fn daylight_and_night_progress() {
+    // Apeldoorn, 2015-03-27
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let daylight = calculate_daylight(time::at_utc(Timespec::new(1427457600, 0)), lat, long);
+    let minute = Duration::minutes(1);
+    assert!((daylight.progress(daylight.noon) - 0.5).abs() < 0.001);
+    assert_eq!(daylight.progress(daylight.sunrise), 0.0);
+    assert_eq!(daylight.progress(daylight.sunrise - minute), 0.0);
+    assert_eq!(daylight.progress(daylight.sunset), 1.0);
+    assert_eq!(daylight.progress(daylight.sunset + minute), 1.0);
+    let quarter = daylight.sunrise + (daylight.sunset - daylight.sunrise) / 4;
+    assert!((daylight.progress(quarter) - 0.25).abs() < 0.001);
+
+    // the night after it, across midnight into the next day
+    let tomorrow = calculate_daylight(time::at_utc(Timespec::new(1427544000, 0)), lat, long);
+    let night = tomorrow.sunrise - daylight.sunset;
+    assert_eq!(night_progress(daylight.sunset, lat, long), 0.0);
+    assert!(night_progress(daylight.sunset + minute, lat, long) > 0.0);
+    assert!((night_progress(daylight.sunset + night / 2, lat, long) - 0.5).abs() < 0.001);
+    assert!(night_progress(tomorrow.sunrise - minute, lat, long) > 0.99);
+    assert_eq!(night_progress(tomorrow.sunrise, lat, long), 0.0);
+    assert_eq!(night_progress(daylight.noon, lat, long), 0.0);
+
+    // Longyearbyen: the polar day runs around noon, the polar night from the
+    // sunset in October until the sunrise in February
+    let june = calculate_daylight(time::at_utc(Timespec::new(1434888000, 0)), 78.22, 15.65);
+    assert!((june.progress(june.noon) - 0.5).abs() < 0.001);
+    assert_eq!(june.progress(june.noon + Duration::hours(13)), 1.0);
+    let december = calculate_daylight(time::at_utc(Timespec::new(1450699200, 0)), 78.22, 15.65);
+    assert_eq!(december.progress(december.noon), 0.0);
+    let progress = night_progress(december.noon, 78.22, 15.65);
+    assert!(progress > 0.4 && progress < 0.6, "{}", progress);
+    assert_eq!(night_progress(december.noon, 90.0, 0.0), 0.0);
+}
+
+#[test]
+fn night_divided() {
+    // Apeldoorn, the night after 2015-03-27
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(date, lat, long);
+    let tomorrow = calculate_daylight(time::at_utc(Timespec::new(1427544000, 0)), lat, long);
+    let halves = night_divisions(date, lat, long, 2, NightBasis::SunsetSunrise);
+    assert_eq!(halves.len(), 3);
+    assert_eq!((halves[0], halves[2]), (daylight.sunset, tomorrow.sunrise));
+    let average = daylight.sunset + (tomorrow.sunrise - daylight.sunset) / 2;
+    assert!((halves[1] - average).num_seconds().abs() <= 1);
+    // near the solar midnight, earlier as the nights shorten
+    assert!((halves[1] - (daylight.noon + Duration::hours(12))).num_minutes().abs() < 5);
+
+    let thirds = night_divisions(date, lat, long, 3, NightBasis::DuskDawn);
+    assert_eq!((thirds[0], thirds[3]), (daylight.twilight_evening, tomorrow.twilight_morning));
+    let third = (tomorrow.twilight_morning - daylight.twilight_evening) / 3;
+    assert!((thirds[2] - (tomorrow.twilight_morning - third)).num_seconds().abs() <= 1);
+    let dusk_dawn = night_divisions(date, lat, long, 2, NightBasis::DuskDawn);
+    let average = daylight.twilight_evening +
+                  (tomorrow.twilight_morning - daylight.twilight_evening) / 2;
+    assert!((dusk_dawn[1] - average).num_seconds().abs() <= 1);
+    assert_eq!(night_divisions(date, lat, long, 1, NightBasis::SunsetSunrise),
+               vec![daylight.sunset, tomorrow.sunrise]);
+
+    // Longyearbyen: no night in the polar day, nor starting in the polar
+    // night; the last sunset in October starts a night of months
+    let june = time::at_utc(Timespec::new(1434888000, 0));
+    assert!(night_divisions(june, 78.22, 15.65, 2, NightBasis::SunsetSunrise).is_empty());
+    let december = time::at_utc(Timespec::new(1450699200, 0));
+    assert!(night_divisions(december, 78.22, 15.65, 2, NightBasis::SunsetSunrise).is_empty());
+    let longest = (0..60)
+        .map(|day| time::at_utc(Timespec::new(1443700800 + day * 86400, 0)))
+        .map(|date| night_divisions(date, 78.22, 15.65, 2, NightBasis::SunsetSunrise))
+        .filter(|divisions| !divisions.is_empty())
+        .map(|divisions| divisions[2] - divisions[0])
+        .max()
+        .unwrap();
+    assert!(longest > Duration::days(90), "{}", longest);
+}
+
+#[test]
+fn light_phases() {
+    use {calculate_daylight_precise, is_daytime, is_twilight};
+
+    // Apeldoorn, through 2015-03-27 and the night after it, around the
+    // events at the position of the sun at each moment
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight_precise(date, lat, long);
+    assert_eq!(light_phase(daylight.noon, lat, long).phase, LightPhase::Day);
+    let minute = Duration::minutes(1);
+    assert_eq!(light_phase(daylight.twilight_evening - minute, lat, long).phase,
+               LightPhase::CivilTwilight);
+    assert_eq!(light_phase(daylight.twilight_evening + minute, lat, long).phase,
+               LightPhase::NauticalTwilight);
+    let mut phases = Vec::new();
+    for step in 0..(24 * 6) {
+        let t = daylight.noon + Duration::minutes(10 * step);
+        let level = light_phase(t, lat, long);
+        assert_eq!(level.phase == LightPhase::Day, is_daytime(t, lat, long));
+        assert_eq!(level.phase == LightPhase::CivilTwilight,
+                   is_twilight(t, lat, long, 6.0));
+        if phases.last() != Some(&level.phase) {
+            phases.push(level.phase);
+        }
+    }
+    // in March the sun sinks below 18 degrees around midnight
+    assert_eq!(phases,
+               [LightPhase::Day,
+                LightPhase::CivilTwilight,
+                LightPhase::NauticalTwilight,
+                LightPhase::AstronomicalTwilight,
+                LightPhase::Night,
+                LightPhase::AstronomicalTwilight,
+                LightPhase::NauticalTwilight,
+                LightPhase::CivilTwilight,
+                LightPhase::Day]);
+
+    // Longyearbyen in the polar night: at most the nautical twilight, at
+    // noon, with the sun about 12 degrees below the horizon
+    let december = calculate_daylight(time::at_utc(Timespec::new(1450699200, 0)), 78.22, 15.65);
+    for step in 0..(24 * 6) {
+        let level = light_phase(december.noon + Duration::minutes(10 * step), 78.22, 15.65);
+        assert!(level.phase != LightPhase::Day && level.phase != LightPhase::CivilTwilight,
+                "{:?}",
+                level);
+    }
+    let noon = light_phase(december.noon, 78.22, 15.65);
+    assert_eq!(noon.phase, LightPhase::NauticalTwilight);
+    assert!((noon.altitude - december.sun_altitude).abs() < 0.1, "{:?}", noon);
+    let midnight = light_phase(december.noon + Duration::hours(12), 78.22, 15.65);
+    assert_eq!(midnight.phase, LightPhase::Night);
+}
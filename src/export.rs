@@ -0,0 +1,271 @@
+//! Export of the daylight of a range of days in plain text formats.
+
+use std::io::{self, Write};
+
+use time::{self, Timespec, Tm};
+
+use {Daylight, DaylightIter, EventKind, calculate_daylight};
+
+/// Version of the JSON object written by `Daylight::to_json`
+const JSON_VERSION: u32 = 1;
+
+/// Columns of the CSV export
+const CSV_HEADER: &str = "date,twilight_morning,sunrise,noon,sunset,twilight_evening,daylength,\
+                          declination,sun_altitude";
+
+/// Formats the instant as an ISO 8601 timestamp in UTC, in whole seconds
+fn iso8601(ts: Timespec) -> String {
+    time::at_utc(Timespec::new(ts.sec, 0)).rfc3339().to_string()
+}
+
+/// Writes the daylight of `days` consecutive days from the start date as
+/// CSV: a header row and a row per day (see `DaylightIter`).
+///
+/// The date is written as `YYYY-MM-DD`, the events as ISO 8601 timestamps in
+/// UTC (like `2015-03-27T05:22:47Z`), the day length in whole seconds and the
+/// declination and maximum altitude of the sun in angle degrees. Numbers
+/// always use a `.` as decimal point, whatever the locale, and every row,
+/// including the last one, ends with a single `\n`.
+pub fn to_csv<W: Write>(start: Tm,
+                        days: u32,
+                        latitude: f64,
+                        longitude: f64,
+                        mut writer: W)
+                        -> io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for (date, daylight) in DaylightIter::new(start, latitude, longitude).take(days as usize) {
+        writeln!(writer,
+                 "{},{},{},{},{},{},{},{:.4},{:.4}",
+                 date.strftime("%Y-%m-%d").unwrap(),
+                 iso8601(daylight.twilight_morning),
+                 iso8601(daylight.sunrise),
+                 iso8601(daylight.noon),
+                 iso8601(daylight.sunset),
+                 iso8601(daylight.twilight_evening),
+                 daylight.daylength.num_seconds(),
+                 daylight.declination,
+                 daylight.sun_altitude)?;
+    }
+    Ok(())
+}
+
+/// Formats an angle as a JSON number, `null` when it is not finite
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+impl Daylight {
+    /// Returns the result as a JSON object, without depending on serde:
+    ///
+    /// ```text
+    /// {"version":1,"polar":null,"twilight_morning":"2015-03-27T04:55:30Z",
+    ///  "sunrise":"2015-03-27T05:22:47Z","noon":"2015-03-27T11:41:27Z",
+    ///  "sunset":"2015-03-27T18:00:07Z","twilight_evening":"2015-03-27T18:27:24Z",
+    ///  "daylength":45440,"declination":2.777312226943968,
+    ///  "sun_altitude":40.560645560277294}
+    /// ```
+    ///
+    /// `version` is 1 for this layout; fields are only added in later
+    /// versions. `polar` is `"day"` when the sun does not set, `"night"` when
+    /// it does not rise and `null` otherwise. The events are ISO 8601
+    /// timestamps in UTC, in whole seconds, or `null` when they do not occur
+    /// (see `Daylight::events`); the noon always occurs. `daylength` is in
+    /// whole seconds, `declination` and `sun_altitude` in angle degrees.
+    pub fn to_json(&self) -> String {
+        let mut events = [None; 5];
+        for event in self.events() {
+            events[event.kind as usize] = Some(event.time);
+        }
+        let event = |kind: EventKind| match events[kind as usize] {
+            Some(ts) => format!("\"{}\"", iso8601(ts)),
+            None => "null".to_string(),
+        };
+        let polar = if self.is_polar_day() {
+            "\"day\""
+        } else if self.is_polar_night() {
+            "\"night\""
+        } else {
+            "null"
+        };
+        format!("{{\"version\":{},\"polar\":{},\"twilight_morning\":{},\"sunrise\":{},\
+                 \"noon\":{},\"sunset\":{},\"twilight_evening\":{},\"daylength\":{},\
+                 \"declination\":{},\"sun_altitude\":{}}}",
+                JSON_VERSION,
+                polar,
+                event(EventKind::TwilightMorning),
+                event(EventKind::Sunrise),
+                event(EventKind::Noon),
+                event(EventKind::Sunset),
+                event(EventKind::TwilightEvening),
+                self.daylength.num_seconds(),
+                json_number(self.declination),
+                json_number(self.sun_altitude))
+    }
+}
+
+/// Returns the number of grid lines from `from` to `to` (inclusive) at the
+/// resolution, allowing for rounding errors in their difference
+pub fn grid_lines(from: f64, to: f64, resolution: f64) -> i64 {
+    ((to - from) / resolution + 1e-9).floor() as i64 + 1
+}
+
+/// Calculates the daylight at the date on a grid of points and returns it as
+/// a GeoJSON `FeatureCollection` of `Point`s.
+///
+/// `bbox` is `[west, south, east, north]` in angle degrees, like the GeoJSON
+/// bounding box; a `west` greater than `east` spans the antimeridian, and
+/// the points are wrapped into longitudes from -180 to 180 (for the whole
+/// globe from -180 to 180, the points at 180 are left out as they are those
+/// at -180). The points start at the south west corner, `resolution_deg`
+/// apart; panics when the resolution is not positive.
+///
+/// Each point has the properties `daylength_seconds`, and `sunrise` and
+/// `sunset` as ISO 8601 timestamps in UTC, which are `null` where the sun
+/// does not rise or set.
+pub fn daylength_grid_geojson(date: Tm, bbox: [f64; 4], resolution_deg: f64) -> String {
+    assert!(resolution_deg > 0.0, "resolution must be positive");
+    let [west, south, east, north] = bbox;
+    let span = if west > east { east + 360.0 - west } else { east - west };
+    let columns = if span >= 360.0 {
+        grid_lines(0.0, 360.0, resolution_deg) - 1
+    } else {
+        grid_lines(0.0, span, resolution_deg)
+    };
+
+    let mut features = Vec::new();
+    for row in 0..grid_lines(south, north, resolution_deg) {
+        let latitude = south + row as f64 * resolution_deg;
+        for column in 0..columns {
+            let longitude = (west + column as f64 * resolution_deg + 180.0).rem_euclid(360.0) -
+                            180.0;
+            let daylight = calculate_daylight(date, latitude, longitude);
+            let (sunrise, sunset) = if daylight.is_polar_day() || daylight.is_polar_night() {
+                ("null".to_string(), "null".to_string())
+            } else {
+                (format!("\"{}\"", iso8601(daylight.sunrise)),
+                 format!("\"{}\"", iso8601(daylight.sunset)))
+            };
+            features.push(format!("{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"Point\",\
+                                   \"coordinates\":[{},{}]}},\"properties\":{{\
+                                   \"daylength_seconds\":{},\"sunrise\":{},\"sunset\":{}}}}}",
+                                  longitude,
+                                  latitude,
+                                  daylight.daylength.num_seconds(),
+                                  sunrise,
+                                  sunset));
+        }
+    }
+    format!("{{\"type\":\"FeatureCollection\",\"features\":[{}]}}",
+            features.join(","))
+}
+
+#[test]
+fn csv_export() {
+    // 2024, Apeldoorn
+    let start = time::at_utc(Timespec::new(1704110400, 0));
+    let mut output = Vec::new();
+    to_csv(start, 366, 52.22, 5.97, &mut output).unwrap();
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.ends_with("\n") && !text.ends_with("\n\n"));
+
+    let rows: Vec<Vec<&str>> = text.lines().map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), 367);
+    assert_eq!(rows[0].len(), 9);
+    assert!(rows.iter().all(|row| row.len() == rows[0].len()));
+    assert_eq!(rows[1][0], "2024-01-01");
+    assert_eq!(rows[366][0], "2024-12-31");
+    assert!(rows[1..].windows(2).all(|pair| pair[0][0] < pair[1][0] && pair[0][3] < pair[1][3]));
+
+    let day = &rows[87];
+    assert_eq!(day[0], "2024-03-27");
+    assert!(day[2].starts_with("2024-03-27T05:") && day[2].ends_with('Z'), "{:?}", day);
+    let daylength: i64 = day[6].parse().unwrap();
+    assert!(daylength > 12 * 3600 && daylength < 13 * 3600, "{:?}", day);
+    let declination: f64 = day[7].parse().unwrap();
+    assert!(declination > 2.5 && declination < 3.5, "{:?}", day);
+}
+
+#[test]
+fn json_export() {
+    extern crate serde_json;
+
+    use calculate_daylight;
+
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let json: serde_json::Value = serde_json::from_str(&daylight.to_json()).unwrap();
+    assert_eq!(json["version"], 1);
+    assert!(json["polar"].is_null());
+    assert_eq!(json["twilight_morning"], "2015-03-27T04:55:30Z");
+    assert_eq!(json["sunrise"], "2015-03-27T05:22:47Z");
+    assert_eq!(json["sunset"], "2015-03-27T18:00:07Z");
+    assert_eq!(json["daylength"], 45440);
+    assert_eq!(json["declination"].as_f64(), Some(daylight.declination));
+    assert_eq!(json.as_object().unwrap().len(), 10);
+
+    // Longyearbyen, polar day and polar night
+    for &(input, polar) in &[(1434888000, "day"), (1450699200, "night")] {
+        let daylight = calculate_daylight(time::at_utc(Timespec::new(input, 0)), 78.22, 15.65);
+        let json: serde_json::Value = serde_json::from_str(&daylight.to_json()).unwrap();
+        assert_eq!(json["polar"], polar);
+        for field in &["twilight_morning", "sunrise", "sunset", "twilight_evening"] {
+            assert!(json[field].is_null(), "{}: {}", field, json);
+        }
+        assert!(json["noon"].is_string());
+    }
+
+    let mut invalid = daylight;
+    invalid.sun_altitude = f64::NAN;
+    let json: serde_json::Value = serde_json::from_str(&invalid.to_json()).unwrap();
+    assert!(json["sun_altitude"].is_null());
+}
+
+#[test]
+fn geojson_grid() {
+    extern crate serde_json;
+
+    // the whole globe on 2015-06-21, every 10 degrees
+    let date = time::at_utc(Timespec::new(1434888000, 0));
+    let geojson = daylength_grid_geojson(date, [-180.0, -90.0, 180.0, 90.0], 10.0);
+    let json: serde_json::Value = serde_json::from_str(&geojson).unwrap();
+    assert_eq!(json["type"], "FeatureCollection");
+    let features = json["features"].as_array().unwrap();
+    assert_eq!(features.len(), 19 * 36);
+
+    for feature in features {
+        let coordinates = feature["geometry"]["coordinates"].as_array().unwrap();
+        let (longitude, latitude) = (coordinates[0].as_f64().unwrap(),
+                                     coordinates[1].as_f64().unwrap());
+        assert!((-180.0..180.0).contains(&longitude), "{}", feature);
+        let properties = &feature["properties"];
+        let daylength = properties["daylength_seconds"].as_i64().unwrap();
+        if latitude == 0.0 {
+            assert!((daylength - 12 * 3600).abs() < 600, "{}", feature);
+        }
+        if latitude.abs() == 90.0 {
+            // the sun does not set at the north pole, nor rise at the south
+            assert!(properties["sunrise"].is_null() && properties["sunset"].is_null(),
+                    "{}",
+                    feature);
+            assert_eq!(daylength, if latitude > 0.0 { 86400 } else { 0 });
+        } else if latitude.abs() < 60.0 {
+            assert!(properties["sunrise"].is_string(), "{}", feature);
+        }
+    }
+
+    // across the antimeridian
+    let geojson = daylength_grid_geojson(date, [170.0, 0.0, -170.0, 0.0], 10.0);
+    let json: serde_json::Value = serde_json::from_str(&geojson).unwrap();
+    let longitudes: Vec<f64> = json["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|feature| feature["geometry"]["coordinates"][0].as_f64().unwrap())
+        .collect();
+    assert_eq!(longitudes, vec![170.0, -180.0, -170.0]);
+}
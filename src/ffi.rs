@@ -0,0 +1,125 @@
+//! C interface to the calculation (enabled with the `ffi` feature).
+//!
+//! The header `include/daylight.h` is generated from this module by
+//! cbindgen into `OUT_DIR` when building with the feature, and a test checks
+//! that the checked-in copy is up to date; update it with
+//! `DAYLIGHT_UPDATE_HEADER=1 cargo build --features ffi`. Link the static
+//! library `libdaylight.a`, built with
+//! `cargo rustc --lib --release --features ffi --crate-type staticlib`
+//! (the manifest only lists the Rust library, which also builds without
+//! `std`). Only C types cross the boundary: the events are unix
+//! seconds, the angles degrees. A panic inside the calculation is caught and
+//! returned as an error code.
+
+use std::panic;
+
+use time::{self, Timespec};
+
+use {DaylightError, calculate_daylight_checked};
+
+/// The daylight is calculated
+pub const DAYLIGHT_OK: i32 = 0;
+/// The result pointer is null
+pub const DAYLIGHT_ERROR_NULL: i32 = 1;
+/// The latitude is not within -90 to 90 degrees
+pub const DAYLIGHT_ERROR_LATITUDE: i32 = 2;
+/// The longitude is not a finite number
+pub const DAYLIGHT_ERROR_LONGITUDE: i32 = 3;
+/// The time is outside of the supported years -9999 to 9999
+pub const DAYLIGHT_ERROR_TIME: i32 = 4;
+/// The calculation failed unexpectedly
+pub const DAYLIGHT_ERROR_INTERNAL: i32 = 5;
+
+/// Result of the daylight calculation (see `Daylight`); the events are in
+/// seconds since 1970-01-01 00:00 UTC
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CDaylight {
+    pub twilight_morning: i64,
+    pub sunrise: i64,
+    pub noon: i64,
+    pub sunset: i64,
+    pub twilight_evening: i64,
+    /// Duration of the day in seconds
+    pub daylength: i64,
+    /// Declination of the sun in angle degrees
+    pub declination: f64,
+    /// Sun altitude in angle degrees
+    pub sun_altitude: f64,
+}
+
+fn error_code(error: DaylightError) -> i32 {
+    match error {
+        DaylightError::LatitudeOutOfRange(_) => DAYLIGHT_ERROR_LATITUDE,
+        DaylightError::LongitudeNotFinite(_) |
+        DaylightError::LongitudeOutOfRange(_) => DAYLIGHT_ERROR_LONGITUDE,
+        DaylightError::TimeOutOfRange => DAYLIGHT_ERROR_TIME,
+        _ => DAYLIGHT_ERROR_INTERNAL,
+    }
+}
+
+/// Calculates civil twilight (am/pm) and sunrise and sunset at the instant
+/// `unix_secs` (see `calculate_daylight`) into `out`; returns `DAYLIGHT_OK`,
+/// or one of the error codes and leaves `out` untouched.
+///
+/// # Safety
+///
+/// `out` must be null or point to a writable `CDaylight`.
+#[no_mangle]
+pub unsafe extern "C" fn daylight_calculate(unix_secs: i64,
+                                            lat: f64,
+                                            lon: f64,
+                                            out: *mut CDaylight)
+                                            -> i32 {
+    if out.is_null() {
+        return DAYLIGHT_ERROR_NULL;
+    }
+    // years -9999 to 9999, checked before `time` converts the instant
+    const LIMIT: i64 = 10_000 * 366 * 86_400;
+    if !(-LIMIT..=LIMIT).contains(&unix_secs) {
+        return DAYLIGHT_ERROR_TIME;
+    }
+    let result = panic::catch_unwind(|| {
+        calculate_daylight_checked(time::at_utc(Timespec::new(unix_secs, 0)), lat, lon)
+    });
+    match result {
+        Ok(Ok(daylight)) => {
+            *out = CDaylight {
+                twilight_morning: daylight.twilight_morning.sec,
+                sunrise: daylight.sunrise.sec,
+                noon: daylight.noon.sec,
+                sunset: daylight.sunset.sec,
+                twilight_evening: daylight.twilight_evening.sec,
+                daylength: daylight.daylength.num_seconds(),
+                declination: daylight.declination,
+                sun_altitude: daylight.sun_altitude,
+            };
+            DAYLIGHT_OK
+        }
+        Ok(Err(error)) => error_code(error),
+        Err(_) => DAYLIGHT_ERROR_INTERNAL,
+    }
+}
+
+#[test]
+fn ffi_calculate() {
+    use std::ptr;
+
+    let mut out = CDaylight::default();
+    let (lat, lon) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    assert_eq!(unsafe { daylight_calculate(1427457600, lat, lon, &mut out) }, DAYLIGHT_OK);
+    assert_eq!([out.twilight_morning, out.sunrise, out.noon, out.sunset, out.twilight_evening],
+               [1427432130, 1427433767, 1427456487, 1427479207, 1427480844]);
+    assert_eq!(out.daylength, 45440);
+
+    let before = out;
+    unsafe {
+        assert_eq!(daylight_calculate(1427457600, 91.0, 0.0, &mut out), DAYLIGHT_ERROR_LATITUDE);
+        assert_eq!(daylight_calculate(1427457600, 0.0, f64::NAN, &mut out),
+                   DAYLIGHT_ERROR_LONGITUDE);
+        assert_eq!(daylight_calculate(i64::MAX, 0.0, 0.0, &mut out), DAYLIGHT_ERROR_TIME);
+        assert_eq!(daylight_calculate(1427457600, 0.0, 0.0, ptr::null_mut()),
+                   DAYLIGHT_ERROR_NULL);
+    }
+    assert_eq!(out, before);
+}
@@ -0,0 +1,274 @@
+//! The default calculation in integer arithmetic only, for targets without
+//! a floating point unit at all (like an 8-bit microcontroller), where even
+//! the `f32` of `calculate_daylight_f32` is emulated in software; also
+//! available without the `std` feature.
+//!
+//! The angles are in degrees as Q16.16 fixed-point numbers (`Q16`), and the
+//! sine is interpolated linearly in a table of whole degrees; the inverse
+//! sine is found by bisection on the table, and the right ascension follows
+//! from the reduction to the equator, a short series in the ecliptic
+//! longitude instead of an arctangent. The day count is split into cycles
+//! of 1461 days like in `calculate_daylight_f32`, so that the mean longitude
+//! and anomaly of the sun are exact in 64-bit integers for any date.
+//!
+//! The events are rounded to whole minutes past midnight. Between 60° south
+//! and 60° north they are within a minute of `calculate_daylight_seconds`
+//! at 12:00 UTC of the date over the years 1900 to 2100 (the test below
+//! checks a year at several latitudes), the difference being the rounding
+//! to the minute; beyond those latitudes the sunrise becomes sensitive to
+//! the declination, like in `calculate_daylight_f32`.
+
+/// Sine of the whole degrees 0 to 90, times 2^30
+const SINE: [i32; 91] = [
+    0, 18739379, 37473049, 56195305, 74900443, 93582766, 112236583, 130856211, 149435979,
+    167970228, 186453311, 204879599, 223243478, 241539355, 259761657, 277904834, 295963357,
+    313931728, 331804471, 349576144, 367241333, 384794656, 402230767, 419544355, 436730145,
+    453782903, 470697435, 487468587, 504091252, 520560366, 536870912, 553017922, 568996477,
+    584801711, 600428808, 615873009, 631129609, 646193961, 661061475, 675727625, 690187940,
+    704438018, 718473518, 732290163, 745883746, 759250125, 772385229, 785285058, 797945680,
+    810363241, 822533958, 834454122, 846120104, 857528349, 868675383, 879557810, 890172315,
+    900515665, 910584710, 920376381, 929887697, 939115760, 948057759, 956710970, 965072759,
+    973140576, 980911966, 988384560, 995556083, 1002424350, 1008987269, 1015242840, 1021189159,
+    1026824413, 1032146887, 1037154959, 1041847103, 1046221891, 1050277989, 1054014162,
+    1057429273, 1060522280, 1063292242, 1065738315, 1067859754, 1069655912, 1071126243,
+    1072270298, 1073087729, 1073578288, 1073741824,
+];
+
+/// One in the fixed-point sine (2^30)
+const ONE: i32 = 1 << 30;
+const RIGHT_ANGLE: i32 = 90 << 16;
+const HALF_CIRCLE: i32 = 180 << 16;
+const FULL_CIRCLE: i32 = 360 << 16;
+/// A full circle in degrees times 2^32, the unit of the mean angles
+const FULL_CIRCLE_32: i64 = 360 << 32;
+/// Days from 1970-01-01 to 2000-01-01
+const DAYS_TO_2000: i64 = 10_957;
+/// Days of the cycle in which the mean longitude and anomaly of the sun
+/// advance by nearly four revolutions
+const CYCLE_DAYS: i64 = 1461;
+/// Mean longitude of the sun at 2000-01-01 00:00 and its advance per day,
+/// in degrees times 2^32 (280.461 and 0.9856474, see `fnsun`)
+const MEAN_LONGITUDE: i64 = 1_204_570_822_803;
+const MEAN_LONGITUDE_RATE: i64 = 4_233_323_348;
+/// Mean anomaly of the sun at 2000-01-01 00:00 and its advance per day, in
+/// degrees times 2^32 (357.528 and 0.9856003)
+const MEAN_ANOMALY: i64 = 1_535_571_067_404;
+const MEAN_ANOMALY_RATE: i64 = 4_233_121_055;
+/// Terms of the equation of center (1.915 and 0.02 degrees)
+const CENTER_1: i32 = 125_501;
+const CENTER_2: i32 = 1_311;
+/// Obliquity of the ecliptic at 2000-01-01 (23.439 degrees), which
+/// decreases by 0.0000004 degrees a day, or 131072 / 10^7 of the Q16.16
+/// degrees per half day
+const OBLIQUITY: i32 = 1_536_098;
+const OBLIQUITY_RATE: i64 = 131_072;
+/// Terms of the reduction to the equator, the difference between the
+/// ecliptic longitude and the right ascension (2.466 and 0.053 degrees)
+const REDUCTION_1: i32 = 161_612;
+const REDUCTION_2: i32 = 3_473;
+/// Depression of the centre of the sun at sunrise and sunset, half the
+/// diameter of the sun and the refraction (0.265 + 34/60 degrees)
+const DEPRESSION: i32 = 54_504;
+const MINUTES_IN_DAY: i64 = 1440;
+
+/// Angle in degrees as a Q16.16 fixed-point number: the degrees times 65536
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Q16(pub i32);
+
+impl Q16 {
+    /// Returns the whole degrees
+    pub const fn from_degrees(degrees: i16) -> Q16 {
+        Q16((degrees as i32) << 16)
+    }
+
+    /// Returns the thousandths of degrees (52220 is 52.22 degrees), within
+    /// 1/65536 degree
+    pub const fn from_millidegrees(millidegrees: i32) -> Q16 {
+        Q16((millidegrees as i64 * 65_536 / 1000) as i32)
+    }
+}
+
+/// Result of the fixed-point calculation in whole minutes past midnight on
+/// the clock of the UTC offset; an event on the day before or after wraps
+/// around midnight
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DaylightMinutes {
+    pub sunrise: u16,
+    pub noon: u16,
+    pub sunset: u16,
+    /// Duration of the day in minutes, 0 in the polar night and 1440 in the
+    /// polar day (when sunrise and sunset fall at midnight, like in
+    /// `calculate_daylight_seconds`)
+    pub daylength: u16,
+}
+
+/// Sine of the Q16.16 angle, times 2^30
+fn sin(angle: i32) -> i32 {
+    let angle = angle.rem_euclid(FULL_CIRCLE);
+    let (angle, sign) = if angle >= HALF_CIRCLE {
+        (angle - HALF_CIRCLE, -1)
+    } else {
+        (angle, 1)
+    };
+    let angle = if angle > RIGHT_ANGLE {
+        HALF_CIRCLE - angle
+    } else {
+        angle
+    };
+    let (index, fraction) = ((angle >> 16) as usize, (angle & 0xffff) as i64);
+    let value = match SINE.get(index + 1) {
+        Some(&next) => SINE[index] + (((next - SINE[index]) as i64 * fraction) >> 16) as i32,
+        None => SINE[index],
+    };
+    sign * value
+}
+
+fn cos(angle: i32) -> i32 {
+    sin(angle + RIGHT_ANGLE)
+}
+
+/// Inverse of `sin` within -90 to 90 degrees, by bisection
+fn asin(value: i32) -> i32 {
+    let (mut low, mut high) = (-RIGHT_ANGLE, RIGHT_ANGLE);
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if sin(middle) <= value {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Returns the Q16.16 coefficient times the sine of the angle
+fn times_sin(coefficient: i32, angle: i32) -> i32 {
+    ((coefficient as i64 * sin(angle) as i64) >> 30) as i32
+}
+
+/// Reduces the angle to the range -180 (exclusive) to 180 degrees
+fn range_signed(angle: i32) -> i32 {
+    let angle = angle.rem_euclid(FULL_CIRCLE);
+    if angle > HALF_CIRCLE {
+        angle - FULL_CIRCLE
+    } else {
+        angle
+    }
+}
+
+/// Returns the mean angle `at + rate * d` (in degrees times 2^32) at 12:00
+/// UTC of the day since 2000, as a Q16.16 angle within 0 to 360 degrees
+fn mean_angle(at: i64, rate: i64, days: i64) -> i32 {
+    let (cycles, day) = (days.div_euclid(CYCLE_DAYS), days.rem_euclid(CYCLE_DAYS));
+    let cycle = (rate * CYCLE_DAYS) % FULL_CIRCLE_32;
+    // half a day after the midnight at which the day count starts
+    let angle = at + (cycles * cycle).rem_euclid(FULL_CIRCLE_32) + rate * (2 * day + 1) / 2;
+    (angle.rem_euclid(FULL_CIRCLE_32) >> 16) as i32
+}
+
+/// Hour angle at which the centre of the sun is `depression` below the
+/// horizon, like `calculate_angle`
+fn hour_angle(latitude: i32, declination: i32, depression: i32) -> i32 {
+    let df = if latitude < 0 { -depression } else { depression };
+    let numerator = sin(declination + df) as i64 * sin(latitude) as i64;
+    let denominator = (cos(declination + df) as i64 * cos(latitude) as i64) >> 30;
+    let f = if denominator == 0 {
+        numerator.signum() * ONE as i64
+    } else {
+        (numerator / denominator).clamp(-ONE as i64, ONE as i64)
+    };
+    asin(f as i32) + RIGHT_ANGLE
+}
+
+/// Rounds the Q16.16 minutes to whole minutes past midnight
+fn to_minutes(minutes: i64) -> u16 {
+    ((minutes + 0x8000) >> 16).rem_euclid(MINUTES_IN_DAY) as u16
+}
+
+/// Calculate sunrise, solar noon and sunset of the date, given as days
+/// since 1970-01-01 (the unix seconds divided by 86400), at the latitude
+/// (within -90 to 90 degrees) and longitude in minutes past midnight on the
+/// clock `utc_offset` minutes ahead of UTC; the position of the sun is that
+/// at 12:00 UTC of the date (see the accuracy above)
+pub fn calculate_daylight(days: i32,
+                          latitude: Q16,
+                          longitude: Q16,
+                          utc_offset: i16)
+                          -> DaylightMinutes {
+    let days = days as i64 - DAYS_TO_2000;
+
+    // find the ecliptic longitude of the sun (see `fnsun`)
+    let mean_longitude = mean_angle(MEAN_LONGITUDE, MEAN_LONGITUDE_RATE, days);
+    let g = mean_angle(MEAN_ANOMALY, MEAN_ANOMALY_RATE, days);
+    let ecliptic_longitude = (mean_longitude + times_sin(CENTER_1, g) +
+                              times_sin(CENTER_2, 2 * g))
+        .rem_euclid(FULL_CIRCLE);
+    let obliq = OBLIQUITY - ((2 * days + 1) * OBLIQUITY_RATE / 10_000_000) as i32;
+
+    // the declination, and the difference of the mean longitude and the
+    // right ascension for the equation of time
+    let declination = asin(((sin(obliq) as i64 * sin(ecliptic_longitude) as i64) >> 30) as i32);
+    let difference = range_signed(mean_longitude - ecliptic_longitude +
+                                  times_sin(REDUCTION_1, 2 * ecliptic_longitude) -
+                                  times_sin(REDUCTION_2, 4 * ecliptic_longitude));
+
+    // in Q16.16 minutes past midnight, 4 minutes per degree
+    let halfday = 4 * hour_angle(latitude.0, declination, DEPRESSION) as i64;
+    let noon = ((720 + utc_offset as i64) << 16) - 4 * (longitude.0 as i64 + difference as i64);
+    DaylightMinutes {
+        sunrise: to_minutes(noon - halfday),
+        noon: to_minutes(noon),
+        sunset: to_minutes(noon + halfday),
+        daylength: ((2 * halfday + 0x8000) >> 16) as u16,
+    }
+}
+
+#[test]
+fn daylight_fixed_compared_to_f64() {
+    use calculate_daylight_seconds;
+
+    // Apeldoorn, 2015-03-27, in CET (see the golden values)
+    let (latitude, longitude) = (Q16::from_millidegrees(52_217), Q16::from_millidegrees(5_967));
+    let daylight = calculate_daylight(16_521, latitude, longitude, 60);
+    assert_eq!(daylight,
+               DaylightMinutes {
+                   sunrise: 6 * 60 + 23,
+                   noon: 12 * 60 + 41,
+                   sunset: 19 * 60,
+                   daylength: 12 * 60 + 37,
+               });
+
+    // every day of 2024, and some days far from 2000
+    let days = (19_723..19_723 + 366).chain([-25_567, -7_305, 29_219, 47_482].iter().cloned());
+    let minutes = |secs: i64, midnight: i64| ((secs - midnight) as f64 / 60.0).round() as i64;
+    let mut max_difference = 0;
+    for day in days {
+        for &latitude in &[-59_900, -33_870, 0, 23_400, 35_410, 52_220, 59_900] {
+            for &longitude in &[-122_420, 0, 5_970, 139_410, 179_900] {
+                let (latitude, longitude) = (Q16::from_millidegrees(latitude),
+                                             Q16::from_millidegrees(longitude));
+                let fixed = calculate_daylight(day, latitude, longitude, 0);
+                let midnight = day as i64 * 86_400;
+                let double = calculate_daylight_seconds(midnight + 43_200,
+                                                        latitude.0 as f64 / 65_536.0,
+                                                        longitude.0 as f64 / 65_536.0);
+                let pairs = [(fixed.sunrise, minutes(double.sunrise, midnight)),
+                             (fixed.noon, minutes(double.noon, midnight)),
+                             (fixed.sunset, minutes(double.sunset, midnight)),
+                             (fixed.daylength, (double.daylength as f64 / 60.0).round() as i64)];
+                for &(fixed, double) in &pairs {
+                    // an event on the day before or after wraps around
+                    let difference = (fixed as i64 - double).rem_euclid(1440);
+                    max_difference = max_difference.max(difference.min(1440 - difference));
+                }
+            }
+        }
+    }
+    assert!(max_difference <= 1, "maximum difference {} minutes", max_difference);
+
+    // the polar day and night
+    let summer = calculate_daylight(19_895, Q16::from_degrees(80), Q16::from_degrees(0), 0);
+    let winter = calculate_daylight(19_895, Q16::from_degrees(-80), Q16::from_degrees(0), 0);
+    assert_eq!((summer.daylength, winter.daylength), (1440, 0));
+    assert_eq!(summer.sunrise, summer.sunset);
+}
@@ -0,0 +1,62 @@
+//! Coordinates from the points of `geo-types` (enabled with the `geo`
+//! feature).
+//!
+//! A `Point` holds the longitude in `x` and the latitude in `y`, the
+//! reverse of the order of `Coordinates::try_new`; the conversions below
+//! swap them, so the order is right at every call site.
+
+use std::convert::TryFrom;
+
+use geo_types::Point;
+
+use {Coordinates, DaylightError, Latitude, Longitude};
+
+/// Converts a point of which `x` is the longitude and `y` the latitude;
+/// fails when either is out of range
+impl TryFrom<Point<f64>> for Coordinates {
+    type Error = DaylightError;
+
+    fn try_from(point: Point<f64>) -> Result<Coordinates, DaylightError> {
+        Ok(Coordinates::new(Latitude::try_new(point.y())?, Longitude::try_new(point.x())?))
+    }
+}
+
+/// Converts to a point of which `x` is the longitude and `y` the latitude
+impl From<Coordinates> for Point<f64> {
+    fn from(coordinates: Coordinates) -> Point<f64> {
+        Point::new(coordinates.longitude().degrees(), coordinates.latitude().degrees())
+    }
+}
+
+#[test]
+fn geo_point_axis_order() {
+    use time::{Timespec, at_utc};
+
+    use {calculate_daylight, calculate_daylight_at};
+
+    // Apeldoorn, of which the latitude and longitude differ enough that a
+    // swap changes the daylight
+    let tm = at_utc(Timespec::new(1427457600, 0));
+    let point = Point::new(5.97, 52.22);
+    let coordinates = Coordinates::try_from(point).unwrap();
+    assert_eq!(coordinates.latitude().degrees(), 52.22);
+    assert_eq!(coordinates.longitude().degrees(), 5.97);
+    assert_eq!(Point::from(coordinates), point);
+
+    let daylight = calculate_daylight_at(tm, point).unwrap();
+    assert_eq!(daylight, calculate_daylight(tm, 52.22, 5.97));
+    assert_eq!(calculate_daylight_at(tm, coordinates), Ok(daylight));
+    let swapped = calculate_daylight_at(tm, Point::new(52.22, 5.97)).unwrap();
+    assert_ne!(swapped.sunrise, daylight.sunrise);
+
+    // San Francisco, in the west and so with a negative `x`
+    let point = Point::new(-122.42, 37.77);
+    assert_eq!(calculate_daylight_at(tm, point),
+               Ok(calculate_daylight(tm, 37.77, -122.42)));
+
+    // a swapped point beyond the latitude range fails
+    assert_eq!(calculate_daylight_at(tm, Point::new(37.77, -122.42)),
+               Err(DaylightError::LatitudeOutOfRange(-122.42)));
+    assert_eq!(Coordinates::try_from(Point::new(181.0, 0.0)),
+               Err(DaylightError::LongitudeOutOfRange(181.0)));
+}
@@ -0,0 +1,130 @@
+//! Day length on a regular grid of locations, like for a raster of the
+//! globe.
+
+use std::ops::RangeInclusive;
+
+use time::Tm;
+
+use export::grid_lines;
+use {DaylightOptions, LatitudeTrig, SunEphemeris, daylight_hours_to_timespec, hours_to_duration,
+     midnight, round_duration, round_timespec, solar_parameters_at, tm_to_timespec};
+
+/// Sunrise, sunset and day length of a cell of a `Grid`, in whole seconds
+/// (see `DaylightSeconds`); during polar day and night the sunrise and
+/// sunset are those of `calculate_daylight`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridCell {
+    pub sunrise: i64,
+    pub sunset: i64,
+    pub daylength: i64,
+}
+
+/// Result of `daylight_grid`: a cell for each latitude and longitude
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+    /// Latitudes of the rows in angle degrees
+    pub latitudes: Vec<f64>,
+    /// Longitudes of the columns in angle degrees
+    pub longitudes: Vec<f64>,
+    /// The cells row by row
+    pub cells: Vec<GridCell>,
+}
+
+impl Grid {
+    /// Returns the cell at the row (latitude) and column (longitude)
+    pub fn cell(&self, row: usize, column: usize) -> GridCell {
+        assert!(column < self.longitudes.len(), "column out of range");
+        self.cells[row * self.longitudes.len() + column]
+    }
+}
+
+/// Calculates sunrise, sunset and day length at the date for a grid of
+/// locations, `step` angle degrees apart from the start of both ranges up
+/// to and including their end; panics when the step is not positive. The
+/// cells are equal to `calculate_daylight` at their location.
+///
+/// At a fixed date and latitude, the position of the sun and the hour
+/// angles are the same for every longitude; only solar noon moves with the
+/// longitude. So they are calculated once per row, with the day length and
+/// the time from noon to sunrise and sunset; the cells of the row only take
+/// a few additions, instead of the trigonometric functions of a complete
+/// calculation.
+pub fn daylight_grid(date: Tm,
+                     lat_range: RangeInclusive<f64>,
+                     lon_range: RangeInclusive<f64>,
+                     step: f64)
+                     -> Grid {
+    assert!(step > 0.0, "step must be positive");
+    let line = |range: &RangeInclusive<f64>, index: i64| range.start() + index as f64 * step;
+    let lines = |range: &RangeInclusive<f64>| {
+        (0..grid_lines(*range.start(), *range.end(), step).max(0))
+            .map(|index| line(range, index))
+            .collect::<Vec<f64>>()
+    };
+    let latitudes = lines(&lat_range);
+    let longitudes = lines(&lon_range);
+
+    let options = DaylightOptions::default();
+    let utc = tm_to_timespec(date);
+    let ephemeris = SunEphemeris::from(solar_parameters_at(utc, &options));
+    let tsmidnight = midnight(utc);
+    let rounding = options.get_rounding();
+    let mut cells = Vec::with_capacity(latitudes.len() * longitudes.len());
+    for &latitude in &latitudes {
+        // like `SolarDay::to_daylight`
+        let row = ephemeris.solar_day(&LatitudeTrig::new(latitude), 0.0, &options);
+        let (morning, evening) = (hours_to_duration(row.morning), hours_to_duration(row.evening));
+        let daylength = round_duration(morning + evening, rounding).num_seconds();
+        for &longitude in &longitudes {
+            // like `SunEphemeris::solar_day`, in the same order of operations
+            let riset = 12.0 - row.morning - longitude / 15.0 + ephemeris.equation_hours();
+            let noon = daylight_hours_to_timespec(tsmidnight, riset + row.morning);
+            cells.push(GridCell {
+                sunrise: round_timespec(noon - morning, rounding).sec,
+                sunset: round_timespec(noon + evening, rounding).sec,
+                daylength,
+            });
+        }
+    }
+
+    Grid {
+        latitudes,
+        longitudes,
+        cells,
+    }
+}
+
+#[test]
+fn daylight_grid_compared_to_scalar() {
+    use time::{self, Timespec};
+
+    use calculate_daylight;
+
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let grid = daylight_grid(date, -90.0..=90.0, -180.0..=180.0, 0.5);
+    assert_eq!((grid.latitudes.len(), grid.longitudes.len()), (361, 721));
+    assert_eq!(grid.cells.len(), 361 * 721);
+
+    // a pseudo-random sample of the cells
+    let mut state = 12345u64;
+    for _ in 0..2000 {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let row = (state >> 33) as usize % grid.latitudes.len();
+        let column = (state >> 13) as usize % grid.longitudes.len();
+        let expected = calculate_daylight(date, grid.latitudes[row], grid.longitudes[column]);
+        assert_eq!(grid.cell(row, column),
+                   GridCell {
+                       sunrise: expected.sunrise.sec,
+                       sunset: expected.sunset.sec,
+                       daylength: expected.daylength.num_seconds(),
+                   },
+                   "at {}, {}",
+                   grid.latitudes[row],
+                   grid.longitudes[column]);
+    }
+
+    // a single cell, and an empty range
+    let grid = daylight_grid(date, 52.22..=52.22, 5.97..=5.97, 0.1);
+    assert_eq!(grid.cells.len(), 1);
+    assert!(daylight_grid(date, 10.0..=0.0, 0.0..=10.0, 1.0).cells.is_empty());
+}
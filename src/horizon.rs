@@ -0,0 +1,313 @@
+//! Sunrise and sunset behind an obstructed horizon, like a ridge around a
+//! valley or the buildings of a street.
+//!
+//! The local horizon line is described by a mask of elevations per azimuth.
+//! The sun is visible when its upper limb, including the refraction at the
+//! elevation of the horizon line, clears the mask; unlike for the flat
+//! horizon this can happen at a different azimuth than the astronomical
+//! sunrise, so the altitude of the sun is followed along its path through
+//! the day.
+
+use std::f64::consts;
+
+use time::{Duration, Timespec, Tm};
+
+use {DaylightError, DaylightOptions, LatitudeTrig, AIRREFR, NSECS_IN_SEC, SECS_IN_HOUR, SUNRADIUS,
+     daylight_hours_to_timespec, fnrange, fnrange_signed, midnight, solar_day, tm_to_timespec,
+     to_degrees, to_radians};
+
+/// Interval in seconds at which the path of the sun is sampled; visibility
+/// through a gap in the horizon line shorter than this may be missed
+const SAMPLE_INTERVAL: i64 = 120;
+/// Elevation of the horizon line in angle degrees below which the refraction
+/// is taken as at this elevation; the formula of Bennett is made for the
+/// sky above the horizon, and diverges at -4.4 degrees
+const MIN_REFRACTION_ELEVATION: f64 = -1.0;
+
+/// Elevation of the local horizon line per azimuth
+#[derive(Clone, Debug, PartialEq)]
+pub struct HorizonMask {
+    /// (azimuth, elevation) in angle degrees, ordered by azimuth in [0, 360)
+    points: Vec<(f64, f64)>,
+}
+
+impl HorizonMask {
+    /// Creates a mask from `(azimuth, elevation)` pairs in angle degrees;
+    /// the azimuth is measured from the north through the east. Between the
+    /// points the elevation is interpolated linearly, wrapping around the
+    /// north. An empty slice is the flat horizon. Fails when a point is
+    /// infinite or not a number.
+    pub fn try_new(points: &[(f64, f64)]) -> Result<HorizonMask, DaylightError> {
+        if let Some(&(azimuth, elevation)) = points.iter()
+            .find(|&&(azimuth, elevation)| !azimuth.is_finite() || !elevation.is_finite()) {
+            return Err(DaylightError::HorizonNotFinite(azimuth, elevation));
+        }
+        let mut points: Vec<(f64, f64)> = points.iter()
+            .map(|&(azimuth, elevation)| (to_degrees(fnrange(to_radians(azimuth))), elevation))
+            .collect();
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Ok(HorizonMask { points })
+    }
+
+    /// Creates a mask with the same elevation in every direction
+    pub fn uniform(elevation: f64) -> HorizonMask {
+        HorizonMask { points: vec![(0.0, elevation)] }
+    }
+
+    /// Returns the elevation of the horizon line in angle degrees at the
+    /// given azimuth
+    pub fn elevation(&self, azimuth: f64) -> f64 {
+        let azimuth = to_degrees(fnrange(to_radians(azimuth)));
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return 0.0,
+        };
+
+        // the segments, including the one wrapping around the north
+        let wrap_start = (last.0 - 360.0, last.1);
+        let wrap_end = (first.0 + 360.0, first.1);
+        let segments = Some((wrap_start, first))
+            .into_iter()
+            .chain(self.points.windows(2).map(|w| (w[0], w[1])))
+            .chain(Some((last, wrap_end)));
+
+        for ((a0, e0), (a1, e1)) in segments {
+            if azimuth >= a0 && azimuth <= a1 {
+                return if a1 > a0 {
+                    e0 + (e1 - e0) * (azimuth - a0) / (a1 - a0)
+                } else {
+                    e0
+                };
+            }
+        }
+        last.1
+    }
+}
+
+/// Visibility of the sun above the local horizon line during a day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HorizonDaylight {
+    /// The sun is first seen above the horizon line at `sunrise` and last at
+    /// `sunset` (it may be hidden in between, e.g. behind a peak)
+    Visible { sunrise: Timespec, sunset: Timespec },
+    /// The sun stays above the horizon line the whole day
+    AlwaysAbove,
+    /// The sun never clears the horizon line
+    AlwaysBelow,
+}
+
+/// Returns the altitude of the centre of the sun (without refraction) and
+/// its azimuth in radians at the given instant
+pub fn sun_position(ts: Timespec,
+                    lat: &LatitudeTrig,
+                    longitude: f64,
+                    options: &DaylightOptions)
+                    -> (f64, f64) {
+    let day = solar_day(ts, lat, longitude, options);
+    let hours = ((ts.sec - midnight(ts).sec) as f64 + ts.nsec as f64 / NSECS_IN_SEC) /
+                SECS_IN_HOUR;
+    let h = fnrange_signed(to_radians(15.0 * (hours - day.noon)));
+    let declin = to_radians(day.declination);
+
+    let altitude = (lat.sin * declin.sin() + lat.cos * declin.cos() * h.cos()).asin();
+    let azimuth = h.sin().atan2(h.cos() * lat.sin - declin.tan() * lat.cos) + consts::PI;
+    (altitude, azimuth)
+}
+
+/// Returns the altitude of the centre of the sun in radians (without
+/// refraction) at which its upper limb appears at the given elevation
+fn apparent_threshold(elevation: f64, options: &DaylightOptions) -> f64 {
+    // refraction by Bennett (in arcminutes), scaled like the refraction at
+    // the horizon for pressure and temperature
+    let h = elevation.clamp(MIN_REFRACTION_ELEVATION, 90.0);
+    let refraction = 1.0 / to_radians(h + 7.31 / (h + 4.4)).tan() / 60.0 *
+                     options.get_refraction() / AIRREFR;
+    to_radians(elevation - 0.5 * SUNRADIUS - refraction)
+}
+
+/// Calculate the first and last moment at which the sun is seen above the
+/// local horizon line, for the day of solar noon at given date
+pub fn calculate_daylight_with_horizon(date: Tm,
+                                       latitude: f64,
+                                       longitude: f64,
+                                       mask: &HorizonMask)
+                                       -> HorizonDaylight {
+    let options = DaylightOptions::default();
+    let utc = tm_to_timespec(date);
+    let lat = LatitudeTrig::new(latitude);
+    let day = solar_day(utc, &lat, longitude, &options);
+    let tsnoon = daylight_hours_to_timespec(midnight(utc), day.noon);
+
+    // height of the sun above the horizon line, positive when visible
+    let above = |ts: Timespec| {
+        let (altitude, azimuth) = sun_position(ts, &lat, longitude, &options);
+        altitude - apparent_threshold(mask.elevation(to_degrees(azimuth)), &options)
+    };
+    // the moment of crossing between a sample below and one above, to the
+    // second
+    let crossing = |mut below: Timespec, mut visible: Timespec| {
+        while (visible.sec - below.sec).abs() > 1 {
+            let mid = Timespec::new((below.sec + visible.sec).div_euclid(2), 0);
+            if above(mid) > 0.0 {
+                visible = mid;
+            } else {
+                below = mid;
+            }
+        }
+        visible
+    };
+
+    let start = tsnoon - Duration::hours(12);
+    let samples: Vec<(Timespec, bool)> = (0..24 * 3600 / SAMPLE_INTERVAL + 1)
+        .map(|i| {
+            let ts = start + Duration::seconds(i * SAMPLE_INTERVAL);
+            (ts, above(ts) > 0.0)
+        })
+        .collect();
+
+    let first = samples.iter().position(|&(_, visible)| visible);
+    let last = samples.iter().rposition(|&(_, visible)| visible);
+    match (first, last) {
+        (Some(first), Some(last)) => {
+            if first == 0 && last == samples.len() - 1 &&
+               samples.iter().all(|&(_, visible)| visible) {
+                return HorizonDaylight::AlwaysAbove;
+            }
+            let sunrise = if first == 0 {
+                samples[0].0
+            } else {
+                crossing(samples[first - 1].0, samples[first].0)
+            };
+            let sunset = if last == samples.len() - 1 {
+                samples[last].0
+            } else {
+                crossing(samples[last + 1].0, samples[last].0)
+            };
+            HorizonDaylight::Visible { sunrise, sunset }
+        }
+        _ => HorizonDaylight::AlwaysBelow,
+    }
+}
+
+#[test]
+fn horizon_mask_interpolation() {
+    let mask = HorizonMask::try_new(&[(90.0, 12.0), (180.0, 4.0), (-90.0, 0.0)]).unwrap();
+    assert_eq!(mask.elevation(90.0), 12.0);
+    assert_eq!(mask.elevation(135.0), 8.0);
+    assert_eq!(mask.elevation(270.0), 0.0);
+    // wrapping around the north
+    assert_eq!(mask.elevation(0.0), 6.0);
+    assert_eq!(mask.elevation(360.0), 6.0);
+    assert_eq!(mask.elevation(-45.0), 3.0);
+
+    assert_eq!(HorizonMask::uniform(10.0).elevation(123.0), 10.0);
+    assert_eq!(HorizonMask::try_new(&[]).unwrap().elevation(123.0), 0.0);
+
+    // points that can not be ordered or interpolated
+    match HorizonMask::try_new(&[(90.0, 12.0), (f64::NAN, 4.0)]) {
+        Err(DaylightError::HorizonNotFinite(azimuth, elevation)) => {
+            assert!(azimuth.is_nan() && elevation == 4.0)
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(HorizonMask::try_new(&[(90.0, f64::INFINITY)]),
+               Err(DaylightError::HorizonNotFinite(90.0, f64::INFINITY)));
+}
+
+#[test]
+fn horizon_refraction_below_the_horizon() {
+    // the refraction is that of -1 degree below it, also where the formula
+    // of Bennett diverges
+    let options = DaylightOptions::default();
+    let threshold = |elevation| to_degrees(apparent_threshold(elevation, &options));
+    assert!(threshold(-4.4).is_finite());
+    assert!((threshold(-10.0) - threshold(-1.0) + 9.0).abs() < 1e-9);
+    assert!(threshold(-10.0) < threshold(-4.4) && threshold(-4.4) < threshold(0.0));
+    assert!(threshold(-1.0) < threshold(0.0) && threshold(0.0) < threshold(10.0));
+}
+
+#[test]
+fn horizon_uniform_mask_matches_altitude_crossing() {
+    use hour_angle;
+
+    // Apeldoorn, 2015-03-27
+    let tm = ::time::at_utc(Timespec::new(1427457600, 0));
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+    let options = DaylightOptions::default();
+
+    let daylight = calculate_daylight_with_horizon(tm,
+                                                   lat_apeldoorn,
+                                                   long_apeldoorn,
+                                                   &HorizonMask::uniform(10.0));
+
+    // the crossing of the same altitude, from the hour angle at noon
+    let lat = LatitudeTrig::new(lat_apeldoorn);
+    let day = solar_day(tm_to_timespec(tm), &lat, long_apeldoorn, &options);
+    let halfday = hour_angle(&lat,
+                             to_radians(day.declination),
+                             -apparent_threshold(10.0, &options)) * 12.0 / consts::PI;
+    let tsnoon = daylight_hours_to_timespec(midnight(tm_to_timespec(tm)), day.noon);
+    let expected_sunrise = daylight_hours_to_timespec(tsnoon, -halfday);
+    let expected_sunset = daylight_hours_to_timespec(tsnoon, halfday);
+
+    match daylight {
+        HorizonDaylight::Visible { sunrise, sunset } => {
+            assert!((sunrise.sec - expected_sunrise.sec).abs() < 60, "{:?}", daylight);
+            assert!((sunset.sec - expected_sunset.sec).abs() < 60, "{:?}", daylight);
+        }
+        _ => panic!("{:?}", daylight),
+    }
+
+    // a flat horizon is close to the regular sunrise and sunset
+    let flat = calculate_daylight_with_horizon(tm,
+                                               lat_apeldoorn,
+                                               long_apeldoorn,
+                                               &HorizonMask::try_new(&[]).unwrap());
+    let regular = ::calculate_daylight_precise(tm, lat_apeldoorn, long_apeldoorn);
+    match flat {
+        HorizonDaylight::Visible { sunrise, sunset } => {
+            assert!((sunrise.sec - regular.sunrise.sec).abs() < 120, "{:?}", flat);
+            assert!((sunset.sec - regular.sunset.sec).abs() < 120, "{:?}", flat);
+        }
+        _ => panic!("{:?}", flat),
+    }
+}
+
+#[test]
+fn horizon_valley_and_polar() {
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+    let march = ::time::at_utc(Timespec::new(1427457600, 0));
+
+    // a 12 degree ridge in the east delays sunrise, but not sunset
+    let valley = HorizonMask::try_new(&[(45.0, 0.0), (90.0, 12.0), (135.0, 12.0), (180.0, 0.0)])
+        .unwrap();
+    let flat = HorizonMask::try_new(&[]).unwrap();
+    match (calculate_daylight_with_horizon(march, lat_apeldoorn, long_apeldoorn, &valley),
+           calculate_daylight_with_horizon(march, lat_apeldoorn, long_apeldoorn, &flat)) {
+        (HorizonDaylight::Visible { sunrise, sunset },
+         HorizonDaylight::Visible { sunrise: flat_sunrise, sunset: flat_sunset }) => {
+            assert!(sunrise - flat_sunrise > Duration::hours(1));
+            assert_eq!(sunset, flat_sunset);
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // at midwinter the sun stays below 15 degrees
+    let december = ::time::at_utc(Timespec::new(1450699200, 0));
+    assert_eq!(calculate_daylight_with_horizon(december,
+                                               lat_apeldoorn,
+                                               long_apeldoorn,
+                                               &HorizonMask::uniform(15.0)),
+               HorizonDaylight::AlwaysBelow);
+
+    // midnight sun in Longyearbyen, unless the mountains are high enough
+    let june = ::time::at_utc(Timespec::new(1434888000, 0));
+    assert_eq!(calculate_daylight_with_horizon(june, 78.22, 15.65, &flat),
+               HorizonDaylight::AlwaysAbove);
+    match calculate_daylight_with_horizon(june, 78.22, 15.65, &HorizonMask::uniform(20.0)) {
+        HorizonDaylight::Visible { sunrise, sunset } => assert!(sunrise < sunset),
+        other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,69 @@
+//! Instants and durations for the calculation without `std`, with the part
+//! of the interface of `time::Timespec` and `time::Duration` it uses.
+
+use core::ops::{Add, Sub};
+
+const NSECS_IN_SEC: i64 = 1_000_000_000;
+
+/// Seconds and nanoseconds since the unix epoch, like `time::Timespec`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timespec {
+    pub sec: i64,
+    pub nsec: i32,
+}
+
+impl Timespec {
+    pub fn new(sec: i64, nsec: i32) -> Timespec {
+        assert!(nsec >= 0 && nsec < NSECS_IN_SEC as i32);
+        Timespec { sec, nsec }
+    }
+}
+
+/// Span of time in nanoseconds; the calculation only needs spans of days
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration {
+    nanoseconds: i64,
+}
+
+impl Duration {
+    pub fn nanoseconds(nanoseconds: i64) -> Duration {
+        Duration { nanoseconds }
+    }
+
+    pub fn num_nanoseconds(&self) -> Option<i64> {
+        Some(self.nanoseconds)
+    }
+
+    pub fn num_seconds(&self) -> i64 {
+        // truncated towards zero, like `time::Duration`
+        self.nanoseconds / NSECS_IN_SEC
+    }
+}
+
+impl Add<Duration> for Timespec {
+    type Output = Timespec;
+
+    fn add(self, other: Duration) -> Timespec {
+        let nanoseconds = self.nsec as i64 + other.nanoseconds.rem_euclid(NSECS_IN_SEC);
+        Timespec::new(self.sec + other.nanoseconds.div_euclid(NSECS_IN_SEC) +
+                      nanoseconds / NSECS_IN_SEC,
+                      (nanoseconds % NSECS_IN_SEC) as i32)
+    }
+}
+
+impl Sub<Duration> for Timespec {
+    type Output = Timespec;
+
+    fn sub(self, other: Duration) -> Timespec {
+        self + Duration::nanoseconds(-other.nanoseconds)
+    }
+}
+
+impl Sub<Timespec> for Timespec {
+    type Output = Duration;
+
+    fn sub(self, other: Timespec) -> Duration {
+        Duration::nanoseconds((self.sec - other.sec) * NSECS_IN_SEC +
+                              (self.nsec - other.nsec) as i64)
+    }
+}
@@ -0,0 +1,130 @@
+//! Latitude at which the day has a given length on a date.
+
+use time::{Duration, Tm};
+
+use {DaylightOptions, LatitudeTrig, SECS_IN_HOUR, solar_day, tm_to_timespec};
+
+/// Hemisphere of the latitude of `latitude_for_daylength`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+/// Degrees between the latitudes sampled before the bisection
+const STEP: f64 = 1.0;
+/// Degrees to which the latitude is bisected
+const PRECISION: f64 = 0.0001;
+
+/// Returns the latitude in the hemisphere, closest to the equator, at which
+/// the day is `target` long on the date (see `calculate_daylight`; the
+/// declination of the sun is that of the instant `date`), or `None` when
+/// there is none, like for 20 hours in January in the north. The latitude is
+/// accurate to well within 0.01 degrees.
+///
+/// A day of 24 hours starts at the latitude of the polar day; due to the
+/// refraction, on the June solstice that is about 65.7 degrees north, below
+/// the arctic circle.
+///
+/// Without the refraction, every day at the equator and every day on the
+/// equinoxes would be 12 hours long. With it, the day at the equator is a
+/// few minutes longer, and around the equinoxes the day length hardly
+/// depends on the latitude: there may be no latitude with a day of exactly
+/// 12 hours. When no latitude has a day of the target length, but the
+/// target is between 12 hours and the day length at the equator, the
+/// equator (0.0) is returned.
+pub fn latitude_for_daylength(date: Tm, target: Duration, hemisphere: Hemisphere) -> Option<f64> {
+    let utc = tm_to_timespec(date);
+    let options = DaylightOptions::default();
+    let target = target.num_milliseconds() as f64 / (SECS_IN_HOUR * 1000.0);
+    let sign = match hemisphere {
+        Hemisphere::North => 1.0,
+        Hemisphere::South => -1.0,
+    };
+    // difference of the day length to the target, at the degrees from the
+    // equator
+    let difference = |degrees: f64| {
+        let day = solar_day(utc, &LatitudeTrig::new(sign * degrees), 0.0, &options);
+        day.morning + day.evening - target
+    };
+
+    let at_equator = difference(0.0);
+    if at_equator == 0.0 {
+        return Some(0.0);
+    }
+    // the first sampled latitude with the day length at or beyond the target
+    // (the day length is not monotonic in the latitude around the equinoxes)
+    let mut low = 0.0;
+    let mut found = None;
+    while low < 90.0 {
+        let high = (low + STEP).min(90.0);
+        if difference(high) * at_equator <= 0.0 {
+            found = Some((low, high));
+            break;
+        }
+        low = high;
+    }
+    match found {
+        Some((mut low, mut high)) => {
+            while high - low > PRECISION {
+                let middle = 0.5 * (low + high);
+                if difference(middle) * at_equator > 0.0 {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            Some(sign * high)
+        }
+        None => {
+            let equator = target + at_equator;
+            if (equator.min(12.0)..=equator.max(12.0)).contains(&target) {
+                Some(0.0)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[test]
+fn latitude_for_daylengths() {
+    use time::{self, Timespec};
+
+    use calculate_daylight;
+
+    // 15 hours on July 1st, about 41 degrees in the north
+    let july = time::at_utc(Timespec::new(1719835200, 0));
+    let latitude = latitude_for_daylength(july, Duration::hours(15), Hemisphere::North).unwrap();
+    assert!((40.5..41.5).contains(&latitude), "{}", latitude);
+    let daylength = calculate_daylight(july, latitude, 0.0).daylength;
+    assert!((daylength - Duration::hours(15)).num_seconds().abs() <= 2, "{}", daylength);
+    // and in the south, 9 hours
+    let latitude = latitude_for_daylength(july, Duration::hours(9), Hemisphere::South).unwrap();
+    assert!((-50.0..-40.0).contains(&latitude), "{}", latitude);
+    let daylength = calculate_daylight(july, latitude, 0.0).daylength;
+    assert!((daylength - Duration::hours(9)).num_seconds().abs() <= 2, "{}", daylength);
+
+    // no 20 hours in January in the north, nor 3 hours in the south
+    let january = time::at_utc(Timespec::new(1704110400, 0));
+    assert_eq!(latitude_for_daylength(january, Duration::hours(20), Hemisphere::North), None);
+    assert_eq!(latitude_for_daylength(january, Duration::hours(3), Hemisphere::South), None);
+
+    // the polar day on the June solstice starts below the arctic circle
+    let solstice = time::at_utc(Timespec::new(1718971200, 0));
+    let latitude = latitude_for_daylength(solstice, Duration::hours(24), Hemisphere::North)
+        .unwrap();
+    assert!((65.6..65.8).contains(&latitude), "{}", latitude);
+    assert!(calculate_daylight(solstice, latitude + 0.01, 0.0).is_polar_day());
+    assert!(!calculate_daylight(solstice, latitude - 0.01, 0.0).is_polar_day());
+    // and the polar night in the south
+    let latitude = latitude_for_daylength(solstice, Duration::zero(), Hemisphere::South).unwrap();
+    assert!((-67.5..-67.3).contains(&latitude), "{}", latitude);
+
+    // the equinox: 12 hours at the equator by convention, in either
+    // hemisphere
+    let equinox = time::at_utc(Timespec::new(1710936000, 0));
+    for &hemisphere in &[Hemisphere::North, Hemisphere::South] {
+        assert_eq!(latitude_for_daylength(equinox, Duration::hours(12), hemisphere), Some(0.0));
+    }
+}
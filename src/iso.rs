@@ -0,0 +1,305 @@
+//! Dates of ISO 8601 text, like of a script or a configuration file.
+
+use std::error::Error;
+use std::fmt;
+
+use time::{self, Timespec, Tm};
+
+use error::{days_in_month, DaylightError};
+use {Daylight, SECS_IN_DAY, calculate_daylight_checked, days_from_civil};
+
+/// Part of the date that can not be parsed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DateParseErrorKind {
+    /// The year is not 4 digits
+    InvalidYear,
+    /// The month is not `-` followed by 2 digits
+    InvalidMonth,
+    /// The day is not `-` followed by 2 digits
+    InvalidDay,
+    /// The time after `T` is not `HH:MM`, `HH:MM:SS` or `HH:MM:SS.F`
+    InvalidTime,
+    /// The time is not followed by `Z` or an offset like `+02:00`
+    InvalidOffset,
+    /// The date or time is followed by more text
+    TrailingText,
+    /// The fields are well-formed, but the month, day, time or offset does
+    /// not exist (like `2023-02-29` or `24:00`)
+    OutOfRange,
+}
+
+/// Error of `parse_iso_date`, with the byte position in the text at which it
+/// deviates from the expected form
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateParseError {
+    kind: DateParseErrorKind,
+    position: usize,
+}
+
+impl DateParseError {
+    /// Returns the reason of the error
+    pub fn kind(&self) -> DateParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte position in the text
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for DateParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            DateParseErrorKind::InvalidYear => "invalid year (expected 4 digits, like 2024-06-21)",
+            DateParseErrorKind::InvalidMonth => "invalid month (expected `-` and 2 digits)",
+            DateParseErrorKind::InvalidDay => "invalid day (expected `-` and 2 digits)",
+            DateParseErrorKind::InvalidTime => {
+                "invalid time (expected like T12:00, T12:00:00 or T12:00:00.5)"
+            }
+            DateParseErrorKind::InvalidOffset => {
+                "invalid offset (expected Z or like +02:00 after the time)"
+            }
+            DateParseErrorKind::TrailingText => "unexpected text after the date",
+            DateParseErrorKind::OutOfRange => "the date, time or offset does not exist",
+        };
+        write!(f, "{} at position {}", reason, self.position)
+    }
+}
+
+impl Error for DateParseError {}
+
+/// Reads the text from the start to the end, keeping the byte position for
+/// the errors
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, kind: DateParseErrorKind) -> DateParseError {
+        DateParseError {
+            kind,
+            position: self.position,
+        }
+    }
+
+    /// Skips the byte when it is next
+    fn eat(&mut self, byte: u8) -> bool {
+        let next = self.text.get(self.position) == Some(&byte);
+        if next {
+            self.position += 1;
+        }
+        next
+    }
+
+    /// Reads a number of exactly `count` digits, which is at most `max`
+    fn digits(&mut self,
+              count: usize,
+              max: u32,
+              kind: DateParseErrorKind)
+              -> Result<u32, DateParseError> {
+        let start = self.position;
+        let mut value = 0;
+        for _ in 0..count {
+            match self.text.get(self.position) {
+                Some(&byte) if byte.is_ascii_digit() => value = value * 10 + (byte - b'0') as u32,
+                _ => return Err(self.error(kind)),
+            }
+            self.position += 1;
+        }
+        if value > max {
+            return Err(DateParseError {
+                kind: DateParseErrorKind::OutOfRange,
+                position: start,
+            });
+        }
+        Ok(value)
+    }
+
+    /// Reads `-` or `:` followed by a number of 2 digits
+    fn field(&mut self,
+             separator: u8,
+             max: u32,
+             kind: DateParseErrorKind)
+             -> Result<u32, DateParseError> {
+        if !self.eat(separator) {
+            return Err(self.error(kind));
+        }
+        self.digits(2, max, kind)
+    }
+
+    /// Reads the digits of a fraction of the second as nanoseconds, ignoring
+    /// those beyond the nanosecond
+    fn fraction(&mut self) -> Result<i32, DateParseError> {
+        let start = self.position;
+        let mut nanoseconds = 0;
+        while let Some(&byte) = self.text.get(self.position) {
+            if !byte.is_ascii_digit() {
+                break;
+            }
+            if self.position - start < 9 {
+                nanoseconds = nanoseconds * 10 + (byte - b'0') as i32;
+            }
+            self.position += 1;
+        }
+        if self.position == start {
+            return Err(self.error(DateParseErrorKind::InvalidTime));
+        }
+        Ok(nanoseconds * 10i32.pow(9 - (self.position - start).min(9) as u32))
+    }
+
+    /// Reads the `Z` of UTC or the offset (`+02:00` or `-05:30`) in seconds
+    /// east of UTC
+    fn utc_offset(&mut self) -> Result<i64, DateParseError> {
+        if self.eat(b'Z') {
+            return Ok(0);
+        }
+        let sign = if self.eat(b'+') {
+            1
+        } else if self.eat(b'-') {
+            -1
+        } else {
+            return Err(self.error(DateParseErrorKind::InvalidOffset));
+        };
+        let hours = self.digits(2, 23, DateParseErrorKind::InvalidOffset)?;
+        let minutes = self.field(b':', 59, DateParseErrorKind::InvalidOffset)?;
+        Ok(sign * (hours * 3600 + minutes * 60) as i64)
+    }
+
+    fn end(&self) -> Result<(), DateParseError> {
+        if self.position == self.text.len() {
+            Ok(())
+        } else {
+            Err(self.error(DateParseErrorKind::TrailingText))
+        }
+    }
+}
+
+/// Parses a date of ISO 8601 text into the instant in UTC.
+///
+/// The text is either a calendar date (`2024-06-21`), of which the instant
+/// is 12:00 UTC like with `calculate_daylight_ymd`, or a date and time with
+/// `Z` or the offset from UTC (`2024-06-21T12:00:00Z` or
+/// `2024-06-21T08:30+02:00`, the seconds and their fraction are optional).
+/// Partial dates (like `2024-06`), the basic format without separators and
+/// times without an offset are ambiguous and rejected.
+pub fn parse_iso_date(text: &str) -> Result<Tm, DateParseError> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        position: 0,
+    };
+    let year = parser.digits(4, 9999, DateParseErrorKind::InvalidYear)? as i64;
+    let month = parser.field(b'-', 12, DateParseErrorKind::InvalidMonth)? as i64;
+    let day_position = parser.position + 1;
+    let day = parser.field(b'-', 31, DateParseErrorKind::InvalidDay)? as i64;
+    if month == 0 || day == 0 || day > days_in_month(year, month as i32 - 1) as i64 {
+        return Err(DateParseError {
+            kind: DateParseErrorKind::OutOfRange,
+            position: if month == 0 { 5 } else { day_position },
+        });
+    }
+    let days = days_from_civil(year, month, day);
+    if !parser.eat(b'T') {
+        parser.end()?;
+        return Ok(time::at_utc(Timespec::new(days * SECS_IN_DAY + SECS_IN_DAY / 2, 0)));
+    }
+
+    let hour = parser.digits(2, 23, DateParseErrorKind::InvalidTime)?;
+    let minute = parser.field(b':', 59, DateParseErrorKind::InvalidTime)?;
+    let (mut second, mut nanosecond) = (0, 0);
+    if parser.text.get(parser.position) == Some(&b':') {
+        // a leap second continues into the next minute
+        second = parser.field(b':', 60, DateParseErrorKind::InvalidTime)?;
+        if parser.eat(b'.') {
+            nanosecond = parser.fraction()?;
+        }
+    }
+    let utc_offset = parser.utc_offset()?;
+    parser.end()?;
+
+    let secs = days * SECS_IN_DAY + (hour * 3600 + minute * 60 + second) as i64 - utc_offset;
+    Ok(time::at_utc(Timespec::new(secs, nanosecond)))
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset of the date in
+/// ISO 8601 text (see `parse_iso_date`), at the instant of the text, so on
+/// its date in UTC; after validating the date and location
+pub fn calculate_daylight_iso(date: &str,
+                              latitude: f64,
+                              longitude: f64)
+                              -> Result<Daylight, DaylightError> {
+    calculate_daylight_checked(parse_iso_date(date)?, latitude, longitude)
+}
+
+#[test]
+fn daylight_iso_dates() {
+    use self::DateParseErrorKind::*;
+    use calculate_daylight_ymd;
+
+    // Apeldoorn, on the leap day of 2024
+    let (latitude, longitude) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let leap_day = calculate_daylight_ymd(2024, 2, 29, latitude, longitude);
+    assert_eq!(calculate_daylight_iso("2024-02-29", latitude, longitude), leap_day);
+    assert_eq!(calculate_daylight_iso("2024-02-29T12:00:00Z", latitude, longitude),
+               leap_day);
+    assert_eq!(calculate_daylight_iso("2024-02-29T14:00+02:00", latitude, longitude),
+               leap_day);
+
+    // early on 2024-03-01 in Tokyo and late on 2024-02-28 in New York are
+    // the leap day in UTC, calculated at the instant
+    let tokyo = parse_iso_date("2024-03-01T02:30:15.25+09:00").unwrap();
+    assert_eq!(tokyo.to_timespec(), Timespec::new(1709227815, 250_000_000));
+    assert_eq!(tokyo.rfc3339().to_string(), "2024-02-29T17:30:15Z");
+    let new_york = parse_iso_date("2024-02-28T23:30:00-05:00").unwrap();
+    assert_eq!(new_york.rfc3339().to_string(), "2024-02-29T04:30:00Z");
+    for &date in &["2024-03-01T02:30:15.25+09:00", "2024-02-28T23:30:00-05:00"] {
+        let daylight = calculate_daylight_iso(date, latitude, longitude);
+        assert_eq!(daylight,
+                   calculate_daylight_checked(parse_iso_date(date).unwrap(), latitude, longitude));
+        assert_eq!(daylight.unwrap().noon.sec.div_euclid(SECS_IN_DAY),
+                   days_from_civil(2024, 2, 29));
+    }
+
+    // the reason and the position at which the text deviates
+    let error = |text| parse_iso_date(text).map(|tm| tm.rfc3339().to_string());
+    let at = |kind, position| Err(DateParseError { kind, position });
+    assert_eq!(error(""), at(InvalidYear, 0));
+    assert_eq!(error("tomorrow"), at(InvalidYear, 0));
+    assert_eq!(error("24-06-21"), at(InvalidYear, 2));
+    assert_eq!(error("20240621"), at(InvalidMonth, 4));
+    assert_eq!(error("2024-06"), at(InvalidDay, 7));
+    assert_eq!(error("2024-6-21"), at(InvalidMonth, 6));
+    assert_eq!(error("2024-06-21 "), at(TrailingText, 10));
+    assert_eq!(error("2024-06-21T12"), at(InvalidTime, 13));
+    assert_eq!(error("2024-06-21T12:00:00"), at(InvalidOffset, 19));
+    assert_eq!(error("2024-06-21T12:00:00.Z"), at(InvalidTime, 20));
+    assert_eq!(error("2024-06-21T12:00:00+0200"), at(InvalidOffset, 22));
+    assert_eq!(error("2024-06-21T12:00:00Zulu"), at(TrailingText, 20));
+    assert_eq!(error("2024-06-21T12:00:00Z\u{e9}"), at(TrailingText, 20));
+    assert_eq!(error("2024-13-01"), at(OutOfRange, 5));
+    assert_eq!(error("2024-00-01"), at(OutOfRange, 5));
+    assert_eq!(error("2023-02-29"), at(OutOfRange, 8));
+    assert_eq!(error("2024-06-31"), at(OutOfRange, 8));
+    assert_eq!(error("2024-06-21T24:00Z"), at(OutOfRange, 11));
+    assert_eq!(error("2024-06-21T12:60Z"), at(OutOfRange, 14));
+    assert_eq!(error("2024-06-21T12:00:61Z"), at(OutOfRange, 17));
+    assert_eq!(error("2024-06-21T12:00+24:00"), at(OutOfRange, 17));
+    assert_eq!(error("2016-12-31T23:59:60Z"), Ok("2017-01-01T00:00:00Z".to_string()));
+
+    // within the calculation error, which also checks the range and location
+    let garbage = calculate_daylight_iso("2024-06-21T12:00:00", latitude, longitude);
+    assert_eq!(garbage,
+               Err(DaylightError::InvalidDateText(DateParseError {
+                   kind: InvalidOffset,
+                   position: 19,
+               })));
+    assert_eq!(garbage.unwrap_err().to_string(),
+               "invalid date: invalid offset (expected Z or like +02:00 after the time) at \
+                position 19");
+    assert_eq!(calculate_daylight_iso("9999-12-31T23:00-05:00", latitude, longitude),
+               Err(DaylightError::TimeOutOfRange));
+    assert_eq!(calculate_daylight_iso("2024-06-21", 91.0, longitude),
+               Err(DaylightError::LatitudeOutOfRange(91.0)));
+}
@@ -0,0 +1,220 @@
+//! Daylight of consecutive days at a fixed location.
+
+use time::{self, Duration, Timespec, Tm};
+
+use error::{self, MAX_RANGE_DAYS};
+use {Daylight, DaylightError, DaylightOptions, LatitudeTrig, daylight_at, tm_to_timespec,
+     ymd_to_tm};
+
+/// More days than the supported years -9999 to 9999 have, so that the last
+/// date of a longer range is not even calculated
+const MAX_SUPPORTED_DAYS: usize = 20_000 * 366;
+
+/// Iterator over the daylight of consecutive days at a location, starting at
+/// a date; each item is the date (at the time of day of the start date, in
+/// UTC) with its daylight
+///
+/// The trigonometric functions of the latitude are calculated once, instead
+/// of for each day like `calculate_daylight` does.
+#[derive(Clone)]
+pub struct DaylightIter<'a> {
+    next: Timespec,
+    step: Duration,
+    lat: LatitudeTrig,
+    longitude: f64,
+    options: DaylightOptions<'a>,
+}
+
+impl<'a> DaylightIter<'a> {
+    /// Creates an iterator starting at the date, one day at a time
+    pub fn new(start_date: Tm, latitude: f64, longitude: f64) -> DaylightIter<'a> {
+        DaylightIter {
+            next: tm_to_timespec(start_date),
+            step: Duration::days(1),
+            lat: LatitudeTrig::new(latitude),
+            longitude,
+            options: DaylightOptions::default(),
+        }
+    }
+
+    /// Creates an iterator starting at 12:00 UTC of the date, with the month
+    /// and day counted from 1; after validating the date and location
+    pub fn from_ymd(year: i32,
+                    month: u8,
+                    day: u8,
+                    latitude: f64,
+                    longitude: f64)
+                    -> Result<DaylightIter<'a>, DaylightError> {
+        let start = ymd_to_tm(year, month, day)?;
+        error::validate(&start, latitude, longitude)?;
+        Ok(DaylightIter::new(start, latitude, longitude))
+    }
+
+    /// Sets the number of days between the items (e.g. `7` for weekly
+    /// samples); panics when zero
+    pub fn step_days(mut self, days: u32) -> DaylightIter<'a> {
+        assert!(days > 0, "step of zero days");
+        self.step = Duration::days(days as i64);
+        self
+    }
+
+    /// Sets the calculation options used for every day
+    pub fn options(mut self, options: &DaylightOptions<'a>) -> DaylightIter<'a> {
+        self.options = *options;
+        self
+    }
+}
+
+impl<'a> Iterator for DaylightIter<'a> {
+    type Item = (Tm, Daylight);
+
+    fn next(&mut self) -> Option<(Tm, Daylight)> {
+        let date = self.next;
+        self.next = date + self.step;
+        Some((time::at_utc(date),
+              daylight_at(date, &self.lat, self.longitude, &self.options)))
+    }
+}
+
+/// Returns the iterator over `days` consecutive days from the start date,
+/// after validating the location and that the first and last date are
+/// supported; shared by the range functions
+fn range_iter(start: Tm,
+              days: usize,
+              latitude: f64,
+              longitude: f64)
+              -> Result<DaylightIter<'static>, DaylightError> {
+    error::validate(&start, latitude, longitude)?;
+    if days > MAX_SUPPORTED_DAYS {
+        return Err(DaylightError::TimeOutOfRange);
+    }
+    if days > 1 {
+        let last = tm_to_timespec(start) + Duration::days(days as i64 - 1);
+        error::validate(&time::at_utc(last), latitude, longitude)?;
+    }
+    Ok(DaylightIter::new(start, latitude, longitude))
+}
+
+/// Calculate the daylight of `days` consecutive days from the start date,
+/// each paired with its date (see `DaylightIter`); after validating the
+/// dates and location, and that `days` does not exceed 40000
+pub fn calculate_daylight_range(start: Tm,
+                                days: u32,
+                                latitude: f64,
+                                longitude: f64)
+                                -> Result<Vec<(Tm, Daylight)>, DaylightError> {
+    if days > MAX_RANGE_DAYS {
+        return Err(DaylightError::TooManyDays(days));
+    }
+    Ok(range_iter(start, days as usize, latitude, longitude)?.take(days as usize).collect())
+}
+
+/// Calculate the daylight of `days` consecutive days from 12:00 UTC of the
+/// start date, with the month and day counted from 1 (see
+/// `calculate_daylight_range`)
+pub fn calculate_daylight_range_ymd(year: i32,
+                                    month: u8,
+                                    day: u8,
+                                    days: u32,
+                                    latitude: f64,
+                                    longitude: f64)
+                                    -> Result<Vec<(Tm, Daylight)>, DaylightError> {
+    calculate_daylight_range(ymd_to_tm(year, month, day)?, days, latitude, longitude)
+}
+
+/// Calculate the daylight of consecutive days from the start date into the
+/// slice, one day for each element, without allocating (like for a static
+/// buffer on an embedded target); after validating the dates and location,
+/// before anything is written. The days are those of
+/// `calculate_daylight_range`.
+pub fn calculate_daylight_range_into(start: Tm,
+                                     latitude: f64,
+                                     longitude: f64,
+                                     out: &mut [Daylight])
+                                     -> Result<(), DaylightError> {
+    let days = range_iter(start, out.len(), latitude, longitude)?;
+    for (slot, (_, daylight)) in out.iter_mut().zip(days) {
+        *slot = daylight;
+    }
+    Ok(())
+}
+
+#[test]
+fn daylight_iter() {
+    use calculate_daylight;
+
+    // 2024 is a leap year
+    let start = time::at_utc(Timespec::new(1704110400, 0));
+    let year: Vec<(Tm, Daylight)> = DaylightIter::new(start, 52.22, 5.97)
+        .take_while(|&(date, _)| date.tm_year == 124)
+        .collect();
+    assert_eq!(year.len(), 366);
+    assert!(year.windows(2).all(|pair| tm_to_timespec(pair[0].0) < tm_to_timespec(pair[1].0)));
+    assert!(year.windows(2).all(|pair| pair[0].1.noon < pair[1].1.noon));
+    assert!(year.iter().any(|&(date, _)| (date.tm_mon, date.tm_mday) == (1, 29)));
+    let (date, daylight) = year[365];
+    assert_eq!((date.tm_mon, date.tm_mday, date.tm_hour), (11, 31, 12));
+    assert_eq!(daylight, calculate_daylight(date, 52.22, 5.97));
+
+    // from a date with the month counted from 1
+    let mut iter = DaylightIter::from_ymd(2024, 1, 1, 52.22, 5.97).unwrap();
+    assert_eq!(iter.nth(59), Some(year[59]));
+    assert_eq!((year[59].0.tm_mon, year[59].0.tm_mday), (1, 29));
+    assert_eq!(DaylightIter::from_ymd(2023, 2, 29, 52.22, 5.97).err(),
+               Some(DaylightError::InvalidDate));
+    assert!(DaylightIter::from_ymd(2024, 1, 1, 91.0, 5.97).is_err());
+
+    // weekly samples
+    let weeks: Vec<Tm> = DaylightIter::new(start, 52.22, 5.97)
+        .step_days(7)
+        .map(|(date, _)| date)
+        .take_while(|date| date.tm_year == 124)
+        .collect();
+    assert_eq!(weeks.len(), 53);
+    assert_eq!((weeks[9].tm_mon, weeks[9].tm_mday), (2, 4));
+}
+
+#[test]
+fn daylight_range() {
+    // 2015, at the equator the days last about 12 hours all year
+    let start = time::at_utc(Timespec::new(1420113600, 0));
+    let year = calculate_daylight_range(start, 365, 0.0, 0.0).unwrap();
+    assert_eq!(year.len(), 365);
+    let total = year.iter().fold(Duration::zero(), |total, &(_, day)| total + day.daylength);
+    let excess = total - Duration::hours(365 * 12);
+    assert!(excess >= Duration::zero() && excess < Duration::minutes(365 * 8), "{}", excess);
+    assert_eq!((year[364].0.tm_mon, year[364].0.tm_mday), (11, 31));
+
+    assert_eq!(calculate_daylight_range(start, 0, 0.0, 0.0).map(|days| days.len()), Ok(0));
+    assert_eq!(calculate_daylight_range(start, 40_001, 0.0, 0.0),
+               Err(DaylightError::TooManyDays(40_001)));
+    assert_eq!(calculate_daylight_range(start, 1, 91.0, 0.0),
+               Err(DaylightError::LatitudeOutOfRange(91.0)));
+
+    // from a date with the month counted from 1
+    assert_eq!(calculate_daylight_range_ymd(2015, 1, 1, 365, 0.0, 0.0), Ok(year.clone()));
+    assert_eq!(calculate_daylight_range_ymd(2015, 2, 29, 1, 0.0, 0.0),
+               Err(DaylightError::InvalidDate));
+
+    // into a slice, the same days
+    let mut days = [year[0].1; 365];
+    calculate_daylight_range_into(start, 0.0, 0.0, &mut days).unwrap();
+    assert!(days.iter().zip(year.iter()).all(|(day, &(_, expected))| *day == expected));
+    assert_eq!(calculate_daylight_range_into(start, 0.0, 0.0, &mut []), Ok(()));
+    assert_eq!(calculate_daylight_range_into(start, 91.0, 0.0, &mut days),
+               Err(DaylightError::LatitudeOutOfRange(91.0)));
+
+    // the last day must be supported as well, by both; the slice is then
+    // left as it is
+    let december = time::at_utc(Timespec::new(253_401_912_000, 0));
+    assert_eq!(december.tm_year + 1900, 9999);
+    assert_eq!(calculate_daylight_range(december, 5, 0.0, 0.0).map(|days| days.len()), Ok(5));
+    assert_eq!(calculate_daylight_range(december, 7, 0.0, 0.0),
+               Err(DaylightError::TimeOutOfRange));
+    let mut week = [year[0].1; 7];
+    assert_eq!(calculate_daylight_range_into(december, 0.0, 0.0, &mut week),
+               Err(DaylightError::TimeOutOfRange));
+    assert!(week.iter().all(|day| *day == year[0].1));
+    assert_eq!(calculate_daylight_range_into(december, 0.0, 0.0, &mut week[..5]), Ok(()));
+    assert!(week[4].noon.sec > 253_402_200_000);
+}
@@ -11,6 +11,18 @@
 //! More recent implementation is available at Github page
 //! https://github.com/jarmol/suncalcs. Parts of the code (variable and
 //! function names) are kept intentionally the same as reference.
+//!
+//! Dates before 2000 and before 1970 (negative epoch seconds) are supported;
+//! results are verified against almanac values from 1900 up to 2100. All
+//! date arithmetic is done in 64 bits, also on 32-bit platforms, so there is
+//! no limit at 2038. The truncated series for the position of the sun keep
+//! solar noon within about 15 seconds of the NOAA calculator over that whole
+//! range; the drift between 1900 and 2100 itself is only a few seconds.
+//!
+//! Without the default `std` feature the crate is `no_std`, for targets
+//! like microcontrollers: the calculation on unix seconds
+//! (`calculate_daylight_seconds`) and its `DaylightOptions` remain, with the
+//! floating point functions of `libm`.
 
 // Original text in rscalc.c:
 //
@@ -19,33 +31,260 @@
 // Note, twilight calculation gives insufficient accuracy of results
 // Jarmo Lammi 1999 - 2001
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(not(feature = "std"))]
+extern crate libm;
+#[cfg(feature = "std")]
 extern crate time;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "tz")]
+extern crate chrono_tz;
+#[cfg(feature = "geo")]
+extern crate geo_types;
+#[cfg(feature = "uom")]
+extern crate uom;
+#[cfg(feature = "time03")]
+extern crate time03;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "async")]
+extern crate futures_core;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+
+#[cfg(feature = "std")]
+mod alignment;
+pub mod angle;
+#[cfg(feature = "std")]
+#[macro_use]
+mod approx;
+#[cfg(feature = "std")]
+mod aviation;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "std")]
+mod calculator;
+#[cfg(feature = "chrono")]
+mod chrono_interop;
+#[cfg(feature = "std")]
+mod compare;
+#[cfg(feature = "std")]
+mod condition;
+#[cfg(feature = "std")]
+mod coordinates;
+#[cfg(feature = "std")]
+mod crossing;
+#[cfg(feature = "std")]
+mod darkness;
+mod delta_t;
+mod ephemeris;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod events;
+#[cfg(feature = "std")]
+mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+#[cfg(feature = "geo")]
+mod geo_interop;
+#[cfg(feature = "std")]
+mod grid;
+#[cfg(feature = "std")]
+mod horizon;
+#[cfg(not(feature = "std"))]
+mod instant;
+#[cfg(feature = "std")]
+mod iso;
+#[cfg(feature = "std")]
+mod inverse;
+#[cfg(feature = "std")]
+mod iter;
+#[cfg(feature = "std")]
+mod local;
+#[cfg(feature = "std")]
+mod location;
+#[cfg(not(feature = "std"))]
+mod math;
+#[cfg(feature = "std")]
+mod moon;
+mod noaa;
+#[cfg(feature = "std")]
+mod offset;
+mod options;
+#[cfg(feature = "std")]
+mod photo;
+#[cfg(feature = "std")]
+mod prayer;
+#[cfg(feature = "std")]
+mod schedule;
+mod seconds;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "std")]
+mod shadow;
+mod single;
+#[cfg(feature = "std")]
+mod summary;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "std")]
+mod sun_path;
+#[cfg(feature = "std")]
+mod table;
+#[cfg(feature = "std")]
+mod terminator;
+#[cfg(feature = "time03")]
+mod time03_interop;
+#[cfg(feature = "std")]
+mod twilight;
+#[cfg(feature = "std")]
+mod uncertainty;
+#[cfg(feature = "uom")]
+pub mod units;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+mod zmanim;
+#[cfg(feature = "tz")]
+mod zoned;
 
+use angle::Degrees;
+#[cfg(feature = "std")]
 use time::{Timespec, Tm, Duration};
-use std::f64::consts;
+#[cfg(not(feature = "std"))]
+use instant::{Timespec, Duration};
+// with `std` linked, as in the tests, its methods take precedence
+#[cfg(not(any(feature = "std", test)))]
+use math::Float;
+use core::f64::consts;
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "std")]
+pub use alignment::alignment_dates;
+#[cfg(feature = "std")]
+pub use aviation::{NightPeriod, night_period};
+#[cfg(feature = "std")]
+pub use batch::calculate_daylight_batch;
+#[cfg(feature = "std")]
+pub use calculator::SolarCalculator;
+#[cfg(feature = "chrono")]
+pub use chrono_interop::{DaylightChrono, calculate_daylight_chrono};
+#[cfg(feature = "std")]
+pub use compare::{DayComparison, DaylightComparison, compare, compare_with_offsets};
+#[cfg(feature = "std")]
+pub use condition::{Comparison, EventCondition, first_date_matching};
+#[cfg(feature = "std")]
+pub use coordinates::{Coordinates, Latitude, Longitude, calculate_daylight_at};
+#[cfg(feature = "std")]
+pub use crossing::{CrossingDirection, altitude_crossings, altitude_crossings_with,
+                   next_altitude_crossing, next_altitude_crossing_with};
+#[cfg(feature = "std")]
+pub use darkness::{DarknessPeriod, astro_darkness, astro_darkness_duration, darkness_periods};
+pub use delta_t::{DeltaT, estimate_delta_t};
+pub use ephemeris::{SolarAlgorithm, SunEphemeris};
+#[cfg(feature = "std")]
+pub use error::DaylightError;
+#[cfg(feature = "std")]
+pub use events::{DayPart, EventKind, LightLevel, LightPhase, NightBasis, SolarEvent,
+                 UpcomingEvents, light_phase, next_event, next_sunrise, next_sunset,
+                 night_divisions, night_progress, time_until, upcoming_events};
+#[cfg(feature = "std")]
+pub use export::{daylength_grid_geojson, to_csv};
+#[cfg(feature = "std")]
+pub use grid::{Grid, GridCell, daylight_grid};
+#[cfg(feature = "std")]
+pub use horizon::{HorizonDaylight, HorizonMask, calculate_daylight_with_horizon};
+#[cfg(feature = "std")]
+pub use inverse::{Hemisphere, latitude_for_daylength};
+#[cfg(feature = "std")]
+pub use iso::{DateParseError, DateParseErrorKind, calculate_daylight_iso, parse_iso_date};
+#[cfg(feature = "std")]
+pub use iter::{DaylightIter, calculate_daylight_range, calculate_daylight_range_into,
+               calculate_daylight_range_ymd};
+#[cfg(feature = "std")]
+pub use local::{ABSENT_EVENT, LocalDaylight};
+#[cfg(feature = "std")]
+pub use location::{Location, SolarPosition};
+#[cfg(feature = "std")]
+pub use moon::{DarkSkyOptions, dark_sky_windows};
+#[cfg(feature = "std")]
+pub use offset::{OffsetEvent, UpcomingOffsetEvents, next_offset_event, upcoming_offset_events};
+pub use options::{Algorithm, DaylightOptions, Horizon, Limb, Rounding, Series};
+#[cfg(feature = "std")]
+pub use photo::{PhotoDay, PhotoWindow, photo_calendar};
+#[cfg(feature = "std")]
+pub use prayer::{AsrMethod, CalculationMethod, HighLatitudeRule, PrayerOptions, PrayerTimes,
+                 prayer_times, prayer_times_with};
+#[cfg(feature = "std")]
+pub use schedule::{ParseError, ParseErrorKind, SolarSchedule};
+pub use seconds::{DaylightSeconds, calculate_daylight_seconds, calculate_daylight_seconds_with};
+#[cfg(feature = "serde")]
+pub use serde_support::rfc3339;
+#[cfg(feature = "std")]
+pub use shadow::{ShadowPoint, shadow_trajectory};
+pub use single::calculate_daylight_f32;
+#[cfg(feature = "std")]
+pub use summary::{AnnualDaylight, AnnualSummary, DatedEvent, DatedLength, PhotoperiodBasis,
+                  PhotoperiodCrossing, SeriesPoint, accumulated_daylight, annual_daylight,
+                  annual_summary, daylength_by_date, daylength_by_day, photoperiod_crossings,
+                  photoperiod_crossings_with, sunrise_series};
+#[cfg(feature = "async")]
+pub use stream::{MissedEvents, SolarEventStream, solar_event_stream};
+#[cfg(feature = "std")]
+pub use sun_path::{SunPathData, sun_path_diagram};
+#[cfg(feature = "std")]
+pub use table::{DayRow, daily_rows, format_rows, monthly_rows, monthly_table};
+#[cfg(feature = "std")]
+pub use terminator::{terminator, terminator_with_depression};
+#[cfg(feature = "time03")]
+pub use time03_interop::{DaylightOffset, calculate_daylight_date, calculate_daylight_offset,
+                         calculate_daylight_offset_with};
+#[cfg(feature = "std")]
+pub use twilight::{DawnDusk, dawn_dusk, dawn_dusk_with};
+#[cfg(feature = "std")]
+pub use uncertainty::{Uncertainty, calculate_daylight_with_uncertainty};
+#[cfg(feature = "std")]
+pub use zmanim::{ProportionalHours, ZmanimBasis, proportional_hours, proportional_hours_with};
+#[cfg(feature = "tz")]
+pub use zoned::{ZonedDaylight, calculate_daylight_zoned, calculate_daylight_zoned_range};
 
 const SUNRADIUS: f64 = 0.53;
+/// Refraction at the horizon in angle degrees for the standard atmosphere
 const AIRREFR: f64 = 34.0 / 60.0;
-const Y2000: Tm = Tm {
-    tm_sec: 0,
-    tm_min: 0,
-    tm_hour: 0,
-    tm_mday: 1,
-    tm_mon: 0,
-    tm_year: 100,
-    tm_wday: 6,
-    tm_yday: 0,
-    tm_isdst: 0,
-    tm_utcoff: 0,
-    tm_nsec: 0,
-};
+/// Midnight 2000-01-01 UTC in seconds since the unix epoch
+const Y2000: i64 = 946_684_800;
 const SECS_IN_HOUR: f64 = 3600.0;
-const HOURS_IN_DAY: f64 = 24.0;
 const FRAC_HOURS_IN_DAY_2: f64 = 12.0;
+const SECS_IN_DAY: i64 = 86_400;
+const NSECS_IN_SEC: f64 = 1e9;
+const REFINEMENT_PASSES: usize = 2;
+/// Annual aberration in angle degrees (20.5 arcseconds)
+const ABERRATION: f64 = 0.00569;
+/// Mean radius of the earth in meters
+const EARTH_RADIUS: f64 = 6_371_000.0;
+/// Terrestrial refraction bends the line of sight to the horizon as if the
+/// earth were larger by this factor
+const REFRACTION_RADIUS_FACTOR: f64 = 7.0 / 6.0;
 
+#[inline]
 fn to_radians(target: f64) -> f64 {
-    let value: f64 = consts::PI;
-    target * (value / 180.0)
+    target * (consts::PI / 180.0)
 }
 
 #[inline]
@@ -53,22 +292,121 @@ fn to_degrees(target: f64) -> f64 {
     target * (180.0f64 / consts::PI)
 }
 
+/// Latitude in radians and its trigonometric functions, calculated once and
+/// shared by the calculation of all events
+#[derive(Clone, Copy)]
+struct LatitudeTrig {
+    radians: f64,
+    sin: f64,
+    cos: f64,
+    tan: f64,
+}
+
+impl LatitudeTrig {
+    fn new(degrees: f64) -> LatitudeTrig {
+        let radians = to_radians(degrees);
+        let (sin, cos) = radians.sin_cos();
+        LatitudeTrig {
+            radians,
+            sin,
+            cos,
+            tan: radians.tan(),
+        }
+    }
+}
+
 /// Result of the daylight calculation (calculated times are UTC based)
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Daylight {
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::unix_seconds"))]
     pub twilight_morning: Timespec,
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::unix_seconds"))]
     pub sunrise: Timespec,
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::unix_seconds"))]
     pub sunset: Timespec,
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::unix_seconds"))]
     pub twilight_evening: Timespec,
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::unix_seconds"))]
     pub noon: Timespec,
-    /// Declination of the sun in angle degrees
+    /// Declination of the sun in angle degrees, at the input instant (or at
+    /// solar noon, when the calculation is refined)
     pub declination: f64,
-    /// Duration of the day (calculated in seconds)
+    /// Duration of the day; equal to `sunset - sunrise` before rounding (see
+    /// `DaylightOptions::rounding`)
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::seconds"))]
     pub daylength: Duration,
     /// Sun altitude in angle degrees
     pub sun_altitude: f64,
 }
 
+impl Daylight {
+    /// Returns the declination of the sun (see `declination`)
+    pub fn declination_angle(&self) -> Degrees {
+        Degrees(self.declination)
+    }
+
+    /// Returns the sun altitude (see `sun_altitude`)
+    pub fn sun_altitude_angle(&self) -> Degrees {
+        Degrees(self.sun_altitude)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Daylight {
+    /// Returns whether the sun stays above the horizon the whole day
+    fn is_polar_day(&self) -> bool {
+        self.daylength >= Duration::hours(24)
+    }
+
+    /// Returns whether the sun stays below the horizon the whole day
+    fn is_polar_night(&self) -> bool {
+        !self.is_polar_day() && self.sunrise == self.sunset
+    }
+
+    /// Returns whether the twilight starts and ends; not when the sun stays
+    /// above the twilight depression, or below it the whole day
+    fn has_twilight(&self) -> bool {
+        !self.is_polar_day() && self.twilight_morning < self.sunrise &&
+        self.noon - self.twilight_morning < Duration::hours(12)
+    }
+}
+
+/// Summary of the events in UTC, one per line; during polar day and night
+/// the missing sunrise and sunset are named instead
+#[cfg(feature = "std")]
+impl fmt::Display for Daylight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let utc = |ts: Timespec| time::at_utc(Timespec::new(ts.sec, 0)).rfc3339().to_string();
+
+        if self.has_twilight() {
+            writeln!(f, "twilight morning: {}", utc(self.twilight_morning))?;
+        }
+        if self.is_polar_day() {
+            writeln!(f, "sunrise/sunset:   sun does not set")?;
+        } else if self.is_polar_night() {
+            writeln!(f, "sunrise/sunset:   sun does not rise")?;
+        } else {
+            writeln!(f, "sunrise:          {}", utc(self.sunrise))?;
+        }
+        writeln!(f, "noon:             {}", utc(self.noon))?;
+        if !self.is_polar_day() && !self.is_polar_night() {
+            writeln!(f, "sunset:           {}", utc(self.sunset))?;
+        }
+        if self.has_twilight() {
+            writeln!(f, "twilight evening: {}", utc(self.twilight_evening))?;
+        }
+        let seconds = self.daylength.num_seconds();
+        writeln!(f,
+                 "daylength:        {:02}:{:02}:{:02}",
+                 seconds / 3600,
+                 seconds / 60 % 60,
+                 seconds % 60)?;
+        writeln!(f, "declination:      {:.2}°", self.declination)?;
+        write!(f, "sun altitude:     {:.2}°", self.sun_altitude)
+    }
+}
+
 /// the function below returns an angle in the range 0 to 2*pi
 fn fnrange(x: f64) -> f64 {
     let b = 0.5 * x / consts::PI;
@@ -80,32 +418,70 @@ fn fnrange(x: f64) -> f64 {
     }
 }
 
-// Commonality between original f0 and f1 function
-fn calculate_angle(lat: f64, declin: f64, fraction: f64) -> f64 {
+/// the function below returns an angle in the range -pi (exclusive) to pi
+fn fnrange_signed(x: f64) -> f64 {
+    let a = fnrange(x);
+    if a > consts::PI {
+        a - consts::PI * 2.0
+    } else {
+        a
+    }
+}
+
+/// Calculating the hourangle at which the centre of the sun is `fraction`
+/// below the horizon, for sunrise and sunset as well as for twilight times
+fn calculate_angle(lat: &LatitudeTrig, declin: f64, fraction: f64) -> f64 {
     // Correction: different sign as S HS
-    let df = if lat.is_sign_negative() {
+    let df = if lat.radians.is_sign_negative() {
         -fraction
     } else {
         fraction
     };
-    let f = (declin + df).tan() * lat.tan();
-    f.min(1.0).max(-1.0).asin() + consts::FRAC_PI_2
+    let f = (declin + df).tan() * lat.tan;
+    f.clamp(-1.0, 1.0).asin() + consts::FRAC_PI_2
 }
 
-/// Calculating the hourangle
-fn f0(lat: f64, declin: f64) -> f64 {
-    let df0 = to_radians(0.5 * SUNRADIUS + AIRREFR);
-    calculate_angle(lat, declin, df0)
+/// Calculating the hourangle at which the centre of the sun is `depression`
+/// below the horizon, using the rigorous spherical relation instead of the
+/// approximation in `calculate_angle`
+fn hour_angle(lat: &LatitudeTrig, declin: f64, depression: f64) -> f64 {
+    let (sin_declin, cos_declin) = declin.sin_cos();
+    let f = ((-depression).sin() - lat.sin * sin_declin) / (lat.cos * cos_declin);
+    f.clamp(-1.0, 1.0).acos()
 }
 
-/// Calculating the hourangle for twilight times
-fn f1(lat: f64, declin: f64) -> f64 {
-    let df1 = to_radians(6.0);
-    calculate_angle(lat, declin, df1)
+/// Dip of the horizon in radians for an observer `elevation` meters above
+/// it; approaches but never exceeds 90 degrees
+fn horizon_dip(elevation: f64) -> f64 {
+    if elevation > 0.0 {
+        let radius = EARTH_RADIUS * REFRACTION_RADIUS_FACTOR;
+        (radius / (radius + elevation)).acos()
+    } else {
+        0.0
+    }
 }
 
-/// Find the ecliptic longitude of the sun
-fn fnsun(d: f64) -> (f64, f64) {
+/// Depression of the centre of the sun below the horizon at sunrise and
+/// sunset in radians
+fn sunrise_depression(options: &DaylightOptions) -> f64 {
+    match options.get_horizon() {
+        Horizon::Apparent => {
+            let semidiameter = match options.get_limb() {
+                Limb::Upper => 0.5 * SUNRADIUS,
+                Limb::Center => 0.0,
+                Limb::Lower => -0.5 * SUNRADIUS,
+            };
+            to_radians(semidiameter + options.get_refraction()) +
+            horizon_dip(options.get_elevation())
+        }
+        Horizon::Geometric => 0.0,
+        Horizon::Custom(depression) => to_radians(depression),
+    }
+}
+
+/// Find the ecliptic longitude of the sun, with its mean longitude and mean
+/// anomaly
+fn fnsun(d: f64) -> (f64, f64, f64) {
     // mean longitude of the sun
     let mean_longitude = fnrange(to_radians(280.461) + to_radians(0.9856474) * d);
 
@@ -116,100 +492,672 @@ fn fnsun(d: f64) -> (f64, f64) {
     let ecliptic_longitude = fnrange(mean_longitude + to_radians(1.915) * g.sin() +
                                      to_radians(0.02) * (2.0 * g).sin());
 
-    (ecliptic_longitude, mean_longitude)
+    (ecliptic_longitude, mean_longitude, g)
 }
 
-/// Returns the number of days (including fraction) since midnight 2000-01-01
-fn days_since_2000(date: Tm) -> f64 {
-    let duration = date - Y2000;
+/// Returns the number of days since 1970-01-01 of the given proleptic
+/// Gregorian date (month and day are 1 based)
+#[cfg(feature = "std")]
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Converts a Tm to Timespec using 64-bit arithmetic only (unlike
+/// `Tm::to_timespec`, which depends on the platform's `time_t` and is
+/// limited to 2038 on 32-bit systems)
+#[cfg(feature = "std")]
+fn tm_to_timespec(tm: Tm) -> Timespec {
+    let days = days_from_civil(tm.tm_year as i64 + 1900,
+                               tm.tm_mon as i64 + 1,
+                               tm.tm_mday as i64);
+    let secs = tm.tm_hour as i64 * 3600 + tm.tm_min as i64 * 60 + tm.tm_sec as i64;
+
+    Timespec::new(days * SECS_IN_DAY + secs - tm.tm_utcoff as i64, tm.tm_nsec)
+}
+
+/// Returns 12:00 UTC of the date (month and day are 1 based), after
+/// validating it
+#[cfg(feature = "std")]
+fn ymd_to_tm(year: i32, month: u8, day: u8) -> Result<Tm, DaylightError> {
+    error::validate_ymd(year, month, day)?;
+    let days = days_from_civil(year as i64, month as i64, day as i64);
+    Ok(time::at_utc(Timespec::new(days * SECS_IN_DAY + SECS_IN_DAY / 2, 0)))
+}
+
+/// Returns the Timespec of the preceding UTC midnight
+fn midnight(ts: Timespec) -> Timespec {
+    Timespec::new(ts.sec - ts.sec.rem_euclid(SECS_IN_DAY), 0)
+}
 
-    duration.num_seconds() as f64 / (HOURS_IN_DAY * SECS_IN_HOUR)
+/// Returns the number of days (including fraction) since midnight 2000-01-01
+fn days_since_2000(ts: Timespec) -> f64 {
+    ((ts.sec - Y2000) as f64 + ts.nsec as f64 / NSECS_IN_SEC) / SECS_IN_DAY as f64
 }
 
-/// Converts daylight hours to Timespec
+/// Converts daylight hours to Timespec, including the fraction of the second
+/// (hours may be negative when the event happens before midnight, and
+/// midnight itself may be before 1970; in both cases the whole seconds are
+/// rounded down, like for positive values)
 fn daylight_hours_to_timespec(midnight: Timespec, hours: f64) -> Timespec {
-    Timespec {
-        sec: midnight.sec + (hours * SECS_IN_HOUR) as i64,
-        nsec: 0,
+    // the casts below saturate, a NaN would become midnight
+    assert!(hours.is_finite(), "hour value {} is not a finite number", hours);
+    let secs = hours * SECS_IN_HOUR;
+    let whole = secs.floor();
+    Timespec::new(midnight.sec + whole as i64, midnight.nsec) +
+    Duration::nanoseconds(((secs - whole) * NSECS_IN_SEC) as i64)
+}
+
+/// Converts the length in hours to a duration, negative lengths to zero
+fn hours_to_duration(hours: f64) -> Duration {
+    assert!(hours.is_finite(), "hour value {} is not a finite number", hours);
+    Duration::nanoseconds((hours.max(0.0) * SECS_IN_HOUR * NSECS_IN_SEC) as i64)
+}
+
+/// Rounds the number of nanoseconds to a multiple of `unit` nanoseconds
+fn round_nanoseconds(nanoseconds: i64, unit: i64, rounding: Rounding) -> i64 {
+    match rounding {
+        Rounding::Exact => nanoseconds,
+        Rounding::Floor => nanoseconds.div_euclid(unit) * unit,
+        Rounding::Nearest | Rounding::NearestMinute => {
+            (nanoseconds + unit / 2).div_euclid(unit) * unit
+        }
     }
 }
 
-/// Calculate civil twilight (am/pm) and sunrise and sunset at given date
-pub fn calculate_daylight(date: Tm, latitude: f64, longitude: f64) -> Daylight {
-    let lat_rad = to_radians(latitude);
-    let utc = date.to_utc();
-    let d2000 = days_since_2000(utc);
+/// Rounds the timestamp as specified
+fn round_timespec(ts: Timespec, rounding: Rounding) -> Timespec {
+    let unit = if rounding == Rounding::NearestMinute { 60 } else { 1 };
+    // the whole units are exact, only the remainder needs to be rounded
+    let base = ts.sec - ts.sec.rem_euclid(unit);
+    let remainder = (ts.sec - base) * NSECS_IN_SEC as i64 + ts.nsec as i64;
+    let rounded = round_nanoseconds(remainder, unit * NSECS_IN_SEC as i64, rounding);
+    Timespec::new(base, 0) + Duration::nanoseconds(rounded)
+}
+
+/// Rounds the duration (of less than a few days) as specified
+fn round_duration(duration: Duration, rounding: Rounding) -> Duration {
+    let unit = if rounding == Rounding::NearestMinute { 60 } else { 1 };
+    let nanoseconds = duration.num_nanoseconds().expect("duration out of range");
+    Duration::nanoseconds(round_nanoseconds(nanoseconds, unit * NSECS_IN_SEC as i64, rounding))
+}
+
+/// Intermediate result of the calculation for a single date and location
+/// (hour values are relative to UTC midnight of the input date)
+#[derive(Clone, Copy)]
+struct SolarDay {
+    /// Moment of solar noon in hours
+    noon: f64,
+    /// Time between sunrise and noon in hours
+    morning: f64,
+    /// Time between noon and sunset in hours
+    evening: f64,
+    /// Length of the civil twilight before sunrise in hours
+    twilight_morning: f64,
+    /// Length of the civil twilight after sunset in hours
+    twilight_evening: f64,
+    /// Declination of the sun in angle degrees
+    declination: f64,
+    /// Sun altitude at noon in radians
+    altitude: f64,
+}
+
+impl SolarDay {
+    fn to_daylight(self, tsnoon: Timespec, rounding: Rounding) -> Daylight {
+        // all events are derived from the same noon and lengths, so that
+        // sunrise + daylength == sunset holds exactly before rounding; as
+        // none of the lengths can be negative, the events are ordered by
+        // construction (twilight_morning <= sunrise <= noon <= sunset <=
+        // twilight_evening), which the (monotonic) rounding preserves
+        let duration = hours_to_duration;
+        let sunrise = tsnoon - duration(self.morning);
+        let sunset = tsnoon + duration(self.evening);
+        let round = |ts: Timespec| round_timespec(ts, rounding);
+
+        Daylight {
+            twilight_morning: round(sunrise - duration(self.twilight_morning)),
+            sunrise: round(sunrise),
+            sunset: round(sunset),
+            twilight_evening: round(sunset + duration(self.twilight_evening)),
+            noon: round(tsnoon),
+            declination: self.declination,
+            daylength: round_duration(sunset - sunrise, rounding),
+            sun_altitude: to_degrees(self.altitude),
+        }
+    }
+
+    /// Re-evaluates the position of the sun at the (approximate) moment of
+    /// each event, instead of at the input instant; `tsmidnight` is the
+    /// reference of the hour values
+    fn refine(self,
+              tsmidnight: Timespec,
+              lat: &LatitudeTrig,
+              longitude: f64,
+              options: &DaylightOptions)
+              -> SolarDay {
+        let at = |hours: f64| {
+            solar_day(daylight_hours_to_timespec(tsmidnight, hours),
+                      lat,
+                      longitude,
+                      options)
+        };
+        let mut day = self;
+
+        for _ in 0..REFINEMENT_PASSES {
+            let sunrise = day.noon - day.morning;
+            let sunset = day.noon + day.evening;
+            let twam = sunrise - day.twilight_morning;
+            let twpm = sunset + day.twilight_evening;
+
+            let at_noon = at(day.noon);
+            let at_sunrise = at(sunrise);
+            let at_sunset = at(sunset);
+            let at_twam = at(twam);
+            let at_twpm = at(twpm);
+
+            let sunrise = at_sunrise.noon - at_sunrise.morning;
+            let sunset = at_sunset.noon + at_sunset.evening;
+            let twam = at_twam.noon - at_twam.morning - at_twam.twilight_morning;
+            let twpm = at_twpm.noon + at_twpm.evening + at_twpm.twilight_evening;
+
+            // like for a single evaluation, neither half of the day (including
+            // its twilight) can exceed 12 hours; a half that is whole or empty
+            // at its event (the polar day or night) stays so, although the
+            // noon of that evaluation differs a few seconds from the noon of
+            // the day
+            let half = |hours: f64, at_event: f64| {
+                if at_event >= FRAC_HOURS_IN_DAY_2 {
+                    FRAC_HOURS_IN_DAY_2
+                } else if at_event <= 0.0 {
+                    0.0
+                } else {
+                    hours.clamp(0.0, FRAC_HOURS_IN_DAY_2)
+                }
+            };
+            let morning = half(at_noon.noon - sunrise, at_sunrise.morning);
+            let evening = half(sunset - at_noon.noon, at_sunset.evening);
+
+            day = SolarDay {
+                noon: at_noon.noon,
+                morning,
+                evening,
+                twilight_morning: half(at_noon.noon - twam,
+                                       at_twam.morning + at_twam.twilight_morning) -
+                                  morning,
+                twilight_evening: half(twpm - at_noon.noon,
+                                       at_twpm.evening + at_twpm.twilight_evening) -
+                                  evening,
+                declination: at_noon.declination,
+                altitude: at_noon.altitude,
+            };
+        }
+
+        day
+    }
+}
+
+/// Find the apparent ecliptic longitude of the sun (excluding nutation), its
+/// mean longitude, the obliquity of the ecliptic and the mean anomaly,
+/// including the higher order terms of the equation of center and the
+/// secular terms of the low precision formulae (Meeus, Astronomical
+/// Algorithms, chapter 25)
+fn fnsun_extended(d: f64) -> (f64, f64, f64, f64) {
+    // Julian centuries since J2000.0
+    let t = d / 36525.0;
+
+    // mean longitude of the sun
+    let mean_longitude = fnrange(to_radians(280.46646 + t * (36000.76983 + t * 0.0003032)));
+
+    // mean anomaly of the sun
+    let g = to_radians(357.52911 + t * (35999.05029 - t * 0.0001537));
+
+    // equation of center
+    let center = (1.914602 - t * (0.004817 + t * 0.000014)) * g.sin() +
+                 (0.019993 - t * 0.000101) * (2.0 * g).sin() +
+                 0.000289 * (3.0 * g).sin();
 
-    // find the ecliptic longitude of the sun
-    let (ecliptic_longitude, mean_longitude) = fnsun(d2000);
+    // Ecliptic longitude of the sun, corrected for the annual aberration
+    // (like the constants of `fnsun` implicitly are)
+    let ecliptic_longitude = fnrange(mean_longitude + to_radians(center - ABERRATION));
 
     // Obliquity of the ecliptic
-    let obliq = to_radians(23.439) - to_radians(0.0000004) * d2000;
+    let obliq_seconds = 21.448 - t * (46.815 + t * (0.00059 - t * 0.001813));
+    let obliq = to_radians(23.0 + (26.0 + obliq_seconds / 60.0) / 60.0);
 
-    // Find the RA and DEC of the sun
-    let alpha = (obliq.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
-    let delta = (obliq.sin() * ecliptic_longitude.sin()).asin();
+    (ecliptic_longitude, mean_longitude, obliq, g)
+}
 
-    // Find the equation of time
-    // in minutes
-    // Correction suggested by David Smith
-    let mean_longitude_corr = mean_longitude - alpha;
-    let mean_longitude_corr2 = if mean_longitude_corr < consts::PI {
-        mean_longitude_corr + consts::PI * 2.0
-    } else {
-        mean_longitude_corr
-    };
-    let equation = HOURS_IN_DAY * (1.0 - mean_longitude_corr2 / (consts::PI * 2.0));
-    let ha = f0(lat_rad, delta);
-    let hb = f1(lat_rad, delta);
-    let twx_radians = hb - ha; // length of twilight in radions
-    let twx = FRAC_HOURS_IN_DAY_2 * twx_radians / consts::PI; // lenth of twilight in hours
-
-    // artic winter
-    let halfday = FRAC_HOURS_IN_DAY_2 * ha / consts::PI;
-    let riset = FRAC_HOURS_IN_DAY_2 - halfday - longitude / 15.0 + equation;
-    let settm = FRAC_HOURS_IN_DAY_2 + halfday - longitude / 15.0 + equation;
-    let noon = riset + halfday;
-
-    let twam = riset - twx;
-    let twpm = settm + twx;
-
-    let altmax_nh = consts::FRAC_PI_2 + delta - lat_rad;
-    let altmax = if lat_rad < delta {
+/// Returns the altitude of the sun at noon (in radians)
+fn noon_altitude(lat: &LatitudeTrig, declin: f64) -> f64 {
+    let altmax_nh = consts::FRAC_PI_2 + declin - lat.radians;
+    if lat.radians < declin {
         consts::PI - altmax_nh
     } else {
         altmax_nh
+    }
+}
+
+/// Calculates the solar day for the given instant and location
+fn solar_day(utc: Timespec,
+             lat: &LatitudeTrig,
+             longitude: f64,
+             options: &DaylightOptions)
+             -> SolarDay {
+    match options.get_algorithm() {
+        Algorithm::Lammi => solar_day_lammi(utc, lat, longitude, options),
+        Algorithm::Noaa => noaa::solar_day(utc, lat, longitude, options),
+        Algorithm::Custom(algorithm) => {
+            algorithm.sun_position(days_since_2000(utc)).solar_day(lat, longitude, options)
+        }
+    }
+}
+
+/// Calculates the solar day using the algorithm of Jarmo Lammi
+fn solar_day_lammi(utc: Timespec,
+                   lat: &LatitudeTrig,
+                   longitude: f64,
+                   options: &DaylightOptions)
+                   -> SolarDay {
+    SunEphemeris::from(solar_parameters_at(utc, options)).solar_day(lat, longitude, options)
+}
+
+/// Position of the sun at an instant, the intermediate result of the
+/// calculation before the location is taken into account (see
+/// `solar_parameters`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolarParameters {
+    /// Declination of the sun in angle degrees, positive to the north
+    pub declination: f64,
+    /// Right ascension of the sun in angle degrees (not hours), within 0 to
+    /// 360
+    pub right_ascension: f64,
+    /// Equation of time in minutes: apparent minus mean solar time, so
+    /// positive when the sun crosses the meridian before 12:00 of the mean
+    /// solar time (like in early November)
+    pub equation_of_time: f64,
+    /// Obliquity of the ecliptic in angle degrees
+    pub obliquity: f64,
+    /// Apparent ecliptic longitude of the sun in angle degrees, within 0 to
+    /// 360 (see `ecliptic_longitude`)
+    pub ecliptic_longitude: f64,
+    /// Distance from the earth to the sun in astronomical units, from the
+    /// mean anomaly (within about 0.0002)
+    pub distance: f64,
+}
+
+/// Returns the ecliptic longitude, mean longitude and mean anomaly of the
+/// sun, and the obliquity of the ecliptic in radians at the instant `days`
+/// since 2000-01-01 00:00 UTC, for the algorithm of Jarmo Lammi
+fn sun_ecliptic(days: f64, options: &DaylightOptions) -> (f64, f64, f64, f64) {
+    let d2000_ut = if options.get_refined() || options.get_series() != Series::Standard {
+        // the series below are defined relative to J2000.0 (2000-01-01
+        // 12:00), while the default calculation counts from midnight
+        days - 0.5
+    } else {
+        days
     };
+    // the ephemeris is based on terrestrial time
+    let d2000 = d2000_ut + options.get_delta_t().seconds(d2000_ut) / SECS_IN_DAY as f64;
 
-    // get midnight reference
-    let utcmidnight = Tm {
-        tm_mday: utc.tm_mday,
-        tm_mon: utc.tm_mon,
-        tm_year: utc.tm_year,
-        tm_wday: utc.tm_wday,
-        tm_yday: utc.tm_yday,
-        tm_utcoff: utc.tm_utcoff,
-        tm_isdst: utc.tm_isdst,
-        tm_nsec: 0,
-        tm_sec: 0,
-        tm_min: 0,
-        tm_hour: 0,
+    let (ecliptic_longitude, mean_longitude, obliq, g) = match options.get_series() {
+        Series::Standard => {
+            // find the ecliptic longitude of the sun
+            let (ecliptic_longitude, mean_longitude, g) = fnsun(d2000);
+
+            // Obliquity of the ecliptic
+            let obliq = to_radians(23.439) - to_radians(0.0000004) * d2000;
+
+            (ecliptic_longitude, mean_longitude, obliq, g)
+        }
+        Series::Extended => fnsun_extended(d2000),
     };
-    let tsmidnight = utcmidnight.to_timespec();
-
-    Daylight {
-        twilight_morning: daylight_hours_to_timespec(tsmidnight, twam),
-        sunrise: daylight_hours_to_timespec(tsmidnight, riset),
-        sunset: daylight_hours_to_timespec(tsmidnight, settm),
-        twilight_evening: daylight_hours_to_timespec(tsmidnight, twpm),
-        noon: daylight_hours_to_timespec(tsmidnight, noon),
+    if options.get_nutation() {
+        // principal terms of the nutation in longitude and obliquity, with
+        // the nutation in right ascension (the equation of the equinoxes)
+        // added to the mean longitude for the equation of time (Meeus,
+        // Astronomical Algorithms, chapters 25 and 28)
+        let omega = to_radians(125.04 - 1934.136 * d2000 / 36525.0);
+        let longitude_nutation = to_radians(-0.00478) * omega.sin();
+        let obliq = obliq + to_radians(0.00256) * omega.cos();
+        return (fnrange(ecliptic_longitude + longitude_nutation),
+                mean_longitude + longitude_nutation * obliq.cos(),
+                obliq,
+                g);
+    }
+    (ecliptic_longitude, mean_longitude, obliq, g)
+}
+
+/// Calculates the position of the sun at the instant for the algorithm of
+/// Jarmo Lammi
+fn solar_parameters_at(utc: Timespec, options: &DaylightOptions) -> SolarParameters {
+    solar_parameters_days(days_since_2000(utc), options)
+}
+
+/// Calculates the position of the sun at the instant `days` since
+/// 2000-01-01 00:00 UTC for the algorithm of Jarmo Lammi
+fn solar_parameters_days(days: f64, options: &DaylightOptions) -> SolarParameters {
+    let (ecliptic_longitude, mean_longitude, obliq, g) = sun_ecliptic(days, options);
+
+    // Find the RA and DEC of the sun
+    let (sin_longitude, cos_longitude) = ecliptic_longitude.sin_cos();
+    let (sin_obliq, cos_obliq) = obliq.sin_cos();
+    let alpha = (cos_obliq * sin_longitude).atan2(cos_longitude);
+    let delta = (sin_obliq * sin_longitude).asin();
+
+    // Find the equation of time
+    // in minutes, 4 per degree; the difference between the mean longitude
+    // and the right ascension is normalized into (-pi, pi], so it can never
+    // be off by a full revolution
+    let equation = 4.0 * to_degrees(fnrange_signed(mean_longitude - alpha));
+
+    SolarParameters {
         declination: to_degrees(delta),
-        daylength: Duration::seconds((halfday * SECS_IN_HOUR * 2.0) as i64),
-        sun_altitude: to_degrees(altmax),
+        right_ascension: to_degrees(fnrange(alpha)),
+        equation_of_time: equation,
+        obliquity: to_degrees(obliq),
+        ecliptic_longitude: to_degrees(ecliptic_longitude),
+        distance: 1.00014 - 0.01671 * g.cos() - 0.00014 * (2.0 * g).cos(),
+    }
+}
+
+impl SunEphemeris {
+    /// Returns the correction of the solar noon for the equation of time in
+    /// hours
+    fn equation_hours(&self) -> f64 {
+        -self.equation_of_time / 60.0
+    }
+
+    /// Calculates the solar day at the location using the algorithm of Jarmo
+    /// Lammi
+    fn solar_day(&self,
+                 lat: &LatitudeTrig,
+                 longitude: f64,
+                 options: &DaylightOptions)
+                 -> SolarDay {
+        let (delta, equation) = (to_radians(self.declination), self.equation_hours());
+        let depression = sunrise_depression(options);
+        let twilight = to_radians(options.get_twilight_angle());
+        let (ha, hb) = if options.get_refined() {
+            (hour_angle(lat, delta, depression), hour_angle(lat, delta, twilight))
+        } else {
+            (calculate_angle(lat, delta, depression), calculate_angle(lat, delta, twilight))
+        };
+        let twx_radians = hb - ha; // length of twilight in radions
+        let twx = FRAC_HOURS_IN_DAY_2 * twx_radians / consts::PI; // lenth of twilight in hours
+
+        // artic winter
+        let halfday = FRAC_HOURS_IN_DAY_2 * ha / consts::PI;
+        let riset = FRAC_HOURS_IN_DAY_2 - halfday - longitude / 15.0 + equation;
+        let noon = riset + halfday;
+
+        let altmax = noon_altitude(lat, delta);
+
+        SolarDay {
+            noon,
+            morning: halfday,
+            evening: halfday,
+            twilight_morning: twx,
+            twilight_evening: twx,
+            declination: self.declination,
+            altitude: altmax,
+        }
+    }
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date
+///
+/// The input is not validated (see `calculate_daylight_checked`): a date with
+/// out of range fields is normalized (e.g. 31 April is 1 May), and a latitude
+/// or longitude that is not a finite number or an invalid `tm_nsec` panics.
+#[cfg(feature = "std")]
+pub fn calculate_daylight(date: Tm, latitude: f64, longitude: f64) -> Daylight {
+    calculate_daylight_with(date, latitude, longitude, &DaylightOptions::default())
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// after validating the date and location
+#[cfg(feature = "std")]
+pub fn calculate_daylight_checked(date: Tm,
+                                  latitude: f64,
+                                  longitude: f64)
+                                  -> Result<Daylight, DaylightError> {
+    error::validate(&date, latitude, longitude)?;
+    Ok(calculate_daylight(date, latitude, longitude))
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at 12:00 UTC of
+/// the date, with the month and day counted from 1 (unlike the fields of
+/// `Tm`); after validating the date and location
+#[cfg(feature = "std")]
+pub fn calculate_daylight_ymd(year: i32,
+                              month: u8,
+                              day: u8,
+                              latitude: f64,
+                              longitude: f64)
+                              -> Result<Daylight, DaylightError> {
+    calculate_daylight_checked(ymd_to_tm(year, month, day)?, latitude, longitude)
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// using the given calculation options
+#[cfg(feature = "std")]
+pub fn calculate_daylight_with(date: Tm,
+                               latitude: f64,
+                               longitude: f64,
+                               options: &DaylightOptions)
+                               -> Daylight {
+    daylight_at(tm_to_timespec(date), &LatitudeTrig::new(latitude), longitude, options)
+}
+
+/// Calculates the daylight at the instant, for a latitude of which the
+/// trigonometric functions are already calculated
+fn daylight_at(utc: Timespec,
+               lat: &LatitudeTrig,
+               longitude: f64,
+               options: &DaylightOptions)
+               -> Daylight {
+    let day = refined_solar_day(utc, lat, longitude, options);
+    day.to_daylight(daylight_hours_to_timespec(midnight(utc), day.noon),
+                    options.get_rounding())
+}
+
+/// Calculates the solar day at the instant, refined when the options or the
+/// algorithm ask for it (see `SolarDay::refine`); the hours are relative to
+/// the preceding UTC midnight
+fn refined_solar_day(utc: Timespec,
+                     lat: &LatitudeTrig,
+                     longitude: f64,
+                     options: &DaylightOptions)
+                     -> SolarDay {
+    assert!(lat.radians.is_finite() && longitude.is_finite(),
+            "latitude {} and longitude {} must be finite numbers",
+            to_degrees(lat.radians),
+            longitude);
+    let day = solar_day(utc, lat, longitude, options);
+
+    if options.get_refined() || options.get_algorithm() == Algorithm::Noaa {
+        day.refine(midnight(utc), lat, longitude, options)
+    } else {
+        day
     }
 }
 
+/// Calculate civil twilight (am/pm) and sunrise and sunset at the instant
+/// `secs` seconds since the unix epoch (negative before 1970)
+#[cfg(feature = "std")]
+pub fn calculate_daylight_unix(secs: i64, latitude: f64, longitude: f64) -> Daylight {
+    calculate_daylight(time::at_utc(Timespec::new(secs, 0)), latitude, longitude)
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at the given
+/// system time, also before 1970
+#[cfg(feature = "std")]
+pub fn calculate_daylight_systemtime(t: SystemTime, latitude: f64, longitude: f64) -> Daylight {
+    let ts = match t.duration_since(UNIX_EPOCH) {
+        Ok(since) => Timespec::new(since.as_secs() as i64, since.subsec_nanos() as i32),
+        Err(before) => {
+            let before = before.duration();
+            Timespec::new(0, 0) - Duration::seconds(before.as_secs() as i64) -
+            Duration::nanoseconds(before.subsec_nanos() as i64)
+        }
+    };
+    calculate_daylight(time::at_utc(ts), latitude, longitude)
+}
+
+/// Returns whether the sun is up at the instant: whether the centre of the
+/// sun is at or above the altitude it has at sunrise and sunset (see
+/// `Horizon::Apparent`), consistent with `calculate_daylight_precise`.
+///
+/// Unlike comparing with the events of `calculate_daylight`, this does not
+/// depend on the solar day the instant is assigned to.
+#[cfg(feature = "std")]
+pub fn is_daytime(t: Timespec, latitude: f64, longitude: f64) -> bool {
+    let options = DaylightOptions::default().refined(true);
+    sun_altitude_at(t, latitude, longitude, &options) >= -sunrise_depression(&options)
+}
+
+/// Returns whether the sun is below the horizon at the instant, but its
+/// centre at most `depression_deg` angle degrees below the true horizon
+/// (e.g. `6.0` for the civil twilight)
+#[cfg(feature = "std")]
+pub fn is_twilight(t: Timespec, latitude: f64, longitude: f64, depression_deg: f64) -> bool {
+    let options = DaylightOptions::default().refined(true);
+    let altitude = sun_altitude_at(t, latitude, longitude, &options);
+    altitude < -sunrise_depression(&options) && altitude >= -to_radians(depression_deg)
+}
+
+/// Returns the apparent ecliptic longitude of the sun at the instant in angle
+/// degrees, from 0 up to 360: 0 at the March equinox, 90 at the June
+/// solstice. It is the longitude from which `calculate_daylight` derives the
+/// declination of the sun.
+///
+/// The series of the Astronomical Almanac that the calculation uses includes
+/// the aberration (about -20.5 arcseconds), but not the nutation (up to
+/// about 17 arcseconds, see `DaylightOptions::nutation`). Like the rest of
+/// the default calculation, it counts the days from midnight instead of from
+/// noon, and is about half a day (0.5 degrees) ahead; with the refined
+/// options (see `ecliptic_longitude_with`) it is within about 0.01 degrees
+/// of the apparent longitude of an ephemeris.
+#[cfg(feature = "std")]
+pub fn ecliptic_longitude(time: Tm) -> f64 {
+    ecliptic_longitude_with(time, &DaylightOptions::default())
+}
+
+/// Returns the apparent ecliptic longitude of the sun at the instant in angle
+/// degrees (see `ecliptic_longitude`), using the given calculation options
+/// like `calculate_daylight_with`
+#[cfg(feature = "std")]
+pub fn ecliptic_longitude_with(time: Tm, options: &DaylightOptions) -> f64 {
+    solar_parameters_with(time, options).ecliptic_longitude
+}
+
+/// Returns the position of the sun at the instant (see `SolarParameters`),
+/// from which `calculate_daylight` calculates the events; the same for every
+/// location
+#[cfg(feature = "std")]
+pub fn solar_parameters(time: Tm) -> SolarParameters {
+    solar_parameters_with(time, &DaylightOptions::default())
+}
+
+/// Returns the position of the sun at the instant (see `solar_parameters`),
+/// using the given calculation options like `calculate_daylight_with`
+#[cfg(feature = "std")]
+pub fn solar_parameters_with(time: Tm, options: &DaylightOptions) -> SolarParameters {
+    solar_parameters_at(tm_to_timespec(time), options)
+}
+
+/// Returns the altitude of the centre of the sun in radians at the instant
+#[cfg(feature = "std")]
+fn sun_altitude_at(t: Timespec, latitude: f64, longitude: f64, options: &DaylightOptions) -> f64 {
+    horizon::sun_position(t, &LatitudeTrig::new(latitude), longitude, options).0
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// evaluating the position of the sun at the moment of each event (see
+/// `DaylightOptions::refined`)
+#[cfg(feature = "std")]
+pub fn calculate_daylight_precise(date: Tm, latitude: f64, longitude: f64) -> Daylight {
+    calculate_daylight_with(date,
+                            latitude,
+                            longitude,
+                            &DaylightOptions::default().refined(true))
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset for the civil
+/// date at the location itself.
+///
+/// The year, month and day of `date` are taken as the calendar date at the
+/// location (its time and offset fields are ignored). The day is anchored on
+/// local mean solar time (`longitude / 15` hours from UTC), so noon always
+/// falls within that local day; even near the date line, where
+/// `calculate_daylight` may return the events of the neighbouring day.
+#[cfg(feature = "std")]
+pub fn calculate_daylight_local(date: Tm, latitude: f64, longitude: f64) -> Daylight {
+    let utc_offset = (longitude * SECS_IN_HOUR / 15.0).round() as i32;
+    calculate_daylight_local_with_offset(date, latitude, longitude, utc_offset)
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset for the civil
+/// date at the location, of which the clock is `utc_offset` seconds ahead
+/// of UTC (e.g. `14 * 3600` for Kiribati).
+///
+/// Like `calculate_daylight_local`, but anchored on the given UTC offset
+/// instead of the longitude. Noon falls within the local day
+/// `[00:00, 24:00)`; for locations with a regular offset so do sunrise and
+/// sunset, except in polar conditions where the day is 24 hours long.
+#[cfg(feature = "std")]
+pub fn calculate_daylight_local_with_offset(date: Tm,
+                                            latitude: f64,
+                                            longitude: f64,
+                                            utc_offset: i32)
+                                            -> Daylight {
+    local_daylight(date,
+                   latitude,
+                   longitude,
+                   utc_offset,
+                   &DaylightOptions::default())
+}
+
+/// Calculates the daylight of the civil date at a location with the given
+/// UTC offset (see `calculate_daylight_local_with_offset`)
+#[cfg(feature = "std")]
+fn local_daylight(date: Tm,
+                  latitude: f64,
+                  longitude: f64,
+                  utc_offset: i32,
+                  options: &DaylightOptions)
+                  -> Daylight {
+    let date_midnight = days_from_civil(date.tm_year as i64 + 1900,
+                                        date.tm_mon as i64 + 1,
+                                        date.tm_mday as i64) * SECS_IN_DAY;
+    let local_midnight = Timespec::new(date_midnight - utc_offset as i64, 0);
+    let local_noon = local_midnight + Duration::hours(12);
+    let lat = LatitudeTrig::new(latitude);
+    let day = solar_day(local_noon, &lat, longitude, options);
+
+    let tsmidnight = midnight(local_noon);
+    let day = if options.get_refined() || options.get_algorithm() == Algorithm::Noaa {
+        day.refine(tsmidnight, &lat, longitude, options)
+    } else {
+        day
+    };
+
+    // shift the calculated noon by whole days into the local day
+    let tsnoon = daylight_hours_to_timespec(tsmidnight, day.noon);
+    let offset_in_day = (tsnoon.sec - local_midnight.sec).rem_euclid(SECS_IN_DAY);
+
+    day.to_daylight(Timespec::new(local_midnight.sec + offset_in_day, tsnoon.nsec),
+                    options.get_rounding())
+}
+
 #[test]
+#[cfg(feature = "std")]
 fn days_since_20150327_1200_utc() {
     let tm20150327_1200 = Tm {
         tm_sec: 0,
@@ -225,10 +1173,20 @@ fn days_since_20150327_1200_utc() {
         tm_nsec: 0,
     };
 
-    assert_eq!(days_since_2000(tm20150327_1200), 5564.5);
+    assert_eq!(days_since_2000(tm_to_timespec(tm20150327_1200)), 5564.5);
+}
+
+/// Calculates the golden values with the default algorithm, both directly
+/// and through the `SolarAlgorithm` of its series (see `Algorithm::Custom`)
+#[cfg(all(test, feature = "std"))]
+fn golden_daylight(date: Tm, latitude: f64, longitude: f64) -> [Daylight; 2] {
+    let custom = DaylightOptions::default().algorithm(Algorithm::Custom(&Series::Standard));
+    [calculate_daylight(date, latitude, longitude),
+     calculate_daylight_with(date, latitude, longitude, &custom)]
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn daylight_apeldoorn_20150327_1200_utc() {
     let tm20150327_1200 = Tm {
         tm_sec: 0,
@@ -246,23 +1204,25 @@ fn daylight_apeldoorn_20150327_1200_utc() {
     let lat_apeldoorn = 52.0 + 13.0 / 60.0;
     let long_apeldoorn = 5.0 + 58.0 / 60.0;
 
-    let daylight = calculate_daylight(tm20150327_1200, lat_apeldoorn, long_apeldoorn);
-
-    assert_eq!(daylight.twilight_morning.sec, 1427432129); // 2015-03-27T05:55:29+01:00
-    assert_eq!(daylight.sunrise.sec, 1427433766); // 2015-03-27T06:22:46+01:00
-    assert_eq!(daylight.noon.sec, 1427456487);
-    assert_eq!(daylight.sunset.sec, 1427479207); // 2015-03-27T19:00:07+01:00
-    assert_eq!(daylight.twilight_evening.sec, 1427480844); // 2015-03-27T19:27:24+01:00
-    assert_eq!(daylight.daylength.num_seconds(), 45440);
-    assert!(daylight.declination > 2.777311 && daylight.declination < 2.777313,
-            "declination != {}",
-            daylight.declination);
-    assert!(daylight.sun_altitude > 40.55 && daylight.sun_altitude < 40.57,
-            "sun_altitude != {}",
-            daylight.sun_altitude);
+    // default options: the golden values are rounded to the nearest second
+    for daylight in &golden_daylight(tm20150327_1200, lat_apeldoorn, long_apeldoorn) {
+        assert_eq!(daylight.twilight_morning.sec, 1427432130); // 2015-03-27T05:55:30+01:00
+        assert_eq!(daylight.sunrise.sec, 1427433767); // 2015-03-27T06:22:47+01:00
+        assert_eq!(daylight.noon.sec, 1427456487);
+        assert_eq!(daylight.sunset.sec, 1427479207); // 2015-03-27T19:00:07+01:00
+        assert_eq!(daylight.twilight_evening.sec, 1427480844); // 2015-03-27T19:27:24+01:00
+        assert_eq!(daylight.daylength.num_seconds(), 45440);
+        assert!(daylight.declination > 2.777311 && daylight.declination < 2.777313,
+                "declination != {}",
+                daylight.declination);
+        assert!(daylight.sun_altitude > 40.55 && daylight.sun_altitude < 40.57,
+                "sun_altitude != {}",
+                daylight.sun_altitude);
+    }
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn daylight_tokyo_20150327_1200_utc() {
     let tm20150327_1200 = Tm {
         tm_sec: 0,
@@ -280,23 +1240,25 @@ fn daylight_tokyo_20150327_1200_utc() {
     let lat_tokyo = 35.41;
     let long_tokyo = 139.41;
 
-    let daylight = calculate_daylight(tm20150327_1200, lat_tokyo, long_tokyo);
-
-    assert_eq!(daylight.twilight_morning.sec, 1427401349);
-    assert_eq!(daylight.sunrise.sec, 1427402244);
-    assert_eq!(daylight.noon.sec, 1427424460);
-    assert_eq!(daylight.sunset.sec, 1427446677);
-    assert_eq!(daylight.twilight_evening.sec, 1427447573);
-    assert_eq!(daylight.daylength.num_seconds(), 44433);
-    assert!(daylight.declination > 2.777311 && daylight.declination < 2.777313,
-            "declination != {}",
-            daylight.declination);
-    assert!(daylight.sun_altitude > 57.35 && daylight.sun_altitude < 57.37,
-            "sun_altitude != {}",
-            daylight.sun_altitude);
+    // default options: the golden values are rounded to the nearest second
+    for daylight in &golden_daylight(tm20150327_1200, lat_tokyo, long_tokyo) {
+        assert_eq!(daylight.twilight_morning.sec, 1427401348);
+        assert_eq!(daylight.sunrise.sec, 1427402244);
+        assert_eq!(daylight.noon.sec, 1427424461);
+        assert_eq!(daylight.sunset.sec, 1427446677);
+        assert_eq!(daylight.twilight_evening.sec, 1427447573);
+        assert_eq!(daylight.daylength.num_seconds(), 44434);
+        assert!(daylight.declination > 2.777311 && daylight.declination < 2.777313,
+                "declination != {}",
+                daylight.declination);
+        assert!(daylight.sun_altitude > 57.35 && daylight.sun_altitude < 57.37,
+                "sun_altitude != {}",
+                daylight.sun_altitude);
+    }
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn daylight_avarua_20150327_1200_utc() {
     let tm20150327_1200 = Tm {
         tm_sec: 0,
@@ -314,23 +1276,25 @@ fn daylight_avarua_20150327_1200_utc() {
     let lat_tokyo = -21.12;
     let long_tokyo = -159.46;
 
-    let daylight = calculate_daylight(tm20150327_1200, lat_tokyo, long_tokyo);
-
-    assert_eq!(daylight.twilight_morning.sec, 1427474290);
-    assert_eq!(daylight.sunrise.sec, 1427474769);
-    assert_eq!(daylight.noon.sec, 1427496189);
-    assert_eq!(daylight.sunset.sec, 1427517608);
-    assert_eq!(daylight.twilight_evening.sec, 1427518088);
-    assert_eq!(daylight.daylength.num_seconds(), 42839);
-    assert!(daylight.declination > 2.777311 && daylight.declination < 2.777313,
-            "declination != {}",
-            daylight.declination);
-    assert!(daylight.sun_altitude > 66.09 && daylight.sun_altitude < 66.11,
-            "sun_altitude != {}",
-            daylight.sun_altitude);
+    // default options: the golden values are rounded to the nearest second
+    for daylight in &golden_daylight(tm20150327_1200, lat_tokyo, long_tokyo) {
+        assert_eq!(daylight.twilight_morning.sec, 1427474290);
+        assert_eq!(daylight.sunrise.sec, 1427474770);
+        assert_eq!(daylight.noon.sec, 1427496189);
+        assert_eq!(daylight.sunset.sec, 1427517609);
+        assert_eq!(daylight.twilight_evening.sec, 1427518089);
+        assert_eq!(daylight.daylength.num_seconds(), 42839);
+        assert!(daylight.declination > 2.777311 && daylight.declination < 2.777313,
+                "declination != {}",
+                daylight.declination);
+        assert!(daylight.sun_altitude > 66.09 && daylight.sun_altitude < 66.11,
+                "sun_altitude != {}",
+                daylight.sun_altitude);
+    }
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn daylight_longyearbyen_20150621_1200_utc_midsummer() {
     let tm20150621_1200 = Tm {
         tm_sec: 0,
@@ -348,25 +1312,26 @@ fn daylight_longyearbyen_20150621_1200_utc_midsummer() {
     let lat_tokyo = 78.22;
     let long_tokyo = 15.65;
 
-    let daylight = calculate_daylight(tm20150621_1200, lat_tokyo, long_tokyo);
-
-    assert_eq!((daylight.sunset - daylight.sunrise).num_minutes(),
-               23 * 60 + 59); // midsummer
-    assert_eq!(daylight.twilight_morning.sec, 1434841155);
-    assert_eq!(daylight.sunrise.sec, 1434841155);
-    assert_eq!(daylight.noon.sec, 1434884354);
-    assert_eq!(daylight.sunset.sec, 1434927554);
-    assert_eq!(daylight.twilight_evening.sec, 1434927554);
-    assert_eq!(daylight.daylength.num_seconds(), 86400);
-    assert!(daylight.declination > 23.436411 && daylight.declination < 23.436413,
-            "declination != {}",
-            daylight.declination);
-    assert!(daylight.sun_altitude > 35.20 && daylight.sun_altitude < 35.22,
-            "sun_altitude != {}",
-            daylight.sun_altitude);
+    // default options: the golden values are rounded to the nearest second
+    for daylight in &golden_daylight(tm20150621_1200, lat_tokyo, long_tokyo) {
+        assert_eq!((daylight.sunset - daylight.sunrise).num_minutes(), 24 * 60); // midsummer
+        assert_eq!(daylight.twilight_morning.sec, 1434841154);
+        assert_eq!(daylight.sunrise.sec, 1434841154);
+        assert_eq!(daylight.noon.sec, 1434884354);
+        assert_eq!(daylight.sunset.sec, 1434927554);
+        assert_eq!(daylight.twilight_evening.sec, 1434927554);
+        assert_eq!(daylight.daylength.num_seconds(), 86400);
+        assert!(daylight.declination > 23.436411 && daylight.declination < 23.436413,
+                "declination != {}",
+                daylight.declination);
+        assert!(daylight.sun_altitude > 35.20 && daylight.sun_altitude < 35.22,
+                "sun_altitude != {}",
+                daylight.sun_altitude);
+    }
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn daylight_longyearbyen_20151221_1200_utc_midwinter() {
     let tm20151221_1200 = Tm {
         tm_sec: 0,
@@ -384,29 +1349,42 @@ fn daylight_longyearbyen_20151221_1200_utc_midwinter() {
     let lat_tokyo = 78.22;
     let long_tokyo = 15.65;
 
-    let daylight = calculate_daylight(tm20151221_1200, lat_tokyo, long_tokyo);
+    // default options: the golden values are rounded to the nearest second
+    for daylight in &golden_daylight(tm20151221_1200, lat_tokyo, long_tokyo) {
+        assert_eq!((daylight.sunset - daylight.sunrise).num_minutes(), 0); // midwinter
+        assert_eq!(daylight.twilight_morning.sec, 1450695334);
+        assert_eq!(daylight.sunrise.sec, 1450695334);
+        assert_eq!(daylight.noon.sec, 1450695334);
+        assert_eq!(daylight.sunset.sec, 1450695334);
+        assert_eq!(daylight.twilight_evening.sec, 1450695334);
+        assert_eq!(daylight.daylength.num_seconds(), 0);
+        assert!(daylight.declination > -23.43652 && daylight.declination < -23.43650,
+                "declination != {}",
+                daylight.declination);
+        assert!(daylight.sun_altitude > -11.66 && daylight.sun_altitude < -11.64,
+                "sun_altitude != {}",
+                daylight.sun_altitude);
+    }
+}
 
-    assert_eq!((daylight.sunset - daylight.sunrise).num_minutes(), 0); // midwinter
-    assert_eq!(daylight.twilight_morning.sec, 1450695334);
-    assert_eq!(daylight.sunrise.sec, 1450695334);
-    assert_eq!(daylight.noon.sec, 1450695334);
-    assert_eq!(daylight.sunset.sec, 1450695334);
-    assert_eq!(daylight.twilight_evening.sec, 1450695334);
-    assert_eq!(daylight.daylength.num_seconds(), 0);
-    assert!(daylight.declination > -23.43652 && daylight.declination < -23.43650,
-            "declination != {}",
-            daylight.declination);
-    assert!(daylight.sun_altitude > -11.66 && daylight.sun_altitude < -11.64,
-            "sun_altitude != {}",
-            daylight.sun_altitude);
+#[cfg(all(test, feature = "std"))]
+fn assert_ordered(daylight: &Daylight) {
+    assert!(daylight.twilight_morning <= daylight.sunrise, "{:?}", daylight);
+    assert!(daylight.sunrise <= daylight.noon, "{:?}", daylight);
+    assert!(daylight.noon <= daylight.sunset, "{:?}", daylight);
+    assert!(daylight.sunset <= daylight.twilight_evening, "{:?}", daylight);
+    assert!(daylight.daylength.num_seconds() >= 0, "{:?}", daylight);
+    // the day length is rounded on its own
+    let delta = daylight.sunrise + daylight.daylength - daylight.sunset;
+    assert!(delta.num_seconds().abs() <= 1, "{:?}", daylight);
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn range_check() {
     for long in (-180..180).filter(|x| x % 8 == 0) {
         for lat in (-90..91).filter(|x| x % 8 == 0) {
-            for year in (70..138).filter(|x| x % 2 == 0) {
-                // 138 -> last supported year at 32-bit systems
+            for year in (0..201).filter(|x| x % 2 == 0) {
                 for month in 0..12 {
                     let tm = Tm {
                         tm_sec: 0,
@@ -419,24 +1397,971 @@ fn range_check() {
                         tm_yday: 0,
                         tm_isdst: 0,
                         tm_utcoff: 0,
-                        tm_nsec: 0, /* 2 january; because 1 january may still result in 1969 timestamps */
+                        tm_nsec: 0,
                     };
 
                     let daylight = calculate_daylight(tm, lat as f64, long as f64);
 
-                    assert!(daylight.twilight_morning.sec > 0,
+                    // events stay close to the input date, also before 1970
+                    let offset = daylight.twilight_morning - tm_to_timespec(tm);
+                    assert!(offset.num_days().abs() < 2,
                             "daylight={:?} lat={} long={} year={}",
                             daylight,
                             lat,
                             long,
                             year);
-                    assert!(daylight.twilight_morning <= daylight.sunrise);
-                    assert!(daylight.sunrise <= daylight.noon);
-                    assert!(daylight.noon <= daylight.sunset);
-                    assert!(daylight.sunset <= daylight.twilight_evening);
-                    assert!(daylight.daylength.num_seconds() >= 0);
+                    assert_ordered(&daylight);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn range_check_polar_circles() {
+    // 1 degree steps around both polar circles (and the poles themselves),
+    // where the hour angles get clamped, at longitudes near the date line
+    let latitudes = (60..91).chain(-90..-59);
+    for lat in latitudes {
+        for &long in &[-180.0, -179.5, -179.0, 0.0, 179.0, 179.5, 180.0] {
+            for day in (0..366).filter(|x| x % 3 == 0) {
+                // every third day of 2016
+                let tm = time::at_utc(Timespec::new(1451606400 + day * 86400, 0));
+
+                let daylight = calculate_daylight(tm, lat as f64, long);
+                assert_ordered(&daylight);
+
+                let daylight = calculate_daylight_precise(tm, lat as f64, long);
+                assert_ordered(&daylight);
+            }
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn refined_polar_day_and_night() {
+    let refined = DaylightOptions::default().refined(true);
+    let noaa = DaylightOptions::default().algorithm(Algorithm::Noaa);
+
+    // the polar day in Longyearbyen (2020-08-15), the polar night at McMurdo
+    // Station (2020-06-21) and the night that stays within the civil
+    // twilight in Longyearbyen (2023-04-15), all at 12:00 UTC
+    let cases = [(1597492800, 78.22, 15.65), (1592740800, -77.85, 166.67)];
+    for options in &[refined, noaa] {
+        for &(secs, lat, long) in &cases {
+            let tm = time::at_utc(Timespec::new(secs, 0));
+            let daylight = calculate_daylight_with(tm, lat, long, options);
+            let kinds: Vec<EventKind> = daylight.events().map(|event| event.kind).collect();
+            assert_eq!(kinds, [EventKind::Noon], "{:?}", daylight);
+        }
+
+        let tm = time::at_utc(Timespec::new(1681560000, 0));
+        let daylight = calculate_daylight_with(tm, 78.22, 15.65, options);
+        let kinds: Vec<EventKind> = daylight.events().map(|event| event.kind).collect();
+        assert_eq!(kinds,
+                   [EventKind::Sunrise, EventKind::Noon, EventKind::Sunset],
+                   "{:?}",
+                   daylight);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+fn assert_local_date(daylight: &Daylight, utc_offset: i32, mday: i32) {
+    let local_mday = |ts: Timespec| time::at_utc(ts + Duration::seconds(utc_offset as i64)).tm_mday;
+
+    assert_eq!(local_mday(daylight.sunrise), mday, "{:?}", daylight);
+    assert_eq!(local_mday(daylight.noon), mday, "{:?}", daylight);
+    assert_eq!(local_mday(daylight.sunset), mday, "{:?}", daylight);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_local_kiritimati_20150327() {
+    let tm20150327 = Tm {
+        tm_sec: 0,
+        tm_min: 0,
+        tm_hour: 0,
+        tm_mday: 27,
+        tm_mon: 2,
+        tm_year: 115,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_utcoff: 0,
+        tm_nsec: 0,
+    };
+    let lat_kiritimati = 1.87;
+    let long_kiritimati = -157.40;
+
+    let daylight = calculate_daylight_local_with_offset(tm20150327,
+                                                        lat_kiritimati,
+                                                        long_kiritimati,
+                                                        14 * 3600);
+
+    assert_local_date(&daylight, 14 * 3600, 27);
+    // the UTC based calculation returns the events of the local 28th
+    let daylight_utc = calculate_daylight(tm20150327, lat_kiritimati, long_kiritimati);
+    assert_local_date(&daylight_utc, 14 * 3600, 28);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_local_apia_and_avarua_20150327() {
+    let tm20150327 = Tm {
+        tm_sec: 0,
+        tm_min: 0,
+        tm_hour: 0,
+        tm_mday: 27,
+        tm_mon: 2,
+        tm_year: 115,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_utcoff: 0,
+        tm_nsec: 0,
+    };
+
+    let apia = calculate_daylight_local_with_offset(tm20150327, -13.83, -171.76, 13 * 3600);
+    assert_local_date(&apia, 13 * 3600, 27);
+
+    let avarua = calculate_daylight_local_with_offset(tm20150327, -21.21, -159.78, -10 * 3600);
+    assert_local_date(&avarua, -10 * 3600, 27);
+
+    // anchored on mean solar time, the events fall within the same civil date
+    let avarua_solar = calculate_daylight_local(tm20150327, -21.21, -159.78);
+    let solar_offset = (-159.78f64 * 240.0).round() as i32;
+    assert_local_date(&avarua_solar, solar_offset, 27);
+    assert!((avarua_solar.sunset - avarua.sunset).num_seconds().abs() < 60);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_london_19000621_and_19501221() {
+    // reference values from the NOAA solar calculator
+    let lat_london = 51.5074;
+    let long_london = -0.1278;
+    let tm19000621 = Tm {
+        tm_sec: 0,
+        tm_min: 0,
+        tm_hour: 12,
+        tm_mday: 21,
+        tm_mon: 5,
+        tm_year: 0,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_utcoff: 0,
+        tm_nsec: 0,
+    };
+    let tm19501221 = Tm {
+        tm_mday: 21,
+        tm_mon: 11,
+        tm_year: 50,
+        ..tm19000621
+    };
+
+    assert!(days_since_2000(tm_to_timespec(tm19000621)) < 0.0);
+
+    let daylight = calculate_daylight(tm19000621, lat_london, long_london);
+    assert!((daylight.sunrise.sec - -2194201044).abs() < 120, "{:?}", daylight); // 03:42:36Z
+    assert!((daylight.sunset.sec - -2194141123).abs() < 120, "{:?}", daylight); // 20:21:17Z
+
+    let daylight = calculate_daylight(tm19501221, lat_london, long_london);
+    assert!((daylight.sunrise.sec - -600537379).abs() < 120, "{:?}", daylight); // 08:03:41Z
+    assert!((daylight.sunset.sec - -600509205).abs() < 120, "{:?}", daylight); // 15:53:15Z
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_apeldoorn_21000621_1200_utc() {
+    let tm21000621_1200 = Tm {
+        tm_sec: 0,
+        tm_min: 0,
+        tm_hour: 12,
+        tm_mday: 21,
+        tm_mon: 5,
+        tm_year: 200,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_utcoff: 0,
+        tm_nsec: 0,
+    };
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+
+    // default options: the golden values are rounded to the nearest second
+    let daylight = calculate_daylight(tm21000621_1200, lat_apeldoorn, long_apeldoorn);
+
+    // reference values from the NOAA solar calculator
+    assert!((daylight.sunrise.sec - 4117230894).abs() < 120, "{:?}", daylight);
+    assert!((daylight.noon.sec - 4117261089).abs() < 30, "{:?}", daylight);
+    assert!((daylight.sunset.sec - 4117291283).abs() < 120, "{:?}", daylight);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_apeldoorn_21500327_delta_t() {
+    let tm21500327_1200 = Tm {
+        tm_sec: 0,
+        tm_min: 0,
+        tm_hour: 12,
+        tm_mday: 27,
+        tm_mon: 2,
+        tm_year: 250,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_utcoff: 0,
+        tm_nsec: 0,
+    };
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+
+    let default = calculate_daylight(tm21500327_1200, lat_apeldoorn, long_apeldoorn);
+    let options = DaylightOptions::default();
+    assert_eq!(calculate_daylight_with(tm21500327_1200, lat_apeldoorn, long_apeldoorn, &options),
+               default);
+
+    let options = DaylightOptions::default().delta_t(DeltaT::Estimated);
+    let corrected = calculate_daylight_with(tm21500327_1200, lat_apeldoorn, long_apeldoorn, &options);
+
+    // ΔT is over 5 minutes in 2150; around the equinox the sun moves north
+    // by ~0.4 degree per day, so the corrected declination is larger
+    let delta_t = estimate_delta_t(2150.23);
+    assert!(delta_t > 300.0 && delta_t < 330.0, "delta_t = {}", delta_t);
+    let expected = 0.395 * delta_t / 86400.0;
+    let difference = corrected.declination - default.declination;
+    assert!((difference - expected).abs() < 0.0002,
+            "declination difference {} != {}",
+            difference,
+            expected);
+    // ... which lengthens the day, though only slightly
+    assert!(corrected.daylength >= default.daylength);
+
+    // without rounding: the larger declination widens the half day by 0.47
+    // seconds (0.0015 degree at 1.3 hour angle per degree), while noon
+    // moves 0.07 seconds earlier with the equation of time
+    let exact = DaylightOptions::default().rounding(Rounding::Exact);
+    let default = calculate_daylight_with(tm21500327_1200, lat_apeldoorn, long_apeldoorn, &exact);
+    let exact = exact.delta_t(DeltaT::Estimated);
+    let corrected = calculate_daylight_with(tm21500327_1200, lat_apeldoorn, long_apeldoorn, &exact);
+    let shifts = [(default.sunrise, corrected.sunrise, -532),
+                  (default.sunset, corrected.sunset, 396),
+                  (default.noon, corrected.noon, -67)];
+    for &(from, to, expected) in &shifts {
+        let milliseconds = (to - from).num_milliseconds();
+        assert!(milliseconds != 0 && (milliseconds - expected).abs() <= 2,
+                "shift of {} ms instead of {} ms",
+                milliseconds,
+                expected);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_precise_60n_march_equinox() {
+    // reference values (sunrise, noon, sunset) from the NOAA solar calculator
+    // at 60N 10E, for 10, 20 and 30 March 2015
+    let references = [(9, 1425966783, 1425987020, 1426007335),
+                      (19, 1426828976, 1426850853, 1426872813),
+                      (29, 1427691157, 1427714673, 1427738277)];
+
+    for &(mday, sunrise, noon, sunset) in &references {
+        let tm = Tm {
+            tm_sec: 0,
+            tm_min: 0,
+            tm_hour: 12,
+            tm_mday: mday + 1,
+            tm_mon: 2,
+            tm_year: 115,
+            tm_wday: 0,
+            tm_yday: 0,
+            tm_isdst: 0,
+            tm_utcoff: 0,
+            tm_nsec: 0,
+        };
+
+        let default = calculate_daylight(tm, 60.0, 10.0);
+        assert!((default.sunrise.sec - sunrise).abs() > 60, "{:?}", default);
+
+        let precise = calculate_daylight_precise(tm, 60.0, 10.0);
+        assert!((precise.sunrise.sec - sunrise).abs() < 10, "{:?}", precise);
+        assert!((precise.noon.sec - noon).abs() < 10, "{:?}", precise);
+        assert!((precise.sunset.sec - sunset).abs() < 10, "{:?}", precise);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn equation_of_time_hourly_sweep() {
+    let options = DaylightOptions::default();
+
+    for &long in &[-179.9, -90.0, 0.0, 90.0, 179.9] {
+        // every hour of 2015
+        for hour in 0..365 * 24 {
+            let ts = Timespec::new(1420070400 + hour * 3600, 0);
+
+            let day = solar_day(ts, &LatitudeTrig::new(0.0), long, &options);
+            let equation = day.noon - FRAC_HOURS_IN_DAY_2 + long / 15.0;
+            assert!(equation.abs() < 20.0 / 60.0,
+                    "equation of time {} h at {:?}",
+                    equation,
+                    ts);
+
+            // noon is within 20 minutes of mean local noon of the UTC date
+            let daylight = calculate_daylight(time::at_utc(ts), 52.0, long);
+            let mean_noon = midnight(ts) +
+                            Duration::seconds(((12.0 - long / 15.0) * SECS_IN_HOUR) as i64);
+            assert!((daylight.noon - mean_noon).num_minutes().abs() < 20,
+                    "{:?} at {:?}",
+                    daylight,
+                    ts);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn declination_extended_series() {
+    // NOAA declination at (approximately) solar noon in Apeldoorn
+    let references = [(0, 0, 15, -21.156963754),
+                      (0, 4, 10, 17.569721152),
+                      (115, 0, 15, -21.127502900),
+                      (115, 7, 1, 18.022383313),
+                      (200, 7, 1, 17.901021043),
+                      (200, 10, 15, -18.554227058)];
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+    let standard = DaylightOptions::default().refined(true);
+    let extended = standard.series(Series::Extended);
+    let mut standard_error = 0.0;
+    let mut extended_error = 0.0;
+
+    for &(year, mon, mday, declination) in &references {
+        let tm = Tm {
+            tm_sec: 0,
+            tm_min: 0,
+            tm_hour: 12,
+            tm_mday: mday,
+            tm_mon: mon,
+            tm_year: year,
+            tm_wday: 0,
+            tm_yday: 0,
+            tm_isdst: 0,
+            tm_utcoff: 0,
+            tm_nsec: 0,
+        };
+
+        let daylight = calculate_daylight_with(tm, lat_apeldoorn, long_apeldoorn, &standard);
+        standard_error += (daylight.declination - declination).abs();
+
+        let daylight = calculate_daylight_with(tm, lat_apeldoorn, long_apeldoorn, &extended);
+        let error = (daylight.declination - declination).abs();
+        assert!(error < 0.003, "declination {} != {}", daylight.declination, declination);
+        extended_error += error;
+    }
+
+    assert!(extended_error < standard_error * 0.8,
+            "extended {} standard {}",
+            extended_error,
+            standard_error);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn algorithms_invariants() {
+    for &algorithm in &[Algorithm::Lammi, Algorithm::Noaa] {
+        let options = DaylightOptions::default().algorithm(algorithm);
+
+        for long in (-180..181).filter(|x| x % 30 == 0) {
+            for lat in (-90..91).filter(|x| x % 5 == 0) {
+                for day in (0..365).filter(|x| x % 15 == 0) {
+                    // 2015, every 15 days
+                    let tm = time::at_utc(Timespec::new(1420070400 + day * 86400, 0));
+
+                    let daylight = calculate_daylight_with(tm, lat as f64, long as f64, &options);
+
+                    assert_ordered(&daylight);
+                    assert!(daylight.daylength <= Duration::days(1), "{:?}", daylight);
+                    assert!(daylight.declination.abs() < 23.5, "{:?}", daylight);
                 }
             }
         }
     }
 }
+
+#[test]
+#[cfg(feature = "std")]
+fn algorithms_compared_to_noaa_calculator() {
+    // (lat, long, yday of 2015, sunrise, noon, sunset) from the NOAA solar
+    // calculator
+    let references = [(52.2167, 5.9667, 14, 1421307511, 1421322327, 1421337167),
+                      (52.2167, 5.9667, 171, 1434856474, 1434886671, 1434916869),
+                      (60.0, 10.0, 265, 1442984734, 1443006751, 1443028685),
+                      (-33.9, 18.4, 14, 1421293844, 1421319342, 1421344819),
+                      (0.0, -78.5, 78, 1426850293, 1426872088, 1426893884),
+                      (64.8, -147.7, 318, 1447611939, 1447623323, 1447634656)];
+    let noaa = DaylightOptions::default().algorithm(Algorithm::Noaa);
+
+    for &(lat, long, day, sunrise, noon, sunset) in &references {
+        let tm = time::at_utc(Timespec::new(1420070400 + day * 86400 + 43200, 0));
+
+        let daylight = calculate_daylight_with(tm, lat, long, &noaa);
+        assert!((daylight.sunrise.sec - sunrise).abs() <= 5, "{:?}", daylight);
+        assert!((daylight.noon.sec - noon).abs() <= 5, "{:?}", daylight);
+        assert!((daylight.sunset.sec - sunset).abs() <= 5, "{:?}", daylight);
+
+        // typical delta of the default algorithm
+        let daylight = calculate_daylight(tm, lat, long);
+        assert!((daylight.sunrise.sec - sunrise).abs() <= 240, "{:?}", daylight);
+        assert!((daylight.noon.sec - noon).abs() <= 30, "{:?}", daylight);
+        assert!((daylight.sunset.sec - sunset).abs() <= 240, "{:?}", daylight);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn observer_elevation() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+
+    for &algorithm in &[Algorithm::Lammi, Algorithm::Noaa] {
+        let options = DaylightOptions::default().algorithm(algorithm);
+        let sea_level = calculate_daylight_with(tm, lat_apeldoorn, long_apeldoorn, &options);
+        let mountain = calculate_daylight_with(tm,
+                                               lat_apeldoorn,
+                                               long_apeldoorn,
+                                               &options.elevation(3000.0));
+
+        // a dip of about 1.6 degree
+        let earlier = (sea_level.sunrise - mountain.sunrise).num_seconds();
+        assert!(earlier > 8 * 60 && earlier < 12 * 60, "{:?}", mountain);
+        let later = (mountain.sunset - sea_level.sunset).num_seconds();
+        assert!(later > 8 * 60 && later < 12 * 60, "{:?}", mountain);
+        assert_eq!(mountain.noon, sea_level.noon);
+        // twilight is relative to the true horizon
+        assert!((mountain.twilight_morning - sea_level.twilight_morning).num_seconds().abs() <= 1);
+
+        // zero and negative elevations are sea level
+        for &elevation in &[0.0, -100.0] {
+            let daylight = calculate_daylight_with(tm,
+                                                   lat_apeldoorn,
+                                                   long_apeldoorn,
+                                                   &options.elevation(elevation));
+            assert_eq!(daylight, sea_level);
+        }
+    }
+
+    // about 1.8 arcminutes times the square root of the elevation
+    assert!((to_degrees(horizon_dip(100.0)) * 60.0 - 17.8).abs() < 0.1);
+    assert!((to_degrees(horizon_dip(12_000.0)) * 60.0 - 195.0).abs() < 1.0);
+    assert!(horizon_dip(1e12) < consts::FRAC_PI_2);
+    assert_eq!(horizon_dip(0.0), 0.0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn atmospheric_refraction() {
+    // Longyearbyen, 2015-02-28
+    let tm = time::at_utc(Timespec::new(1425124800, 0));
+    let options = DaylightOptions::default();
+    assert_eq!(options.get_refraction(), AIRREFR);
+
+    let standard = calculate_daylight_with(tm, 78.22, 15.65, &options);
+    assert_eq!(standard, calculate_daylight(tm, 78.22, 15.65));
+
+    // a cold and dense polar winter atmosphere
+    let cold = options.temperature(-30.0).pressure(1040.0);
+    assert!(cold.get_refraction() > 0.65, "{}", cold.get_refraction());
+    let daylight = calculate_daylight_with(tm, 78.22, 15.65, &cold);
+    assert!(daylight.sunset - standard.sunset > Duration::minutes(2), "{:?}", daylight);
+    assert!(standard.sunrise - daylight.sunrise > Duration::minutes(2), "{:?}", daylight);
+
+    // warm and thin air refracts less
+    let warm = options.temperature(30.0).pressure(900.0);
+    let daylight = calculate_daylight_with(tm, 78.22, 15.65, &warm);
+    assert!(daylight.sunset < standard.sunset, "{:?}", daylight);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn geometric_horizon() {
+    for &algorithm in &[Algorithm::Lammi, Algorithm::Noaa] {
+        for &refined in &[false, true] {
+            let options = DaylightOptions::default().algorithm(algorithm).refined(refined);
+            let geometric = options.horizon(Horizon::Geometric);
+
+            // at the equator the centre of the sun is above the true horizon
+            // for half a day (when refined, up to the change of the equation
+            // of time in between)
+            for day in (0..365).filter(|x| x % 30 == 0) {
+                let tm = time::at_utc(Timespec::new(1420070400 + day * 86400, 0));
+                let daylight = calculate_daylight_with(tm, 0.0, 30.0, &geometric);
+                assert!((daylight.daylength - Duration::hours(12)).num_seconds().abs() <= 20,
+                        "{:?}",
+                        daylight);
+            }
+
+            // Apeldoorn, 2015-03-27
+            let tm = time::at_utc(Timespec::new(1427457600, 0));
+            let apparent = calculate_daylight_with(tm, 52.22, 5.97, &options);
+            let daylight = calculate_daylight_with(tm, 52.22, 5.97, &geometric);
+            // the sun takes about five minutes to rise the 50 arcminutes of
+            // the apparent horizon at this latitude
+            let delta = daylight.sunrise - apparent.sunrise;
+            assert!(delta > Duration::minutes(3) && delta < Duration::minutes(7),
+                    "{:?}",
+                    daylight);
+            assert_eq!((daylight.twilight_morning - apparent.twilight_morning).num_seconds(), 0);
+
+            // a custom depression of 50 arcminutes is (almost) the apparent
+            // horizon
+            let custom = options.horizon(Horizon::Custom(0.5 * SUNRADIUS + AIRREFR));
+            let daylight = calculate_daylight_with(tm, 52.22, 5.97, &custom);
+            assert!((daylight.sunrise - apparent.sunrise).num_seconds().abs() <= 1);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn solar_limb() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+
+    for &algorithm in &[Algorithm::Lammi, Algorithm::Noaa] {
+        let options = DaylightOptions::default().algorithm(algorithm);
+        let upper = calculate_daylight_with(tm, 52.22, 5.97, &options);
+        assert_eq!(upper,
+                   calculate_daylight_with(tm, 52.22, 5.97, &options.limb(Limb::Upper)));
+        let center = calculate_daylight_with(tm, 52.22, 5.97, &options.limb(Limb::Center));
+        let lower = calculate_daylight_with(tm, 52.22, 5.97, &options.limb(Limb::Lower));
+
+        // the disk takes about three minutes to rise at this latitude
+        let delta = lower.sunrise - upper.sunrise;
+        assert!(delta > Duration::minutes(2) && delta < Duration::minutes(4),
+                "{:?}",
+                lower);
+        let delta = upper.sunset - lower.sunset;
+        assert!(delta > Duration::minutes(2) && delta < Duration::minutes(4),
+                "{:?}",
+                lower);
+        assert!(upper.sunrise < center.sunrise && center.sunrise < lower.sunrise);
+        assert!(upper.sunset > center.sunset && center.sunset > lower.sunset);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn twilight_angle() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+
+    for &algorithm in &[Algorithm::Lammi, Algorithm::Noaa] {
+        let options = DaylightOptions::default().algorithm(algorithm);
+        let civil = calculate_daylight_with(tm, 52.22, 5.97, &options);
+        assert_eq!(civil,
+                   calculate_daylight_with(tm, 52.22, 5.97, &options.twilight_angle(6.0)));
+        let nautical = calculate_daylight_with(tm, 52.22, 5.97, &options.twilight_angle(12.0));
+        let astronomical = calculate_daylight_with(tm, 52.22, 5.97, &options.twilight_angle(18.0));
+
+        // each further 6 degrees take about 40 minutes at this latitude
+        for &(earlier, later) in &[(nautical, civil), (astronomical, nautical)] {
+            let delta = later.twilight_morning - earlier.twilight_morning;
+            assert!(delta > Duration::minutes(30) && delta < Duration::minutes(60),
+                    "{:?}",
+                    earlier);
+            let delta = earlier.twilight_evening - later.twilight_evening;
+            assert!(delta > Duration::minutes(30) && delta < Duration::minutes(60),
+                    "{:?}",
+                    earlier);
+        }
+        assert_eq!(astronomical.sunrise, civil.sunrise);
+        assert_eq!(astronomical.sunset, civil.sunset);
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn sub_second_precision() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+
+    let exact = DaylightOptions::default().rounding(Rounding::Exact);
+    for options in &[exact, exact.refined(true)] {
+        let daylight = calculate_daylight_with(tm, 52.22, 5.97, options);
+
+        assert!(daylight.noon.nsec != 0, "{:?}", daylight);
+        for ts in &[daylight.twilight_morning,
+                    daylight.sunrise,
+                    daylight.noon,
+                    daylight.sunset,
+                    daylight.twilight_evening] {
+            assert!(ts.nsec >= 0 && ts.nsec < 1_000_000_000, "{:?}", daylight);
+        }
+        assert_eq!(daylight.daylength, daylight.sunset - daylight.sunrise);
+    }
+
+    // before midnight (and before 1970) the whole seconds are rounded down
+    let ts = daylight_hours_to_timespec(Timespec::new(-86400, 0), -0.25 / SECS_IN_HOUR);
+    assert_eq!(ts, Timespec::new(-86401, 750_000_000));
+    let ts = daylight_hours_to_timespec(Timespec::new(0, 0), 1.5 / SECS_IN_HOUR);
+    assert_eq!(ts, Timespec::new(1, 500_000_000));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn rounding_policies() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let options = DaylightOptions::default();
+    let exact = calculate_daylight_with(tm, 52.22, 5.97, &options.rounding(Rounding::Exact));
+    let floor = calculate_daylight_with(tm, 52.22, 5.97, &options.rounding(Rounding::Floor));
+    let nearest = calculate_daylight_with(tm, 52.22, 5.97, &options);
+    let minute = calculate_daylight_with(tm,
+                                         52.22,
+                                         5.97,
+                                         &options.rounding(Rounding::NearestMinute));
+
+    let events = |d: &Daylight| {
+        [d.twilight_morning, d.sunrise, d.noon, d.sunset, d.twilight_evening]
+    };
+    for (i, &ts) in events(&exact).iter().enumerate() {
+        assert_eq!(events(&floor)[i], Timespec::new(ts.sec, 0));
+        let rounded = if ts.nsec >= 500_000_000 { ts.sec + 1 } else { ts.sec };
+        assert_eq!(events(&nearest)[i], Timespec::new(rounded, 0));
+        let ts = events(&minute)[i];
+        assert_eq!((ts.sec % 60, ts.nsec), (0, 0));
+        assert!((ts - events(&exact)[i]).num_milliseconds().abs() <= 30_000);
+    }
+
+    // the day length is rounded from the exact value
+    let daylength = exact.daylength.num_nanoseconds().unwrap();
+    assert_eq!(floor.daylength.num_seconds(), daylength / 1_000_000_000);
+    assert_eq!(nearest.daylength.num_seconds(),
+               (daylength + 500_000_000) / 1_000_000_000);
+    assert_eq!(minute.daylength.num_seconds() % 60, 0);
+
+    // rounding down also before 1970
+    assert_eq!(round_timespec(Timespec::new(-61, 400_000_000), Rounding::Floor),
+               Timespec::new(-61, 0));
+    assert_eq!(round_timespec(Timespec::new(-61, 600_000_000), Rounding::Nearest),
+               Timespec::new(-60, 0));
+    assert_eq!(round_timespec(Timespec::new(-91, 0), Rounding::NearestMinute),
+               Timespec::new(-120, 0));
+    assert_eq!(round_timespec(Timespec::new(-90, 0), Rounding::NearestMinute),
+               Timespec::new(-60, 0));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_display() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    assert_eq!(daylight.to_string(),
+               "twilight morning: 2015-03-27T04:55:30Z\n\
+                sunrise:          2015-03-27T05:22:47Z\n\
+                noon:             2015-03-27T11:41:27Z\n\
+                sunset:           2015-03-27T18:00:07Z\n\
+                twilight evening: 2015-03-27T18:27:24Z\n\
+                daylength:        12:37:20\n\
+                declination:      2.78°\n\
+                sun altitude:     40.56°");
+
+    // Longyearbyen at midsummer and midwinter
+    let june = time::at_utc(Timespec::new(1434888000, 0));
+    assert_eq!(calculate_daylight(june, 78.22, 15.65).to_string(),
+               "sunrise/sunset:   sun does not set\n\
+                noon:             2015-06-21T10:59:14Z\n\
+                daylength:        24:00:00\n\
+                declination:      23.44°\n\
+                sun altitude:     35.22°");
+    let december = time::at_utc(Timespec::new(1450699200, 0));
+    assert_eq!(calculate_daylight(december, 78.22, 15.65).to_string(),
+               "sunrise/sunset:   sun does not rise\n\
+                noon:             2015-12-21T10:55:34Z\n\
+                daylength:        00:00:00\n\
+                declination:      -23.44°\n\
+                sun altitude:     -11.66°");
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daytime_and_twilight() {
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let daylight = calculate_daylight_precise(tm, lat, long);
+    let minute = Duration::minutes(1);
+    assert!(is_daytime(daylight.sunrise + minute, lat, long));
+    assert!(is_daytime(daylight.sunset - minute, lat, long));
+    assert!(!is_daytime(daylight.sunrise - minute, lat, long));
+    assert!(is_twilight(daylight.sunrise - minute, lat, long, 6.0));
+    assert!(is_twilight(daylight.twilight_evening - minute, lat, long, 6.0));
+    assert!(!is_twilight(daylight.twilight_evening + minute, lat, long, 6.0));
+    assert!(!is_twilight(daylight.noon, lat, long, 6.0));
+
+    // around midnight UTC it is midday at longitude 179 degrees, though the
+    // events calculated for 2015-03-27 are those of the solar day before
+    let midnight = Timespec::new(1427500800, 0);
+    for &t in &[midnight - minute, midnight, midnight + minute] {
+        assert!(is_daytime(t, 0.0, 179.0));
+        assert!(!is_daytime(t, 0.0, -1.0));
+        assert!(!is_twilight(t, 0.0, 179.0, 6.0));
+    }
+    assert!(calculate_daylight(time::at_utc(midnight - minute), 0.0, 179.0).sunset <
+            midnight - minute);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_unix_and_systemtime() {
+    use std::time::Duration as StdDuration;
+
+    // Apeldoorn 2015-03-27, London 1900-06-21 and 1950-12-21 and a fraction
+    // of a second before and after the epoch
+    for &(secs, nanos) in &[(1427457600, 0),
+                            (-2194344000, 0),
+                            (-600436800, 0),
+                            (-1, 250_000_000),
+                            (0, 750_000_000)] {
+        let ts = Timespec::new(secs, nanos);
+        let expected = calculate_daylight(time::at_utc(ts), 51.48, 0.0);
+
+        let t = if secs >= 0 {
+            UNIX_EPOCH + StdDuration::new(secs as u64, nanos as u32)
+        } else {
+            UNIX_EPOCH - StdDuration::from_nanos((-secs) as u64 * 1_000_000_000 - nanos as u64)
+        };
+        assert_eq!(calculate_daylight_systemtime(t, 51.48, 0.0), expected);
+        if nanos == 0 {
+            assert_eq!(calculate_daylight_unix(secs, 51.48, 0.0), expected);
+        }
+    }
+    assert_eq!(calculate_daylight_unix(1427457600, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0)
+                   .sunrise
+                   .sec,
+               1427433767);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_not_finite() {
+    use std::panic;
+
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let inputs = [(f64::NAN, 5.97),
+                  (f64::INFINITY, 5.97),
+                  (f64::NEG_INFINITY, 5.97),
+                  (52.22, f64::NAN),
+                  (52.22, f64::INFINITY)];
+    for &(latitude, longitude) in &inputs {
+        // unchecked, the location panics instead of becoming a timestamp
+        assert!(panic::catch_unwind(|| calculate_daylight(tm, latitude, longitude)).is_err());
+        assert!(panic::catch_unwind(|| calculate_daylight_unix(1427457600, latitude, longitude))
+            .is_err());
+        assert!(calculate_daylight_checked(tm, latitude, longitude).is_err());
+    }
+
+    // nor can a NaN of the options
+    let options = DaylightOptions::default().twilight_angle(f64::NAN);
+    assert!(panic::catch_unwind(|| calculate_daylight_with(tm, 52.22, 5.97, &options)).is_err());
+    assert!(panic::catch_unwind(|| daylight_hours_to_timespec(Timespec::new(0, 0), f64::NAN))
+        .is_err());
+    assert!(panic::catch_unwind(|| hours_to_duration(f64::INFINITY)).is_err());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_ymd() {
+    // the month counted from 1, where `tm_mon` counts from 0
+    let tm20150327_1200 = time::at_utc(Timespec::new(1427457600, 0));
+    assert_eq!(calculate_daylight_ymd(2015, 3, 27, 52.22, 5.97),
+               Ok(calculate_daylight(tm20150327_1200, 52.22, 5.97)));
+    let tm20240101_1200 = Tm {
+        tm_year: 2024 - 1900,
+        tm_mon: 0,
+        tm_mday: 1,
+        tm_hour: 12,
+        ..tm20150327_1200
+    };
+    assert_eq!(calculate_daylight_ymd(2024, 1, 1, -33.87, 151.21),
+               Ok(calculate_daylight(tm20240101_1200, -33.87, 151.21)));
+
+    // leap days
+    let leap_day = calculate_daylight_ymd(2024, 2, 29, 52.22, 5.97).unwrap();
+    assert_eq!(leap_day.noon.sec / 86_400, 19_782);
+    assert!(calculate_daylight_ymd(2000, 2, 29, 52.22, 5.97).is_ok());
+    for &(year, month, day) in &[(2023, 2, 29), (1900, 2, 29), (2024, 4, 31), (2024, 0, 1),
+                                 (2024, 13, 1), (2024, 1, 0), (2024, 1, 32)] {
+        assert_eq!(calculate_daylight_ymd(year, month, day, 52.22, 5.97),
+                   Err(DaylightError::InvalidDate));
+    }
+
+    assert_eq!(calculate_daylight_ymd(10_000, 1, 1, 52.22, 5.97),
+               Err(DaylightError::TimeOutOfRange));
+    assert_eq!(calculate_daylight_ymd(i32::MIN, 1, 1, 52.22, 5.97),
+               Err(DaylightError::TimeOutOfRange));
+    assert_eq!(calculate_daylight_ymd(2024, 2, 29, 91.0, 5.97),
+               Err(DaylightError::LatitudeOutOfRange(91.0)));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn daylight_checked() {
+    let tm20150327_1200 = time::at_utc(Timespec::new(1427457600, 0));
+    let lat_apeldoorn = 52.0 + 13.0 / 60.0;
+    let long_apeldoorn = 5.0 + 58.0 / 60.0;
+
+    let daylight = calculate_daylight_checked(tm20150327_1200, lat_apeldoorn, long_apeldoorn)
+        .unwrap();
+    assert_eq!(daylight,
+               calculate_daylight(tm20150327_1200, lat_apeldoorn, long_apeldoorn));
+    assert_eq!(daylight.sunrise.sec, 1427433767);
+    assert_eq!(daylight.sunset.sec, 1427479207);
+    assert!(calculate_daylight_checked(tm20150327_1200, 90.0, -180.0).is_ok());
+
+    // subnormal values are just very small
+    assert_eq!(calculate_daylight_checked(tm20150327_1200, 5e-324, -5e-324),
+               Ok(calculate_daylight(tm20150327_1200, 0.0, 0.0)));
+
+    for &latitude in &[90.5, -91.0, f64::NAN, f64::INFINITY] {
+        match calculate_daylight_checked(tm20150327_1200, latitude, long_apeldoorn) {
+            Err(DaylightError::LatitudeOutOfRange(_)) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+    for &longitude in &[f64::NAN, f64::NEG_INFINITY] {
+        match calculate_daylight_checked(tm20150327_1200, lat_apeldoorn, longitude) {
+            Err(DaylightError::LongitudeNotFinite(_)) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+    // a longitude beyond 180 degrees shifts the events a day per turn, until
+    // they would overflow
+    assert!(calculate_daylight_checked(tm20150327_1200, lat_apeldoorn, 540.0).is_ok());
+    assert_eq!(calculate_daylight_checked(tm20150327_1200, lat_apeldoorn, 1e300),
+               Err(DaylightError::TimeOutOfRange));
+
+    let invalid_dates = [Tm { tm_mday: 31, tm_mon: 3, ..tm20150327_1200 },
+                         Tm { tm_mday: 29, tm_mon: 1, ..tm20150327_1200 },
+                         Tm { tm_mday: 0, ..tm20150327_1200 },
+                         Tm { tm_mon: 12, ..tm20150327_1200 },
+                         Tm { tm_hour: 24, ..tm20150327_1200 },
+                         Tm { tm_min: -1, ..tm20150327_1200 },
+                         Tm { tm_nsec: 1_000_000_000, ..tm20150327_1200 },
+                         Tm { tm_utcoff: 86_400, ..tm20150327_1200 }];
+    for &date in &invalid_dates {
+        assert_eq!(calculate_daylight_checked(date, lat_apeldoorn, long_apeldoorn),
+                   Err(DaylightError::InvalidDate),
+                   "{:?}",
+                   date);
+    }
+    // 2016 is a leap year
+    let leap_day = Tm {
+        tm_mday: 29,
+        tm_mon: 1,
+        tm_year: 116,
+        ..tm20150327_1200
+    };
+    assert!(calculate_daylight_checked(leap_day, lat_apeldoorn, long_apeldoorn).is_ok());
+
+    for &year in &[-20000, 8100] {
+        let date = Tm { tm_year: year, ..tm20150327_1200 };
+        let error = calculate_daylight_checked(date, lat_apeldoorn, long_apeldoorn).unwrap_err();
+        assert_eq!(error, DaylightError::TimeOutOfRange);
+        assert_eq!(error.to_string(),
+                   "date is out of the supported range (years -9999 to 9999)");
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn ecliptic_longitude_drives_declination() {
+    // every 5th day of 2024
+    for day in 0..74 {
+        let ts = Timespec::new(1704110400 + day * 5 * 86400, 0);
+        let longitude = to_radians(ecliptic_longitude(time::at_utc(ts)));
+        assert!((0.0..consts::PI * 2.0).contains(&longitude), "{}", longitude);
+        let obliq = to_radians(23.439 - 0.0000004 * days_since_2000(ts));
+        let declination = to_degrees((obliq.sin() * longitude.sin()).asin());
+        let daylight = calculate_daylight(time::at_utc(ts), 52.22, 5.97);
+        assert!((daylight.declination - declination).abs() < 1e-9, "{}", day);
+    }
+
+    // the March equinox at 2024-03-20 03:06 UTC and the June solstice at
+    // 2024-06-20 20:51 UTC: half a day ahead by default
+    let refined = DaylightOptions::default().refined(true);
+    let equinox = time::at_utc(Timespec::new(1710903960, 0));
+    let longitude = ecliptic_longitude(equinox);
+    assert!((longitude - 0.5).abs() < 0.02, "{}", longitude);
+    let longitude = ecliptic_longitude_with(equinox, &refined);
+    assert!(longitude.min(360.0 - longitude) < 0.01, "{}", longitude);
+    let solstice = time::at_utc(Timespec::new(1718916660, 0));
+    let longitude = ecliptic_longitude_with(solstice, &refined);
+    assert!((longitude - 90.0).abs() < 0.01, "{}", longitude);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn solar_parameters_feed_daylight() {
+    // every 3rd day of 2024 at 12:00 UTC: the declination of the events is
+    // that of the parameters, and the noon at the prime meridian is 12:00
+    // minus the equation of time
+    for day in 0..122 {
+        let ts = Timespec::new(1704110400 + day * 3 * 86400, 0);
+        let parameters = solar_parameters(time::at_utc(ts));
+        for &(latitude, longitude) in &[(52.22, 5.97), (-33.87, 151.21), (78.22, 15.65)] {
+            let daylight = calculate_daylight(time::at_utc(ts), latitude, longitude);
+            assert_eq!(daylight.declination, parameters.declination);
+        }
+        let noon = calculate_daylight(time::at_utc(ts), 0.0, 0.0).noon;
+        assert!((noon.sec - ts.sec + (parameters.equation_of_time * 60.0) as i64).abs() <= 1);
+        assert!((0.0..360.0).contains(&parameters.right_ascension));
+        assert_eq!(parameters.ecliptic_longitude, ecliptic_longitude(time::at_utc(ts)));
+    }
+
+    // Meeus, Astronomical Algorithms, examples 25.a and 28.a, 1992-10-13
+    // 00:00 TD
+    let date = time::at_utc(Timespec::new(718934400, 0));
+    let parameters = solar_parameters_with(date, &DaylightOptions::default().refined(true));
+    assert!((parameters.declination + 7.78507).abs() < 0.01, "{:?}", parameters);
+    assert!((parameters.right_ascension - 198.38083).abs() < 0.01, "{:?}", parameters);
+    assert!((parameters.ecliptic_longitude - 199.90988).abs() < 0.01, "{:?}", parameters);
+    assert!((parameters.obliquity - 23.44023).abs() < 0.001, "{:?}", parameters);
+    assert!((parameters.equation_of_time - 13.71).abs() < 0.05, "{:?}", parameters);
+    assert!((parameters.distance - 0.99766).abs() < 0.0002, "{:?}", parameters);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn nutation_apparent_declination() {
+    // the apparent place of Meeus, Astronomical Algorithms, example 25.a:
+    // 1992-10-13 00:00 TD, declination -7.78507 degrees
+    let date = time::at_utc(Timespec::new(718934400, 0));
+    let options = DaylightOptions::default().series(Series::Extended);
+    let without = calculate_daylight_with(date, 0.0, 0.0, &options).declination;
+    let with = calculate_daylight_with(date, 0.0, 0.0, &options.nutation(true)).declination;
+    // the nutation moves the sun south at that date, about 6 arcseconds
+    assert!((without - with - 0.0017).abs() < 0.0003, "{} {}", without, with);
+    assert!((with + 7.78507).abs() < 0.0003, "{}", with);
+    assert!((with + 7.78507).abs() < (without + 7.78507).abs());
+
+    // off by default, and the events move by seconds at most
+    assert!(!DaylightOptions::default().get_nutation());
+    let apeldoorn = time::at_utc(Timespec::new(1427457600, 0));
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let refined = DaylightOptions::default().refined(true);
+    let without = calculate_daylight_with(apeldoorn, lat, long, &refined);
+    let with = calculate_daylight_with(apeldoorn, lat, long, &refined.nutation(true));
+    assert!(with != without);
+    for &(a, b) in &[(with.sunrise, without.sunrise), (with.sunset, without.sunset)] {
+        assert!((a - b).num_seconds().abs() <= 3, "{:?} {:?}", with, without);
+    }
+}
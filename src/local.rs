@@ -0,0 +1,185 @@
+//! Events of a calculated day on the clock of a fixed UTC offset.
+
+use time::{self, Duration, ParseError, Timespec, Tm};
+
+use {Daylight, EventKind};
+
+/// Text of an event that does not occur on the day, like the sunrise during
+/// the polar day or night (see `Daylight::format_event`)
+pub const ABSENT_EVENT: &str = "—";
+
+/// Result of the daylight calculation in local time (see `Daylight`)
+///
+/// The fields of each `Tm` are the local calendar date and time, with
+/// `tm_utcoff` set to the offset; use the UTC based `Daylight` to compare or
+/// convert instants (`Tm::to_timespec` of the `time` crate only honours the
+/// offset of the system time zone).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalDaylight {
+    pub twilight_morning: Tm,
+    pub sunrise: Tm,
+    pub sunset: Tm,
+    pub twilight_evening: Tm,
+    pub noon: Tm,
+    /// Declination of the sun in angle degrees
+    pub declination: f64,
+    /// Duration of the day
+    pub daylength: Duration,
+    /// Sun altitude in angle degrees
+    pub sun_altitude: f64,
+    /// Number of seconds the local clock is ahead of UTC
+    pub utc_offset: i32,
+}
+
+/// Returns the local calendar time of an instant, for a clock `utc_offset`
+/// seconds ahead of UTC
+pub fn to_local_tm(ts: Timespec, utc_offset: i32) -> Tm {
+    let mut tm = time::at_utc(ts + Duration::seconds(utc_offset as i64));
+    tm.tm_utcoff = utc_offset;
+    tm
+}
+
+impl Daylight {
+    /// Returns the events on the clock of a location that is `offset_seconds`
+    /// ahead of UTC (e.g. `3600` for CET or `13 * 3600 + 45 * 60` for the
+    /// Chatham Islands); an event may fall on the local day before or after
+    /// the calculated date
+    pub fn to_local(&self, offset_seconds: i32) -> LocalDaylight {
+        LocalDaylight {
+            twilight_morning: to_local_tm(self.twilight_morning, offset_seconds),
+            sunrise: to_local_tm(self.sunrise, offset_seconds),
+            sunset: to_local_tm(self.sunset, offset_seconds),
+            twilight_evening: to_local_tm(self.twilight_evening, offset_seconds),
+            noon: to_local_tm(self.noon, offset_seconds),
+            declination: self.declination,
+            daylength: self.daylength,
+            sun_altitude: self.sun_altitude,
+            utc_offset: offset_seconds,
+        }
+    }
+
+    /// Formats the event with `strftime` of the `time` crate on the clock of
+    /// a location that is `utc_offset` seconds ahead of UTC (`%z` prints the
+    /// offset); an event that does not occur on the day is `ABSENT_EVENT`.
+    /// Fails on an invalid format, also when the event is absent.
+    pub fn format_event(&self,
+                        kind: EventKind,
+                        fmt: &str,
+                        utc_offset: i32)
+                        -> Result<String, ParseError> {
+        let time = self.event_time(kind);
+        // the format is validated on the noon when the event is absent
+        let tm = to_local_tm(time.unwrap_or(self.noon), utc_offset);
+        let formatted = tm.strftime(fmt)?.to_string();
+        Ok(match time {
+            Some(_) => formatted,
+            None => ABSENT_EVENT.to_string(),
+        })
+    }
+
+    /// Returns the event as RFC 3339 text (like `2015-03-27T07:22:47+02:00`,
+    /// or with `Z` in UTC) on the clock of a location that is `utc_offset`
+    /// seconds ahead of UTC; an event that does not occur on the day is
+    /// `ABSENT_EVENT`. As RFC 3339 has no seconds in the offset, the offset
+    /// is rounded to whole minutes (and the local time with it), so the text
+    /// is still the exact instant.
+    pub fn rfc3339(&self, kind: EventKind, utc_offset: i32) -> String {
+        let offset = (utc_offset as f64 / 60.0).round() as i32 * 60;
+        match self.event_time(kind) {
+            Some(time) => {
+                to_local_tm(Timespec::new(time.sec, 0), offset).rfc3339().to_string()
+            }
+            None => ABSENT_EVENT.to_string(),
+        }
+    }
+}
+
+#[test]
+fn local_daylight() {
+    use {calculate_daylight, tm_to_timespec};
+
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+
+    // Apeldoorn, 2015-03-27 (still winter time)
+    let local = daylight.to_local(3600);
+    assert_eq!(local.twilight_morning.rfc3339().to_string(),
+               "2015-03-27T05:55:30+01:00");
+    assert_eq!(local.sunrise.rfc3339().to_string(), "2015-03-27T06:22:47+01:00");
+    assert_eq!(local.sunset.rfc3339().to_string(), "2015-03-27T19:00:07+01:00");
+    assert_eq!(local.twilight_evening.rfc3339().to_string(),
+               "2015-03-27T19:27:24+01:00");
+    assert_eq!(local.daylength, daylight.daylength);
+    assert_eq!(tm_to_timespec(local.sunrise), daylight.sunrise);
+    assert_eq!(daylight.to_local(2 * 3600).sunrise.rfc3339().to_string(),
+               "2015-03-27T07:22:47+02:00");
+
+    // the sunset falls on the next local day in the Chatham Islands, the
+    // sunrise on the previous day in Hawaii
+    let chatham = daylight.to_local(13 * 3600 + 45 * 60);
+    assert_eq!(chatham.sunrise.rfc3339().to_string(), "2015-03-27T19:07:47+13:45");
+    assert_eq!(chatham.sunset.rfc3339().to_string(), "2015-03-28T07:45:07+13:45");
+    assert_eq!(chatham.sunset.tm_mday, 28);
+    let hawaii = daylight.to_local(-10 * 3600);
+    assert_eq!(hawaii.sunrise.rfc3339().to_string(), "2015-03-26T19:22:47-10:00");
+    assert_eq!((hawaii.sunrise.tm_mday, hawaii.sunset.tm_mday), (26, 27));
+    assert_eq!(tm_to_timespec(hawaii.sunset), daylight.sunset);
+}
+
+#[test]
+fn daylight_formatted() {
+    use {calculate_daylight, calculate_daylight_with, DaylightOptions};
+
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let kinds = [EventKind::TwilightMorning,
+                 EventKind::Sunrise,
+                 EventKind::Noon,
+                 EventKind::Sunset,
+                 EventKind::TwilightEvening];
+    let formatted = |offset| -> Vec<String> {
+        kinds.iter().map(|&kind| daylight.rfc3339(kind, offset)).collect()
+    };
+    assert_eq!(formatted(0),
+               ["2015-03-27T04:55:30Z",
+                "2015-03-27T05:22:47Z",
+                "2015-03-27T11:41:27Z",
+                "2015-03-27T18:00:07Z",
+                "2015-03-27T18:27:24Z"]);
+    assert_eq!(formatted(2 * 3600),
+               ["2015-03-27T06:55:30+02:00",
+                "2015-03-27T07:22:47+02:00",
+                "2015-03-27T13:41:27+02:00",
+                "2015-03-27T20:00:07+02:00",
+                "2015-03-27T20:27:24+02:00"]);
+    assert_eq!(daylight.format_event(EventKind::Sunset, "%H:%M", 0).unwrap(), "18:00");
+    assert_eq!(daylight.format_event(EventKind::Sunset, "%H:%M %z", 2 * 3600).unwrap(),
+               "20:00 +0200");
+    assert_eq!(daylight.format_event(EventKind::Sunrise, "%Y-%m-%d %H:%M:%S", 2 * 3600)
+                   .unwrap(),
+               "2015-03-27 07:22:47");
+
+    // offsets behind UTC and in parts of an hour; the local mean time of
+    // Amsterdam (+00:19:32) is rounded to a whole minute
+    assert_eq!(daylight.rfc3339(EventKind::Sunrise, -(9 * 3600 + 30 * 60)),
+               "2015-03-26T19:52:47-09:30");
+    assert_eq!(daylight.rfc3339(EventKind::Sunrise, 13 * 3600 + 45 * 60),
+               "2015-03-27T19:07:47+13:45");
+    assert_eq!(daylight.rfc3339(EventKind::Sunrise, 19 * 60 + 32),
+               "2015-03-27T05:42:47+00:20");
+
+    // an invalid format fails, whether or not the event occurs
+    assert!(daylight.format_event(EventKind::Sunrise, "%Q", 0).is_err());
+
+    // Svalbard: no sunrise and sunset during the polar night, but the noon
+    let polar_night = calculate_daylight_with(time::at_utc(Timespec::new(1450699200, 0)),
+                                              78.22,
+                                              15.65,
+                                              &DaylightOptions::default());
+    assert_eq!(polar_night.rfc3339(EventKind::Sunrise, 3600), ABSENT_EVENT);
+    assert_eq!(polar_night.format_event(EventKind::Sunset, "%H:%M", 3600).unwrap(),
+               ABSENT_EVENT);
+    assert!(polar_night.format_event(EventKind::Sunset, "%Q", 3600).is_err());
+    assert_ne!(polar_night.rfc3339(EventKind::Noon, 3600), ABSENT_EVENT);
+}
@@ -0,0 +1,194 @@
+//! Named place on earth, keeping the coordinates together with the
+//! elevation and the UTC offset of its clock.
+
+use time::Tm;
+
+use angle::Degrees;
+use coordinates::Coordinates;
+use {Daylight, DaylightOptions, LatitudeTrig, LocalDaylight, SECS_IN_HOUR, horizon, local_daylight,
+     to_degrees, tm_to_timespec};
+
+/// Position of the sun in the sky
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SolarPosition {
+    /// Altitude of the centre of the sun above the horizon in angle degrees,
+    /// without refraction
+    pub altitude: f64,
+    /// Azimuth in angle degrees, measured from the north through the east
+    pub azimuth: f64,
+}
+
+impl SolarPosition {
+    /// Returns the altitude (see `altitude`)
+    pub fn altitude_angle(&self) -> Degrees {
+        Degrees(self.altitude)
+    }
+
+    /// Returns the azimuth (see `azimuth`)
+    pub fn azimuth_angle(&self) -> Degrees {
+        Degrees(self.azimuth)
+    }
+}
+
+/// Place on earth for which the daylight is calculated
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Location {
+    name: String,
+    coordinates: Coordinates,
+    elevation: f64,
+    utc_offset: i32,
+}
+
+impl Location {
+    /// Creates a location at sea level; its clock is on local mean solar
+    /// time (`longitude / 15` hours from UTC, rounded to the second) until
+    /// `utc_offset` is set
+    pub fn new(name: &str, coordinates: Coordinates) -> Location {
+        let utc_offset = (coordinates.longitude().degrees() * SECS_IN_HOUR / 15.0).round();
+        Location {
+            name: name.to_string(),
+            coordinates,
+            elevation: 0.0,
+            utc_offset: utc_offset as i32,
+        }
+    }
+
+    /// Sets the elevation of the observer in meters (see
+    /// `DaylightOptions::elevation`)
+    pub fn elevation(mut self, meters: f64) -> Location {
+        self.elevation = meters;
+        self
+    }
+
+    /// Sets the number of seconds the clock at the location is ahead of UTC
+    /// (e.g. `3600` for Central European Time)
+    pub fn utc_offset(mut self, seconds: i32) -> Location {
+        self.utc_offset = seconds;
+        self
+    }
+
+    /// Returns the name of the location
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the coordinates of the location
+    pub fn get_coordinates(&self) -> Coordinates {
+        self.coordinates
+    }
+
+    /// Returns the elevation of the observer in meters
+    pub fn get_elevation(&self) -> f64 {
+        self.elevation
+    }
+
+    /// Returns the number of seconds the clock at the location is ahead of
+    /// UTC
+    pub fn get_utc_offset(&self) -> i32 {
+        self.utc_offset
+    }
+
+    /// Calculate civil twilight (am/pm) and sunrise and sunset for the civil
+    /// date at the location (see `calculate_daylight_local_with_offset`),
+    /// with the horizon dip of its elevation
+    pub fn daylight(&self, date: Tm) -> Daylight {
+        self.daylight_with(date, &DaylightOptions::default())
+    }
+
+    /// Calculate civil twilight (am/pm) and sunrise and sunset for the civil
+    /// date at the location, using the given calculation options; the
+    /// elevation of the location replaces the elevation of the options
+    pub fn daylight_with(&self, date: Tm, options: &DaylightOptions) -> Daylight {
+        local_daylight(date,
+                       self.coordinates.latitude().degrees(),
+                       self.coordinates.longitude().degrees(),
+                       self.utc_offset,
+                       &options.elevation(self.elevation))
+    }
+
+    /// Calculate the events for the civil date at the location, on the clock
+    /// of the location (see `Daylight::to_local`)
+    pub fn local_daylight(&self, date: Tm) -> LocalDaylight {
+        self.daylight(date).to_local(self.utc_offset)
+    }
+
+    /// Returns the position of the sun at the given time
+    pub fn solar_position(&self, time: Tm) -> SolarPosition {
+        let (altitude, azimuth) =
+            horizon::sun_position(tm_to_timespec(time),
+                                  &LatitudeTrig::new(self.coordinates.latitude().degrees()),
+                                  self.coordinates.longitude().degrees(),
+                                  &DaylightOptions::default());
+        SolarPosition {
+            altitude: to_degrees(altitude),
+            azimuth: to_degrees(azimuth),
+        }
+    }
+}
+
+#[test]
+fn location_builder() {
+    let apeldoorn = Location::new("Apeldoorn", Coordinates::try_new(52.22, 5.97).unwrap());
+    assert_eq!(apeldoorn.get_name(), "Apeldoorn");
+    assert_eq!(apeldoorn.get_elevation(), 0.0);
+    assert_eq!(apeldoorn.get_utc_offset(), 1433);
+
+    let zugspitze = Location::new("Zugspitze", Coordinates::try_new(47.42, 10.99).unwrap())
+        .elevation(2962.0)
+        .utc_offset(3600);
+    assert_eq!(zugspitze.get_coordinates().latitude().degrees(), 47.42);
+    assert_eq!(zugspitze.get_elevation(), 2962.0);
+    assert_eq!(zugspitze.get_utc_offset(), 3600);
+}
+
+#[test]
+fn location_daylight() {
+    use time::{Duration, Timespec, at_utc};
+    use calculate_daylight_local_with_offset;
+
+    // 2015-03-27
+    let tm = at_utc(Timespec::new(1427457600, 0));
+    let coordinates = Coordinates::try_new(47.42, 10.99).unwrap();
+    let valley = Location::new("Garmisch", coordinates).utc_offset(3600);
+    assert_eq!(valley.daylight(tm),
+               calculate_daylight_local_with_offset(tm, 47.42, 10.99, 3600));
+    let local = valley.local_daylight(tm);
+    assert_eq!((local.sunrise.tm_mday, local.sunrise.tm_utcoff), (27, 3600));
+
+    // the summit sees the sun earlier, due to the dip of the horizon
+    let summit = valley.clone().elevation(2962.0);
+    let (valley, summit) = (valley.daylight(tm), summit.daylight(tm));
+    assert!(valley.sunrise - summit.sunrise > Duration::minutes(5),
+            "{:?} {:?}",
+            valley,
+            summit);
+    assert_eq!(valley.noon, summit.noon);
+
+    // the sun is highest around noon, in the south
+    let location = Location::new("Garmisch", coordinates);
+    let noon = location.solar_position(at_utc(valley.noon));
+    assert!((noon.altitude - valley.sun_altitude).abs() < 0.1, "{:?}", noon);
+    assert!((noon.azimuth - 180.0).abs() < 0.5, "{:?}", noon);
+    let morning = location.solar_position(at_utc(valley.sunrise));
+    assert!(morning.altitude < 0.0 && morning.azimuth < 180.0, "{:?}", morning);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn location_serde() {
+    extern crate serde_json;
+
+    let location = Location::new("Apeldoorn", Coordinates::try_new(52.22, 5.97).unwrap())
+        .elevation(12.0)
+        .utc_offset(3600);
+    let json = serde_json::to_string(&location).unwrap();
+    assert_eq!(json,
+               "{\"name\":\"Apeldoorn\",\"coordinates\":{\"latitude\":52.22,\"longitude\":5.97},\
+                \"elevation\":12.0,\"utc_offset\":3600}");
+    assert_eq!(serde_json::from_str::<Location>(&json).unwrap(), location);
+
+    // the coordinates are validated
+    assert!(serde_json::from_str::<Location>(&json.replace("52.22", "92.22")).is_err());
+}
@@ -0,0 +1,123 @@
+//! Floating point functions for the calculation without `std`, which only
+//! has them for `f64` when linked with the platform's math library; these
+//! come from `libm` instead, under the names of the methods of `std`.
+//!
+//! Whenever `std` is linked, like in the tests, its methods take precedence
+//! over the trait; the test below checks that both agree, so that the
+//! numeric tests hold for either.
+
+use libm;
+
+pub trait Float {
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn sin_cos(self) -> (Self, Self) where Self: Sized;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn floor(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+}
+
+impl Float for f64 {
+    fn sin(self) -> f64 {
+        libm::sin(self)
+    }
+
+    fn cos(self) -> f64 {
+        libm::cos(self)
+    }
+
+    fn tan(self) -> f64 {
+        libm::tan(self)
+    }
+
+    fn sin_cos(self) -> (f64, f64) {
+        libm::sincos(self)
+    }
+
+    fn asin(self) -> f64 {
+        libm::asin(self)
+    }
+
+    fn acos(self) -> f64 {
+        libm::acos(self)
+    }
+
+    fn atan2(self, other: f64) -> f64 {
+        libm::atan2(self, other)
+    }
+
+    fn floor(self) -> f64 {
+        libm::floor(self)
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        libm::pow(self, n as f64)
+    }
+}
+
+impl Float for f32 {
+    fn sin(self) -> f32 {
+        libm::sinf(self)
+    }
+
+    fn cos(self) -> f32 {
+        libm::cosf(self)
+    }
+
+    fn tan(self) -> f32 {
+        libm::tanf(self)
+    }
+
+    fn sin_cos(self) -> (f32, f32) {
+        libm::sincosf(self)
+    }
+
+    fn asin(self) -> f32 {
+        libm::asinf(self)
+    }
+
+    fn acos(self) -> f32 {
+        libm::acosf(self)
+    }
+
+    fn atan2(self, other: f32) -> f32 {
+        libm::atan2f(self, other)
+    }
+
+    fn floor(self) -> f32 {
+        libm::floorf(self)
+    }
+
+    fn powi(self, n: i32) -> f32 {
+        libm::powf(self, n as f32)
+    }
+}
+
+#[test]
+fn libm_matches_std() {
+    let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * a.abs().max(1.0);
+    for step in -400..400 {
+        let x = step as f64 * 0.0173;
+        assert!(close(Float::sin(x), x.sin()), "sin {}", x);
+        assert!(close(Float::cos(x), x.cos()), "cos {}", x);
+        assert!(close(Float::tan(x), x.tan()), "tan {}", x);
+        assert!(close(Float::sin_cos(x).0, x.sin()) && close(Float::sin_cos(x).1, x.cos()));
+        assert!(close(Float::atan2(x, 1.5), x.atan2(1.5)), "atan2 {}", x);
+        assert_eq!(Float::floor(x), x.floor());
+        for n in 2..7 {
+            assert!(close(Float::powi(x, n), x.powi(n)), "powi {} {}", x, n);
+        }
+        let y = x / 7.0;
+        assert!(close(Float::asin(y), y.asin()) && close(Float::acos(y), y.acos()));
+
+        // single precision, to its own accuracy
+        let x = x as f32;
+        let close = |a: f32, b: f32| (a - b).abs() <= 1e-6 * a.abs().max(1.0);
+        assert!(close(Float::sin(x), x.sin()) && close(Float::cos(x), x.cos()), "{}", x);
+        assert!(close(Float::tan(x), x.tan()) && close(Float::atan2(x, 1.5), x.atan2(1.5)));
+        assert_eq!(Float::floor(x), x.floor());
+    }
+}
@@ -0,0 +1,252 @@
+//! Position and phase of the moon, and the windows of a night in which
+//! neither the sun nor the moon brightens the sky.
+//!
+//! The moon follows the low precision series of the Astronomical Almanac,
+//! within about 0.3 degrees of its position, which moves its rise and set by
+//! a minute or two: plenty to plan a night under the stars.
+
+use time::{Duration, Timespec, Tm};
+
+use darkness::astro_darkness;
+use {LatitudeTrig, days_since_2000, fnrange, fnsun, to_radians};
+
+/// Altitude in angle degrees of the centre of the moon when its upper limb
+/// appears on the horizon, like for the sun (see `SUNRADIUS` and `AIRREFR`)
+const MOONRISE_ALTITUDE: f64 = -0.833;
+/// Interval at which the moon is sampled during the night
+const STEP_SECS: i64 = 600;
+
+/// Sum of the terms `amplitude * sin(phase + rate * t)` (or `cos`) of the
+/// series, in angle degrees
+fn series(terms: &[(f64, f64, f64)], t: f64, cos: bool) -> f64 {
+    terms.iter()
+        .map(|&(amplitude, phase, rate)| {
+            let angle = to_radians(phase + rate * t);
+            amplitude * if cos { angle.cos() } else { angle.sin() }
+        })
+        .sum()
+}
+
+/// Returns the ecliptic longitude and latitude, and the horizontal parallax
+/// of the moon in radians, at the instant
+fn moon_ecliptic(ts: Timespec) -> (f64, f64, f64) {
+    // Julian centuries since J2000.0
+    let t = (days_since_2000(ts) - 0.5) / 36525.0;
+    let longitude = 218.32 + 481267.881 * t +
+                    series(&[(6.29, 135.0, 477198.87),
+                             (-1.27, 259.3, -413335.36),
+                             (0.66, 235.7, 890534.22),
+                             (0.21, 269.9, 954397.74),
+                             (-0.19, 357.5, 35999.05),
+                             (-0.11, 186.5, 966404.03)],
+                           t,
+                           false);
+    let latitude = series(&[(5.13, 93.3, 483202.02),
+                            (0.28, 228.2, 960400.89),
+                            (-0.28, 318.3, 6003.15),
+                            (-0.17, 217.6, -407332.21)],
+                          t,
+                          false);
+    let parallax = 0.9508 +
+                   series(&[(0.0518, 135.0, 477198.87),
+                            (0.0095, 259.3, -413335.36),
+                            (0.0078, 235.7, 890534.22),
+                            (0.0028, 269.9, 954397.74)],
+                          t,
+                          true);
+    (fnrange(to_radians(longitude)), to_radians(latitude), to_radians(parallax))
+}
+
+/// Returns the altitude of the centre of the moon in radians, as seen from
+/// the location (without refraction) at the instant
+pub fn moon_altitude(ts: Timespec, lat: &LatitudeTrig, longitude: f64) -> f64 {
+    let (ecliptic_longitude, ecliptic_latitude, parallax) = moon_ecliptic(ts);
+    let obliq = to_radians(23.439);
+    let (sin_longitude, cos_longitude) = ecliptic_longitude.sin_cos();
+    let (sin_latitude, cos_latitude) = ecliptic_latitude.sin_cos();
+    let x = cos_latitude * cos_longitude;
+    let y = obliq.cos() * cos_latitude * sin_longitude - obliq.sin() * sin_latitude;
+    let z = obliq.sin() * cos_latitude * sin_longitude + obliq.cos() * sin_latitude;
+    let (right_ascension, declination) = (y.atan2(x), z.asin());
+
+    // Greenwich mean sidereal time
+    let d = days_since_2000(ts) - 0.5;
+    let sidereal = to_radians(280.46061837 + 360.98564736629 * d);
+    let h = sidereal + to_radians(longitude) - right_ascension;
+    let altitude = (lat.sin * declination.sin() + lat.cos * declination.cos() * h.cos()).asin();
+    // seen from the surface instead of the centre of the earth
+    altitude - (parallax.sin() * altitude.cos()).asin()
+}
+
+/// Returns the illuminated fraction of the disk of the moon at the instant,
+/// 0 at new moon and 1 at full moon
+pub fn moon_illumination(ts: Timespec) -> f64 {
+    let (longitude, latitude, _) = moon_ecliptic(ts);
+    let (sun, _, _) = fnsun(days_since_2000(ts) - 0.5);
+    // elongation of the moon from the sun
+    let cos_elongation = latitude.cos() * (longitude - sun).cos();
+    (1.0 - cos_elongation) / 2.0
+}
+
+/// When the moon spoils the dark sky (see `dark_sky_windows`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DarkSkyOptions {
+    max_moon_altitude: f64,
+    max_illumination: f64,
+}
+
+impl Default for DarkSkyOptions {
+    fn default() -> DarkSkyOptions {
+        DarkSkyOptions {
+            max_moon_altitude: MOONRISE_ALTITUDE,
+            max_illumination: 0.0,
+        }
+    }
+}
+
+impl DarkSkyOptions {
+    /// Sets the altitude in angle degrees of the centre of the moon below
+    /// which it does not spoil the sky; by default the moon is set (at about
+    /// 0.833 degrees below the horizon, like the sun)
+    pub fn max_moon_altitude(mut self, degrees: f64) -> DarkSkyOptions {
+        self.max_moon_altitude = degrees;
+        self
+    }
+
+    /// Returns the altitude below which the moon does not spoil the sky
+    pub fn get_max_moon_altitude(&self) -> f64 {
+        self.max_moon_altitude
+    }
+
+    /// Sets the illuminated fraction of the moon (0 to 1) up to which it does
+    /// not spoil the sky, even when it is up; by default 0, any moon that is
+    /// up spoils the sky
+    pub fn max_illumination(mut self, fraction: f64) -> DarkSkyOptions {
+        self.max_illumination = fraction;
+        self
+    }
+
+    /// Returns the illuminated fraction up to which the moon does not spoil
+    /// the sky
+    pub fn get_max_illumination(&self) -> f64 {
+        self.max_illumination
+    }
+}
+
+/// Returns the windows of the night after the date (of which only the date
+/// is used) in which the sky is truly dark: within the astronomical darkness
+/// (see `astro_darkness`), while the moon is below the altitude or not more
+/// illuminated than the fraction of the options. In order, empty when there
+/// is no astronomical darkness or the moon is up all of it; two windows when
+/// the moon rises and sets again within the darkness.
+///
+/// The moon is sampled every 10 minutes and its rise and set are bisected to
+/// the second; a moon grazing the altitude between two samples is missed.
+pub fn dark_sky_windows(date: Tm,
+                        latitude: f64,
+                        longitude: f64,
+                        options: &DarkSkyOptions)
+                        -> Vec<(Timespec, Timespec)> {
+    let (start, end) = match astro_darkness(date, latitude, longitude) {
+        Some(night) => night,
+        None => return Vec::new(),
+    };
+    let lat = LatitudeTrig::new(latitude);
+    let max_altitude = to_radians(options.max_moon_altitude);
+    let dark = |ts: Timespec| {
+        moon_altitude(ts, &lat, longitude) < max_altitude ||
+        moon_illumination(ts) <= options.max_illumination
+    };
+    // the first instant, to the second, at which it is dark as at `after`
+    let change = |mut before: Timespec, mut after: Timespec| {
+        let was_dark = dark(before);
+        while after - before > Duration::seconds(1) {
+            let middle = before + (after - before) / 2;
+            if dark(middle) == was_dark {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        after
+    };
+
+    let mut windows = Vec::new();
+    let mut was_dark = dark(start);
+    let mut window_start = if was_dark { Some(start) } else { None };
+    let mut ts = start;
+    while ts < end {
+        let next = (ts + Duration::seconds(STEP_SECS)).min(end);
+        let is_dark = dark(next);
+        if is_dark != was_dark {
+            let at = change(ts, next);
+            match window_start.take() {
+                Some(window_start) => windows.push((window_start, at)),
+                None => window_start = Some(at),
+            }
+        }
+        was_dark = is_dark;
+        ts = next;
+    }
+    if let Some(window_start) = window_start.filter(|&window_start| window_start < end) {
+        windows.push((window_start, end));
+    }
+    windows
+}
+
+#[test]
+fn moon_phase_and_altitude() {
+    use to_degrees;
+
+    // new moon on 2024-01-11 11:57 UTC, full moon on 2024-01-25 17:54 UTC
+    assert!(moon_illumination(Timespec::new(1704974220, 0)) < 0.01);
+    assert!(moon_illumination(Timespec::new(1706205240, 0)) > 0.99);
+    // first quarter on 2024-01-18 03:53 UTC
+    let quarter = moon_illumination(Timespec::new(1705549980, 0));
+    assert!((0.45..0.55).contains(&quarter), "{}", quarter);
+
+    // the full moon of January stands high at midnight in Apeldoorn, about
+    // 90 - 52 + 20 degrees
+    let lat = LatitudeTrig::new(52.22);
+    let altitude = to_degrees(moon_altitude(Timespec::new(1706223600, 0), &lat, 5.97));
+    assert!((55.0..62.0).contains(&altitude), "{}", altitude);
+}
+
+#[test]
+fn dark_sky_around_the_moon() {
+    use time;
+
+    use to_degrees;
+
+    let (lat, long) = (52.22, 5.97);
+    let date = |secs: i64| time::at_utc(Timespec::new(secs, 0));
+    let options = DarkSkyOptions::default();
+
+    // the full moon is up all night
+    let full = date(1706184000);
+    assert!(dark_sky_windows(full, lat, long, &options).is_empty());
+    // unless its light is accepted, or only a low moon spoils the sky
+    let night = astro_darkness(full, lat, long).unwrap();
+    let accepted = options.max_illumination(1.0);
+    assert_eq!(dark_sky_windows(full, lat, long, &accepted), [night]);
+    let low = DarkSkyOptions::default().max_moon_altitude(90.0);
+    assert_eq!(dark_sky_windows(full, lat, long, &low), [night]);
+
+    // the new moon sets and rises with the sun
+    let new = date(1704974400);
+    let night = astro_darkness(new, lat, long).unwrap();
+    assert_eq!(dark_sky_windows(new, lat, long, &options), [night]);
+
+    // the first quarter sets around midnight, dark from then on until dawn
+    let quarter = date(1705492800);
+    let night = astro_darkness(quarter, lat, long).unwrap();
+    let windows = dark_sky_windows(quarter, lat, long, &options);
+    assert_eq!(windows.len(), 1, "{:?}", windows);
+    let (moonset, dawn) = windows[0];
+    assert_eq!(dawn, night.1);
+    assert!(moonset > night.0 + Duration::hours(3), "{:?} {:?}", windows, night);
+    let lat = LatitudeTrig::new(lat);
+    let altitude = |ts: Timespec| to_degrees(moon_altitude(ts, &lat, long));
+    assert!(altitude(moonset - Duration::seconds(2)) >= MOONRISE_ALTITUDE);
+    assert!(altitude(moonset) < MOONRISE_ALTITUDE);
+}
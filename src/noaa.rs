@@ -0,0 +1,74 @@
+//! Alternative backend based on the equations of the NOAA solar calculator
+//! spreadsheet (https://gml.noaa.gov/grad/solcalc/calcdetails.html), which
+//! in turn are based on Meeus, Astronomical Algorithms.
+//!
+//! Compared to the default algorithm it includes the higher order terms of
+//! the equation of center, nutation and aberration, and the rigorous
+//! relation between hour angle and altitude. The events are always
+//! evaluated at their own moment (see `DaylightOptions::refined`), which
+//! puts sunrise and sunset within a few seconds of the NOAA calculator.
+
+use core::f64::consts;
+
+#[cfg(not(any(feature = "std", test)))]
+use math::Float;
+use {DaylightOptions, LatitudeTrig, SolarDay, Timespec, FRAC_HOURS_IN_DAY_2, SECS_IN_DAY,
+     days_since_2000, fnrange, hour_angle, noon_altitude, sunrise_depression, to_degrees,
+     to_radians};
+
+/// Calculates the solar day for the given instant and location
+pub fn solar_day(utc: Timespec,
+                 lat: &LatitudeTrig,
+                 longitude: f64,
+                 options: &DaylightOptions)
+                 -> SolarDay {
+    let d2000_ut = days_since_2000(utc) - 0.5;
+    let d2000 = d2000_ut + options.get_delta_t().seconds(d2000_ut) / SECS_IN_DAY as f64;
+
+    // Julian centuries since J2000.0
+    let t = d2000 / 36525.0;
+
+    let mean_longitude = fnrange(to_radians(280.46646 + t * (36000.76983 + t * 0.0003032)));
+    let mean_anomaly = to_radians(357.52911 + t * (35999.05029 - t * 0.0001537));
+    let eccentricity = 0.016708634 - t * (0.000042037 + t * 0.0000001267);
+    let center = (1.914602 - t * (0.004817 + t * 0.000014)) * mean_anomaly.sin() +
+                 (0.019993 - t * 0.000101) * (2.0 * mean_anomaly).sin() +
+                 0.000289 * (3.0 * mean_anomaly).sin();
+
+    // apparent longitude, corrected for nutation and aberration
+    let omega = to_radians(125.04 - 1934.136 * t);
+    let apparent_longitude = mean_longitude +
+                             to_radians(center - 0.00569 - 0.00478 * omega.sin());
+
+    // corrected obliquity of the ecliptic
+    let obliq_seconds = 21.448 - t * (46.815 + t * (0.00059 - t * 0.001813));
+    let mean_obliq = 23.0 + (26.0 + obliq_seconds / 60.0) / 60.0;
+    let obliq = to_radians(mean_obliq + 0.00256 * omega.cos());
+
+    let delta = (obliq.sin() * apparent_longitude.sin()).asin();
+
+    // equation of time in hours
+    let y = (obliq / 2.0).tan().powi(2);
+    let equation_radians = y * (2.0 * mean_longitude).sin() -
+                           2.0 * eccentricity * mean_anomaly.sin() +
+                           4.0 * eccentricity * y * mean_anomaly.sin() *
+                           (2.0 * mean_longitude).cos() -
+                           0.5 * y * y * (4.0 * mean_longitude).sin() -
+                           1.25 * eccentricity * eccentricity * (2.0 * mean_anomaly).sin();
+    let equation = FRAC_HOURS_IN_DAY_2 * equation_radians / consts::PI;
+
+    let ha = hour_angle(lat, delta, sunrise_depression(options));
+    let hb = hour_angle(lat, delta, to_radians(options.get_twilight_angle()));
+    let halfday = FRAC_HOURS_IN_DAY_2 * ha / consts::PI;
+    let twilight = FRAC_HOURS_IN_DAY_2 * (hb - ha) / consts::PI;
+
+    SolarDay {
+        noon: FRAC_HOURS_IN_DAY_2 - longitude / 15.0 - equation,
+        morning: halfday,
+        evening: halfday,
+        twilight_morning: twilight,
+        twilight_evening: twilight,
+        declination: to_degrees(delta),
+        altitude: noon_altitude(lat, delta),
+    }
+}
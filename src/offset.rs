@@ -0,0 +1,336 @@
+//! Events relative to the solar events, like "20 minutes after sunset", as
+//! automation rules are usually phrased.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+use time::{self, Duration, Timespec};
+
+use events::{EventKind, SEARCH_DAYS, find_next};
+use iter::DaylightIter;
+use {Daylight, SECS_IN_DAY, SECS_IN_HOUR, midnight, tm_to_timespec};
+
+/// Moment at an offset from an event of the day; negative offsets are before
+/// the event
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OffsetEvent {
+    pub kind: EventKind,
+    pub offset: Duration,
+}
+
+impl OffsetEvent {
+    /// Creates the moment `offset` after (or before, when negative) the
+    /// event
+    pub fn new(kind: EventKind, offset: Duration) -> OffsetEvent {
+        OffsetEvent { kind, offset }
+    }
+}
+
+impl Daylight {
+    /// Returns the moment of the offset event of this day; `None` when the
+    /// event does not occur (see `events`), like the sunrise during the polar
+    /// day and night. The offset may move it into another (civil) day.
+    pub fn at(&self, event: OffsetEvent) -> Option<Timespec> {
+        self.event_time(event.kind).map(|time| time + event.offset)
+    }
+}
+
+/// Returns the first moment of the offset event strictly after the instant
+/// (see `next_event`); `None` when its event does not occur within a year
+pub fn next_offset_event(after: Timespec,
+                         event: OffsetEvent,
+                         latitude: f64,
+                         longitude: f64)
+                         -> Option<Timespec> {
+    find_next(after - event.offset, latitude, longitude, |kind| kind == event.kind)
+        .map(|e| e.time + event.offset)
+}
+
+/// Deterministic random delay of the moments (see
+/// `UpcomingOffsetEvents::with_jitter`)
+#[derive(Clone, Copy)]
+struct Jitter {
+    start: Duration,
+    /// Width of the range in nanoseconds
+    span: u64,
+    seed: u64,
+}
+
+impl Jitter {
+    /// Returns the delay of the event of the day (days since the unix epoch)
+    fn delay(&self, day: i64, event: OffsetEvent) -> Duration {
+        // SplitMix64 over the seed, the day and the event
+        let mix = |mut z: u64| {
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        let offset = event.offset.num_nanoseconds().unwrap_or(i64::MAX) as u64;
+        let hash = [day as u64, event.kind as u64, offset]
+            .iter()
+            .fold(self.seed, |hash, &value| {
+                mix(hash.wrapping_add(0x9e3779b97f4a7c15).wrapping_add(value))
+            });
+        self.start + Duration::nanoseconds((hash % self.span) as i64)
+    }
+}
+
+/// Iterator over the moments of several offset events in chronological
+/// order across the days (see `upcoming_offset_events`)
+#[derive(Clone)]
+pub struct UpcomingOffsetEvents {
+    from: Timespec,
+    latitude: f64,
+    longitude: f64,
+    jitter: Option<Jitter>,
+    days: DaylightIter<'static>,
+    events: Vec<OffsetEvent>,
+    /// Moments calculated, but not yet returned
+    pending: BinaryHeap<Reverse<(Timespec, OffsetEvent)>>,
+    /// Earliest moment the days not yet calculated can contribute
+    horizon: Timespec,
+    /// Hours the events of a day can be before its 12:00 UTC
+    lead: Duration,
+    last: Timespec,
+    idle_days: i64,
+}
+
+impl UpcomingOffsetEvents {
+    fn new(from: Timespec,
+           mut events: Vec<OffsetEvent>,
+           latitude: f64,
+           longitude: f64,
+           jitter: Option<Jitter>)
+           -> UpcomingOffsetEvents {
+        events.sort();
+        events.dedup();
+        // the events of a day are within 12 hours of its solar noon, which is
+        // at most `longitude / 15` hours (and the equation of time) from
+        // 12:00 UTC; with the offset and the jitter the moments are up to
+        // `lead` before 12:00 UTC
+        let (jitter_start, jitter_end) = match jitter {
+            Some(Jitter { start, span, .. }) => (start, start + Duration::nanoseconds(span as i64)),
+            None => (Duration::zero(), Duration::zero()),
+        };
+        let offsets = events.iter().map(|event| event.offset);
+        let latest = offsets.clone().max().unwrap_or_else(Duration::zero) + jitter_end;
+        let earliest = offsets.min().unwrap_or_else(Duration::zero) + jitter_start;
+        let lead = Duration::seconds(((12.5 + longitude.abs() / 15.0) * SECS_IN_HOUR) as i64) -
+                   earliest.min(Duration::zero());
+        // like `find_next`, from the day before the first one that can
+        // contribute
+        let midday = midnight(from - latest.max(Duration::zero())) + Duration::hours(12) -
+                     Duration::days(1);
+        UpcomingOffsetEvents {
+            from,
+            latitude,
+            longitude,
+            jitter,
+            days: DaylightIter::new(time::at_utc(midday), latitude, longitude),
+            events,
+            pending: BinaryHeap::new(),
+            horizon: midday - lead,
+            lead,
+            last: from,
+            idle_days: 0,
+        }
+    }
+
+    /// Delays each moment by a random duration within the range, like for
+    /// presence simulation; panics when the range is empty. The delay is
+    /// drawn per date and offset event from the seed, so the same seed gives
+    /// the same schedule in every run. The moments stay in chronological
+    /// order, which is the order after the delay: events closer together than
+    /// the width of the range (like the sunset and the end of the twilight)
+    /// can change places. Starts over from the instant of
+    /// `upcoming_offset_events`.
+    pub fn with_jitter(self, range: Range<Duration>, seed: u64) -> UpcomingOffsetEvents {
+        assert!(range.start < range.end, "empty jitter range");
+        let span = (range.end - range.start).num_nanoseconds().expect("jitter range too wide");
+        let jitter = Jitter {
+            start: range.start,
+            span: span as u64,
+            seed,
+        };
+        UpcomingOffsetEvents::new(self.from,
+                                  self.events,
+                                  self.latitude,
+                                  self.longitude,
+                                  Some(jitter))
+    }
+}
+
+impl Iterator for UpcomingOffsetEvents {
+    type Item = (OffsetEvent, Timespec);
+
+    fn next(&mut self) -> Option<(OffsetEvent, Timespec)> {
+        loop {
+            // only when no day still to calculate can have an earlier moment,
+            // so that the offsets are applied before the ordering
+            if let Some(&Reverse((time, event))) = self.pending.peek() {
+                if time < self.horizon {
+                    self.pending.pop();
+                    self.last = time;
+                    return Some((event, time));
+                }
+            } else if self.idle_days > SEARCH_DAYS {
+                // none of the events occurs within a year
+                return None;
+            }
+            let (tm, daylight) = self.days.next().expect("the days never end");
+            let midday = tm_to_timespec(tm);
+            let found = self.pending.len();
+            for &event in &self.events {
+                let delay = match self.jitter {
+                    Some(ref jitter) => jitter.delay(midday.sec.div_euclid(SECS_IN_DAY), event),
+                    None => Duration::zero(),
+                };
+                match daylight.at(event).map(|time| time + delay) {
+                    Some(time) if time > self.last => self.pending.push(Reverse((time, event))),
+                    _ => {}
+                }
+            }
+            self.idle_days = if self.pending.len() > found { 0 } else { self.idle_days + 1 };
+            self.horizon = midday + Duration::days(1) - self.lead;
+        }
+    }
+}
+
+/// Returns the moments of the offset events strictly after the instant, day
+/// after day in chronological order (like `upcoming_events`), each with its
+/// offset event. The moments of an event are ordered after applying the
+/// offset, also when it moves them past the moments of other events, or past
+/// midnight. Days without the event, like during the polar night, are
+/// skipped; the iterator ends when none of the events occurs within a year.
+pub fn upcoming_offset_events(from: Timespec,
+                              events: &[OffsetEvent],
+                              latitude: f64,
+                              longitude: f64)
+                              -> UpcomingOffsetEvents {
+    UpcomingOffsetEvents::new(from, events.to_vec(), latitude, longitude, None)
+}
+
+#[test]
+fn offset_event_of_day() {
+    use calculate_daylight;
+
+    // Apeldoorn, 2015-03-27
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let daylight = calculate_daylight(time::at_utc(Timespec::new(1427457600, 0)), lat, long);
+    let lights_on = OffsetEvent::new(EventKind::Sunset, Duration::minutes(20));
+    let blinds_down = OffsetEvent::new(EventKind::Sunrise, Duration::minutes(-45));
+    assert_eq!(daylight.at(lights_on), Some(Timespec::new(1427479207 + 1200, 0)));
+    assert_eq!(daylight.at(blinds_down), Some(Timespec::new(1427433767 - 2700, 0)));
+    // past midnight, into the next day
+    let late = OffsetEvent::new(EventKind::TwilightEvening, Duration::hours(10));
+    assert_eq!(daylight.at(late), Some(Timespec::new(1427480844 + 36000, 0)));
+
+    // Longyearbyen, no sunrise in the polar night, but a noon
+    let december = calculate_daylight(time::at_utc(Timespec::new(1450699200, 0)), 78.22, 15.65);
+    assert_eq!(december.at(blinds_down), None);
+    let noon = OffsetEvent::new(EventKind::Noon, Duration::zero());
+    assert_eq!(december.at(noon), Some(december.noon));
+}
+
+#[test]
+fn next_offset_event_across_days() {
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let sunset = Timespec::new(1427479207, 0);
+    let lights_on = OffsetEvent::new(EventKind::Sunset, Duration::minutes(20));
+    assert_eq!(next_offset_event(sunset + Duration::minutes(10), lights_on, lat, long),
+               Some(sunset + Duration::minutes(20)));
+    let tomorrow = next_offset_event(sunset + Duration::minutes(20), lights_on, lat, long)
+        .unwrap();
+    assert!(tomorrow - sunset > Duration::hours(24), "{:?}", time::at_utc(tomorrow));
+
+    // an offset longer than the night: the moment of the evening before is
+    // still ahead in the morning
+    let late = OffsetEvent::new(EventKind::Sunset, Duration::hours(14));
+    let morning = Timespec::new(1427500800, 0);
+    assert_eq!(next_offset_event(morning, late, lat, long),
+               Some(sunset + Duration::hours(14)));
+
+    // at the pole no day has a sunrise
+    let november = Timespec::new(1446724800, 0);
+    assert_eq!(next_offset_event(november, lights_on, 90.0, 0.0), None);
+}
+
+#[test]
+fn upcoming_offset_events_ordered() {
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let events = [OffsetEvent::new(EventKind::Sunset, Duration::minutes(20)),
+                  OffsetEvent::new(EventKind::Sunrise, Duration::minutes(-45)),
+                  OffsetEvent::new(EventKind::Sunset, Duration::hours(10)),
+                  OffsetEvent::new(EventKind::TwilightMorning, Duration::hours(-13)),
+                  OffsetEvent::new(EventKind::Sunset, Duration::minutes(20))];
+    let start = Timespec::new(1427414400, 0);
+    let moments: Vec<_> = upcoming_offset_events(start, &events, lat, long).take(400).collect();
+    assert!(moments.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    // each event as repeated `next_offset_event`, the duplicate only once
+    for &event in &events[..4] {
+        let mut t = start;
+        for &(_, time) in moments.iter().filter(|&&(e, _)| e == event) {
+            assert_eq!(Some(time), next_offset_event(t, event, lat, long));
+            t = time;
+        }
+        assert_eq!(moments.iter().filter(|&&(e, _)| e == event).count(), 100);
+    }
+
+    // Longyearbyen, the sunrise stops in the polar night and returns
+    let sunrise = [OffsetEvent::new(EventKind::Sunrise, Duration::hours(-1))];
+    let october = Timespec::new(1443657600, 0);
+    let mut t = october;
+    for (_, time) in upcoming_offset_events(october, &sunrise, 78.22, 15.65).take(60) {
+        assert_eq!(Some(time), next_offset_event(t, sunrise[0], 78.22, 15.65));
+        t = time;
+    }
+    assert!(t > Timespec::new(1454284800, 0), "{:?}", time::at_utc(t));
+    // at the pole it never rises
+    assert_eq!(upcoming_offset_events(october, &sunrise, 90.0, 0.0).next(), None);
+}
+
+#[test]
+fn upcoming_offset_events_jitter() {
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let start = Timespec::new(1427414400, 0);
+    let sunset = [OffsetEvent::new(EventKind::Sunset, Duration::zero())];
+    let range = Duration::minutes(5)..Duration::minutes(20);
+    let jittered = |seed| {
+        upcoming_offset_events(start, &sunset, lat, long)
+            .with_jitter(range.clone(), seed)
+            .take(365)
+            .collect::<Vec<_>>()
+    };
+
+    // the same for the same seed, within the range of the actual sunset,
+    // and different each day
+    let moments = jittered(42);
+    assert_eq!(moments, jittered(42));
+    assert!(moments != jittered(43));
+    let delays: Vec<Duration> = upcoming_offset_events(start, &sunset, lat, long)
+        .zip(&moments)
+        .map(|((_, sunset), &(_, time))| time - sunset)
+        .collect();
+    assert!(delays.iter().all(|delay| range.start <= *delay && *delay < range.end));
+    assert!(delays.windows(2).filter(|pair| pair[0] == pair[1]).count() < 5);
+    let mean = delays.iter().fold(Duration::zero(), |sum, delay| sum + *delay) / 365;
+    assert!(mean > Duration::minutes(11) && mean < Duration::minutes(14), "{}", mean);
+
+    // still in chronological order, also when the delay exceeds the time
+    // between the events; a negative range moves them ahead
+    let events = [OffsetEvent::new(EventKind::Sunset, Duration::zero()),
+                  OffsetEvent::new(EventKind::TwilightEvening, Duration::zero()),
+                  OffsetEvent::new(EventKind::TwilightMorning, Duration::zero())];
+    for range in [Duration::zero()..Duration::hours(1), Duration::hours(-3)..Duration::hours(3)] {
+        let moments: Vec<_> = upcoming_offset_events(start, &events, lat, long)
+            .with_jitter(range, 7)
+            .take(300)
+            .collect();
+        assert!(moments.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert!(moments[0].1 > start);
+        for event in &events {
+            assert_eq!(moments.iter().filter(|&&(e, _)| e == *event).count(), 100);
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Optional parameters of the daylight calculation
+
+use core::{fmt, ptr};
+
+use angle::Degrees;
+use delta_t::DeltaT;
+use {SolarAlgorithm, AIRREFR};
+
+/// Atmospheric pressure in hPa for which `AIRREFR` holds
+const STANDARD_PRESSURE: f64 = 1010.0;
+/// Air temperature in degrees Celsius for which `AIRREFR` holds
+const STANDARD_TEMPERATURE: f64 = 10.0;
+/// Depression of the centre of the sun in angle degrees at civil twilight
+const CIVIL_TWILIGHT: f64 = 6.0;
+
+/// Series used to calculate the position of the sun
+///
+/// Accuracy of the declination compared to the NOAA calculator between 1900
+/// and 2100 (the equation of time follows the same pattern, within a few
+/// seconds):
+///
+/// | mode                                   | declination   |
+/// |----------------------------------------|---------------|
+/// | `Standard` (default)                   | ~0.2 degree   |
+/// | `Standard` with `refined`              | ~0.005 degree |
+/// | `Extended` (with or without `refined`) | ~0.003 degree |
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Series {
+    /// The two-term series of the original algorithm (default). Unless the
+    /// calculation is refined, it counts days from midnight 2000-01-01 like
+    /// the original port, instead of from J2000.0 (noon): the declination
+    /// is then off by up to 0.2 degree (half a day of motion of the sun),
+    /// which moves mid latitude sunrise and sunset by up to a minute or two.
+    #[default]
+    Standard,
+    /// The low precision formulae with the higher order terms of the
+    /// equation of center and the secular terms of the mean longitude,
+    /// anomaly and obliquity (Meeus, Astronomical Algorithms, chapter 25),
+    /// relative to J2000.0. The remaining error is mostly nutation; combined
+    /// with `DaylightOptions::refined` events are within a few seconds of
+    /// the NOAA calculator.
+    Extended,
+}
+
+/// Algorithm used to calculate the position of the sun and the events
+#[derive(Clone, Copy, Default)]
+#[non_exhaustive]
+pub enum Algorithm<'a> {
+    /// The fast algorithm of Jarmo Lammi (default); `Series` and `refined`
+    /// select its accuracy
+    #[default]
+    Lammi,
+    /// The equations of the NOAA solar calculator, evaluated at the moment
+    /// of each event; sunrise and sunset are within a few seconds of the
+    /// NOAA calculator, at roughly six times the cost of the default (the
+    /// `series` option does not apply). The default algorithm typically
+    /// differs from it by up to two or three minutes at mid latitudes and
+    /// close to four minutes at the equator, where its approximation of the
+    /// hour angle is weakest.
+    Noaa,
+    /// The position of the sun of another implementation, like an ephemeris
+    /// of another crate (see `SolarAlgorithm`), with the events solved like
+    /// by the default algorithm: at the input instant with its approximation
+    /// of the hour angle, or at the moment of each event when `refined`. The
+    /// `series`, `nutation` and `delta_t` options do not apply. The
+    /// reference may be borrowed, like of an ephemeris loaded at runtime,
+    /// for as long as the options are used.
+    ///
+    /// Two custom algorithms are only compared by reference, not by their
+    /// results: they are equal when the references point to the same
+    /// address, which distinct values of a zero-sized type may share.
+    Custom(&'a dyn SolarAlgorithm),
+}
+
+impl<'a> PartialEq for Algorithm<'a> {
+    fn eq(&self, other: &Algorithm<'a>) -> bool {
+        match (*self, *other) {
+            (Algorithm::Lammi, Algorithm::Lammi) | (Algorithm::Noaa, Algorithm::Noaa) => true,
+            (Algorithm::Custom(a), Algorithm::Custom(b)) => ptr::addr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for Algorithm<'a> {}
+
+impl<'a> fmt::Debug for Algorithm<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Algorithm::Lammi => write!(f, "Lammi"),
+            Algorithm::Noaa => write!(f, "Noaa"),
+            Algorithm::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Definition of the horizon crossing at sunrise and sunset
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[non_exhaustive]
+pub enum Horizon {
+    /// The upper limb of the sun appears at the horizon, including the
+    /// refraction of the atmosphere and the dip of the horizon for the
+    /// elevation of the observer (default)
+    #[default]
+    Apparent,
+    /// The centre of the sun crosses the true horizon, without refraction
+    /// and semidiameter
+    Geometric,
+    /// The centre of the sun is the given number of angle degrees below the
+    /// true horizon (negative for above)
+    Custom(f64),
+}
+
+/// Part of the solar disk that defines sunrise and sunset at the apparent
+/// horizon
+///
+/// The disk is taken as 0.53 degree across; its annual variation of less
+/// than two percent moves the events by a few seconds at most.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Limb {
+    /// The top of the disk touches the horizon (default)
+    #[default]
+    Upper,
+    /// The centre of the disk is at the horizon
+    Center,
+    /// The disk fully clears the horizon
+    Lower,
+}
+
+/// Rounding of the calculated timestamps and the day length
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Rounding {
+    /// Keep the fraction of the second
+    Exact,
+    /// Round down to the whole second, like earlier versions of this crate
+    Floor,
+    /// Round to the nearest second (default)
+    #[default]
+    Nearest,
+    /// Round to the nearest minute, like almanacs
+    NearestMinute,
+}
+
+/// Parameters of the daylight calculation; `DaylightOptions::default()`
+/// reproduces the results of `calculate_daylight`
+///
+/// New options may be added in any release; they are only set through the
+/// builder methods, with defaults that keep the earlier results.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct DaylightOptions<'a> {
+    delta_t: DeltaT,
+    refined: bool,
+    series: Series,
+    nutation: bool,
+    algorithm: Algorithm<'a>,
+    elevation: f64,
+    pressure: f64,
+    temperature: f64,
+    horizon: Horizon,
+    limb: Limb,
+    rounding: Rounding,
+    twilight_angle: f64,
+}
+
+impl<'a> Default for DaylightOptions<'a> {
+    fn default() -> DaylightOptions<'a> {
+        DaylightOptions {
+            delta_t: DeltaT::default(),
+            refined: false,
+            series: Series::default(),
+            nutation: false,
+            algorithm: Algorithm::default(),
+            elevation: 0.0,
+            pressure: STANDARD_PRESSURE,
+            temperature: STANDARD_TEMPERATURE,
+            horizon: Horizon::default(),
+            limb: Limb::default(),
+            rounding: Rounding::default(),
+            twilight_angle: CIVIL_TWILIGHT,
+        }
+    }
+}
+
+impl<'a> DaylightOptions<'a> {
+    /// Sets the ΔT correction applied when computing the position of the sun
+    /// (the calculated times stay UTC based)
+    pub fn delta_t(mut self, delta_t: DeltaT) -> DaylightOptions<'a> {
+        self.delta_t = delta_t;
+        self
+    }
+
+    /// Returns the ΔT correction
+    pub fn get_delta_t(&self) -> DeltaT {
+        self.delta_t
+    }
+
+    /// Evaluates the declination and equation of time at the (iteratively
+    /// approximated) moment of each event, instead of once at the input
+    /// instant. Near the equinoxes this moves high latitude sunrise and
+    /// sunset by up to a minute. The reported declination and sun altitude
+    /// then refer to the moment of solar noon.
+    ///
+    /// The refined calculation also counts the days for the series from
+    /// J2000.0 (noon) instead of from midnight, and uses the rigorous hour
+    /// angle relation instead of the approximation of the original
+    /// algorithm; together this brings sunrise and sunset within a few
+    /// seconds of the NOAA calculator at mid and high latitudes.
+    pub fn refined(mut self, refined: bool) -> DaylightOptions<'a> {
+        self.refined = refined;
+        self
+    }
+
+    /// Returns whether the calculation is refined
+    pub fn get_refined(&self) -> bool {
+        self.refined
+    }
+
+    /// Sets the series used to calculate the position of the sun
+    pub fn series(mut self, series: Series) -> DaylightOptions<'a> {
+        self.series = series;
+        self
+    }
+
+    /// Returns the series used to calculate the position of the sun
+    pub fn get_series(&self) -> Series {
+        self.series
+    }
+
+    /// Applies the principal term of the nutation (driven by the node of the
+    /// lunar orbit) to the longitude of the sun and the obliquity of the
+    /// ecliptic (default off). It moves the apparent position of the sun by
+    /// up to about 17 arcseconds, and events by a few seconds at most. Both
+    /// series already include the annual aberration, and the NOAA algorithm
+    /// both corrections, so this only changes the series of the algorithm
+    /// of Jarmo Lammi.
+    pub fn nutation(mut self, nutation: bool) -> DaylightOptions<'a> {
+        self.nutation = nutation;
+        self
+    }
+
+    /// Returns whether the nutation is applied
+    pub fn get_nutation(&self) -> bool {
+        self.nutation
+    }
+
+    /// Sets the algorithm used for the calculation
+    pub fn algorithm(mut self, algorithm: Algorithm<'a>) -> DaylightOptions<'a> {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Returns the algorithm used for the calculation
+    pub fn get_algorithm(&self) -> Algorithm<'a> {
+        self.algorithm
+    }
+
+    /// Sets the elevation of the observer above the horizon in meters
+    /// (default 0). The dip of the horizon makes the sun rise earlier and
+    /// set later, by roughly 1.8 arcminutes times the square root of the
+    /// elevation; negative elevations are treated as 0.
+    pub fn elevation(mut self, meters: f64) -> DaylightOptions<'a> {
+        self.elevation = meters;
+        self
+    }
+
+    /// Returns the elevation of the observer in meters
+    pub fn get_elevation(&self) -> f64 {
+        self.elevation
+    }
+
+    /// Sets the atmospheric pressure at the observer in hPa (default 1010),
+    /// which scales the refraction at the horizon
+    pub fn pressure(mut self, hpa: f64) -> DaylightOptions<'a> {
+        self.pressure = hpa;
+        self
+    }
+
+    /// Returns the atmospheric pressure in hPa
+    pub fn get_pressure(&self) -> f64 {
+        self.pressure
+    }
+
+    /// Sets the air temperature at the observer in degrees Celsius (default
+    /// 10), which scales the refraction at the horizon; cold air, like in a
+    /// polar winter inversion, refracts more and so advances sunrise and
+    /// delays sunset
+    pub fn temperature(mut self, celsius: f64) -> DaylightOptions<'a> {
+        self.temperature = celsius;
+        self
+    }
+
+    /// Returns the air temperature in degrees Celsius
+    pub fn get_temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    /// Returns the refraction at the horizon in angle degrees used for
+    /// sunrise and sunset: the standard 34 arcminutes, scaled by
+    /// `P / 1010 * 283 / (273 + T)` for the pressure and temperature
+    pub fn get_refraction(&self) -> f64 {
+        AIRREFR * (self.pressure / STANDARD_PRESSURE) *
+        ((273.0 + STANDARD_TEMPERATURE) / (273.0 + self.temperature))
+    }
+
+    /// Sets the definition of the horizon crossing at sunrise and sunset;
+    /// the elevation, pressure, temperature and limb only apply to
+    /// `Horizon::Apparent`. The civil twilight is not affected.
+    pub fn horizon(mut self, horizon: Horizon) -> DaylightOptions<'a> {
+        self.horizon = horizon;
+        self
+    }
+
+    /// Returns the definition of the horizon crossing
+    pub fn get_horizon(&self) -> Horizon {
+        self.horizon
+    }
+
+    /// Sets the part of the solar disk that defines sunrise and sunset at
+    /// the apparent horizon; with the lower limb the sun rises a few
+    /// minutes later and sets a few minutes earlier than with the upper limb
+    pub fn limb(mut self, limb: Limb) -> DaylightOptions<'a> {
+        self.limb = limb;
+        self
+    }
+
+    /// Returns the part of the solar disk that defines sunrise and sunset
+    pub fn get_limb(&self) -> Limb {
+        self.limb
+    }
+
+    /// Sets the rounding of the five timestamps and the day length. Each is
+    /// rounded on its own, after the calculation; so the rounded day length
+    /// may differ by one unit from the difference of the rounded sunset and
+    /// sunrise.
+    pub fn rounding(mut self, rounding: Rounding) -> DaylightOptions<'a> {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Returns the rounding of the timestamps and the day length
+    pub fn get_rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    /// Sets the depression of the centre of the sun below the true horizon
+    /// in angle degrees at which the morning twilight starts and the evening
+    /// twilight ends (default 6, civil twilight); 12 gives the nautical and
+    /// 18 the astronomical twilight
+    pub fn twilight_angle(mut self, degrees: f64) -> DaylightOptions<'a> {
+        self.twilight_angle = degrees;
+        self
+    }
+
+    /// Returns the depression of the sun at twilight in angle degrees
+    pub fn get_twilight_angle(&self) -> f64 {
+        self.twilight_angle
+    }
+
+    /// Sets the depression of the sun at twilight like `twilight_angle`, as
+    /// an angle of either unit
+    pub fn twilight_depression<A: Into<Degrees>>(mut self, angle: A) -> DaylightOptions<'a> {
+        self.twilight_angle = angle.into().0;
+        self
+    }
+
+    /// Returns the depression of the sun at twilight
+    pub fn get_twilight_depression(&self) -> Degrees {
+        Degrees(self.twilight_angle)
+    }
+}
@@ -0,0 +1,267 @@
+//! Calendar of the blue and golden hours of a month, for planning the light
+//! of photographs at a location.
+
+use std::f64::consts;
+
+use time::{self, Duration, Timespec, Tm};
+
+use error::days_in_month;
+use local::to_local_tm;
+use {DaylightOptions, LatitudeTrig, SECS_IN_DAY, days_from_civil, hour_angle, local_daylight,
+     sunrise_depression, to_degrees, to_radians};
+
+/// Altitude of the centre of the sun in angle degrees at which the blue hour
+/// starts in the morning and ends in the evening
+const BLUE_HOUR_LOW: f64 = -6.0;
+/// Altitude in angle degrees between the blue and the golden hour
+const GOLDEN_HOUR_LOW: f64 = -4.0;
+/// Altitude in angle degrees at which the golden hour ends in the morning
+/// and starts in the evening
+const GOLDEN_HOUR_HIGH: f64 = 6.0;
+
+/// Period between two moments on the local clock
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhotoWindow {
+    pub start: Tm,
+    pub end: Tm,
+}
+
+/// The light of a day for photography (see `photo_calendar`); a window is
+/// `None` when the sun does not pass both of its altitudes that day, like the
+/// blue hours in the white nights of a high latitude summer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhotoDay {
+    /// Local date, at 00:00
+    pub date: Tm,
+    /// While the sun rises from 6 to 4 degrees below the horizon
+    pub morning_blue_hour: Option<PhotoWindow>,
+    /// While the sun rises from 4 degrees below to 6 degrees above the
+    /// horizon
+    pub morning_golden_hour: Option<PhotoWindow>,
+    /// While the sun sets from 6 degrees above to 4 degrees below the horizon
+    pub evening_golden_hour: Option<PhotoWindow>,
+    /// While the sun sets from 4 to 6 degrees below the horizon
+    pub evening_blue_hour: Option<PhotoWindow>,
+    /// Direction of the sunrise in angle degrees, from the north through the
+    /// east (see `SolarPosition::azimuth`)
+    pub sunrise_azimuth: Option<f64>,
+    /// Direction of the sunset in angle degrees
+    pub sunset_azimuth: Option<f64>,
+}
+
+/// Position of the sun during a day, from the noon and declination that are
+/// shared by all of its windows
+struct SunDay {
+    lat: LatitudeTrig,
+    noon: Timespec,
+    declination: f64,
+    utc_offset: i32,
+}
+
+impl SunDay {
+    /// Returns the hour angle at which the centre of the sun is at the
+    /// altitude in radians, or `None` when it stays above or below it
+    fn hour_angle(&self, altitude: f64) -> Option<f64> {
+        let angle = hour_angle(&self.lat, self.declination, -altitude);
+        if angle > 0.0 && angle < consts::PI {
+            Some(angle)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the moment on the local clock at the hour angle, before noon
+    /// for a negative one
+    fn at(&self, hour_angle: f64) -> Tm {
+        let seconds = (hour_angle / (2.0 * consts::PI) * SECS_IN_DAY as f64).round();
+        to_local_tm(self.noon + Duration::seconds(seconds as i64), self.utc_offset)
+    }
+
+    /// Returns the morning and evening windows while the sun is between the
+    /// altitudes in angle degrees
+    fn windows(&self, low: f64, high: f64) -> (Option<PhotoWindow>, Option<PhotoWindow>) {
+        match (self.hour_angle(to_radians(low)), self.hour_angle(to_radians(high))) {
+            (Some(low), Some(high)) => {
+                (Some(PhotoWindow { start: self.at(-low), end: self.at(-high) }),
+                 Some(PhotoWindow { start: self.at(high), end: self.at(low) }))
+            }
+            _ => (None, None),
+        }
+    }
+
+    /// Returns the azimuth in angle degrees at which the sun rises through
+    /// the altitude in radians
+    fn rising_azimuth(&self, altitude: f64) -> f64 {
+        let cos_azimuth = (self.declination.sin() - self.lat.sin * altitude.sin()) /
+                          (self.lat.cos * altitude.cos());
+        to_degrees(cos_azimuth.clamp(-1.0, 1.0).acos())
+    }
+}
+
+/// Returns the blue and golden hours and the directions of the sunrise and
+/// sunset of every day of the month (1 to 12) at the location, of which the
+/// clock is `utc_offset` seconds ahead of UTC (see `monthly_rows`); panics
+/// for an invalid month.
+///
+/// The noon and declination of each day are calculated once and shared by
+/// its windows, which are then within a minute or two of the moments the
+/// sun passes the altitudes (see `altitude_crossings`).
+pub fn photo_calendar(year: i32,
+                      month: u32,
+                      latitude: f64,
+                      longitude: f64,
+                      utc_offset: i32)
+                      -> Vec<PhotoDay> {
+    assert!((1..=12).contains(&month), "invalid month {}", month);
+    let first = days_from_civil(year as i64, month as i64, 1);
+    let days = days_in_month(year as i64, month as i32 - 1) as i64;
+    let options = DaylightOptions::default();
+    let sunrise_altitude = -sunrise_depression(&options);
+    (first..first + days)
+        .map(|day| {
+            let date = time::at_utc(Timespec::new(day * SECS_IN_DAY, 0));
+            let daylight = local_daylight(date, latitude, longitude, utc_offset, &options);
+            let sun = SunDay {
+                lat: LatitudeTrig::new(latitude),
+                noon: daylight.noon,
+                declination: to_radians(daylight.declination),
+                utc_offset,
+            };
+            let (morning_blue_hour, evening_blue_hour) = sun.windows(BLUE_HOUR_LOW,
+                                                                     GOLDEN_HOUR_LOW);
+            let (morning_golden_hour, evening_golden_hour) = sun.windows(GOLDEN_HOUR_LOW,
+                                                                         GOLDEN_HOUR_HIGH);
+            let rising = sun.hour_angle(sunrise_altitude)
+                .map(|_| sun.rising_azimuth(sunrise_altitude));
+            PhotoDay {
+                date,
+                morning_blue_hour,
+                morning_golden_hour,
+                evening_golden_hour,
+                evening_blue_hour,
+                sunrise_azimuth: rising,
+                sunset_azimuth: rising.map(|azimuth| 360.0 - azimuth),
+            }
+        })
+        .collect()
+}
+
+/// Formats the days of a calendar as lines of local times and azimuths
+#[cfg(test)]
+fn snapshot(days: &[PhotoDay]) -> String {
+    let clock = |window: Option<PhotoWindow>| match window {
+        Some(window) => {
+            format!("{}-{}",
+                    window.start.strftime("%H:%M").unwrap(),
+                    window.end.strftime("%H:%M").unwrap())
+        }
+        None => "—".to_string(),
+    };
+    let azimuth = |azimuth: Option<f64>| azimuth.map_or("—".to_string(), |a| format!("{:.1}", a));
+    days.iter()
+        .map(|day| {
+            format!("{:02} {} {} {} {} {} {}\n",
+                    day.date.tm_mday,
+                    clock(day.morning_blue_hour),
+                    clock(day.morning_golden_hour),
+                    clock(day.evening_golden_hour),
+                    clock(day.evening_blue_hour),
+                    azimuth(day.sunrise_azimuth),
+                    azimuth(day.sunset_azimuth))
+        })
+        .collect()
+}
+
+#[test]
+fn photo_calendar_snapshots() {
+    use {Coordinates, Location, altitude_crossings, calculate_daylight, tm_to_timespec};
+
+    // Apeldoorn, March 2024, before the switch to summer time
+    let apeldoorn = photo_calendar(2024, 3, 52.22, 5.97, 3600);
+    assert_eq!(snapshot(&apeldoorn),
+               "01 06:45-06:58 06:58-08:06 17:30-18:37 18:37-18:50 100.6 259.4\n\
+                02 06:43-06:56 06:56-08:03 17:32-18:39 18:39-18:52 99.9 260.1\n\
+                03 06:41-06:54 06:54-08:01 17:34-18:41 18:41-18:54 99.3 260.7\n\
+                04 06:39-06:52 06:52-07:58 17:36-18:42 18:42-18:56 98.7 261.3\n\
+                05 06:36-06:50 06:50-07:56 17:38-18:44 18:44-18:57 98.0 262.0\n\
+                06 06:34-06:47 06:47-07:54 17:40-18:46 18:46-18:59 97.4 262.6\n\
+                07 06:32-06:45 06:45-07:51 17:42-18:48 18:48-19:01 96.8 263.2\n\
+                08 06:30-06:43 06:43-07:49 17:43-18:50 18:50-19:03 96.1 263.9\n\
+                09 06:27-06:40 06:40-07:47 17:45-18:51 18:51-19:04 95.5 264.5\n\
+                10 06:25-06:38 06:38-07:44 17:47-18:53 18:53-19:06 94.8 265.2\n\
+                11 06:23-06:36 06:36-07:42 17:49-18:55 18:55-19:08 94.2 265.8\n\
+                12 06:21-06:34 06:34-07:39 17:51-18:57 18:57-19:10 93.5 266.5\n\
+                13 06:18-06:31 06:31-07:37 17:53-18:58 18:58-19:11 92.9 267.1\n\
+                14 06:16-06:29 06:29-07:35 17:54-19:00 19:00-19:13 92.3 267.7\n\
+                15 06:14-06:27 06:27-07:32 17:56-19:02 19:02-19:15 91.6 268.4\n\
+                16 06:11-06:24 06:24-07:30 17:58-19:04 19:04-19:17 91.0 269.0\n\
+                17 06:09-06:22 06:22-07:27 18:00-19:05 19:05-19:19 90.3 269.7\n\
+                18 06:07-06:20 06:20-07:25 18:02-19:07 19:07-19:20 89.7 270.3\n\
+                19 06:04-06:17 06:17-07:23 18:03-19:09 19:09-19:22 89.0 271.0\n\
+                20 06:02-06:15 06:15-07:20 18:05-19:11 19:11-19:24 88.4 271.6\n\
+                21 05:59-06:13 06:13-07:18 18:07-19:12 19:12-19:26 87.7 272.3\n\
+                22 05:57-06:10 06:10-07:16 18:09-19:14 19:14-19:27 87.1 272.9\n\
+                23 05:55-06:08 06:08-07:13 18:11-19:16 19:16-19:29 86.5 273.5\n\
+                24 05:52-06:06 06:06-07:11 18:12-19:18 19:18-19:31 85.8 274.2\n\
+                25 05:50-06:03 06:03-07:09 18:14-19:19 19:19-19:33 85.2 274.8\n\
+                26 05:47-06:01 06:01-07:06 18:16-19:21 19:21-19:35 84.5 275.5\n\
+                27 05:45-05:58 05:58-07:04 18:17-19:23 19:23-19:36 83.9 276.1\n\
+                28 05:43-05:56 05:56-07:02 18:19-19:25 19:25-19:38 83.2 276.8\n\
+                29 05:40-05:54 05:54-06:59 18:21-19:27 19:27-19:40 82.6 277.4\n\
+                30 05:38-05:51 05:51-06:57 18:23-19:28 19:28-19:42 82.0 278.0\n\
+                31 05:35-05:49 05:49-06:55 18:24-19:30 19:30-19:44 81.3 278.7\n");
+
+    // the windows within a couple of minutes of the crossings of the
+    // altitudes, and the sunrise in the direction of the sun at the sunrise
+    let day = apeldoorn[14];
+    let noon = time::at_utc(Timespec::new(1710504000, 0));
+    let within = |tm: Tm, ts: Option<Timespec>| {
+        (tm_to_timespec(tm) - ts.unwrap()).num_seconds().abs() <= 120
+    };
+    let (rising, setting) = altitude_crossings(noon, 52.22, 5.97, BLUE_HOUR_LOW);
+    assert!(within(day.morning_blue_hour.unwrap().start, rising));
+    assert!(within(day.evening_blue_hour.unwrap().end, setting));
+    let (rising, setting) = altitude_crossings(noon, 52.22, 5.97, GOLDEN_HOUR_HIGH);
+    assert!(within(day.morning_golden_hour.unwrap().end, rising));
+    assert!(within(day.evening_golden_hour.unwrap().start, setting));
+    let location = Location::new("Apeldoorn", Coordinates::try_new(52.22, 5.97).unwrap());
+    let sunrise = calculate_daylight(noon, 52.22, 5.97).sunrise;
+    let position = location.solar_position(time::at_utc(sunrise));
+    assert!((position.azimuth - day.sunrise_azimuth.unwrap()).abs() < 0.5);
+
+    // Oulu, May 2024: the blue hours disappear in the middle of the month,
+    // the golden hours a week and a half later, into the white nights
+    let oulu = photo_calendar(2024, 5, 65.01, 25.47, 3 * 3600);
+    assert_eq!(snapshot(&oulu),
+               "01 03:22-03:56 03:56-05:56 20:33-22:34 22:34-23:07 48.6 311.4\n\
+                02 03:17-03:51 03:51-05:53 20:36-22:38 22:38-23:12 47.7 312.3\n\
+                03 03:11-03:47 03:47-05:50 20:38-22:42 22:42-23:18 46.8 313.2\n\
+                04 03:05-03:42 03:42-05:48 20:41-22:47 22:47-23:23 45.9 314.1\n\
+                05 02:59-03:37 03:37-05:45 20:44-22:51 22:51-23:29 45.0 315.0\n\
+                06 02:53-03:33 03:33-05:42 20:46-22:56 22:56-23:35 44.1 315.9\n\
+                07 02:46-03:28 03:28-05:39 20:49-23:00 23:00-23:42 43.2 316.8\n\
+                08 02:39-03:23 03:23-05:37 20:52-23:05 23:05-23:49 42.4 317.6\n\
+                09 02:32-03:18 03:18-05:34 20:54-23:10 23:10-23:56 41.5 318.5\n\
+                10 02:24-03:13 03:13-05:31 20:57-23:15 23:15-00:04 40.6 319.4\n\
+                11 02:15-03:08 03:08-05:29 20:59-23:20 23:20-00:13 39.7 320.3\n\
+                12 02:05-03:03 03:03-05:26 21:02-23:25 23:25-00:23 38.8 321.2\n\
+                13 01:52-02:58 02:58-05:24 21:04-23:30 23:30-00:35 37.9 322.1\n\
+                14 01:34-02:52 02:52-05:21 21:07-23:36 23:36-00:54 37.1 322.9\n\
+                15 — 02:47-05:19 21:09-23:41 — 36.2 323.8\n\
+                16 — 02:41-05:16 21:12-23:47 — 35.3 324.7\n\
+                17 — 02:35-05:14 21:14-23:53 — 34.4 325.6\n\
+                18 — 02:28-05:12 21:17-00:00 — 33.6 326.4\n\
+                19 — 02:22-05:09 21:19-00:07 — 32.7 327.3\n\
+                20 — 02:14-05:07 21:21-00:14 — 31.9 328.1\n\
+                21 — 02:06-05:05 21:24-00:23 — 31.0 329.0\n\
+                22 — 01:56-05:03 21:26-00:32 — 30.2 329.8\n\
+                23 — 01:45-05:01 21:28-00:44 — 29.4 330.6\n\
+                24 — 01:23-04:59 21:30-01:07 — 28.5 331.5\n\
+                25 — — — — 27.7 332.3\n\
+                26 — — — — 26.9 333.1\n\
+                27 — — — — 26.1 333.9\n\
+                28 — — — — 25.3 334.7\n\
+                29 — — — — 24.5 335.5\n\
+                30 — — — — 23.7 336.3\n\
+                31 — — — — 23.0 337.0\n");
+}
@@ -0,0 +1,301 @@
+//! Islamic prayer times, which follow the position of the sun: Fajr and
+//! Isha at a depression of the sun that depends on the calculation method,
+//! Dhuhr at solar noon, Asr at a length of the shadow, and Maghrib at
+//! sunset.
+//!
+//! The times are those of the common PrayTimes.org algorithm, without the
+//! adjustments of minutes some timetables add for safety. The events are
+//! refined (see `DaylightOptions::refined`), like the position of the sun at
+//! each time in that algorithm.
+
+use time::{Duration, Timespec, Tm};
+
+use {Daylight, DaylightOptions, LatitudeTrig, SECS_IN_HOUR, calculate_daylight_with, hour_angle,
+     to_degrees, to_radians};
+
+/// Convention for the depression of the sun at Fajr and Isha
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum CalculationMethod {
+    /// Muslim World League: Fajr at 18, Isha at 17 degrees
+    MuslimWorldLeague,
+    /// Islamic Society of North America: Fajr and Isha at 15 degrees
+    Isna,
+    /// Egyptian General Authority of Survey: Fajr at 19.5, Isha at 17.5
+    /// degrees
+    Egyptian,
+    /// Umm al-Qura University, Makkah: Fajr at 18.5 degrees, Isha 90 minutes
+    /// after Maghrib (120 minutes during Ramadan is not applied)
+    UmmAlQura,
+    /// University of Islamic Sciences, Karachi: Fajr and Isha at 18 degrees
+    Karachi,
+    /// Fajr and Isha at the given depressions in angle degrees
+    Custom { fajr: f64, isha: f64 },
+}
+
+/// Isha of a calculation method
+enum Isha {
+    /// At a depression of the sun in angle degrees
+    Angle(f64),
+    /// At minutes after Maghrib
+    Minutes(i64),
+}
+
+impl CalculationMethod {
+    /// Returns the depression of the sun at Fajr in angle degrees
+    pub fn fajr_angle(&self) -> f64 {
+        match *self {
+            CalculationMethod::MuslimWorldLeague |
+            CalculationMethod::Karachi => 18.0,
+            CalculationMethod::Isna => 15.0,
+            CalculationMethod::Egyptian => 19.5,
+            CalculationMethod::UmmAlQura => 18.5,
+            CalculationMethod::Custom { fajr, .. } => fajr,
+        }
+    }
+
+    fn isha(&self) -> Isha {
+        match *self {
+            CalculationMethod::MuslimWorldLeague => Isha::Angle(17.0),
+            CalculationMethod::Isna => Isha::Angle(15.0),
+            CalculationMethod::Egyptian => Isha::Angle(17.5),
+            CalculationMethod::UmmAlQura => Isha::Minutes(90),
+            CalculationMethod::Karachi => Isha::Angle(18.0),
+            CalculationMethod::Custom { isha, .. } => Isha::Angle(isha),
+        }
+    }
+}
+
+/// Juristic convention for Asr
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AsrMethod {
+    /// When the shadow of an object is its length plus its shadow at noon
+    /// (Shafi'i, Maliki and Hanbali; default)
+    #[default]
+    Standard,
+    /// When the shadow is twice the length plus the shadow at noon (Hanafi)
+    Hanafi,
+}
+
+/// Rule for Fajr and Isha at high latitudes, where the sun does not reach
+/// the depression of the method in summer, or only shortly before sunrise.
+/// Fajr is no earlier than a portion of the night before sunrise, and Isha
+/// no later than that portion after sunset; the night is the time from
+/// sunset until sunrise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum HighLatitudeRule {
+    /// Half of the night (default)
+    #[default]
+    MiddleOfNight,
+    /// A seventh of the night
+    OneSeventh,
+    /// The depression angle divided by 60 (a sixtieth of the night per
+    /// degree, like 18 / 60 of the night for 18 degrees)
+    AngleBased,
+}
+
+impl HighLatitudeRule {
+    /// Returns the longest time between the twilight at the depression and
+    /// sunrise or sunset
+    fn portion(&self, night: Duration, angle: f64) -> Duration {
+        let fraction = match *self {
+            HighLatitudeRule::MiddleOfNight => 0.5,
+            HighLatitudeRule::OneSeventh => 1.0 / 7.0,
+            HighLatitudeRule::AngleBased => angle / 60.0,
+        };
+        Duration::seconds((night.num_seconds() as f64 * fraction).round() as i64)
+    }
+}
+
+/// Parameters of the prayer times
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrayerOptions {
+    method: CalculationMethod,
+    asr: AsrMethod,
+    high_latitude: HighLatitudeRule,
+}
+
+impl PrayerOptions {
+    /// Creates the options of the method, with the standard Asr and the
+    /// middle of the night at high latitudes
+    pub fn new(method: CalculationMethod) -> PrayerOptions {
+        PrayerOptions {
+            method,
+            asr: AsrMethod::default(),
+            high_latitude: HighLatitudeRule::default(),
+        }
+    }
+
+    /// Sets the juristic convention for Asr
+    pub fn asr(mut self, asr: AsrMethod) -> PrayerOptions {
+        self.asr = asr;
+        self
+    }
+
+    /// Returns the juristic convention for Asr
+    pub fn get_asr(&self) -> AsrMethod {
+        self.asr
+    }
+
+    /// Sets the rule for Fajr and Isha at high latitudes
+    pub fn high_latitude(mut self, rule: HighLatitudeRule) -> PrayerOptions {
+        self.high_latitude = rule;
+        self
+    }
+
+    /// Returns the rule for Fajr and Isha at high latitudes
+    pub fn get_high_latitude(&self) -> HighLatitudeRule {
+        self.high_latitude
+    }
+
+    /// Returns the calculation method
+    pub fn get_method(&self) -> CalculationMethod {
+        self.method
+    }
+}
+
+/// Prayer times of a day (calculated times are UTC based); during the polar
+/// day and night the sunrise and Maghrib follow `Daylight`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrayerTimes {
+    pub fajr: Timespec,
+    pub sunrise: Timespec,
+    pub dhuhr: Timespec,
+    pub asr: Timespec,
+    pub maghrib: Timespec,
+    pub isha: Timespec,
+}
+
+/// Returns the twilight at the depression of the day, when the sun reaches
+/// it (see `Daylight::has_twilight`)
+fn twilight(date: Tm, latitude: f64, longitude: f64, angle: f64) -> Option<Daylight> {
+    let options = DaylightOptions::default().refined(true).twilight_angle(angle);
+    Some(calculate_daylight_with(date, latitude, longitude, &options))
+        .filter(|daylight| daylight.has_twilight())
+}
+
+/// Calculates the prayer times of the solar day at the given date with the
+/// method, the standard Asr and the middle of the night at high latitudes
+pub fn prayer_times(date: Tm,
+                    latitude: f64,
+                    longitude: f64,
+                    method: CalculationMethod)
+                    -> PrayerTimes {
+    prayer_times_with(date, latitude, longitude, &PrayerOptions::new(method))
+}
+
+/// Calculates the prayer times of the solar day at the given date, using the
+/// given options
+pub fn prayer_times_with(date: Tm,
+                         latitude: f64,
+                         longitude: f64,
+                         options: &PrayerOptions)
+                         -> PrayerTimes {
+    let daylight = calculate_daylight_with(date,
+                                           latitude,
+                                           longitude,
+                                           &DaylightOptions::default().refined(true));
+    let night = Duration::days(1) - (daylight.sunset - daylight.sunrise);
+
+    let fajr_angle = options.method.fajr_angle();
+    let portion = options.high_latitude.portion(night, fajr_angle);
+    let fajr = match twilight(date, latitude, longitude, fajr_angle) {
+        Some(fajr) if daylight.sunrise - fajr.twilight_morning <= portion => fajr.twilight_morning,
+        _ => daylight.sunrise - portion,
+    };
+
+    let isha = match options.method.isha() {
+        Isha::Angle(angle) => {
+            let portion = options.high_latitude.portion(night, angle);
+            match twilight(date, latitude, longitude, angle) {
+                Some(isha) if isha.twilight_evening - daylight.sunset <= portion => {
+                    isha.twilight_evening
+                }
+                _ => daylight.sunset + portion,
+            }
+        }
+        Isha::Minutes(minutes) => daylight.sunset + Duration::minutes(minutes),
+    };
+
+    // the altitude at which the shadow is `factor` times the length of the
+    // object plus its shadow at noon
+    let factor = match options.asr {
+        AsrMethod::Standard => 1.0,
+        AsrMethod::Hanafi => 2.0,
+    };
+    let declination = to_radians(daylight.declination);
+    let lat = LatitudeTrig::new(latitude);
+    let altitude = (1.0 / (factor + (lat.radians - declination).abs().tan())).atan();
+    let hours = to_degrees(hour_angle(&lat, declination, -altitude)) / 15.0;
+    let asr = daylight.noon + Duration::seconds((hours * SECS_IN_HOUR).round() as i64);
+
+    PrayerTimes {
+        fajr,
+        sunrise: daylight.sunrise,
+        dhuhr: daylight.noon,
+        asr,
+        maghrib: daylight.sunset,
+        isha,
+    }
+}
+
+#[test]
+fn prayer_times_reference() {
+    use time;
+
+    // reference times of the PrayTimes.org algorithm, in unix seconds
+    let cases = [// Apeldoorn, 2015-03-27, Muslim World League
+                 (1427457600,
+                  52.0 + 13.0 / 60.0,
+                  5.0 + 58.0 / 60.0,
+                  PrayerOptions::new(CalculationMethod::MuslimWorldLeague),
+                  [1427426724, 1427433806, 1427456496, 1427468832, 1427479248, 1427485902]),
+                 // Makkah, 2015-03-27, Umm al-Qura
+                 (1427457600,
+                  21.4225,
+                  39.8262,
+                  PrayerOptions::new(CalculationMethod::UmmAlQura),
+                  [1427421746, 1427426332, 1427448372, 1427460686, 1427470431, 1427475831]),
+                 // New York, 2015-12-21, ISNA
+                 (1450699200,
+                  40.7128,
+                  -74.006,
+                  PrayerOptions::new(CalculationMethod::Isna),
+                  [1450695246, 1450700183, 1450716843, 1450725244, 1450733502, 1450738438]),
+                 // Karachi, 2015-03-27, Hanafi Asr
+                 (1427457600,
+                  24.8607,
+                  67.0011,
+                  PrayerOptions::new(CalculationMethod::Karachi).asr(AsrMethod::Hanafi),
+                  [1427415189, 1427419767, 1427441851, 1427457758, 1427463957, 1427468540]),
+                 // London, 2015-06-21, where the sun stays above 18 degrees
+                 // below the horizon: the middle of the night
+                 (1434888000,
+                  51.5074,
+                  -0.1278,
+                  PrayerOptions::new(CalculationMethod::MuslimWorldLeague),
+                  [1434844934, 1434858181, 1434888134, 1434903904, 1434918088, 1434931334]),
+                 // and a seventh of the night
+                 (1434888000,
+                  51.5074,
+                  -0.1278,
+                  PrayerOptions::new(CalculationMethod::MuslimWorldLeague)
+                      .high_latitude(HighLatitudeRule::OneSeventh),
+                  [1434854397, 1434858181, 1434888134, 1434903904, 1434918088, 1434921872])];
+    for &(input, latitude, longitude, options, expected) in &cases {
+        let times = prayer_times_with(time::at_utc(Timespec::new(input, 0)),
+                                      latitude,
+                                      longitude,
+                                      &options);
+        let actual = [times.fajr, times.sunrise, times.dhuhr, times.asr, times.maghrib, times.isha];
+        for (time, &expected) in actual.iter().zip(expected.iter()) {
+            assert!((time.sec - expected).abs() <= 120,
+                    "{:?} instead of {} at {}, {}",
+                    times,
+                    expected,
+                    latitude,
+                    longitude);
+        }
+    }
+}
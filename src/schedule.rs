@@ -0,0 +1,453 @@
+//! Schedules relative to the solar events in a small text format, as used
+//! in the configuration of home automation: an event, an optional offset
+//! and optional limits on the time of day, like
+//! `sunset-00:30,not_before=17:00`.
+//!
+//! The events are `sunrise`, `sunset`, `noon`, `civil_dawn` (start of the
+//! morning twilight) and `civil_dusk` (end of the evening twilight). The
+//! offset is a sign followed by hours and minutes, optionally with seconds
+//! and a fraction of them (`+00:01:30.5`). The limits `not_before` and
+//! `not_after` are a time of day (`HH:MM`) at the location.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use time::{self, Duration, Timespec};
+
+use events::{EventKind, SEARCH_DAYS};
+use iter::DaylightIter;
+use offset::OffsetEvent;
+use {SECS_IN_DAY, SECS_IN_HOUR, midnight};
+
+/// Names of the events in a schedule
+const EVENT_NAMES: [(&str, EventKind); 5] = [("sunrise", EventKind::Sunrise),
+                                             ("sunset", EventKind::Sunset),
+                                             ("noon", EventKind::Noon),
+                                             ("civil_dawn", EventKind::TwilightMorning),
+                                             ("civil_dusk", EventKind::TwilightEvening)];
+
+/// Reason why a schedule can not be parsed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The event is not one of the known names
+    UnknownEvent,
+    /// The offset is not a sign followed by `HH:MM` or `HH:MM:SS`
+    InvalidOffset,
+    /// The limit is not `not_before` or `not_after` followed by `=`
+    UnknownOption,
+    /// The time of day of a limit is not `HH:MM` before 24:00
+    InvalidTime,
+    /// The limit is given twice
+    DuplicateOption,
+    /// `not_before` is later than `not_after`
+    EmptyRange,
+}
+
+/// Error of `SolarSchedule::parse`, with the offending part of the text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    position: usize,
+    token: String,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind, text: &str, token: &str) -> ParseError {
+        ParseError {
+            kind,
+            position: token.as_ptr() as usize - text.as_ptr() as usize,
+            token: token.to_string(),
+        }
+    }
+
+    /// Returns the reason of the error
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte position of the offending part in the text
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the offending part of the text
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            ParseErrorKind::UnknownEvent => {
+                "unknown event (expected sunrise, sunset, noon, civil_dawn or civil_dusk)"
+            }
+            ParseErrorKind::InvalidOffset => {
+                "invalid offset (expected like +01:15, -00:30 or +00:01:30)"
+            }
+            ParseErrorKind::UnknownOption => {
+                "unknown option (expected not_before=HH:MM or not_after=HH:MM)"
+            }
+            ParseErrorKind::InvalidTime => "invalid time of day (expected HH:MM before 24:00)",
+            ParseErrorKind::DuplicateOption => "option given twice",
+            ParseErrorKind::EmptyRange => "not_after is before not_before",
+        };
+        write!(f, "{} at position {}: `{}`", reason, self.position, self.token)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Moment relative to a solar event, optionally limited to a time of day,
+/// like `sunset-00:30,not_before=17:00`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SolarSchedule {
+    event: OffsetEvent,
+    not_before: Option<i64>,
+    not_after: Option<i64>,
+}
+
+/// Parses `HH:MM` as seconds; the hours have one or two digits
+fn parse_clock(text: &str) -> Option<i64> {
+    let (hours, minutes) = text.split_once(':')?;
+    let digits = |s: &str, max: usize| {
+        !s.is_empty() && s.len() <= max && s.bytes().all(|b| b.is_ascii_digit())
+    };
+    if !digits(hours, 2) || !digits(minutes, 2) || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes): (i64, i64) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if minutes >= 60 {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60)
+}
+
+/// Formats the seconds as `HH:MM`
+fn format_clock(secs: i64) -> String {
+    format!("{:02}:{:02}", secs / 3600, secs / 60 % 60)
+}
+
+/// Parses an offset without its sign: `HH:MM` with any number of hours,
+/// optionally followed by the seconds (`HH:MM:SS`) and their fraction up to
+/// nanoseconds (`HH:MM:SS.fff`); `None` beyond the range of `Duration`
+fn parse_offset(text: &str) -> Option<Duration> {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let (clock, fraction) = match text.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (text, None),
+    };
+    let mut fields = clock.split(':');
+    let hours = fields.next().filter(|s| digits(s))?;
+    let mut secs: i64 = hours.parse().ok()?;
+    let mut count = 1;
+    for field in fields {
+        if count == 3 || !digits(field) || field.len() != 2 {
+            return None;
+        }
+        let value: i64 = field.parse().ok()?;
+        if value >= 60 {
+            return None;
+        }
+        secs = secs.checked_mul(60)?.checked_add(value)?;
+        count += 1;
+    }
+    let secs = match count {
+        2 => secs.checked_mul(60)?,
+        3 => secs,
+        _ => return None,
+    };
+    let nanos = match fraction {
+        Some(fraction) if count == 3 && digits(fraction) && fraction.len() <= 9 => {
+            fraction.parse::<i64>().ok()? * 10i64.pow(9 - fraction.len() as u32)
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+    if secs >= Duration::max_value().num_seconds() {
+        return None;
+    }
+    Some(Duration::seconds(secs) + Duration::nanoseconds(nanos))
+}
+
+/// Formats the (non-negative) offset as `HH:MM`, with the seconds and their
+/// fraction when present, like `parse_offset` reads it
+fn format_offset(offset: Duration) -> String {
+    let secs = offset.num_seconds();
+    let nanos = (offset - Duration::seconds(secs)).num_nanoseconds().unwrap_or(0);
+    let mut text = format!("{:02}:{:02}", secs / 3600, secs / 60 % 60);
+    if secs % 60 != 0 || nanos != 0 {
+        text += &format!(":{:02}", secs % 60);
+    }
+    if nanos != 0 {
+        text += format!(".{:09}", nanos).trim_end_matches('0');
+    }
+    text
+}
+
+impl SolarSchedule {
+    /// Creates a schedule at the offset event, without limits
+    pub fn new(event: OffsetEvent) -> SolarSchedule {
+        SolarSchedule {
+            event,
+            not_before: None,
+            not_after: None,
+        }
+    }
+
+    /// Parses a schedule like `sunset-00:30,not_before=17:00` (see the
+    /// module documentation)
+    pub fn parse(text: &str) -> Result<SolarSchedule, ParseError> {
+        let mut parts = text.split(',').map(str::trim);
+        let first = parts.next().unwrap_or(text);
+
+        let (name, offset) = match first.find(['+', '-']) {
+            Some(sign) => (first[..sign].trim_end(), Some(&first[sign..])),
+            None => (first, None),
+        };
+        let kind = EVENT_NAMES.iter()
+            .find(|&&(event, _)| event == name)
+            .map(|&(_, kind)| kind)
+            .ok_or_else(|| ParseError::new(ParseErrorKind::UnknownEvent, text, name))?;
+        let offset = match offset {
+            Some(offset) => {
+                let duration = parse_offset(offset[1..].trim_start())
+                    .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidOffset, text, offset))?;
+                if offset.starts_with('-') { -duration } else { duration }
+            }
+            None => Duration::zero(),
+        };
+
+        let mut schedule = SolarSchedule::new(OffsetEvent::new(kind, offset));
+        for part in parts {
+            let (key, value) = part.split_once('=')
+                .map(|(key, value)| (key.trim_end(), value.trim_start()))
+                .ok_or_else(|| ParseError::new(ParseErrorKind::UnknownOption, text, part))?;
+            let limit = match key {
+                "not_before" => &mut schedule.not_before,
+                "not_after" => &mut schedule.not_after,
+                _ => return Err(ParseError::new(ParseErrorKind::UnknownOption, text, key)),
+            };
+            if limit.is_some() {
+                return Err(ParseError::new(ParseErrorKind::DuplicateOption, text, key));
+            }
+            *limit = Some(parse_clock(value)
+                .filter(|&secs| secs < SECS_IN_DAY)
+                .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidTime, text, value))?);
+            if let (Some(not_before), Some(not_after)) = (schedule.not_before, schedule.not_after) {
+                if not_before > not_after {
+                    return Err(ParseError::new(ParseErrorKind::EmptyRange, text, part));
+                }
+            }
+        }
+        Ok(schedule)
+    }
+
+    /// Returns the event and offset of the schedule
+    pub fn get_event(&self) -> OffsetEvent {
+        self.event
+    }
+
+    /// Returns the earliest time of day, as the time since midnight
+    pub fn get_not_before(&self) -> Option<Duration> {
+        self.not_before.map(Duration::seconds)
+    }
+
+    /// Returns the latest time of day, as the time since midnight
+    pub fn get_not_after(&self) -> Option<Duration> {
+        self.not_after.map(Duration::seconds)
+    }
+
+    /// Returns the first moment of the schedule strictly after the instant;
+    /// the limits are on local mean solar time (`longitude / 15` hours from
+    /// UTC, like `calculate_daylight_local`). `None` when the event does not
+    /// occur within a year.
+    pub fn next_occurrence(&self,
+                           after: Timespec,
+                           latitude: f64,
+                           longitude: f64)
+                           -> Option<Timespec> {
+        let utc_offset = (longitude * SECS_IN_HOUR / 15.0).round() as i32;
+        self.next_occurrence_with_offset(after, latitude, longitude, utc_offset)
+    }
+
+    /// Returns the first moment of the schedule strictly after the instant,
+    /// with the limits on a clock `utc_offset` seconds ahead of UTC (see
+    /// `next_occurrence`)
+    pub fn next_occurrence_with_offset(&self,
+                                       after: Timespec,
+                                       latitude: f64,
+                                       longitude: f64,
+                                       utc_offset: i32)
+                                       -> Option<Timespec> {
+        // the limits move a moment by less than a day, so the search starts
+        // a day before the one of `find_next`
+        let midday = midnight(after - self.event.offset) + Duration::hours(12) -
+                     Duration::days(2);
+        DaylightIter::new(time::at_utc(midday), latitude, longitude)
+            .take(SEARCH_DAYS as usize + 3)
+            .filter_map(|(_, daylight)| daylight.at(self.event))
+            .map(|moment| self.limit(moment, utc_offset as i64))
+            .find(|&moment| moment > after)
+    }
+
+    /// Moves the moment within the limits of its local day
+    fn limit(&self, moment: Timespec, utc_offset: i64) -> Timespec {
+        let local = moment.sec + utc_offset;
+        let day_start = Timespec::new(local - local.rem_euclid(SECS_IN_DAY) - utc_offset, 0);
+        match (self.not_before, self.not_after) {
+            (Some(not_before), _) if moment < day_start + Duration::seconds(not_before) => {
+                day_start + Duration::seconds(not_before)
+            }
+            (_, Some(not_after)) if moment > day_start + Duration::seconds(not_after) => {
+                day_start + Duration::seconds(not_after)
+            }
+            _ => moment,
+        }
+    }
+}
+
+impl FromStr for SolarSchedule {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<SolarSchedule, ParseError> {
+        SolarSchedule::parse(text)
+    }
+}
+
+/// Formats the schedule like it is parsed, e.g. `sunset-00:30,not_before=17:00`
+impl fmt::Display for SolarSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = EVENT_NAMES.iter()
+            .find(|&&(_, kind)| kind == self.event.kind)
+            .map_or("", |&(name, _)| name);
+        write!(f, "{}", name)?;
+        let offset = self.event.offset;
+        if offset < Duration::zero() {
+            write!(f, "-{}", format_offset(-offset))?;
+        } else if offset > Duration::zero() {
+            write!(f, "+{}", format_offset(offset))?;
+        }
+        if let Some(not_before) = self.not_before {
+            write!(f, ",not_before={}", format_clock(not_before))?;
+        }
+        if let Some(not_after) = self.not_after {
+            write!(f, ",not_after={}", format_clock(not_after))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn solar_schedule_parse() {
+    let schedule = |kind, minutes| {
+        SolarSchedule::new(OffsetEvent::new(kind, Duration::minutes(minutes)))
+    };
+    let valid = [("sunset-00:30", schedule(EventKind::Sunset, -30)),
+                 ("sunrise+01:15", schedule(EventKind::Sunrise, 75)),
+                 ("noon", schedule(EventKind::Noon, 0)),
+                 ("civil_dusk", schedule(EventKind::TwilightEvening, 0)),
+                 (" civil_dawn - 0:05 ", schedule(EventKind::TwilightMorning, -5)),
+                 ("sunset+25:00", schedule(EventKind::Sunset, 1500)),
+                 ("sunset-00:30,not_before=17:00",
+                  SolarSchedule {
+                      not_before: Some(17 * 3600),
+                      ..schedule(EventKind::Sunset, -30)
+                  }),
+                 ("sunrise, not_after = 07:30, not_before=06:00",
+                  SolarSchedule {
+                      not_before: Some(6 * 3600),
+                      not_after: Some(7 * 3600 + 1800),
+                      ..schedule(EventKind::Sunrise, 0)
+                  })];
+    for &(text, expected) in &valid {
+        assert_eq!(SolarSchedule::parse(text), Ok(expected), "{}", text);
+        assert_eq!(text.parse::<SolarSchedule>(), Ok(expected));
+        // the canonical form parses to the same
+        assert_eq!(expected.to_string().parse::<SolarSchedule>(), Ok(expected));
+    }
+    assert_eq!(valid[6].1.to_string(), "sunset-00:30,not_before=17:00");
+
+    // offsets that are not whole minutes, or of 100 hours or more
+    let sunset = |offset| SolarSchedule::new(OffsetEvent::new(EventKind::Sunset, offset));
+    let offsets = [(Duration::seconds(90), "sunset+00:01:30"),
+                   (Duration::seconds(-30), "sunset-00:00:30"),
+                   (Duration::milliseconds(-1500), "sunset-00:00:01.5"),
+                   (Duration::nanoseconds(1), "sunset+00:00:00.000000001"),
+                   (Duration::hours(100), "sunset+100:00"),
+                   (Duration::days(-400) - Duration::seconds(59), "sunset-9600:00:59")];
+    for &(offset, text) in &offsets {
+        assert_eq!(sunset(offset).to_string(), text);
+        assert_eq!(sunset(offset).to_string().parse(), Ok(sunset(offset)));
+    }
+
+    let invalid = [("sunsett-00:30", ParseErrorKind::UnknownEvent, 0, "sunsett"),
+                   ("", ParseErrorKind::UnknownEvent, 0, ""),
+                   ("-00:30", ParseErrorKind::UnknownEvent, 0, ""),
+                   ("sunset-0030", ParseErrorKind::InvalidOffset, 6, "-0030"),
+                   ("sunset+00:60", ParseErrorKind::InvalidOffset, 6, "+00:60"),
+                   ("sunset-00:30-", ParseErrorKind::InvalidOffset, 6, "-00:30-"),
+                   ("sunset+00:01:60", ParseErrorKind::InvalidOffset, 6, "+00:01:60"),
+                   ("sunset+00:01.5", ParseErrorKind::InvalidOffset, 6, "+00:01.5"),
+                   ("sunset+00:00:00:01", ParseErrorKind::InvalidOffset, 6, "+00:00:00:01"),
+                   ("sunset+9999999999999:00", ParseErrorKind::InvalidOffset, 6,
+                    "+9999999999999:00"),
+                   ("sunset,not_before", ParseErrorKind::UnknownOption, 7, "not_before"),
+                   ("sunset,before=17:00", ParseErrorKind::UnknownOption, 7, "before"),
+                   ("sunset,not_before=24:00", ParseErrorKind::InvalidTime, 18, "24:00"),
+                   ("sunset,not_before=5pm", ParseErrorKind::InvalidTime, 18, "5pm"),
+                   ("noon,not_after=1:00,not_after=2:00", ParseErrorKind::DuplicateOption, 20,
+                    "not_after"),
+                   ("noon,not_before=14:00,not_after=13:00", ParseErrorKind::EmptyRange, 22,
+                    "not_after=13:00")];
+    for &(text, kind, position, token) in &invalid {
+        let error = SolarSchedule::parse(text).unwrap_err();
+        assert_eq!((error.kind(), error.position(), error.token()),
+                   (kind, position, token),
+                   "{}",
+                   text);
+    }
+    assert_eq!(SolarSchedule::parse("sunsett").unwrap_err().to_string(),
+               "unknown event (expected sunrise, sunset, noon, civil_dawn or civil_dusk) at \
+                position 0: `sunsett`");
+}
+
+#[test]
+fn solar_schedule_next_occurrence() {
+    // Apeldoorn, 2015-03-27 12:00 UTC; local mean solar time is 1432
+    // seconds ahead of UTC
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let midday = Timespec::new(1427457600, 0);
+    let sunset = 1427479207;
+    let next = |text: &str, after| {
+        SolarSchedule::parse(text).unwrap().next_occurrence(after, lat, long)
+    };
+
+    assert_eq!(next("sunset-00:30", midday), Some(Timespec::new(sunset - 1800, 0)));
+    // the sunset of the next day, once past
+    let tomorrow = next("sunset-00:30", Timespec::new(sunset - 1800, 0)).unwrap();
+    assert!(tomorrow.sec - sunset > 23 * 3600, "{:?}", time::at_utc(tomorrow));
+    // an hour after sunset is 19:24 local time, so it is limited to 19:00
+    let limited = next("sunset+01:00,not_after=19:00", midday).unwrap();
+    assert_eq!(limited, Timespec::new(1427414400 + 19 * 3600 - 1432, 0));
+    assert_eq!(next("sunset+01:00,not_after=20:00", midday),
+               Some(Timespec::new(sunset + 3600, 0)));
+    // noon is at 12:15 local time, but not before 13:00; from the evening,
+    // that of the next day
+    let noon = next("noon,not_before=13:00", midday).unwrap();
+    assert_eq!(noon, Timespec::new(1427414400 + 13 * 3600 - 1432, 0));
+    assert_eq!(next("noon,not_before=13:00", noon), Some(noon + Duration::days(1)));
+    // an explicit clock
+    let schedule = SolarSchedule::parse("noon,not_before=13:00").unwrap();
+    assert_eq!(schedule.next_occurrence_with_offset(midday, lat, long, 0),
+               Some(Timespec::new(1427414400 + 13 * 3600, 0)));
+    assert_eq!(schedule.next_occurrence_with_offset(midday - Duration::hours(4), lat, long, 7200),
+               Some(Timespec::new(1427456487, 0)));
+
+    // at the pole there is no sunset
+    let november = Timespec::new(1446724800, 0);
+    assert_eq!(SolarSchedule::parse("sunset").unwrap().next_occurrence(november, 90.0, 0.0),
+               None);
+}
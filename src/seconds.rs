@@ -0,0 +1,109 @@
+//! Calculation on plain unix seconds and angle degrees, which is also
+//! available without the `std` feature (like on a microcontroller).
+
+use {Daylight, DaylightOptions, LatitudeTrig, Timespec, daylight_at};
+
+/// Result of the daylight calculation (see `Daylight`) in whole seconds; the
+/// events are seconds since 1970-01-01 00:00 UTC
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DaylightSeconds {
+    pub twilight_morning: i64,
+    pub sunrise: i64,
+    pub noon: i64,
+    pub sunset: i64,
+    pub twilight_evening: i64,
+    /// Duration of the day in seconds
+    pub daylength: i64,
+    /// Declination of the sun in angle degrees
+    pub declination: f64,
+    /// Sun altitude in angle degrees
+    pub sun_altitude: f64,
+}
+
+/// Drops the fraction of the seconds, which only remains when the events are
+/// not rounded (see `DaylightOptions::rounding`)
+impl From<Daylight> for DaylightSeconds {
+    fn from(daylight: Daylight) -> DaylightSeconds {
+        DaylightSeconds {
+            twilight_morning: daylight.twilight_morning.sec,
+            sunrise: daylight.sunrise.sec,
+            noon: daylight.noon.sec,
+            sunset: daylight.sunset.sec,
+            twilight_evening: daylight.twilight_evening.sec,
+            daylength: daylight.daylength.num_seconds(),
+            declination: daylight.declination,
+            sun_altitude: daylight.sun_altitude,
+        }
+    }
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at the instant
+/// `unix_secs` seconds since the unix epoch (see `calculate_daylight`)
+pub fn calculate_daylight_seconds(unix_secs: i64,
+                                  latitude: f64,
+                                  longitude: f64)
+                                  -> DaylightSeconds {
+    calculate_daylight_seconds_with(unix_secs, latitude, longitude, &DaylightOptions::default())
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at the instant
+/// `unix_secs` seconds since the unix epoch, using the given calculation
+/// options
+pub fn calculate_daylight_seconds_with(unix_secs: i64,
+                                       latitude: f64,
+                                       longitude: f64,
+                                       options: &DaylightOptions)
+                                       -> DaylightSeconds {
+    daylight_at(Timespec::new(unix_secs, 0),
+                &LatitudeTrig::new(latitude),
+                longitude,
+                options)
+        .into()
+}
+
+#[test]
+fn daylight_seconds_golden_values() {
+    // the golden values of the calculation on `Tm`, 2015-03-27 12:00 UTC
+    let apeldoorn = calculate_daylight_seconds(1427457600, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    assert_eq!([apeldoorn.twilight_morning,
+                apeldoorn.sunrise,
+                apeldoorn.noon,
+                apeldoorn.sunset,
+                apeldoorn.twilight_evening],
+               [1427432130, 1427433767, 1427456487, 1427479207, 1427480844]);
+    assert_eq!(apeldoorn.daylength, 45440);
+    assert!(apeldoorn.declination > 2.777311 && apeldoorn.declination < 2.777313);
+    assert!(apeldoorn.sun_altitude > 40.55 && apeldoorn.sun_altitude < 40.57);
+
+    let tokyo = calculate_daylight_seconds(1427457600, 35.41, 139.41);
+    assert_eq!([tokyo.twilight_morning, tokyo.sunrise, tokyo.noon, tokyo.sunset,
+                tokyo.twilight_evening],
+               [1427401348, 1427402244, 1427424461, 1427446677, 1427447573]);
+    assert_eq!(tokyo.daylength, 44434);
+
+    // Longyearbyen, midsummer and midwinter
+    let midsummer = calculate_daylight_seconds(1434888000, 78.22, 15.65);
+    assert_eq!([midsummer.sunrise, midsummer.noon, midsummer.sunset],
+               [1434841154, 1434884354, 1434927554]);
+    assert_eq!(midsummer.daylength, 86400);
+    let midwinter = calculate_daylight_seconds(1450699200, 78.22, 15.65);
+    assert_eq!([midwinter.twilight_morning, midwinter.sunset], [1450695334, 1450695334]);
+    assert_eq!(midwinter.daylength, 0);
+    assert!(midwinter.sun_altitude > -11.66 && midwinter.sun_altitude < -11.64);
+}
+
+#[test]
+fn daylight_seconds_options() {
+    use Rounding;
+
+    // the events are whole seconds also when they are not rounded, before
+    // 1970 rounded down like after
+    let options = DaylightOptions::default().rounding(Rounding::Exact).refined(true);
+    let exact = calculate_daylight_seconds_with(-1427457600, 52.22, 5.97, &options);
+    let rounded = calculate_daylight_seconds_with(-1427457600,
+                                                  52.22,
+                                                  5.97,
+                                                  &options.rounding(Rounding::Floor));
+    assert_eq!(exact, rounded);
+    assert!(exact.sunrise < exact.noon && exact.noon < exact.sunset);
+}
@@ -0,0 +1,135 @@
+//! Serialization of the results (enabled with the `serde` feature).
+//!
+//! `Daylight` is serialized with stable field names, so other languages can
+//! read it: `twilight_morning`, `sunrise`, `sunset`, `twilight_evening` and
+//! `noon` as unix seconds (the fraction of a second of `Rounding::Exact` is
+//! dropped), `declination` and `sun_altitude` in angle degrees and
+//! `daylength` in seconds.
+//!
+//! The timestamps can be written as RFC 3339 strings in UTC instead, with
+//! `#[serde(with = "daylight::rfc3339")]` on a `Daylight` field.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use time::{self, Duration, Timespec};
+
+use {Daylight, tm_to_timespec};
+
+/// Timestamp as unix seconds
+pub mod unix_seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(ts: &Timespec, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(ts.sec)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timespec, D::Error> {
+        Ok(Timespec::new(i64::deserialize(deserializer)?, 0))
+    }
+}
+
+/// Duration as whole seconds
+pub mod seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::seconds(i64::deserialize(deserializer)?))
+    }
+}
+
+/// `Daylight` with the timestamps as RFC 3339 strings in UTC, like
+/// `2015-03-27T05:22:47Z`
+pub mod rfc3339 {
+    use super::*;
+    use serde::de::Error;
+
+    #[derive(Serialize, Deserialize)]
+    struct Rfc3339Daylight {
+        twilight_morning: String,
+        sunrise: String,
+        sunset: String,
+        twilight_evening: String,
+        noon: String,
+        declination: f64,
+        daylength: i64,
+        sun_altitude: f64,
+    }
+
+    fn format(ts: Timespec) -> String {
+        time::at_utc(Timespec::new(ts.sec, 0)).rfc3339().to_string()
+    }
+
+    fn parse<E: Error>(text: &str) -> Result<Timespec, E> {
+        time::strptime(text, "%Y-%m-%dT%H:%M:%SZ")
+            .map(tm_to_timespec)
+            .map_err(|e| E::custom(format_args!("invalid RFC 3339 time {:?}: {}", text, e)))
+    }
+
+    pub fn serialize<S: Serializer>(daylight: &Daylight, serializer: S) -> Result<S::Ok, S::Error> {
+        Rfc3339Daylight {
+                twilight_morning: format(daylight.twilight_morning),
+                sunrise: format(daylight.sunrise),
+                sunset: format(daylight.sunset),
+                twilight_evening: format(daylight.twilight_evening),
+                noon: format(daylight.noon),
+                declination: daylight.declination,
+                daylength: daylight.daylength.num_seconds(),
+                sun_altitude: daylight.sun_altitude,
+            }
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Daylight, D::Error> {
+        let daylight = Rfc3339Daylight::deserialize(deserializer)?;
+        Ok(Daylight {
+            twilight_morning: parse(&daylight.twilight_morning)?,
+            sunrise: parse(&daylight.sunrise)?,
+            sunset: parse(&daylight.sunset)?,
+            twilight_evening: parse(&daylight.twilight_evening)?,
+            noon: parse(&daylight.noon)?,
+            declination: daylight.declination,
+            daylength: Duration::seconds(daylight.daylength),
+            sun_altitude: daylight.sun_altitude,
+        })
+    }
+}
+
+#[test]
+fn serde_daylight() {
+    extern crate serde_json;
+
+    use calculate_daylight;
+
+    // Apeldoorn, 2015-03-27
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(tm, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+
+    let json = serde_json::to_value(daylight).unwrap();
+    assert_eq!(json["twilight_morning"], 1427432130);
+    assert_eq!(json["sunrise"], 1427433767);
+    assert_eq!(json["sunset"], 1427479207);
+    assert_eq!(json["twilight_evening"], 1427480844);
+    assert_eq!(json["noon"], 1427456487);
+    assert_eq!(json["daylength"], 45440);
+    assert!(json["declination"].is_f64() && json["sun_altitude"].is_f64());
+    assert_eq!(json.as_object().unwrap().len(), 8);
+    assert_eq!(serde_json::from_value::<Daylight>(json).unwrap(), daylight);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Cached {
+        #[serde(with = "rfc3339")]
+        daylight: Daylight,
+    }
+    let cached = Cached { daylight };
+    let json = serde_json::to_value(&cached).unwrap();
+    assert_eq!(json["daylight"]["sunrise"], "2015-03-27T05:22:47Z");
+    assert_eq!(json["daylight"]["daylength"], 45440);
+    assert_eq!(serde_json::from_value::<Cached>(json.clone()).unwrap(), cached);
+
+    let mut invalid = json;
+    invalid["daylight"]["sunset"] = "2015-03-27 18:00:07".into();
+    assert!(serde_json::from_value::<Cached>(invalid).is_err());
+}
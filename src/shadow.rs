@@ -0,0 +1,105 @@
+//! Path of the tip of the shadow of a vertical gnomon on level ground, as
+//! drawn on a sundial.
+
+use time::{Duration, Timespec, Tm};
+
+use {DaylightOptions, LatitudeTrig, daylight_at, horizon, tm_to_timespec, to_radians};
+
+/// Lowest altitude of the sun in angle degrees at which the shadow is
+/// sampled; there the shadow is already 29 times the height of the gnomon,
+/// and it grows without bound towards the horizon
+const MIN_ALTITUDE: f64 = 2.0;
+
+/// Position of the tip of the shadow relative to the foot of the gnomon
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowPoint {
+    pub time: Timespec,
+    /// Distance to the east in meters (negative for the west)
+    pub x: f64,
+    /// Distance to the north in meters (negative for the south)
+    pub y: f64,
+}
+
+/// Returns the tip of the shadow of a vertical gnomon of `height_m` meters
+/// through the day calculated at the given date (see `calculate_daylight`),
+/// at whole steps before and after its solar noon from the sunrise until the
+/// sunset, or through the 24 hours around the noon during the polar day;
+/// empty during the polar night. Panics when the step is not positive.
+///
+/// The shadow is derived from the position of the sun (see
+/// `Location::solar_position`). Samples where the sun is less than 2 degrees
+/// above the horizon are left out, as the shadow grows without bound near
+/// the sunrise and sunset.
+pub fn shadow_trajectory(date: Tm,
+                         latitude: f64,
+                         longitude: f64,
+                         height_m: f64,
+                         step: Duration)
+                         -> Vec<ShadowPoint> {
+    assert!(step > Duration::zero(), "the step {} must be positive", step);
+    let lat = LatitudeTrig::new(latitude);
+    let options = DaylightOptions::default();
+    let daylight = daylight_at(tm_to_timespec(date), &lat, longitude, &options);
+    let (start, end) = if daylight.is_polar_night() {
+        return Vec::new();
+    } else if daylight.is_polar_day() {
+        (daylight.noon - Duration::hours(12), daylight.noon + Duration::hours(12))
+    } else {
+        (daylight.sunrise, daylight.sunset)
+    };
+    let step_ms = step.num_milliseconds();
+    let before = (daylight.noon - start).num_milliseconds() / step_ms;
+    let after = (end - daylight.noon).num_milliseconds() / step_ms;
+    (-before..=after)
+        .map(|index| daylight.noon + Duration::milliseconds(index * step_ms))
+        .filter_map(|time| {
+            let (altitude, azimuth) = horizon::sun_position(time, &lat, longitude, &options);
+            if altitude < to_radians(MIN_ALTITUDE) {
+                return None;
+            }
+            // away from the sun
+            let length = height_m / altitude.tan();
+            Some(ShadowPoint {
+                time,
+                x: -length * azimuth.sin(),
+                y: -length * azimuth.cos(),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn shadow_points_north_at_noon() {
+    use time;
+
+    use calculate_daylight;
+
+    // Apeldoorn, 2015-03-27, with a gnomon of 2 meters
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let date = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = calculate_daylight(date, lat, long);
+    let points = shadow_trajectory(date, lat, long, 2.0, Duration::minutes(10));
+    let noon = points.iter().find(|point| point.time == daylight.noon).unwrap();
+    assert!(noon.x.abs() < 0.01 && noon.y > 0.0, "{:?}", noon);
+    let expected = 2.0 / to_radians(daylight.sun_altitude).tan();
+    assert!((noon.y - expected).abs() < 0.005, "{} {}", noon.y, expected);
+    // the shortest shadow at noon, from the west in the morning to the east
+    // in the evening, within the cap near the horizon
+    let length = |point: &ShadowPoint| point.x.hypot(point.y);
+    assert!(points.iter().all(|point| length(point) >= length(noon) - 1e-9));
+    assert!(points.iter().all(|point| length(point) <= 2.0 / to_radians(MIN_ALTITUDE).tan()));
+    assert!(points[0].x < 0.0 && points[points.len() - 1].x > 0.0);
+    assert!(points.first().unwrap().time >= daylight.sunrise &&
+            points.last().unwrap().time <= daylight.sunset);
+    assert!(points.windows(2).all(|pair| pair[1].time - pair[0].time >= Duration::minutes(10)));
+
+    // Sydney: to the south; Longyearbyen: around the clock in the polar day,
+    // nothing in the polar night
+    let sydney = shadow_trajectory(date, -33.87, 151.21, 1.0, Duration::hours(1));
+    let noon = calculate_daylight(date, -33.87, 151.21).noon;
+    assert!(sydney.iter().find(|point| point.time == noon).unwrap().y < 0.0);
+    let june = time::at_utc(Timespec::new(1434888000, 0));
+    assert_eq!(shadow_trajectory(june, 78.22, 15.65, 1.0, Duration::hours(1)).len(), 25);
+    let december = time::at_utc(Timespec::new(1450699200, 0));
+    assert!(shadow_trajectory(december, 78.22, 15.65, 1.0, Duration::hours(1)).is_empty());
+}
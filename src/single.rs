@@ -0,0 +1,177 @@
+//! The default calculation in single precision (`f32`), for targets where
+//! double precision floating point is emulated in software and slow, like
+//! a Cortex-M0; also available without the `std` feature.
+//!
+//! A `f32` holds about 7 significant digits, so the days since 2000 can not
+//! be a single number like in `calculate_daylight`: around 2025 the days of
+//! a `f32` are about a minute and a half apart. The whole days
+//! stay an integer instead, split into cycles of 1461 days (four years) in
+//! which the mean longitude and anomaly of the sun advance by nearly a
+//! whole number of revolutions; only the small remainder of each cycle and
+//! the days within the cycle are multiplied in `f32`. The angles are
+//! reduced in degrees before they are converted to radians.
+//!
+//! What remains is the rounding of the trigonometric functions themselves.
+//! Between 60° south and 60° north the events are within 2 seconds of
+//! `calculate_daylight_seconds` over the years 1900 to 2100 (the test below
+//! checks a year at several latitudes). Closer to the poles, around the
+//! start and end of the polar day or night, when the sun barely reaches the
+//! horizon, the difference grows, as the time of sunrise is then very
+//! sensitive to the declination. At mid latitudes it stays well below the
+//! difference of the default calculation to the actual sunrise (see
+//! `Series`).
+
+use core::f32::consts;
+
+#[cfg(not(any(feature = "std", test)))]
+use math::Float;
+use {DaylightSeconds, AIRREFR, SECS_IN_DAY, SUNRADIUS, Y2000};
+
+/// Days of the cycle in which the mean longitude and anomaly of the sun
+/// advance by nearly four revolutions
+const CYCLE_DAYS: i64 = 1461;
+/// Advance of the mean longitude in a cycle beyond four revolutions, in
+/// angle degrees (0.9856474 * 1461 - 1440)
+const CYCLE_LONGITUDE: f32 = 0.0308514;
+/// Advance of the mean anomaly in a cycle beyond four revolutions, in angle
+/// degrees (0.9856003 * 1461 - 1440)
+const CYCLE_ANOMALY: f32 = -0.0379617;
+
+/// Reduces the angle in degrees to the range 0 to 360
+fn range_degrees(degrees: f32) -> f32 {
+    degrees - 360.0 * (degrees / 360.0).floor()
+}
+
+/// Hour angle at which the centre of the sun is `depression` below the
+/// horizon, like `calculate_angle`
+fn hour_angle(sin_lat: f32, cos_lat: f32, declin: f32, depression: f32) -> f32 {
+    let df = if sin_lat.is_sign_negative() {
+        -depression
+    } else {
+        depression
+    };
+    // the cosine of the latitude is never negative, but is at 90 degrees in
+    // `f32`, as the nearest `f32` of pi/2 is slightly larger
+    let f = (declin + df).tan() * (sin_lat / cos_lat.abs());
+    f.clamp(-1.0, 1.0).asin() + consts::FRAC_PI_2
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at the instant
+/// `unix_secs` seconds since the unix epoch like `calculate_daylight_seconds`,
+/// in single precision (see the accuracy above); panics when the latitude or
+/// longitude is not a finite number
+pub fn calculate_daylight_f32(unix_secs: i64, latitude: f32, longitude: f32) -> DaylightSeconds {
+    // the casts below saturate, a NaN would become midnight
+    assert!(latitude.is_finite() && longitude.is_finite(),
+            "latitude {} and longitude {} must be finite numbers",
+            latitude,
+            longitude);
+    let since_2000 = unix_secs - Y2000;
+    let days = since_2000.div_euclid(SECS_IN_DAY);
+    let fraction = since_2000.rem_euclid(SECS_IN_DAY) as f32 / SECS_IN_DAY as f32;
+    let (cycles, day_in_cycle) = (days.div_euclid(CYCLE_DAYS), days.rem_euclid(CYCLE_DAYS));
+    let (cycles, d) = (cycles as f32, day_in_cycle as f32 + fraction);
+
+    // find the ecliptic longitude of the sun (see `fnsun`)
+    let mean_longitude = range_degrees(280.461 + cycles * CYCLE_LONGITUDE + 0.9856474 * d);
+    let g = range_degrees(357.528 + cycles * CYCLE_ANOMALY + 0.9856003 * d).to_radians();
+    let ecliptic_longitude = (mean_longitude + 1.915 * g.sin() + 0.02 * (2.0 * g).sin())
+        .to_radians();
+
+    // Obliquity of the ecliptic, which changes slowly enough for the day
+    // count in `f32`
+    let obliq = (23.439 - 0.0000004 * (days as f32 + fraction)).to_radians();
+
+    // Find the RA and DEC of the sun
+    let (sin_longitude, cos_longitude) = ecliptic_longitude.sin_cos();
+    let alpha = (obliq.cos() * sin_longitude).atan2(cos_longitude);
+    let delta = (obliq.sin() * sin_longitude).asin();
+
+    // Find the equation of time in hours, the difference in (-180, 180]
+    let mut difference = range_degrees(mean_longitude - alpha.to_degrees());
+    if difference > 180.0 {
+        difference -= 360.0;
+    }
+    let equation = -difference / 15.0;
+
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let depression = (0.5 * SUNRADIUS as f32 + AIRREFR as f32).to_radians();
+    let ha = hour_angle(sin_lat, cos_lat, delta, depression);
+    let hb = hour_angle(sin_lat, cos_lat, delta, 6.0f32.to_radians());
+
+    // in seconds, relative to midnight of the instant
+    let halfday = 43200.0 * ha / consts::PI;
+    let twilight = 43200.0 * (hb - ha) / consts::PI;
+    let noon = 3600.0 * (12.0 - longitude / 15.0 + equation);
+
+    let altmax_nh = consts::FRAC_PI_2 + delta - latitude.to_radians();
+    let altmax = if latitude.to_radians() < delta {
+        consts::PI - altmax_nh
+    } else {
+        altmax_nh
+    };
+
+    let midnight = unix_secs - since_2000.rem_euclid(SECS_IN_DAY);
+    let at = |secs: f32| midnight + (secs + 0.5).floor() as i64;
+    DaylightSeconds {
+        twilight_morning: at(noon - halfday - twilight),
+        sunrise: at(noon - halfday),
+        noon: at(noon),
+        sunset: at(noon + halfday),
+        twilight_evening: at(noon + halfday + twilight),
+        daylength: (2.0 * halfday + 0.5).floor() as i64,
+        declination: delta.to_degrees() as f64,
+        sun_altitude: altmax.to_degrees() as f64,
+    }
+}
+
+#[test]
+fn daylight_f32_compared_to_f64() {
+    use calculate_daylight_seconds;
+
+    // the Apeldoorn golden values within a few seconds
+    let daylight = calculate_daylight_f32(1427457600, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let expected = [1427432130, 1427433767, 1427456487, 1427479207, 1427480844];
+    let events = [daylight.twilight_morning,
+                  daylight.sunrise,
+                  daylight.noon,
+                  daylight.sunset,
+                  daylight.twilight_evening];
+    for (event, expected) in events.iter().zip(expected.iter()) {
+        assert!((event - expected).abs() <= 5, "{:?}", daylight);
+    }
+
+    // every 3rd day of 2024, and some days far from 2000
+    let instants = (0..122)
+        .map(|day| 1704110400 + day * 3 * 86400)
+        .chain([-2208945600, -631108800, 2524651200, 4102401600].iter().cloned());
+    let mut max_difference = 0;
+    for unix_secs in instants {
+        for &latitude in &[-60.0f32, -33.87, 0.0, 23.4, 35.41, 52.22, 60.0] {
+            for &longitude in &[-122.42f32, 0.0, 5.97, 139.41, 179.9] {
+                let single = calculate_daylight_f32(unix_secs, latitude, longitude);
+                let double = calculate_daylight_seconds(unix_secs,
+                                                        latitude as f64,
+                                                        longitude as f64);
+                let differences = [single.twilight_morning - double.twilight_morning,
+                                   single.sunrise - double.sunrise,
+                                   single.noon - double.noon,
+                                   single.sunset - double.sunset,
+                                   single.twilight_evening - double.twilight_evening,
+                                   single.daylength - double.daylength];
+                for difference in differences.iter() {
+                    max_difference = max_difference.max(difference.abs());
+                }
+                assert!((single.declination - double.declination).abs() < 0.001);
+                assert!((single.sun_altitude - double.sun_altitude).abs() < 0.001);
+            }
+        }
+    }
+    assert!(max_difference <= 2, "maximum difference {} s", max_difference);
+}
+
+#[test]
+#[should_panic(expected = "must be finite numbers")]
+fn daylight_f32_not_finite() {
+    calculate_daylight_f32(1427457600, f32::NAN, 5.97);
+}
@@ -0,0 +1,183 @@
+//! Events as they occur, as an asynchronous stream on the timers of tokio
+//! (enabled with the `async` feature).
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use time::{self, Duration, Timespec};
+use tokio::time::{self as timer, Sleep};
+
+use events::{UpcomingEvents, upcoming_events};
+use SolarEvent;
+
+/// Longest wait before the clock is read again; the timers of tokio run on
+/// a monotonic clock, which stops while the system is suspended and does not
+/// follow adjustments of the system clock
+const MAX_WAIT_SECS: i64 = 60;
+/// Delay after which an event counts as missed, a multiple of the longest
+/// wait so that an event is never missed while the process runs
+const MISSED_AFTER_SECS: i64 = 2 * MAX_WAIT_SECS;
+
+/// What `SolarEventStream` does with the events that passed while the
+/// process was suspended, or that the system clock jumped over
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum MissedEvents {
+    /// Emit them as soon as the stream is polled, in order (the default)
+    #[default]
+    Emit,
+    /// Leave out the events that are more than two minutes late
+    Skip,
+}
+
+/// Stream of the events at a location, each emitted at (or just after) the
+/// moment it occurs by the system clock (see `solar_event_stream`)
+pub struct SolarEventStream {
+    upcoming: UpcomingEvents,
+    next: SolarEvent,
+    missed: MissedEvents,
+    clock: Box<dyn Fn() -> Timespec + Send + Sync>,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl SolarEventStream {
+    /// Sets what to do with missed events (see `MissedEvents`)
+    pub fn missed(mut self, missed: MissedEvents) -> SolarEventStream {
+        self.missed = missed;
+        self
+    }
+
+    /// Sets the clock of the events instead of the system clock, like for a
+    /// simulated day
+    pub fn clock<F>(mut self, clock: F) -> SolarEventStream
+        where F: Fn() -> Timespec + Send + Sync + 'static
+    {
+        self.clock = Box::new(clock);
+        self
+    }
+}
+
+impl fmt::Debug for SolarEventStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SolarEventStream")
+            .field("next", &self.next)
+            .field("missed", &self.missed)
+            .finish()
+    }
+}
+
+impl Stream for SolarEventStream {
+    type Item = SolarEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<SolarEvent>> {
+        loop {
+            // the clock is read on every wake-up, so a suspension or a jump
+            // of the clock is noticed within the longest wait
+            let now = (self.clock)();
+            let event = self.next;
+            if event.time <= now {
+                self.next = self.upcoming.next().expect("the events never end");
+                let late = now - event.time > Duration::seconds(MISSED_AFTER_SECS);
+                if late && self.missed == MissedEvents::Skip {
+                    continue;
+                }
+                return Poll::Ready(Some(event));
+            }
+
+            let wait = (event.time - now).min(Duration::seconds(MAX_WAIT_SECS));
+            let wait = wait.to_std().expect("the event is in the future");
+            let deadline = timer::Instant::now() + wait;
+            self.sleep.as_mut().reset(deadline);
+            if self.sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// Returns the events strictly after the instant `from` (see
+/// `upcoming_events`) as a stream without end, emitting each when the system
+/// clock passes it; the following day is calculated when its first event is
+/// needed. Events before the first poll, or passed during a suspension of
+/// the process or a jump of the clock, are missed (see `MissedEvents`). Must
+/// be polled within a tokio runtime with the timers enabled.
+pub fn solar_event_stream(latitude: f64, longitude: f64, from: Timespec) -> SolarEventStream {
+    let mut upcoming = upcoming_events(from, latitude, longitude);
+    let next = upcoming.next().expect("the events never end");
+    SolarEventStream {
+        upcoming,
+        next,
+        missed: MissedEvents::default(),
+        clock: Box::new(time::get_time),
+        sleep: Box::pin(timer::sleep(std::time::Duration::from_secs(0))),
+    }
+}
+
+#[test]
+fn event_stream_simulated_day() {
+    use std::future;
+    use std::sync::{Arc, Mutex};
+
+    use tokio::runtime::Builder;
+
+    use EventKind;
+
+    let runtime = Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+    let _guard = runtime.enter();
+
+    // the simulated clock starts at 2015-03-27 00:00 UTC and follows the
+    // paused timers of tokio, shifted by the simulated suspensions
+    let start = (timer::Instant::now(), Timespec::new(1427414400, 0));
+    let shift = Arc::new(Mutex::new(Duration::zero()));
+    let clock = {
+        let shift = shift.clone();
+        move || {
+            let elapsed = Duration::from_std(timer::Instant::now() - start.0).unwrap();
+            start.1 + elapsed + *shift.lock().unwrap()
+        }
+    };
+    let now = clock.clone();
+    let (latitude, longitude) = (52.22, 5.97);
+    let mut stream = solar_event_stream(latitude, longitude, start.1).clock(clock.clone());
+    let mut next = || {
+        runtime.block_on(future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))).unwrap()
+    };
+
+    // Apeldoorn: each event at the moment it occurs, in order
+    let expected: Vec<SolarEvent> = upcoming_events(start.1, latitude, longitude)
+        .take(10)
+        .collect();
+    for expected in &expected[..5] {
+        let event = next();
+        assert_eq!(event, *expected);
+        assert_eq!(now(), event.time);
+    }
+    assert_eq!(expected[1].kind, EventKind::Sunrise);
+
+    // suspended from the evening twilight until 3 hours after the next
+    // sunrise; the missed events are emitted at once, in order
+    *shift.lock().unwrap() = expected[6].time - expected[4].time + Duration::hours(3);
+    assert_eq!(next(), expected[5]);
+    assert_eq!(next(), expected[6]);
+    let event = next();
+    assert_eq!((event, now()), (expected[7], expected[7].time));
+
+    // or skipped, apart from those that just passed
+    let mut stream = solar_event_stream(latitude, longitude, start.1)
+        .clock(clock)
+        .missed(MissedEvents::Skip);
+    let late = expected[1].time + Duration::seconds(30) - now();
+    let mut shifted = shift.lock().unwrap();
+    *shifted = *shifted + late;
+    drop(shifted);
+    let event = runtime.block_on(future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)));
+    assert_eq!(event, Some(expected[1]));
+    let event = runtime.block_on(future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)));
+    assert_eq!(event, Some(expected[2]));
+}
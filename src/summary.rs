@@ -0,0 +1,640 @@
+//! Statistics of the daylight over a calendar year, or another period, at a
+//! location.
+
+use time::{self, Duration, Timespec, Tm};
+
+use {DaylightIter, DaylightOptions, LatitudeTrig, NSECS_IN_SEC, SECS_IN_DAY, SECS_IN_HOUR,
+     days_from_civil, daylight_hours_to_timespec, hours_to_duration, midnight, round_duration,
+     solar_day, tm_to_timespec};
+
+/// Event on a date of the year
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DatedEvent {
+    /// Date of the calculated day (at 12:00 UTC)
+    pub date: Tm,
+    /// Instant of the event
+    pub time: Timespec,
+}
+
+/// Day length on a date of the year
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DatedLength {
+    /// Date of the calculated day (at 12:00 UTC)
+    pub date: Tm,
+    pub daylength: Duration,
+}
+
+/// Summary of the daylight of every day of a calendar year (see
+/// `annual_summary`)
+///
+/// Of days that are equal, the first one of the year is reported. The
+/// earliest and latest sunrise and sunset compare the time of day in local
+/// mean solar time (`longitude / 15` hours from UTC) and leave out the polar
+/// day and night; they are `None` when the sun never rises and sets on the
+/// same day.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnnualSummary {
+    pub year: i32,
+    /// Sum of the day lengths
+    pub total_daylight: Duration,
+    /// Sum of the civil twilight in the morning and evening
+    pub total_twilight: Duration,
+    pub mean_daylength: Duration,
+    pub shortest_day: DatedLength,
+    pub longest_day: DatedLength,
+    pub earliest_sunrise: Option<DatedEvent>,
+    pub latest_sunrise: Option<DatedEvent>,
+    pub earliest_sunset: Option<DatedEvent>,
+    pub latest_sunset: Option<DatedEvent>,
+    /// Number of days on which the sun does not set
+    pub polar_days: u32,
+    /// Number of days on which the sun does not rise
+    pub polar_nights: u32,
+}
+
+/// Daylight over a calendar year at a location (see `annual_daylight`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnnualDaylight {
+    /// Sum of the day lengths
+    pub total: Duration,
+    /// Part of the year the sun is up, from 0.0 to 1.0
+    pub fraction_of_year: f64,
+}
+
+/// Sunrise of a day in the series of a year (see `sunrise_series`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeriesPoint {
+    /// Date of the calculated day (at 12:00 UTC)
+    pub date: Tm,
+    /// `None` during the polar day and night
+    pub sunrise: Option<Timespec>,
+    /// Change of the time of day of the sunrise since the day before,
+    /// negative when it is earlier; `None` on the first day of the year and
+    /// when either day has no sunrise
+    pub delta_from_previous: Option<Duration>,
+}
+
+/// Direction in which the day length crosses the threshold of
+/// `photoperiod_crossings`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PhotoperiodCrossing {
+    /// The day length reaches the threshold
+    Rising,
+    /// The day length drops below the threshold
+    Falling,
+}
+
+/// Definition of the day length of `photoperiod_crossings_with`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum PhotoperiodBasis {
+    /// From sunrise to sunset
+    #[default]
+    Daylength,
+    /// From the start of the morning civil twilight to the end of the
+    /// evening civil twilight
+    CivilTwilight,
+}
+
+/// Keeps the event when its time of day is earlier (`earlier`) or later
+/// than the one kept so far
+fn keep(kept: &mut Option<DatedEvent>, event: DatedEvent, utc_offset: i64, earlier: bool) {
+    let time_of_day = |event: &DatedEvent| (event.time.sec + utc_offset).rem_euclid(SECS_IN_DAY);
+    let replace = match *kept {
+        None => true,
+        Some(ref current) if earlier => time_of_day(&event) < time_of_day(current),
+        Some(ref current) => time_of_day(&event) > time_of_day(current),
+    };
+    if replace {
+        *kept = Some(event);
+    }
+}
+
+/// Summarizes the daylight of every day of the year at the location, each
+/// day calculated at 12:00 UTC
+pub fn annual_summary(year: i32, latitude: f64, longitude: f64) -> AnnualSummary {
+    let utc_offset = (longitude * SECS_IN_HOUR / 15.0).round() as i64;
+    let start = days_from_civil(year as i64, 1, 1) * SECS_IN_DAY + SECS_IN_DAY / 2;
+    let first = time::at_utc(Timespec::new(start, 0));
+    let mut summary = AnnualSummary {
+        year,
+        total_daylight: Duration::zero(),
+        total_twilight: Duration::zero(),
+        mean_daylength: Duration::zero(),
+        shortest_day: DatedLength { date: first, daylength: Duration::days(1) },
+        longest_day: DatedLength { date: first, daylength: -Duration::days(1) },
+        earliest_sunrise: None,
+        latest_sunrise: None,
+        earliest_sunset: None,
+        latest_sunset: None,
+        polar_days: 0,
+        polar_nights: 0,
+    };
+
+    let mut days = 0;
+    for (date, daylight) in DaylightIter::new(first, latitude, longitude)
+        .take_while(|&(date, _)| date.tm_year + 1900 == year) {
+        let daylength = daylight.daylength;
+        days += 1;
+        summary.total_daylight = summary.total_daylight + daylength;
+        let twilight = (daylight.sunrise - daylight.twilight_morning) +
+                       (daylight.twilight_evening - daylight.sunset);
+        summary.total_twilight = summary.total_twilight + twilight;
+        if daylength < summary.shortest_day.daylength {
+            summary.shortest_day = DatedLength { date, daylength };
+        }
+        if daylength > summary.longest_day.daylength {
+            summary.longest_day = DatedLength { date, daylength };
+        }
+
+        if daylight.is_polar_day() {
+            summary.polar_days += 1;
+        } else if daylight.is_polar_night() {
+            summary.polar_nights += 1;
+        } else {
+            let sunrise = DatedEvent { date, time: daylight.sunrise };
+            let sunset = DatedEvent { date, time: daylight.sunset };
+            keep(&mut summary.earliest_sunrise, sunrise, utc_offset, true);
+            keep(&mut summary.latest_sunrise, sunrise, utc_offset, false);
+            keep(&mut summary.earliest_sunset, sunset, utc_offset, true);
+            keep(&mut summary.latest_sunset, sunset, utc_offset, false);
+        }
+    }
+    summary.mean_daylength = summary.total_daylight / days;
+    summary
+}
+
+/// Sums the day lengths of every day of the year at the location, each day
+/// calculated at 12:00 UTC.
+///
+/// The day lengths are summed before they are rounded, so that the rounding
+/// of each day does not add up; each with the position of the sun at 12:00
+/// UTC and the rigorous hour angle of `DaylightOptions::refined` (the
+/// `total_daylight` of `annual_summary` is up to a few minutes a day shorter
+/// at the equator). Over a year every latitude gets slightly more than half
+/// of the time in daylight: the sunrise and sunset are defined by the upper
+/// limb of the sun after refraction, when its centre is still below the
+/// horizon (see `Horizon::Apparent`).
+pub fn annual_daylight(year: i32, latitude: f64, longitude: f64) -> AnnualDaylight {
+    let lat = LatitudeTrig::new(latitude);
+    // the rigorous hour angle; the approximation of the default calculation
+    // leaves out the depression of the sun at the equator
+    let options = DaylightOptions::default().refined(true);
+    let first = days_from_civil(year as i64, 1, 1);
+    let days = days_from_civil(year as i64 + 1, 1, 1) - first;
+    let hours: f64 = (first..first + days)
+        .map(|day| {
+            let midday = Timespec::new(day * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+            let day = solar_day(midday, &lat, longitude, &options);
+            day.morning + day.evening
+        })
+        .sum();
+    AnnualDaylight {
+        total: hours_to_duration(hours),
+        fraction_of_year: hours / (days as f64 * 24.0),
+    }
+}
+
+/// Returns the day length of every day of the year at the location, each day
+/// calculated at 12:00 UTC; equal to the `daylength` of `calculate_daylight`,
+/// but without the other events and the calendar dates of each day
+pub fn daylength_by_day(year: i32, latitude: f64, longitude: f64) -> Vec<Duration> {
+    let lat = LatitudeTrig::new(latitude);
+    let options = DaylightOptions::default();
+    let first = days_from_civil(year as i64, 1, 1);
+    let days = days_from_civil(year as i64 + 1, 1, 1) - first;
+    (first..first + days)
+        .map(|day| {
+            let midday = Timespec::new(day * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+            // like `SolarDay::to_daylight`
+            let half = hours_to_duration(solar_day(midday, &lat, longitude, &options).morning);
+            round_duration(half + half, options.get_rounding())
+        })
+        .collect()
+}
+
+/// Returns the date (at 12:00 UTC) and day length of every day of the year
+/// at the location (see `daylength_by_day`)
+pub fn daylength_by_date(year: i32, latitude: f64, longitude: f64) -> Vec<(Tm, Duration)> {
+    let first = days_from_civil(year as i64, 1, 1);
+    daylength_by_day(year, latitude, longitude)
+        .into_iter()
+        .zip(first..)
+        .map(|(daylength, day)| {
+            (time::at_utc(Timespec::new(day * SECS_IN_DAY + SECS_IN_DAY / 2, 0)), daylength)
+        })
+        .collect()
+}
+
+/// Returns the sunrise of every day of the year at the location, each day
+/// calculated at 12:00 UTC (like `calculate_daylight`), with how much
+/// earlier or later it is than the day before.
+///
+/// The change is the difference of the unrounded sunrises, less a day, so
+/// it does not jump with the rounding to the second. It is the change in
+/// UTC, or any fixed UTC offset: on the clock of a location with daylight
+/// saving time, the sunrise also moves by an hour at the switches, which is
+/// not part of the change.
+pub fn sunrise_series(year: i32, latitude: f64, longitude: f64) -> Vec<SeriesPoint> {
+    let lat = LatitudeTrig::new(latitude);
+    let options = DaylightOptions::default();
+    let first = days_from_civil(year as i64, 1, 1);
+    let days = days_from_civil(year as i64 + 1, 1, 1) - first;
+    // the unrounded sunrise of the day before, in hours after its midnight
+    let mut previous: Option<f64> = None;
+    (first..first + days)
+        .map(|day| {
+            let midday = Timespec::new(day * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+            let solar = solar_day(midday, &lat, longitude, &options);
+            let tsnoon = daylight_hours_to_timespec(midnight(midday), solar.noon);
+            let daylight = solar.to_daylight(tsnoon, options.get_rounding());
+            let hours = if daylight.is_polar_day() || daylight.is_polar_night() {
+                None
+            } else {
+                Some(solar.noon - solar.morning)
+            };
+            let delta = match (previous, hours) {
+                (Some(previous), Some(hours)) => {
+                    let nanoseconds = (hours - previous) * SECS_IN_HOUR * NSECS_IN_SEC;
+                    Some(Duration::nanoseconds(nanoseconds as i64))
+                }
+                _ => None,
+            };
+            previous = hours;
+            SeriesPoint {
+                date: time::at_utc(midday),
+                sunrise: hours.map(|_| daylight.sunrise),
+                delta_from_previous: delta,
+            }
+        })
+        .collect()
+}
+
+/// Returns the dates (at 12:00 UTC) in the year on which the day length at
+/// the location crosses the threshold, in order: the first day on which it
+/// is at least the threshold (`Rising`) and the first day on which it is
+/// less again (`Falling`); the day length is from sunrise to sunset (see
+/// `photoperiod_crossings_with`).
+///
+/// Empty when the day length stays on one side of the threshold the whole
+/// year, like for 14 hours near the equator. The first day is compared to
+/// the day before the year, so a crossing on January 1st is included. The
+/// day lengths are unrounded; at the start of the polar day the day length
+/// jumps to 24 hours, so a threshold above the longest day length before it
+/// is crossed on that day.
+pub fn photoperiod_crossings(year: i32,
+                             latitude: f64,
+                             longitude: f64,
+                             threshold: Duration)
+                             -> Vec<(Tm, PhotoperiodCrossing)> {
+    photoperiod_crossings_with(year, latitude, longitude, threshold, PhotoperiodBasis::default())
+}
+
+/// Returns the dates on which the day length crosses the threshold (see
+/// `photoperiod_crossings`), with the day length defined by the basis
+pub fn photoperiod_crossings_with(year: i32,
+                                  latitude: f64,
+                                  longitude: f64,
+                                  threshold: Duration,
+                                  basis: PhotoperiodBasis)
+                                  -> Vec<(Tm, PhotoperiodCrossing)> {
+    let lat = LatitudeTrig::new(latitude);
+    let options = DaylightOptions::default();
+    let threshold = threshold.num_milliseconds() as f64 / (SECS_IN_HOUR * 1000.0);
+    let first = days_from_civil(year as i64, 1, 1);
+    let days = days_from_civil(year as i64 + 1, 1, 1) - first;
+    let reached = |day: i64| {
+        let midday = Timespec::new(day * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+        let day = solar_day(midday, &lat, longitude, &options);
+        let hours = match basis {
+            PhotoperiodBasis::Daylength => day.morning + day.evening,
+            PhotoperiodBasis::CivilTwilight => {
+                day.twilight_morning + day.morning + day.evening + day.twilight_evening
+            }
+        };
+        hours >= threshold
+    };
+    let mut previous = reached(first - 1);
+    let mut crossings = Vec::new();
+    for day in first..first + days {
+        let current = reached(day);
+        if current != previous {
+            let crossing = if current {
+                PhotoperiodCrossing::Rising
+            } else {
+                PhotoperiodCrossing::Falling
+            };
+            let date = time::at_utc(Timespec::new(day * SECS_IN_DAY + SECS_IN_DAY / 2, 0));
+            crossings.push((date, crossing));
+        }
+        previous = current;
+    }
+    crossings
+}
+
+/// Returns how long the sun is up at the location from `start` up to, but
+/// not including, `end`; zero when `end` is not after `start`.
+///
+/// Only the part of the daylight of a day within the period counts: from a
+/// start at 15:00, the daylight until the sunset of that day. The daylight
+/// of a day is from its sunrise to its sunset, unrounded, as calculated at
+/// 12:00 UTC of the day (like `calculate_daylight`), and the days are summed
+/// unrounded. During the polar day, each day is the day around its solar
+/// noon, from halfway since the previous solar noon to halfway to the next,
+/// so the days follow each other without overlap or gap; the polar night
+/// adds nothing.
+pub fn accumulated_daylight(start: Tm, end: Tm, latitude: f64, longitude: f64) -> Duration {
+    let (start, end) = (tm_to_timespec(start), tm_to_timespec(end));
+    if end <= start {
+        return Duration::zero();
+    }
+    let lat = LatitudeTrig::new(latitude);
+    let options = DaylightOptions::default();
+    let base = midnight(start);
+    // hours since `base`
+    let hours = |ts: Timespec| {
+        ((ts.sec - base.sec) as f64 + ts.nsec as f64 / NSECS_IN_SEC) / SECS_IN_HOUR
+    };
+    let (from, to) = (hours(start), hours(end));
+    // solar noon, hours of sunrise and sunset from noon, of the day `day`
+    // days after `base`
+    let day = |day: i64| {
+        let midday = Timespec::new(base.sec + day * SECS_IN_DAY + SECS_IN_DAY / 2, 0);
+        let solar = solar_day(midday, &lat, longitude, &options);
+        (day as f64 * 24.0 + solar.noon, solar.morning, solar.evening)
+    };
+
+    let last = (to / 24.0).ceil() as i64 + 1;
+    let (mut previous, mut current) = (day(-2), day(-1));
+    let mut total = 0.0;
+    for index in -1..=last {
+        let next = day(index + 1);
+        let (noon, morning, evening) = current;
+        let (day_start, day_end) = ((previous.0 + noon) / 2.0, (noon + next.0) / 2.0);
+        let (sunrise, sunset) = if morning + evening >= 24.0 {
+            // the polar day, the whole day around the noon
+            (day_start, day_end)
+        } else {
+            ((noon - morning).max(day_start), (noon + evening).min(day_end))
+        };
+        let (sunrise, sunset) = (sunrise.max(from), sunset.min(to));
+        if sunset > sunrise {
+            total += sunset - sunrise;
+        }
+        previous = current;
+        current = next;
+    }
+    hours_to_duration(total)
+}
+
+#[test]
+fn annual_summary_apeldoorn() {
+    let summary = annual_summary(2015, 52.22, 5.97);
+    let date = |dated: &Tm| (dated.tm_mon + 1, dated.tm_mday);
+    assert_eq!(date(&summary.longest_day.date), (6, 21));
+    assert_eq!(date(&summary.shortest_day.date), (12, 21));
+    assert!(summary.longest_day.daylength > Duration::hours(16), "{:?}", summary);
+    assert!(summary.shortest_day.daylength < Duration::hours(8), "{:?}", summary);
+    assert_eq!((summary.polar_days, summary.polar_nights), (0, 0));
+
+    // the earliest sunrise is a few days before the longest day, the
+    // earliest sunset before and the latest sunrise after the shortest day
+    let earliest_sunrise = summary.earliest_sunrise.unwrap();
+    assert_eq!(date(&earliest_sunrise.date).0, 6);
+    assert!(date(&earliest_sunrise.date).1 < 21, "{:?}", earliest_sunrise);
+    assert_eq!(date(&summary.earliest_sunset.unwrap().date).0, 12);
+    let latest_sunrise = date(&summary.latest_sunrise.unwrap().date);
+    assert!(!((1, 5)..=(12, 25)).contains(&latest_sunrise), "{:?}", latest_sunrise);
+
+    // about 12 hours a day on average, plus the refraction and size of the
+    // sun; over half an hour of twilight a day
+    let mean = summary.mean_daylength;
+    assert!(mean > Duration::hours(12) && mean < Duration::minutes(12 * 60 + 15), "{}", mean);
+    assert_eq!(summary.total_daylight / 365, mean);
+    assert!(summary.total_twilight > Duration::hours(365) / 2, "{:?}", summary);
+}
+
+#[test]
+fn annual_summary_equator_and_arctic() {
+    // 2016 is a leap year; at the equator the days are of equal length
+    let equator = annual_summary(2016, 0.0, 0.0);
+    let spread = equator.longest_day.daylength - equator.shortest_day.daylength;
+    assert!(spread < Duration::minutes(1), "{:?}", equator);
+    assert_eq!(equator.total_daylight / 366, equator.mean_daylength);
+
+    // Longyearbyen has months of polar day and polar night
+    let arctic = annual_summary(2016, 78.22, 15.65);
+    assert!(arctic.polar_days > 100 && arctic.polar_nights > 80, "{:?}", arctic);
+    assert_eq!(arctic.longest_day.daylength, Duration::days(1));
+    assert_eq!(arctic.shortest_day.daylength, Duration::zero());
+    assert!(arctic.earliest_sunrise.is_some());
+
+    // at the pole the sun never rises and sets on the same day
+    let pole = annual_summary(2016, 90.0, 0.0);
+    assert_eq!(pole.polar_days + pole.polar_nights, 366);
+    assert_eq!(pole.earliest_sunrise, None);
+}
+
+#[test]
+fn annual_daylight_half() {
+    use calculate_daylight_with;
+
+    // slightly over half of the year everywhere, most at high latitudes,
+    // where the sun lingers around the horizon
+    let equator = annual_daylight(2015, 0.0, 0.0);
+    let apeldoorn = annual_daylight(2015, 52.22, 5.97);
+    let longyearbyen = annual_daylight(2015, 78.22, 15.65);
+    assert!(0.5 < equator.fraction_of_year);
+    assert!(equator.fraction_of_year < apeldoorn.fraction_of_year);
+    assert!(apeldoorn.fraction_of_year < longyearbyen.fraction_of_year);
+    assert!(longyearbyen.fraction_of_year < 0.53, "{:?}", longyearbyen);
+    // about 7 minutes a day at the equator, half the diameter of the sun and
+    // the refraction at the horizon
+    let minutes = (equator.fraction_of_year - 0.5) * 24.0 * 60.0;
+    assert!(minutes > 6.0 && minutes < 8.0, "{}", minutes);
+
+    for daylight in &[equator, apeldoorn, longyearbyen] {
+        let hours = daylight.total.num_milliseconds() as f64 / 3_600_000.0;
+        assert!((hours / (365.0 * 24.0) - daylight.fraction_of_year).abs() < 1e-9);
+    }
+    // like the sum of the day lengths of the refined calculation, from
+    // 2015-01-01 12:00 UTC
+    let options = DaylightOptions::default().refined(true);
+    let total = (0..365).fold(Duration::zero(), |total, day| {
+        let date = time::at_utc(Timespec::new(1420113600 + day * SECS_IN_DAY, 0));
+        total + calculate_daylight_with(date, 52.22, 5.97, &options).daylength
+    });
+    assert!((total - apeldoorn.total).num_minutes().abs() < 5,
+            "{} {}",
+            total,
+            apeldoorn.total);
+    assert_eq!(annual_daylight(2016, 52.22, 5.97).total.num_days(), 187);
+}
+
+#[test]
+fn daylength_of_every_day() {
+    use calculate_daylight;
+
+    let days = daylength_by_date(2024, 52.22, 5.97);
+    assert_eq!(days.len(), 366);
+    assert_eq!(daylength_by_day(2023, 52.22, 5.97).len(), 365);
+    assert_eq!((days[59].0.tm_mon, days[59].0.tm_mday), (1, 29));
+    assert_eq!((days[365].0.tm_mon, days[365].0.tm_mday), (11, 31));
+    for &(date, daylength) in &days {
+        assert_eq!(daylength, calculate_daylight(date, 52.22, 5.97).daylength);
+    }
+    for &latitude in &[-78.22, 0.0, 66.0, 78.22] {
+        for (daylength, (date, _)) in daylength_by_day(2024, latitude, 0.0).into_iter().zip(&days) {
+            assert_eq!(daylength, calculate_daylight(*date, latitude, 0.0).daylength);
+        }
+    }
+
+    // at the equator, mirrored around the solstices
+    let equator = daylength_by_day(2024, 0.0, 0.0);
+    for &solstice in &[172, 355] {
+        for offset in 1..=90 {
+            let before = equator[solstice - offset];
+            let after = equator[(solstice + offset) % 366];
+            assert!((before - after).num_seconds().abs() < 60, "{} {}", solstice, offset);
+        }
+    }
+}
+
+#[test]
+fn sunrise_series_changes() {
+    use calculate_daylight;
+
+    // Oslo: the sunrise moves fastest around the equinoxes, over 3 minutes a
+    // day, and barely around the solstices
+    let series = sunrise_series(2024, 59.91, 10.75);
+    assert_eq!(series.len(), 366);
+    assert_eq!(series[0].delta_from_previous, None);
+    for pair in series.windows(2) {
+        let (previous, point) = (pair[0], pair[1]);
+        assert_eq!(point.sunrise,
+                   Some(calculate_daylight(point.date, 59.91, 10.75).sunrise));
+        let rounded = point.sunrise.unwrap() - previous.sunrise.unwrap() - Duration::days(1);
+        let delta = point.delta_from_previous.unwrap();
+        assert!((delta - rounded).num_milliseconds().abs() <= 1000, "{:?}", point);
+    }
+    let (fastest, point) = series.iter()
+        .enumerate()
+        .max_by_key(|&(_, point)| {
+            point.delta_from_previous.map(|delta| delta.num_milliseconds().abs())
+        })
+        .unwrap();
+    assert!((60..=100).contains(&fastest) || (245..=290).contains(&fastest), "{}", fastest);
+    assert!(point.delta_from_previous.unwrap().num_seconds().abs() > 180, "{:?}", point);
+    // earlier in spring, later in autumn
+    assert!(series[80].delta_from_previous.unwrap() < Duration::zero());
+    assert!(series[266].delta_from_previous.unwrap() > Duration::zero());
+    assert!(series[172].delta_from_previous.unwrap().num_seconds().abs() < 30);
+
+    // Longyearbyen: no sunrise during the polar day and night, nor a change
+    // at either end of them
+    let arctic = sunrise_series(2024, 78.22, 15.65);
+    assert!(arctic[172].sunrise.is_none() && arctic[0].sunrise.is_none());
+    for pair in arctic.windows(2) {
+        let both = pair[0].sunrise.is_some() && pair[1].sunrise.is_some();
+        assert_eq!(pair[1].delta_from_previous.is_some(), both);
+    }
+}
+
+#[test]
+fn photoperiod_crossings_of_thresholds() {
+    use {calculate_daylight, tm_to_timespec};
+
+    // 14 hours at 52 degrees north: from the second half of April to the
+    // second half of August
+    let crossings = photoperiod_crossings(2024, 52.0, 5.97, Duration::hours(14));
+    let dates: Vec<_> = crossings.iter()
+        .map(|&(date, crossing)| (date.tm_mon + 1, date.tm_mday, crossing))
+        .collect();
+    assert_eq!(dates,
+               [(4, 17, PhotoperiodCrossing::Rising), (8, 25, PhotoperiodCrossing::Falling)]);
+    for &(date, crossing) in &crossings {
+        let before = time::at_utc(tm_to_timespec(date) - Duration::days(1));
+        let longer = |date| calculate_daylight(date, 52.0, 5.97).daylength > Duration::hours(14);
+        assert_eq!(longer(date), crossing == PhotoperiodCrossing::Rising, "{:?}", date);
+        assert_eq!(longer(before), crossing == PhotoperiodCrossing::Falling, "{:?}", date);
+    }
+
+    // with the civil twilight, the days are longer for more of the year
+    let civil = photoperiod_crossings_with(2024,
+                                           52.0,
+                                           5.97,
+                                           Duration::hours(14),
+                                           PhotoperiodBasis::CivilTwilight);
+    assert_eq!(civil.len(), 2);
+    assert!(civil[0].0 < crossings[0].0 && civil[1].0 > crossings[1].0);
+
+    // never 14 hours near the equator, nor 17 hours at 52 degrees; in the
+    // south, the long days are at either end of the year
+    assert!(photoperiod_crossings(2024, 10.0, 0.0, Duration::hours(14)).is_empty());
+    assert!(photoperiod_crossings(2024, 52.0, 0.0, Duration::hours(17)).is_empty());
+    let south = photoperiod_crossings(2024, -52.0, 0.0, Duration::hours(14));
+    assert_eq!(south.iter().map(|&(_, crossing)| crossing).collect::<Vec<_>>(),
+               [PhotoperiodCrossing::Falling, PhotoperiodCrossing::Rising]);
+
+    // Longyearbyen: the polar day starts abruptly, a day length of 24 hours
+    // is reached on its first day
+    let polar = photoperiod_crossings(2024, 78.22, 15.65, Duration::hours(24));
+    assert_eq!(polar.len(), 2);
+    let (start, _) = polar[0];
+    assert!(calculate_daylight(start, 78.22, 15.65).is_polar_day());
+    let before = time::at_utc(tm_to_timespec(start) - Duration::days(1));
+    assert!(!calculate_daylight(before, 78.22, 15.65).is_polar_day());
+}
+
+#[test]
+fn accumulated_daylight_of_periods() {
+    use calculate_daylight;
+
+    let at = |secs: i64| time::at_utc(Timespec::new(secs, 0));
+    let near = |duration: Duration, expected: Duration, limit: i64| {
+        assert!((duration - expected).num_milliseconds().abs() <= limit,
+                "{} {}",
+                duration,
+                expected);
+    };
+    let (latitude, longitude) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+
+    // a whole day, and from 15:00 only the remaining daylight
+    let midnight = 1427414400;
+    let daylight = calculate_daylight(at(1427457600), latitude, longitude);
+    let whole = accumulated_daylight(at(midnight), at(midnight + 86400), latitude, longitude);
+    near(whole, daylight.daylength, 1000);
+    let afternoon = accumulated_daylight(at(midnight + 15 * 3600),
+                                         at(midnight + 86400),
+                                         latitude,
+                                         longitude);
+    near(afternoon, daylight.sunset - tm_to_timespec(at(midnight + 15 * 3600)), 1000);
+    // half-open, and additive
+    let sunrise = time::at_utc(daylight.sunrise);
+    assert_eq!(accumulated_daylight(sunrise, sunrise, latitude, longitude), Duration::zero());
+    assert_eq!(accumulated_daylight(at(midnight + 86400), at(midnight), latitude, longitude),
+               Duration::zero());
+    let morning = accumulated_daylight(at(midnight), at(midnight + 15 * 3600), latitude, longitude);
+    near(morning + afternoon, whole, 0);
+
+    // March 2015: the day lengths of the month
+    let march = days_from_civil(2015, 3, 1) * SECS_IN_DAY;
+    let month = accumulated_daylight(at(march), at(march + 31 * 86400), latitude, longitude);
+    let lengths = (0..31)
+        .map(|day| calculate_daylight(at(march + day * 86400 + 43200), latitude, longitude))
+        .fold(Duration::zero(), |sum, daylight| sum + daylight.daylength);
+    near(month, lengths, 31 * 1000);
+
+    // Longyearbyen: nothing in the polar night of December, all of the
+    // polar day of June
+    let december = days_from_civil(2015, 12, 1) * SECS_IN_DAY;
+    assert_eq!(accumulated_daylight(at(december), at(december + 31 * 86400), 78.22, 15.65),
+               Duration::zero());
+    let june = days_from_civil(2015, 6, 1) * SECS_IN_DAY;
+    near(accumulated_daylight(at(june), at(june + 30 * 86400), 78.22, 15.65),
+         Duration::days(30),
+         1);
+    // from the polar night to the first sunrises
+    let february = days_from_civil(2016, 2, 1) * SECS_IN_DAY;
+    let first = accumulated_daylight(at(february), at(february + 29 * 86400), 78.22, 15.65);
+    assert!(first > Duration::zero() && first < Duration::days(3), "{}", first);
+}
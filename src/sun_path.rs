@@ -0,0 +1,120 @@
+//! Data of a sun-path diagram: the path of the sun through the sky on the
+//! solstices and the equinox, and the analemma of each hour of the day.
+
+use time::{self, Timespec};
+
+use {SECS_IN_DAY, SECS_IN_HOUR, SolarCalculator, SolarPosition, days_from_civil};
+
+/// Interval in seconds between the positions of the paths of a day
+const PATH_INTERVAL: i64 = 600;
+
+/// Positions of the sun for a sun-path diagram (see `sun_path_diagram`)
+#[derive(Clone, Debug, PartialEq)]
+pub struct SunPathData {
+    /// Path on 21 June, the summer solstice of the northern hemisphere
+    pub june_solstice: Vec<SolarPosition>,
+    /// Path on 21 December
+    pub december_solstice: Vec<SolarPosition>,
+    /// Path on 20 March, the equinox
+    pub march_equinox: Vec<SolarPosition>,
+    /// For each hour 0 to 23, the positions at that hour on the 21st of
+    /// each month, January to December
+    pub analemmas: Vec<Vec<SolarPosition>>,
+}
+
+/// Returns the positions of the sun for a sun-path diagram of the location
+/// in the year (see `Location::solar_position`).
+///
+/// Times are in local mean solar time (`longitude / 15` hours from UTC), so
+/// the analemma of hour 12 is around the meridian. Each path runs from 00:00
+/// to 24:00 of its date in steps of 10 minutes, including the positions
+/// below the horizon (a negative altitude), which a diagram clips; the
+/// solstices are taken on the 21st and the equinox on 20 March, which is
+/// within a day of the actual moment.
+pub fn sun_path_diagram(latitude: f64, longitude: f64, year: i32) -> SunPathData {
+    let calculator = SolarCalculator::new(latitude, longitude);
+    let utc_offset = (longitude * SECS_IN_HOUR / 15.0).round() as i64;
+    let local_midnight =
+        |month, day| days_from_civil(year as i64, month, day) * SECS_IN_DAY - utc_offset;
+    let position = |secs| calculator.position(time::at_utc(Timespec::new(secs, 0)));
+    let path = |month, day| {
+        let midnight = local_midnight(month, day);
+        (0..=SECS_IN_DAY / PATH_INTERVAL)
+            .map(|step| position(midnight + step * PATH_INTERVAL))
+            .collect()
+    };
+    SunPathData {
+        june_solstice: path(6, 21),
+        december_solstice: path(12, 21),
+        march_equinox: path(3, 20),
+        analemmas: (0..24)
+            .map(|hour| {
+                (1..=12)
+                    .map(|month| position(local_midnight(month, 21) + hour * 3600))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+/// Returns the altitude at which the path passes the azimuth, interpolated
+/// between the positions (also across the north, where the azimuth wraps)
+#[cfg(test)]
+fn altitude_at(path: &[SolarPosition], azimuth: f64) -> Option<f64> {
+    path.windows(2).find_map(|pair| {
+        let (from, to) = (pair[0].azimuth, pair[1].azimuth);
+        let to = from + (to - from + 180.0).rem_euclid(360.0) - 180.0;
+        [azimuth - 360.0, azimuth, azimuth + 360.0]
+            .iter()
+            .find(|&&azimuth| from.min(to) <= azimuth && azimuth <= from.max(to))
+            .map(|&azimuth| {
+                let fraction = (azimuth - from) / (to - from);
+                pair[0].altitude + fraction * (pair[1].altitude - pair[0].altitude)
+            })
+    })
+}
+
+#[test]
+fn sun_path_brackets_equinox() {
+    use {Coordinates, Location};
+
+    for &(latitude, longitude) in &[(52.22, 5.97), (-33.87, 151.21)] {
+        let data = sun_path_diagram(latitude, longitude, 2024);
+        assert_eq!(data.june_solstice.len(), 145);
+        assert_eq!(data.analemmas.len(), 24);
+        assert!(data.analemmas.iter().all(|analemma| analemma.len() == 12));
+
+        // the positions are those of `solar_position`
+        let location = Location::new("", Coordinates::try_new(latitude, longitude).unwrap());
+        let noon = data.analemmas[12][5];
+        let utc_offset = (longitude * SECS_IN_HOUR / 15.0).round() as i64;
+        let tm = time::at_utc(Timespec::new(1718971200 - utc_offset, 0));
+        assert_eq!(noon, location.solar_position(tm));
+        assert_eq!(data.june_solstice[72], noon);
+
+        // above the horizon, the summer path is above the equinox at every
+        // azimuth, and the winter path below it
+        let (summer, winter) = if latitude > 0.0 {
+            (&data.june_solstice, &data.december_solstice)
+        } else {
+            (&data.december_solstice, &data.june_solstice)
+        };
+        let mut compared = 0;
+        for equinox in data.march_equinox.iter().filter(|position| position.altitude > 0.0) {
+            let high = altitude_at(summer, equinox.azimuth).unwrap();
+            let low = altitude_at(winter, equinox.azimuth).unwrap();
+            assert!(low < equinox.altitude && equinox.altitude < high,
+                    "{} {:?} {} {}",
+                    latitude,
+                    equinox,
+                    low,
+                    high);
+            compared += 1;
+        }
+        assert!(compared > 60, "{}", compared);
+        // the noon of the summer solstice highest in the sky, towards the
+        // equator
+        let highest = summer.iter().map(|position| position.altitude).fold(-90.0, f64::max);
+        assert!((highest - (90.0 - latitude.abs() + 23.44)).abs() < 0.5, "{}", highest);
+    }
+}
@@ -0,0 +1,198 @@
+//! Almanac of the events of each day of a month, on the clock of the
+//! location.
+
+use time::{self, Duration, Timespec, Tm};
+
+use error::days_in_month;
+use {DaylightOptions, EventKind, SECS_IN_DAY, days_from_civil, local_daylight, tm_to_timespec};
+
+/// Events of a day on the local clock (see `monthly_rows`); events that do
+/// not occur during the polar day and night are `None`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DayRow {
+    /// Local date, at 00:00
+    pub date: Tm,
+    /// Start of the civil twilight
+    pub dawn: Option<Tm>,
+    pub sunrise: Option<Tm>,
+    pub noon: Tm,
+    pub sunset: Option<Tm>,
+    /// End of the civil twilight
+    pub dusk: Option<Tm>,
+    pub daylength: Duration,
+}
+
+/// Returns the events of `days` consecutive civil dates from the start date
+/// (of which only the date is used) at the location, of which the clock is
+/// `utc_offset` seconds ahead of UTC (see
+/// `calculate_daylight_local_with_offset`)
+pub fn daily_rows(start: Tm,
+                  days: u32,
+                  latitude: f64,
+                  longitude: f64,
+                  utc_offset: i32)
+                  -> Vec<DayRow> {
+    let first = days_from_civil(start.tm_year as i64 + 1900,
+                                start.tm_mon as i64 + 1,
+                                start.tm_mday as i64);
+    let options = DaylightOptions::default();
+    (0..days as i64)
+        .map(|day| {
+            let date = time::at_utc(Timespec::new((first + day) * SECS_IN_DAY, 0));
+            let daylight = local_daylight(date, latitude, longitude, utc_offset, &options);
+            let local = daylight.to_local(utc_offset);
+            let mut row = DayRow {
+                date,
+                dawn: None,
+                sunrise: None,
+                noon: local.noon,
+                sunset: None,
+                dusk: None,
+                daylength: daylight.daylength,
+            };
+            for event in daylight.events() {
+                match event.kind {
+                    EventKind::TwilightMorning => row.dawn = Some(local.twilight_morning),
+                    EventKind::Sunrise => row.sunrise = Some(local.sunrise),
+                    EventKind::Noon => {}
+                    EventKind::Sunset => row.sunset = Some(local.sunset),
+                    EventKind::TwilightEvening => row.dusk = Some(local.twilight_evening),
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// Returns the events of every day of the month (1 to 12) at the location
+/// (see `daily_rows`); panics for an invalid month
+pub fn monthly_rows(year: i32,
+                    month: u32,
+                    latitude: f64,
+                    longitude: f64,
+                    utc_offset: i32)
+                    -> Vec<DayRow> {
+    assert!((1..=12).contains(&month), "invalid month {}", month);
+    let first = time::at_utc(Timespec::new(days_from_civil(year as i64, month as i64, 1) *
+                                           SECS_IN_DAY,
+                                           0));
+    let days = days_in_month(year as i64, month as i32 - 1) as u32;
+    daily_rows(first, days, latitude, longitude, utc_offset)
+}
+
+/// Formats a local time as hours and minutes, rounded to the nearest
+/// minute; an absent event as a dash
+fn clock(tm: Option<Tm>) -> String {
+    match tm {
+        Some(tm) => {
+            let rounded = tm_to_timespec(tm) + Duration::seconds(tm.tm_utcoff as i64 + 30);
+            time::at_utc(rounded).strftime("%H:%M").unwrap().to_string()
+        }
+        None => "—".to_string(),
+    }
+}
+
+/// Returns a plain text table of the rows, with the times on the local
+/// clock in hours and minutes and a dash for the events that do not occur,
+/// like:
+///
+/// ```text
+/// date        dawn    sunrise noon    sunset  dusk    length
+/// 2024-03-01  06:54   07:21   12:48   18:16   18:43   10:55
+/// ```
+pub fn format_rows(rows: &[DayRow]) -> String {
+    let mut table = format!("{:<12}{:<8}{:<8}{:<8}{:<8}{:<8}{}\n",
+                            "date",
+                            "dawn",
+                            "sunrise",
+                            "noon",
+                            "sunset",
+                            "dusk",
+                            "length");
+    for row in rows {
+        let minutes = (row.daylength.num_seconds() + 30) / 60;
+        table.push_str(&format!("{:<12}{:<8}{:<8}{:<8}{:<8}{:<8}{:02}:{:02}\n",
+                                row.date.strftime("%Y-%m-%d").unwrap().to_string(),
+                                clock(row.dawn),
+                                clock(row.sunrise),
+                                clock(Some(row.noon)),
+                                clock(row.sunset),
+                                clock(row.dusk),
+                                minutes / 60,
+                                minutes % 60));
+    }
+    table
+}
+
+/// Returns a plain text table of the events of every day of the month (see
+/// `monthly_rows` and `format_rows`)
+pub fn monthly_table(year: i32,
+                     month: u32,
+                     latitude: f64,
+                     longitude: f64,
+                     utc_offset: i32)
+                     -> String {
+    format_rows(&monthly_rows(year, month, latitude, longitude, utc_offset))
+}
+
+#[test]
+fn monthly_table_apeldoorn() {
+    // March 2024, before the switch to summer time
+    let table = monthly_table(2024, 3, 52.22, 5.97, 3600);
+    assert_eq!(table,
+               "date        dawn    sunrise noon    sunset  dusk    length\n\
+                2024-03-01  06:54   07:21   12:48   18:16   18:43   10:55\n\
+                2024-03-02  06:52   07:19   12:48   18:17   18:44   10:59\n\
+                2024-03-03  06:50   07:16   12:48   18:19   18:46   11:03\n\
+                2024-03-04  06:47   07:14   12:48   18:21   18:48   11:07\n\
+                2024-03-05  06:45   07:12   12:47   18:23   18:50   11:11\n\
+                2024-03-06  06:43   07:10   12:47   18:25   18:51   11:15\n\
+                2024-03-07  06:41   07:07   12:47   18:26   18:53   11:19\n\
+                2024-03-08  06:38   07:05   12:47   18:28   18:55   11:23\n\
+                2024-03-09  06:36   07:03   12:46   18:30   18:57   11:27\n\
+                2024-03-10  06:34   07:01   12:46   18:32   18:58   11:31\n\
+                2024-03-11  06:32   06:58   12:46   18:34   19:00   11:35\n\
+                2024-03-12  06:29   06:56   12:46   18:35   19:02   11:39\n\
+                2024-03-13  06:27   06:54   12:45   18:37   19:04   11:43\n\
+                2024-03-14  06:25   06:51   12:45   18:39   19:06   11:48\n\
+                2024-03-15  06:22   06:49   12:45   18:41   19:07   11:52\n\
+                2024-03-16  06:20   06:47   12:44   18:42   19:09   11:56\n\
+                2024-03-17  06:18   06:44   12:44   18:44   19:11   12:00\n\
+                2024-03-18  06:15   06:42   12:44   18:46   19:13   12:04\n\
+                2024-03-19  06:13   06:40   12:44   18:48   19:14   12:08\n\
+                2024-03-20  06:10   06:37   12:43   18:49   19:16   12:12\n\
+                2024-03-21  06:08   06:35   12:43   18:51   19:18   12:16\n\
+                2024-03-22  06:06   06:33   12:43   18:53   19:20   12:20\n\
+                2024-03-23  06:03   06:30   12:42   18:55   19:22   12:24\n\
+                2024-03-24  06:01   06:28   12:42   18:56   19:23   12:28\n\
+                2024-03-25  05:58   06:26   12:42   18:58   19:25   12:32\n\
+                2024-03-26  05:56   06:23   12:42   19:00   19:27   12:36\n\
+                2024-03-27  05:54   06:21   12:41   19:01   19:29   12:41\n\
+                2024-03-28  05:51   06:19   12:41   19:03   19:31   12:45\n\
+                2024-03-29  05:49   06:16   12:41   19:05   19:32   12:49\n\
+                2024-03-30  05:46   06:14   12:40   19:07   19:34   12:53\n\
+                2024-03-31  05:44   06:12   12:40   19:08   19:36   12:57\n");
+
+    let rows = monthly_rows(2024, 3, 52.22, 5.97, 3600);
+    assert_eq!(rows.len(), 31);
+    assert_eq!((rows[30].date.tm_mon, rows[30].date.tm_mday), (2, 31));
+    let leap_day = time::at_utc(Timespec::new(1709208000, 0));
+    assert_eq!(daily_rows(leap_day, 3, 52.22, 5.97, 3600)[2], rows[1]);
+    assert!(rows.iter().all(|row| row.sunrise.unwrap().tm_utcoff == 3600));
+}
+
+#[test]
+fn monthly_table_polar() {
+    // Longyearbyen, the polar night in December and the polar day in June
+    let december = monthly_table(2015, 12, 78.22, 15.65, 3600);
+    assert!(december.lines().skip(1).all(|line| line.matches('—').count() == 4),
+            "{}",
+            december);
+    let june = monthly_rows(2015, 6, 78.22, 15.65, 7200);
+    assert_eq!(june.len(), 30);
+    assert!(june.iter().all(|row| row.sunrise.is_none() && row.daylength == Duration::days(1)));
+    let line = monthly_table(2015, 6, 78.22, 15.65, 7200).lines().nth(21).unwrap().to_string();
+    assert!(line.starts_with("2015-06-21  —       —       ") && line.ends_with("24:00"),
+            "{}",
+            line);
+}
@@ -0,0 +1,105 @@
+//! Terminator between day and night on the earth at an instant, for drawing
+//! the night on a world map.
+
+use std::f64::consts;
+
+use time::Timespec;
+
+use {DaylightOptions, NSECS_IN_SEC, SECS_IN_HOUR, fnrange_signed, midnight, solar_parameters_at,
+     to_degrees, to_radians};
+
+/// Returns the latitude and longitude in radians of the point where the sun
+/// is in the zenith at the instant, like `horizon::sun_position`
+fn subsolar_point(time: Timespec) -> (f64, f64) {
+    let parameters = solar_parameters_at(time, &DaylightOptions::default());
+    let hours = ((time.sec - midnight(time).sec) as f64 + time.nsec as f64 / NSECS_IN_SEC) /
+                SECS_IN_HOUR;
+    // where the solar noon (12 hours plus the equation of time before the
+    // local mean solar time) is now
+    let longitude = to_radians(15.0 * (12.0 - parameters.equation_of_time / 60.0 - hours));
+    (to_radians(parameters.declination), fnrange_signed(longitude))
+}
+
+/// Returns `samples` points (latitude, longitude in angle degrees) on the
+/// line where the centre of the sun is on the true horizon at the instant
+/// (without refraction, like `SolarPosition::altitude`); see
+/// `terminator_with_depression`
+pub fn terminator(time: Timespec, samples: usize) -> Vec<(f64, f64)> {
+    terminator_with_depression(time, samples, 0.0)
+}
+
+/// Returns `samples` points (latitude, longitude in angle degrees) on the
+/// line where the centre of the sun is `depression_deg` angle degrees below
+/// the horizon at the instant, like 6, 12 and 18 for the bands of the civil,
+/// nautical and astronomical twilight.
+///
+/// The line is the circle at `90 + depression_deg` degrees from the point
+/// where the sun is in the zenith; the points are equally spaced around it,
+/// in order, and the last one connects to the first. Its longitudes are
+/// continuous: the first is in -180 to 180 and each next one is within 180
+/// degrees of the one before, so instead of jumping at the antimeridian they
+/// run beyond it, up to one revolution when the circle goes around a pole.
+/// Around the equinox the circle passes (close to) both poles, where the
+/// longitude turns by 180 degrees between two points.
+pub fn terminator_with_depression(time: Timespec,
+                                  samples: usize,
+                                  depression_deg: f64)
+                                  -> Vec<(f64, f64)> {
+    let (sub_lat, sub_lon) = subsolar_point(time);
+    let (sin_distance, cos_distance) = to_radians(90.0 + depression_deg).sin_cos();
+    let (sin_sub_lat, cos_sub_lat) = sub_lat.sin_cos();
+    let mut previous: Option<f64> = None;
+    (0..samples)
+        .map(|sample| {
+            // the bearing from the subsolar point, from the north
+            let bearing = 2.0 * consts::PI * sample as f64 / samples as f64;
+            let sin_lat = sin_sub_lat * cos_distance + cos_sub_lat * sin_distance * bearing.cos();
+            let latitude = sin_lat.clamp(-1.0, 1.0).asin();
+            let turn = (bearing.sin() * sin_distance * cos_sub_lat)
+                .atan2(cos_distance - sin_sub_lat * sin_lat);
+            let mut longitude = to_degrees(fnrange_signed(sub_lon + turn));
+            if let Some(previous) = previous {
+                longitude -= 360.0 * ((longitude - previous) / 360.0).round();
+            }
+            previous = Some(longitude);
+            (to_degrees(latitude), longitude)
+        })
+        .collect()
+}
+
+#[test]
+fn terminator_altitude_zero() {
+    use {LatitudeTrig, horizon};
+
+    // 2015-03-27 12:00 UTC, a week after the equinox, and the solstices
+    for &secs in &[1427457600, 1434888000, 1450699200, 1426852800 + 22 * 3600 + 45 * 60] {
+        let time = Timespec::new(secs, 0);
+        for &depression in &[0.0, 6.0, 18.0] {
+            let points = terminator_with_depression(time, 360, depression);
+            assert_eq!(points.len(), 360);
+            assert!(points[0].1 > -180.0 && points[0].1 <= 180.0);
+            for pair in points.windows(2) {
+                assert!((pair[1].1 - pair[0].1).abs() <= 180.0, "{:?}", pair);
+            }
+            for &(latitude, longitude) in &points {
+                let (altitude, _) = horizon::sun_position(time,
+                                                          &LatitudeTrig::new(latitude),
+                                                          longitude,
+                                                          &DaylightOptions::default());
+                assert!((to_degrees(altitude) + depression).abs() < 0.1,
+                        "{} {} {} {}",
+                        secs,
+                        latitude,
+                        longitude,
+                        to_degrees(altitude));
+            }
+        }
+    }
+
+    // in June the night is around the south pole: the line runs once around
+    // the earth, south of the arctic circle
+    let june = terminator(Timespec::new(1434888000, 0), 72);
+    assert!((june[71].1 - june[0].1).abs() > 300.0);
+    assert!(june.iter().all(|&(latitude, _)| latitude.abs() < 67.0));
+    assert_eq!(terminator(Timespec::new(1434888000, 0), 0), vec![]);
+}
@@ -0,0 +1,195 @@
+//! Calculation with the types of `time` 0.3 (enabled with the `time03`
+//! feature).
+//!
+//! The calculation itself keeps using `time` 0.1, so the existing functions
+//! and `Daylight` keep their signatures; the functions below accept and
+//! return the `time` 0.3 types instead and produce exactly the same
+//! instants.
+
+use time::{self, Duration, Timespec};
+use time03::{self, Date, OffsetDateTime};
+
+use {Daylight, DaylightOptions, calculate_daylight_with};
+
+/// Result of the daylight calculation, with `time` 0.3 timestamps in UTC
+/// (see `Daylight`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DaylightOffset {
+    pub twilight_morning: OffsetDateTime,
+    pub sunrise: OffsetDateTime,
+    pub sunset: OffsetDateTime,
+    pub twilight_evening: OffsetDateTime,
+    pub noon: OffsetDateTime,
+    /// Declination of the sun in angle degrees
+    pub declination: f64,
+    /// Duration of the day
+    pub daylength: time03::Duration,
+    /// Sun altitude in angle degrees
+    pub sun_altitude: f64,
+}
+
+/// Converts a timestamp to `time` 0.3; panics outside of the years -9999 to
+/// 9999
+fn to_offset(ts: Timespec) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(ts.sec).expect("timestamp out of range for time 0.3") +
+    time03::Duration::nanoseconds(ts.nsec as i64)
+}
+
+fn from_offset(date: OffsetDateTime) -> Timespec {
+    Timespec::new(date.unix_timestamp(), date.nanosecond() as i32)
+}
+
+impl From<Daylight> for DaylightOffset {
+    fn from(daylight: Daylight) -> DaylightOffset {
+        let seconds = daylight.daylength.num_seconds();
+        let nanoseconds = (daylight.daylength - Duration::seconds(seconds))
+            .num_nanoseconds()
+            .unwrap_or(0);
+        DaylightOffset {
+            twilight_morning: to_offset(daylight.twilight_morning),
+            sunrise: to_offset(daylight.sunrise),
+            sunset: to_offset(daylight.sunset),
+            twilight_evening: to_offset(daylight.twilight_evening),
+            noon: to_offset(daylight.noon),
+            declination: daylight.declination,
+            daylength: time03::Duration::new(seconds, nanoseconds as i32),
+            sun_altitude: daylight.sun_altitude,
+        }
+    }
+}
+
+impl From<DaylightOffset> for Daylight {
+    fn from(daylight: DaylightOffset) -> Daylight {
+        Daylight {
+            twilight_morning: from_offset(daylight.twilight_morning),
+            sunrise: from_offset(daylight.sunrise),
+            sunset: from_offset(daylight.sunset),
+            twilight_evening: from_offset(daylight.twilight_evening),
+            noon: from_offset(daylight.noon),
+            declination: daylight.declination,
+            daylength: Duration::seconds(daylight.daylength.whole_seconds()) +
+                       Duration::nanoseconds(daylight.daylength.subsec_nanoseconds() as i64),
+            sun_altitude: daylight.sun_altitude,
+        }
+    }
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date
+/// (see `calculate_daylight`); the events are returned in UTC, whatever the
+/// offset of `date`
+pub fn calculate_daylight_offset(date: OffsetDateTime,
+                                 latitude: f64,
+                                 longitude: f64)
+                                 -> DaylightOffset {
+    calculate_daylight_offset_with(date, latitude, longitude, &DaylightOptions::default())
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// using the given calculation options (see `calculate_daylight_with`)
+pub fn calculate_daylight_offset_with(date: OffsetDateTime,
+                                      latitude: f64,
+                                      longitude: f64,
+                                      options: &DaylightOptions)
+                                      -> DaylightOffset {
+    calculate_daylight_with(time::at_utc(from_offset(date)), latitude, longitude, options).into()
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at 12:00 UTC on
+/// the given date
+pub fn calculate_daylight_date(date: Date, latitude: f64, longitude: f64) -> DaylightOffset {
+    let midday = date.with_hms(12, 0, 0).expect("12:00 is a valid time");
+    calculate_daylight_offset(midday.assume_utc(), latitude, longitude)
+}
+
+#[test]
+fn time03_golden_values() {
+    use time03::{Month, UtcOffset};
+    use {Algorithm, Series};
+
+    // the golden tests of `time` 0.1 (at 12:00 UTC): twilight, sunrise,
+    // noon, sunset, twilight, day length, declination and sun altitude
+    let golden = [((2015, Month::March, 27),
+                   52.0 + 13.0 / 60.0,
+                   5.0 + 58.0 / 60.0,
+                   [1427432130, 1427433767, 1427456487, 1427479207, 1427480844],
+                   45440,
+                   (2.777311, 2.777313),
+                   (40.55, 40.57)),
+                  ((2015, Month::March, 27),
+                   35.41,
+                   139.41,
+                   [1427401348, 1427402244, 1427424461, 1427446677, 1427447573],
+                   44434,
+                   (2.777311, 2.777313),
+                   (57.35, 57.37)),
+                  ((2015, Month::March, 27),
+                   -21.12,
+                   -159.46,
+                   [1427474290, 1427474770, 1427496189, 1427517609, 1427518089],
+                   42839,
+                   (2.777311, 2.777313),
+                   (66.09, 66.11)),
+                  ((2015, Month::June, 21),
+                   78.22,
+                   15.65,
+                   [1434841154, 1434841154, 1434884354, 1434927554, 1434927554],
+                   86400,
+                   (23.436411, 23.436413),
+                   (35.20, 35.22)),
+                  ((2015, Month::December, 21),
+                   78.22,
+                   15.65,
+                   [1450695334, 1450695334, 1450695334, 1450695334, 1450695334],
+                   0,
+                   (-23.43652, -23.43650),
+                   (-11.66, -11.64))];
+
+    let custom = DaylightOptions::default().algorithm(Algorithm::Custom(&Series::Standard));
+    let offsets = [UtcOffset::UTC,
+                   UtcOffset::from_hms(1, 0, 0).unwrap(),
+                   UtcOffset::from_hms(-10, -30, 0).unwrap()];
+
+    for &((year, month, day), latitude, longitude, events, daylength, declination, altitude) in
+        &golden {
+        let date = Date::from_calendar_date(year, month, day).unwrap();
+        let midday = date.with_hms(12, 0, 0).unwrap().assume_utc();
+        let tm = time::at_utc(Timespec::new(midday.unix_timestamp(), 0));
+
+        // every `time` 0.3 entry point, with the default algorithm and its
+        // series as custom algorithm
+        let mut results = vec![calculate_daylight_date(date, latitude, longitude)];
+        for &offset in &offsets {
+            let instant = midday.to_offset(offset);
+            results.push(calculate_daylight_offset(instant, latitude, longitude));
+            results.push(calculate_daylight_offset_with(instant,
+                                                        latitude,
+                                                        longitude,
+                                                        &DaylightOptions::default()));
+            results.push(calculate_daylight_offset_with(instant, latitude, longitude, &custom));
+        }
+
+        let expected = ::golden_daylight(tm, latitude, longitude);
+        for daylight in &results {
+            assert_eq!([daylight.twilight_morning.unix_timestamp(),
+                        daylight.sunrise.unix_timestamp(),
+                        daylight.noon.unix_timestamp(),
+                        daylight.sunset.unix_timestamp(),
+                        daylight.twilight_evening.unix_timestamp()],
+                       events);
+            assert_eq!(daylight.daylength.whole_seconds(), daylength);
+            assert!(daylight.declination > declination.0 && daylight.declination < declination.1,
+                    "declination != {}",
+                    daylight.declination);
+            assert!(daylight.sun_altitude > altitude.0 && daylight.sun_altitude < altitude.1,
+                    "sun_altitude != {}",
+                    daylight.sun_altitude);
+            assert_eq!(daylight.sunrise.offset(), UtcOffset::UTC);
+
+            // identical to `time` 0.1, in both directions
+            for golden in &expected {
+                assert_eq!(&Daylight::from(*daylight), golden);
+                assert_eq!(&DaylightOffset::from(*golden), daylight);
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Sunrise and sunset with the civil, nautical and astronomical twilight of
+//! a day in a single calculation.
+
+use std::f64::consts;
+
+use time::{Timespec, Tm};
+
+use {Algorithm, Daylight, DaylightOptions, LatitudeTrig, calculate_angle, daylight_at,
+     daylight_hours_to_timespec, midnight, solar_day, tm_to_timespec, to_radians};
+
+/// Depression of the sun in angle degrees at the start and end of the
+/// civil, nautical and astronomical twilight
+const DEPRESSIONS: [f64; 3] = [6.0, 12.0, 18.0];
+
+/// Sunrise and sunset with the dawn and dusk of each twilight (see
+/// `dawn_dusk`); each pair is `(morning, evening)`, `None` when the sun does
+/// not pass that altitude during the day
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DawnDusk {
+    pub sunrise_sunset: Option<(Timespec, Timespec)>,
+    /// The sun 6 degrees below the horizon
+    pub civil: Option<(Timespec, Timespec)>,
+    /// The sun 12 degrees below the horizon
+    pub nautical: Option<(Timespec, Timespec)>,
+    /// The sun 18 degrees below the horizon
+    pub astronomical: Option<(Timespec, Timespec)>,
+}
+
+/// Calculate sunrise and sunset with the civil, nautical and astronomical
+/// dawn and dusk at given date, equal to `calculate_daylight_with` with each
+/// of the twilight angles; the position of the sun and the sunrise are
+/// calculated once and shared by the twilights.
+pub fn dawn_dusk(date: Tm, latitude: f64, longitude: f64) -> DawnDusk {
+    dawn_dusk_with(date, latitude, longitude, &DaylightOptions::default())
+}
+
+/// Calculate sunrise and sunset with the dawn and dusk of each twilight at
+/// given date (see `dawn_dusk`), using the given calculation options apart
+/// from the twilight angle; when the calculation is refined to the moment
+/// of each event, or with the NOAA algorithm, each twilight is calculated
+/// on its own
+pub fn dawn_dusk_with(date: Tm,
+                      latitude: f64,
+                      longitude: f64,
+                      options: &DaylightOptions)
+                      -> DawnDusk {
+    let lat = LatitudeTrig::new(latitude);
+    let utc = tm_to_timespec(date);
+    if options.get_refined() || options.get_algorithm() == Algorithm::Noaa {
+        let twilight = |depression| {
+            twilight_of(&daylight_at(utc, &lat, longitude, &options.twilight_angle(depression)))
+        };
+        return DawnDusk {
+            sunrise_sunset: sun_of(&daylight_at(utc, &lat, longitude, options)),
+            civil: twilight(DEPRESSIONS[0]),
+            nautical: twilight(DEPRESSIONS[1]),
+            astronomical: twilight(DEPRESSIONS[2]),
+        };
+    }
+
+    let day = solar_day(utc, &lat, longitude, options);
+    let tsnoon = daylight_hours_to_timespec(midnight(utc), day.noon);
+    let rounding = options.get_rounding();
+
+    // the twilight of each depression after the same sunrise (see
+    // `SunEphemeris::solar_day`)
+    let (hour_angle, declin) = (consts::PI * day.morning / 12.0, to_radians(day.declination));
+    let twilight = |depression: f64| {
+        let depression = to_radians(depression);
+        let length = 12.0 * (calculate_angle(&lat, declin, depression) - hour_angle) / consts::PI;
+        let mut twilight = day;
+        twilight.twilight_morning = length;
+        twilight.twilight_evening = length;
+        twilight_of(&twilight.to_daylight(tsnoon, rounding))
+    };
+    DawnDusk {
+        sunrise_sunset: sun_of(&day.to_daylight(tsnoon, rounding)),
+        civil: twilight(DEPRESSIONS[0]),
+        nautical: twilight(DEPRESSIONS[1]),
+        astronomical: twilight(DEPRESSIONS[2]),
+    }
+}
+
+/// Returns the sunrise and sunset, unless the sun does not rise or set
+fn sun_of(daylight: &Daylight) -> Option<(Timespec, Timespec)> {
+    if daylight.is_polar_day() || daylight.is_polar_night() {
+        None
+    } else {
+        Some((daylight.sunrise, daylight.sunset))
+    }
+}
+
+/// Returns the dawn and dusk, unless the sun does not pass the twilight
+/// angle
+fn twilight_of(daylight: &Daylight) -> Option<(Timespec, Timespec)> {
+    if daylight.has_twilight() {
+        Some((daylight.twilight_morning, daylight.twilight_evening))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn dawn_dusk_nested() {
+    use time;
+
+    use calculate_daylight_with;
+
+    let mut present = [0; 4];
+    for day in 0..73 {
+        let date = time::at_utc(Timespec::new(1420113600 + day * 5 * 86400, 0));
+        for &latitude in &[-89.0, -70.0, -52.22, 0.0, 35.0, 52.22, 55.0, 60.0, 66.0, 78.22, 90.0] {
+            let all = dawn_dusk(date, latitude, 5.97);
+            let pairs = [all.sunrise_sunset, all.civil, all.nautical, all.astronomical];
+
+            // equal to the separate calculations
+            for (pair, &depression) in pairs[1..].iter().zip(DEPRESSIONS.iter()) {
+                let options = DaylightOptions::default().twilight_angle(depression);
+                let daylight = calculate_daylight_with(date, latitude, 5.97, &options);
+                assert_eq!(*pair, twilight_of(&daylight));
+            }
+
+            // also when refined, with each twilight calculated on its own
+            let refined = DaylightOptions::default().refined(true);
+            let nautical = dawn_dusk_with(date, latitude, 5.97, &refined).nautical;
+            let options = refined.twilight_angle(DEPRESSIONS[1]);
+            assert_eq!(nautical,
+                       twilight_of(&calculate_daylight_with(date, latitude, 5.97, &options)));
+
+            // each deeper twilight starts earlier and ends later
+            let present_pairs: Vec<_> = pairs.iter().filter_map(|&pair| pair).collect();
+            for nested in present_pairs.windows(2) {
+                assert!(nested[1].0 <= nested[0].0 && nested[0].1 <= nested[1].1,
+                        "{} {:?}",
+                        latitude,
+                        all);
+            }
+            for (count, pair) in present.iter_mut().zip(pairs.iter()) {
+                *count += pair.is_some() as u32;
+            }
+        }
+    }
+    // absent at high latitudes in summer and winter
+    assert!(present.iter().all(|&count| count > 0 && count < 73 * 11), "{:?}", present);
+    assert!(present[3] < present[2] && present[2] < present[1], "{:?}", present);
+}
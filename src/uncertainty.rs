@@ -0,0 +1,184 @@
+//! Estimated accuracy of the calculated events.
+//!
+//! An error in the calculated position of the sun translates into an error
+//! in time that depends on how fast the altitude of the sun changes at the
+//! event: near the equator the sun rises steeply and an arcminute is four
+//! seconds, close to the polar circles it skims along the horizon and the
+//! same arcminute can be many minutes; on the days the sun only just reaches
+//! the horizon, whether it rises at all is uncertain. The estimate follows
+//! the altitude curve over the angular error budget of the chosen
+//! calculation:
+//!
+//! | calculation                          | angle        | noon  |
+//! |--------------------------------------|--------------|-------|
+//! | `Lammi` (default)                    | 0.2 degree   | 15 s  |
+//! | `Lammi` with `refined`               | 0.01 degree  | 5 s   |
+//! | `Lammi` with `refined` and `Extended`| 0.005 degree | 3 s   |
+//! | `Noaa`                               | 0.005 degree | 3 s   |
+//!
+//! The accuracy of a `Custom` algorithm is unknown; it is estimated like the
+//! `Lammi` algorithm with the `Standard` series.
+//!
+//! Unless the calculation is refined, the default algorithm also
+//! approximates the hour angle, which is off by up to the depression of the
+//! event itself near the equator (minutes for sunrise, up to half an hour
+//! for the civil twilight); that deviation is added as well.
+//!
+//! The variation of the refraction with the weather is not included; it
+//! typically adds a few arcminutes.
+
+use std::f64::consts;
+
+use time::{Duration, Tm};
+
+use {Algorithm, Daylight, DaylightOptions, LatitudeTrig, Series, calculate_angle,
+     calculate_daylight_with, hour_angle, sunrise_depression, to_radians, FRAC_HOURS_IN_DAY_2,
+     SECS_IN_HOUR};
+
+/// Upper bound of the estimate in hours; reached when the sun only just
+/// touches the horizon, like at the start of the polar night
+const MAX_UNCERTAINTY: f64 = 12.0;
+
+/// Estimated error band (plus or minus) of each event
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Uncertainty {
+    pub twilight_morning: Duration,
+    pub sunrise: Duration,
+    pub noon: Duration,
+    pub sunset: Duration,
+    pub twilight_evening: Duration,
+}
+
+/// Returns the angular error budget in radians and the error of noon in
+/// seconds of the calculation
+fn error_budget(options: &DaylightOptions) -> (f64, f64) {
+    match (options.get_algorithm(), options.get_refined(), options.get_series()) {
+        (Algorithm::Noaa, _, _) |
+        (Algorithm::Lammi, true, Series::Extended) => (to_radians(0.005), 3.0),
+        (Algorithm::Lammi, true, Series::Standard) | (Algorithm::Custom(_), true, _) => {
+            (to_radians(0.01), 5.0)
+        }
+        (Algorithm::Lammi, false, _) | (Algorithm::Custom(_), false, _) => (to_radians(0.2), 15.0),
+    }
+}
+
+/// Estimates the error in hours of the moment at which the centre of the
+/// sun is `depression` below the horizon
+fn event_uncertainty(lat: &LatitudeTrig,
+                     declin: f64,
+                     depression: f64,
+                     angle: f64,
+                     approximated: bool)
+                     -> f64 {
+    // the change of the hour angle for the angular error to either side;
+    // where the altitude changes slowly this is large, and when the sun only
+    // just reaches the depression it is up to the whole (half) day
+    let ha = hour_angle(lat, declin, depression);
+    let mut radians = (hour_angle(lat, declin, depression + angle) - ha)
+        .abs()
+        .max((hour_angle(lat, declin, depression - angle) - ha).abs());
+    if approximated {
+        radians += (calculate_angle(lat, declin, depression) - ha).abs();
+    }
+    (FRAC_HOURS_IN_DAY_2 * radians / consts::PI).min(MAX_UNCERTAINTY)
+}
+
+/// Calculates the daylight like `calculate_daylight_with`, together with
+/// the estimated accuracy of its events (see the table in the documentation
+/// of the `uncertainty` module). The estimate grows large, but stays finite,
+/// when the sun only just reaches the horizon or the twilight depression.
+pub fn calculate_daylight_with_uncertainty(date: Tm,
+                                           latitude: f64,
+                                           longitude: f64,
+                                           options: &DaylightOptions)
+                                           -> (Daylight, Uncertainty) {
+    let daylight = calculate_daylight_with(date, latitude, longitude, options);
+    let (angle, noon) = error_budget(options);
+    let approximated = options.get_algorithm() != Algorithm::Noaa && !options.get_refined();
+    let lat = LatitudeTrig::new(latitude);
+    let declin = to_radians(daylight.declination);
+    let duration = |hours: f64| {
+        Duration::milliseconds(((hours * SECS_IN_HOUR + noon) * 1e3) as i64)
+    };
+
+    let sunrise = duration(event_uncertainty(&lat,
+                                             declin,
+                                             sunrise_depression(options),
+                                             angle,
+                                             approximated));
+    let twilight = duration(event_uncertainty(&lat,
+                                              declin,
+                                              to_radians(options.get_twilight_angle()),
+                                              angle,
+                                              approximated));
+
+    (daylight,
+     Uncertainty {
+         twilight_morning: twilight,
+         sunrise,
+         noon: duration(0.0),
+         sunset: sunrise,
+         twilight_evening: twilight,
+     })
+}
+
+#[test]
+fn uncertainty_by_latitude() {
+    use std::cmp;
+    use time::{Timespec, at_utc};
+
+    let uncertainty = |tm, latitude, longitude, options: &DaylightOptions| {
+        calculate_daylight_with_uncertainty(tm, latitude, longitude, options).1
+    };
+
+    // 2015-02-28
+    let tm = at_utc(Timespec::new(1425124800, 0));
+    let options = DaylightOptions::default();
+
+    let apeldoorn = uncertainty(tm, 52.22, 5.97, &options);
+    let longyearbyen = uncertainty(tm, 78.22, 15.65, &options);
+    assert!(longyearbyen.sunrise > apeldoorn.sunrise,
+            "{:?} {:?}",
+            longyearbyen,
+            apeldoorn);
+    assert_eq!(apeldoorn.noon, Duration::seconds(15));
+
+    // the refined calculation is more accurate, and so dominated by the
+    // slope of the altitude
+    let refined = options.refined(true);
+    let precise = uncertainty(tm, 52.22, 5.97, &refined);
+    assert!(precise.sunrise < apeldoorn.sunrise / 4, "{:?}", precise);
+    assert!(precise.sunrise < Duration::seconds(30), "{:?}", precise);
+    let polar = uncertainty(tm, 78.22, 15.65, &refined);
+    assert!(polar.sunrise - polar.noon > (precise.sunrise - precise.noon) * 3,
+            "{:?} {:?}",
+            polar,
+            precise);
+
+    // the approximated hour angle is weakest at the equator
+    let quito = uncertainty(tm, -0.18, -78.47, &options);
+    assert!(quito.twilight_morning > Duration::minutes(20), "{:?}", quito);
+    let quito = uncertainty(tm, -0.18, -78.47, &refined);
+    assert!(quito.twilight_morning < Duration::seconds(30), "{:?}", quito);
+
+    // around the start of the polar night in Longyearbyen (2015-10-26),
+    // while the sun only just reaches the horizon
+    let mut max = Duration::zero();
+    for day in 0..8 {
+        let tm = at_utc(Timespec::new(1445515200 + day * 86400, 0));
+        let estimate = uncertainty(tm, 78.22, 15.65, &options);
+        assert!(estimate.sunrise <= Duration::hours(12) + estimate.noon, "{:?}", estimate);
+        max = cmp::max(max, estimate.sunrise);
+    }
+    assert!(max > Duration::minutes(30), "{:?}", max);
+
+    // with the small budget of the refined calculation the band only widens
+    // to minutes, when the noon altitude is within it
+    let (angle, _) = error_budget(&refined);
+    let depression = sunrise_depression(&refined);
+    let lat = LatitudeTrig::new(78.22);
+    let declin = lat.radians - consts::FRAC_PI_2 - depression + angle / 2.0;
+    let hours = event_uncertainty(&lat, declin, depression, angle, false);
+    assert!(hours * SECS_IN_HOUR > 120.0, "{}", hours);
+    assert!(Duration::seconds((hours * SECS_IN_HOUR) as i64) > precise.sunrise * 4);
+}
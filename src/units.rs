@@ -0,0 +1,242 @@
+//! The calculation with the quantities of `uom` (enabled with the `uom`
+//! feature), so that degrees and radians, or meters and feet, can not be
+//! mixed up at the boundary.
+//!
+//! Angles are `Angle`, durations `Time` and lengths `Length`; the functions
+//! convert them to the plain angle degrees, seconds and meters of the rest
+//! of the crate, so the results are the same:
+//!
+//! ```
+//! extern crate daylight;
+//! extern crate time;
+//! extern crate uom;
+//!
+//! use daylight::units;
+//! use uom::si::angle::{degree, radian};
+//! use uom::si::f64::{Angle, Length};
+//! use uom::si::length::foot;
+//! use uom::si::time::hour;
+//!
+//! # fn main() {
+//! let date = time::at_utc(time::Timespec::new(1427457600, 0));
+//! let (latitude, longitude) = (Angle::new::<degree>(52.22), Angle::new::<degree>(5.97));
+//! let daylight = units::calculate_daylight(date, latitude, longitude);
+//! assert!((daylight.daylength.get::<hour>() - 12.62).abs() < 0.01);
+//! assert!(daylight.declination.get::<radian>().abs() < 0.1);
+//!
+//! // the sunrise is earlier from the top of a tower of 300 feet
+//! let tower = units::calculate_daylight_at_elevation(date,
+//!                                                    latitude,
+//!                                                    longitude,
+//!                                                    Length::new::<foot>(300.0));
+//! assert!(tower.sunrise < daylight.sunrise);
+//! # }
+//! ```
+
+use time::{Duration, Timespec, Tm};
+use uom::si::angle::{degree, radian};
+use uom::si::f64::{Angle, Length, Time};
+use uom::si::length::meter;
+use uom::si::time::second;
+
+use angle::{Degrees, Radians};
+use {Daylight, DaylightOptions, LatitudeTrig, horizon, shadow, tm_to_timespec, to_degrees};
+
+impl From<Degrees> for Angle {
+    fn from(angle: Degrees) -> Angle {
+        Angle::new::<degree>(angle.0)
+    }
+}
+
+impl From<Radians> for Angle {
+    fn from(angle: Radians) -> Angle {
+        Angle::new::<radian>(angle.0)
+    }
+}
+
+impl From<Angle> for Degrees {
+    fn from(angle: Angle) -> Degrees {
+        Degrees(angle.get::<degree>())
+    }
+}
+
+/// Converts a duration to `Time`, to the nanosecond
+fn to_time(duration: Duration) -> Time {
+    let seconds = duration.num_seconds();
+    let nanoseconds = (duration - Duration::seconds(seconds)).num_nanoseconds().unwrap_or(0);
+    Time::new::<second>(seconds as f64 + nanoseconds as f64 / 1e9)
+}
+
+/// Result of the daylight calculation with quantities (see `Daylight`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DaylightUnits {
+    pub twilight_morning: Timespec,
+    pub sunrise: Timespec,
+    pub sunset: Timespec,
+    pub twilight_evening: Timespec,
+    pub noon: Timespec,
+    /// Declination of the sun
+    pub declination: Angle,
+    /// Duration of the day
+    pub daylength: Time,
+    /// Sun altitude
+    pub sun_altitude: Angle,
+}
+
+impl From<Daylight> for DaylightUnits {
+    fn from(daylight: Daylight) -> DaylightUnits {
+        DaylightUnits {
+            twilight_morning: daylight.twilight_morning,
+            sunrise: daylight.sunrise,
+            sunset: daylight.sunset,
+            twilight_evening: daylight.twilight_evening,
+            noon: daylight.noon,
+            declination: Angle::new::<degree>(daylight.declination),
+            daylength: to_time(daylight.daylength),
+            sun_altitude: Angle::new::<degree>(daylight.sun_altitude),
+        }
+    }
+}
+
+/// Position of the sun with quantities (see `SolarPosition`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolarPositionUnits {
+    /// Altitude of the centre of the sun above the horizon, without
+    /// refraction
+    pub altitude: Angle,
+    /// Azimuth, measured from the north through the east
+    pub azimuth: Angle,
+}
+
+/// Position of the tip of the shadow with quantities (see `ShadowPoint`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowPointUnits {
+    pub time: Timespec,
+    /// Distance to the east (negative for the west)
+    pub x: Length,
+    /// Distance to the north (negative for the south)
+    pub y: Length,
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date
+/// (see `calculate_daylight`)
+pub fn calculate_daylight(date: Tm, latitude: Angle, longitude: Angle) -> DaylightUnits {
+    calculate_daylight_with(date, latitude, longitude, &DaylightOptions::default())
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// using the given calculation options (see `calculate_daylight_with`)
+pub fn calculate_daylight_with(date: Tm,
+                               latitude: Angle,
+                               longitude: Angle,
+                               options: &DaylightOptions)
+                               -> DaylightUnits {
+    ::calculate_daylight_with(date, latitude.get::<degree>(), longitude.get::<degree>(), options)
+        .into()
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset at given date,
+/// for an observer at the elevation above the surroundings (see
+/// `DaylightOptions::elevation`)
+pub fn calculate_daylight_at_elevation(date: Tm,
+                                       latitude: Angle,
+                                       longitude: Angle,
+                                       elevation: Length)
+                                       -> DaylightUnits {
+    let options = DaylightOptions::default().elevation(elevation.get::<meter>());
+    calculate_daylight_with(date, latitude, longitude, &options)
+}
+
+/// Returns the position of the sun at the given time (see
+/// `Location::solar_position`)
+pub fn solar_position(time: Tm, latitude: Angle, longitude: Angle) -> SolarPositionUnits {
+    let (altitude, azimuth) = horizon::sun_position(tm_to_timespec(time),
+                                                    &LatitudeTrig::new(latitude.get::<degree>()),
+                                                    longitude.get::<degree>(),
+                                                    &DaylightOptions::default());
+    SolarPositionUnits {
+        altitude: Angle::new::<degree>(to_degrees(altitude)),
+        azimuth: Angle::new::<degree>(to_degrees(azimuth)),
+    }
+}
+
+/// Returns the tip of the shadow of a vertical gnomon of the height through
+/// the day (see `shadow_trajectory`); the step is rounded to the nanosecond
+pub fn shadow_trajectory(date: Tm,
+                         latitude: Angle,
+                         longitude: Angle,
+                         height: Length,
+                         step: Time)
+                         -> Vec<ShadowPointUnits> {
+    let step = Duration::nanoseconds((step.get::<second>() * 1e9).round() as i64);
+    shadow::shadow_trajectory(date,
+                              latitude.get::<degree>(),
+                              longitude.get::<degree>(),
+                              height.get::<meter>(),
+                              step)
+        .into_iter()
+        .map(|point| {
+            ShadowPointUnits {
+                time: point.time,
+                x: Length::new::<meter>(point.x),
+                y: Length::new::<meter>(point.y),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn units_equal_plain() {
+    use time::at_utc;
+    use uom::si::length::foot;
+    use uom::si::time::minute;
+
+    use {Coordinates, Location, SolarPosition, shadow_trajectory as plain_shadow_trajectory};
+
+    // the angles as the plain API receives them; the conversion of `uom` to
+    // and from radians may change the last digit of the degrees
+    let close = |a: Angle, b: f64| (a.get::<degree>() - b).abs() < 1e-12;
+    let tm = at_utc(Timespec::new(1427457600, 0));
+    for &(latitude, longitude) in &[(52.22, 5.97), (-33.86, 151.21), (78.22, 15.65)] {
+        let (lat, lon) = (Angle::new::<degree>(latitude), Angle::new::<degree>(longitude));
+        let (latitude, longitude) = (lat.get::<degree>(), lon.get::<degree>());
+
+        let plain = ::calculate_daylight(tm, latitude, longitude);
+        let typed = calculate_daylight(tm, lat, lon);
+        assert_eq!((typed.twilight_morning, typed.sunrise, typed.noon),
+                   (plain.twilight_morning, plain.sunrise, plain.noon));
+        assert_eq!((typed.sunset, typed.twilight_evening), (plain.sunset, plain.twilight_evening));
+        assert_eq!(typed.daylength.get::<second>(), plain.daylength.num_seconds() as f64);
+        assert!(close(typed.declination, plain.declination));
+        assert!(close(typed.sun_altitude, plain.sun_altitude));
+
+        // 100 feet are 30.48 meters
+        let options = DaylightOptions::default().elevation(30.48);
+        let plain = ::calculate_daylight_with(tm, latitude, longitude, &options);
+        let typed = calculate_daylight_at_elevation(tm, lat, lon, Length::new::<foot>(100.0));
+        assert_eq!((typed.sunrise, typed.sunset), (plain.sunrise, plain.sunset));
+
+        let location = Location::new("", Coordinates::try_new(latitude, longitude).unwrap());
+        let SolarPosition { altitude, azimuth } = location.solar_position(tm);
+        let typed = solar_position(tm, lat, lon);
+        assert!(close(typed.altitude, altitude) && close(typed.azimuth, azimuth));
+
+        let plain = plain_shadow_trajectory(tm, latitude, longitude, 2.0, Duration::minutes(30));
+        let typed = shadow_trajectory(tm,
+                                      lat,
+                                      lon,
+                                      Length::new::<meter>(2.0),
+                                      Time::new::<minute>(30.0));
+        assert_eq!(typed.len(), plain.len());
+        for (typed, plain) in typed.iter().zip(plain.iter()) {
+            assert_eq!(typed.time, plain.time);
+            assert_eq!((typed.x.get::<meter>(), typed.y.get::<meter>()), (plain.x, plain.y));
+        }
+    }
+
+    // the angle types of the crate convert both ways
+    assert!(close(Angle::from(Degrees(23.44)), 23.44));
+    assert!((Angle::from(Radians(1.0)).get::<radian>() - 1.0).abs() < 1e-15);
+    assert!((Degrees::from(Angle::new::<radian>(::core::f64::consts::PI)).0 - 180.0).abs() <
+            1e-12);
+}
@@ -0,0 +1,132 @@
+//! JavaScript interface to the calculation (enabled with the `wasm`
+//! feature), built like:
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm \
+//!     --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/daylight.wasm
+//! ```
+//!
+//! and used like:
+//!
+//! ```text
+//! import init, { calculateDaylight, solarPosition } from "./pkg/daylight.js";
+//! await init();
+//! const daylight = calculateDaylight(Date.now(), 52.22, 5.97);
+//! console.log(new Date(daylight.sunrise));
+//! ```
+//!
+//! Instants are milliseconds since 1970-01-01 00:00 UTC, like `Date.now()`;
+//! the angles are degrees. Invalid input throws an `Error`.
+//!
+//! On `wasm32-unknown-unknown` there is no libc, and `time` can not tell the
+//! current time; the instants are therefore only converted with the integer
+//! arithmetic of the calculation itself, never through `Tm` or `time::now`.
+//!
+//! Bundle size: the calculation itself is a small amount of floating point
+//! code; most of the module is the formatting and panic machinery of `std`
+//! (for the error messages) and the `wasm-bindgen` glue. Build for release,
+//! run `wasm-opt -Oz` on the result, and consider `lto = true` and
+//! `opt-level = "z"` in the profile; `twiggy top pkg/daylight_bg.wasm` shows
+//! what remains.
+
+use js_sys::{Object, Reflect};
+use time::Timespec;
+use wasm_bindgen::prelude::*;
+
+use {Daylight, DaylightError, DaylightOptions, EventKind, LatitudeTrig, daylight_at, horizon,
+     to_degrees};
+
+/// Milliseconds beyond which the instant is outside of the supported years
+/// -9999 to 9999
+const LIMIT_MILLIS: f64 = 10_000.0 * 366.0 * 86_400_000.0;
+
+/// Throws the error as a JavaScript `Error`
+fn throw(error: DaylightError) -> ! {
+    wasm_bindgen::throw_str(&error.to_string())
+}
+
+/// Returns the instant of the milliseconds, after validating the input
+fn instant(unix_millis: f64, lat: f64, lon: f64) -> Timespec {
+    if !(-90.0..=90.0).contains(&lat) {
+        throw(DaylightError::LatitudeOutOfRange(lat));
+    }
+    if !lon.is_finite() {
+        throw(DaylightError::LongitudeNotFinite(lon));
+    }
+    if !(-LIMIT_MILLIS..=LIMIT_MILLIS).contains(&unix_millis) {
+        throw(DaylightError::TimeOutOfRange);
+    }
+    let secs = (unix_millis / 1000.0).floor();
+    let nsecs = ((unix_millis - secs * 1000.0) * 1_000_000.0) as i32;
+    Timespec::new(secs as i64, nsecs.min(999_999_999))
+}
+
+/// Returns the instant in milliseconds, like `Date.getTime()`
+fn millis(ts: Timespec) -> f64 {
+    ts.sec as f64 * 1000.0 + ts.nsec as f64 / 1_000_000.0
+}
+
+fn set(object: &Object, key: &str, value: JsValue) {
+    Reflect::set(object, &JsValue::from_str(key), &value).unwrap_throw();
+}
+
+fn to_object(daylight: &Daylight) -> Object {
+    let object = Object::new();
+    for &(key, kind) in &[("twilightMorning", EventKind::TwilightMorning),
+                          ("sunrise", EventKind::Sunrise),
+                          ("noon", EventKind::Noon),
+                          ("sunset", EventKind::Sunset),
+                          ("twilightEvening", EventKind::TwilightEvening)] {
+        set(&object, key, JsValue::NULL);
+        if let Some(time) = daylight.event_time(kind) {
+            set(&object, key, JsValue::from_f64(millis(time)));
+        }
+    }
+    let polar = if daylight.is_polar_day() {
+        JsValue::from_str("day")
+    } else if daylight.is_polar_night() {
+        JsValue::from_str("night")
+    } else {
+        JsValue::NULL
+    };
+    set(&object, "polar", polar);
+    let daylength = daylight.daylength.num_milliseconds() as f64;
+    set(&object, "daylength", JsValue::from_f64(daylength));
+    set(&object, "declination", JsValue::from_f64(daylight.declination));
+    set(&object, "sunAltitude", JsValue::from_f64(daylight.sun_altitude));
+    object
+}
+
+/// Calculates civil twilight (am/pm) and sunrise and sunset at the instant
+/// (see `calculate_daylight`), returned as a plain object:
+///
+/// ```text
+/// { twilightMorning, sunrise, noon, sunset, twilightEvening,
+///   polar, daylength, declination, sunAltitude }
+/// ```
+///
+/// The events are milliseconds since the unix epoch, or `null` when they do
+/// not occur (see `Daylight::events`); `polar` is `"day"`, `"night"` or
+/// `null` (see `Daylight::to_json`). `daylength` is in milliseconds,
+/// `declination` and `sunAltitude` in angle degrees.
+#[wasm_bindgen(js_name = calculateDaylight)]
+pub fn calculate_daylight(unix_millis: f64, lat: f64, lon: f64) -> JsValue {
+    let ts = instant(unix_millis, lat, lon);
+    let daylight = daylight_at(ts, &LatitudeTrig::new(lat), lon, &DaylightOptions::default());
+    to_object(&daylight).into()
+}
+
+/// Returns the position of the sun at the instant (see
+/// `Location::solar_position`) as `{ altitude, azimuth }` in angle degrees
+#[wasm_bindgen(js_name = solarPosition)]
+pub fn solar_position(unix_millis: f64, lat: f64, lon: f64) -> JsValue {
+    let ts = instant(unix_millis, lat, lon);
+    let (altitude, azimuth) =
+        horizon::sun_position(ts, &LatitudeTrig::new(lat), lon, &DaylightOptions::default());
+    let object = Object::new();
+    set(&object, "altitude", JsValue::from_f64(to_degrees(altitude)));
+    set(&object, "azimuth", JsValue::from_f64(to_degrees(azimuth)));
+    object.into()
+}
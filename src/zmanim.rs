@@ -0,0 +1,147 @@
+//! Proportional (seasonal) hours of Jewish law: the day from sunrise to
+//! sunset, or from dawn to nightfall, divided into twelve equal hours, which
+//! are longer in summer than in winter.
+
+use time::{Duration, Timespec};
+
+use {Daylight, NSECS_IN_SEC};
+
+/// Start and end of the day divided into the proportional hours
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ZmanimBasis {
+    /// From sunrise to sunset (the Gra; default)
+    #[default]
+    SunriseSunset,
+    /// From the given minutes before sunrise to the minutes after sunset,
+    /// like 72 (the Magen Avraham)
+    FixedMinutes(i64),
+    /// From the start of the morning twilight to the end of the evening
+    /// twilight of the `Daylight`, at its twilight depression (like 16.1
+    /// degrees, see `DaylightOptions::twilight_angle`)
+    Twilight,
+}
+
+/// The day divided into twelve proportional hours (see `proportional_hours`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProportionalHours {
+    start: Timespec,
+    end: Timespec,
+}
+
+impl ProportionalHours {
+    /// Divides the day from `start` to `end` into the hours
+    pub fn new(start: Timespec, end: Timespec) -> ProportionalHours {
+        ProportionalHours { start, end }
+    }
+
+    /// Returns the start of the first hour
+    pub fn get_start(&self) -> Timespec {
+        self.start
+    }
+
+    /// Returns the end of the twelfth hour
+    pub fn get_end(&self) -> Timespec {
+        self.end
+    }
+
+    /// Returns the length of one proportional hour (sha'ah zmanit)
+    pub fn hour_length(&self) -> Duration {
+        (self.end - self.start) / 12
+    }
+
+    /// Returns the moment after the (fractional) number of hours since the
+    /// start of the day, like `10.75` for plag hamincha
+    pub fn at_hour(&self, hours: f64) -> Timespec {
+        let nanoseconds = (self.end - self.start).num_nanoseconds().expect("day out of range");
+        let offset = (nanoseconds as f64 * hours / 12.0 / NSECS_IN_SEC).round();
+        self.start + Duration::seconds(offset as i64)
+    }
+
+    /// Latest time for the morning Shema, after 3 hours
+    pub fn sof_zman_shma(&self) -> Timespec {
+        self.at_hour(3.0)
+    }
+
+    /// Midday, after 6 hours
+    pub fn chatzot(&self) -> Timespec {
+        self.at_hour(6.0)
+    }
+
+    /// Earliest time for the afternoon prayer, after 6.5 hours
+    pub fn mincha_gedola(&self) -> Timespec {
+        self.at_hour(6.5)
+    }
+
+    /// Plag hamincha, after 10.75 hours
+    pub fn plag_hamincha(&self) -> Timespec {
+        self.at_hour(10.75)
+    }
+}
+
+/// Divides the day from sunrise to sunset into the proportional hours;
+/// during the polar day and night, the hours follow the sunrise and sunset
+/// of `Daylight`
+pub fn proportional_hours(daylight: &Daylight) -> ProportionalHours {
+    proportional_hours_with(daylight, ZmanimBasis::SunriseSunset)
+}
+
+/// Divides the day on the given basis into the proportional hours
+pub fn proportional_hours_with(daylight: &Daylight, basis: ZmanimBasis) -> ProportionalHours {
+    match basis {
+        ZmanimBasis::SunriseSunset => ProportionalHours::new(daylight.sunrise, daylight.sunset),
+        ZmanimBasis::FixedMinutes(minutes) => {
+            ProportionalHours::new(daylight.sunrise - Duration::minutes(minutes),
+                                   daylight.sunset + Duration::minutes(minutes))
+        }
+        ZmanimBasis::Twilight => {
+            ProportionalHours::new(daylight.twilight_morning, daylight.twilight_evening)
+        }
+    }
+}
+
+#[test]
+fn proportional_hours_seasons() {
+    use time;
+
+    use {DaylightOptions, calculate_daylight, calculate_daylight_with};
+
+    // Apeldoorn, around the solstices
+    let (lat, long) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+    let summer = calculate_daylight(time::at_utc(Timespec::new(1434888000, 0)), lat, long);
+    let winter = calculate_daylight(time::at_utc(Timespec::new(1450699200, 0)), lat, long);
+    let (summer_hours, winter_hours) = (proportional_hours(&summer), proportional_hours(&winter));
+    assert_eq!(summer_hours.hour_length(), summer.daylength / 12);
+    // an hour of about 83 minutes in summer and 39 in winter, in the ratio of
+    // the day lengths
+    let ratio = summer_hours.hour_length().num_milliseconds() as f64 /
+                winter_hours.hour_length().num_milliseconds() as f64;
+    let expected = summer.daylength.num_milliseconds() as f64 /
+                   winter.daylength.num_milliseconds() as f64;
+    assert!((ratio - expected).abs() < 1e-6);
+    assert!(ratio > 2.0 && ratio < 2.3, "{}", ratio);
+
+    // the derived times, with midday at solar noon
+    assert_eq!(summer_hours.at_hour(0.0), summer.sunrise);
+    assert_eq!(summer_hours.at_hour(12.0), summer.sunset);
+    assert!((summer_hours.chatzot() - summer.noon).num_seconds().abs() <= 1);
+    let hour = summer_hours.hour_length();
+    let within_second = |difference: Duration| difference.num_seconds().abs() <= 1;
+    assert!(within_second(summer_hours.sof_zman_shma() - (summer.sunrise + hour * 3)));
+    assert!(within_second(summer_hours.mincha_gedola() - summer_hours.chatzot() - hour / 2));
+    assert!(within_second(summer.sunset - summer_hours.plag_hamincha() - hour * 5 / 4));
+
+    // from dawn to nightfall, by minutes and by the depression
+    let fixed = proportional_hours_with(&winter, ZmanimBasis::FixedMinutes(72));
+    assert_eq!(fixed.get_start(), winter.sunrise - Duration::minutes(72));
+    assert_eq!(fixed.hour_length(), (winter.daylength + Duration::minutes(144)) / 12);
+    let options = DaylightOptions::default().twilight_angle(16.1);
+    let dawn = calculate_daylight_with(time::at_utc(Timespec::new(1450699200, 0)),
+                                       lat,
+                                       long,
+                                       &options);
+    let twilight = proportional_hours_with(&dawn, ZmanimBasis::Twilight);
+    assert_eq!((twilight.get_start(), twilight.get_end()),
+               (dawn.twilight_morning, dawn.twilight_evening));
+    assert!(twilight.hour_length() > fixed.hour_length());
+}
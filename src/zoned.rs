@@ -0,0 +1,188 @@
+//! Events of a calculated day in a time zone of the IANA database, with the
+//! offset in effect at each event (enabled with the `tz` feature).
+
+use chrono::{self, DateTime, NaiveDate, Offset, TimeZone};
+use chrono_tz::Tz;
+use time::{self, Timespec, Tm};
+
+use error::{self, MAX_RANGE_DAYS};
+use {Daylight, DaylightChrono, DaylightError, calculate_daylight_local_with_offset};
+
+/// Result of the daylight calculation in a time zone (see `Daylight`)
+///
+/// Each event is an instant with the offset of the zone at that instant, so
+/// on the day of a daylight saving time transition the events before and
+/// after it have another offset; the events are instants and never fall in
+/// the skipped hour of the clock.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZonedDaylight {
+    pub twilight_morning: DateTime<Tz>,
+    pub sunrise: DateTime<Tz>,
+    pub sunset: DateTime<Tz>,
+    pub twilight_evening: DateTime<Tz>,
+    pub noon: DateTime<Tz>,
+    /// Declination of the sun in angle degrees
+    pub declination: f64,
+    /// Duration of the day
+    pub daylength: chrono::Duration,
+    /// Sun altitude in angle degrees
+    pub sun_altitude: f64,
+}
+
+impl Daylight {
+    /// Returns the events in the time zone (e.g. `chrono_tz::Europe::Amsterdam`)
+    pub fn in_zone(&self, zone: Tz) -> ZonedDaylight {
+        let daylight = DaylightChrono::from(*self);
+        ZonedDaylight {
+            twilight_morning: daylight.twilight_morning.with_timezone(&zone),
+            sunrise: daylight.sunrise.with_timezone(&zone),
+            sunset: daylight.sunset.with_timezone(&zone),
+            twilight_evening: daylight.twilight_evening.with_timezone(&zone),
+            noon: daylight.noon.with_timezone(&zone),
+            declination: daylight.declination,
+            daylength: daylight.daylength,
+            sun_altitude: daylight.sun_altitude,
+        }
+    }
+}
+
+/// Returns the date at midnight UTC, like the dates of
+/// `calculate_daylight_local`
+fn to_tm(date: NaiveDate) -> Tm {
+    let days = date.signed_duration_since(NaiveDate::default()).num_days();
+    time::at_utc(Timespec::new(days * 86_400, 0))
+}
+
+/// Offset of the zone in seconds at 12:00 on the clock of the date (or at
+/// 12:00 UTC, should that time be skipped)
+fn noon_offset(date: NaiveDate, zone: Tz) -> i32 {
+    let noon = date.and_hms_opt(12, 0, 0).unwrap();
+    let offset = match zone.from_local_datetime(&noon).earliest() {
+        Some(local) => local.offset().fix(),
+        None => zone.offset_from_utc_datetime(&noon).fix(),
+    };
+    offset.local_minus_utc()
+}
+
+/// Calculate civil twilight (am/pm) and sunrise and sunset of the calendar
+/// date in the time zone (see `calculate_daylight_local_with_offset`, with
+/// the offset of the zone at noon of the date)
+pub fn calculate_daylight_zoned(date: NaiveDate,
+                                latitude: f64,
+                                longitude: f64,
+                                zone: Tz)
+                                -> ZonedDaylight {
+    calculate_daylight_local_with_offset(to_tm(date),
+                                         latitude,
+                                         longitude,
+                                         noon_offset(date, zone))
+        .in_zone(zone)
+}
+
+/// Calculate the daylight of `days` consecutive calendar dates in the time
+/// zone from the start date, each paired with its date (see
+/// `calculate_daylight_zoned`); after validating the date and location, and
+/// that `days` does not exceed 40000
+pub fn calculate_daylight_zoned_range(start: NaiveDate,
+                                      days: u32,
+                                      latitude: f64,
+                                      longitude: f64,
+                                      zone: Tz)
+                                      -> Result<Vec<(NaiveDate, ZonedDaylight)>, DaylightError> {
+    if days > MAX_RANGE_DAYS {
+        return Err(DaylightError::TooManyDays(days));
+    }
+    error::validate(&to_tm(start), latitude, longitude)?;
+    Ok(start.iter_days()
+        .take(days as usize)
+        .map(|date| (date, calculate_daylight_zoned(date, latitude, longitude, zone)))
+        .collect())
+}
+
+#[test]
+fn zoned_across_transitions() {
+    use chrono::{Datelike, Timelike};
+    use chrono_tz::Australia::Lord_Howe;
+    use chrono_tz::Europe::Amsterdam;
+
+    let offset = |time: DateTime<Tz>| time.offset().fix().local_minus_utc();
+    let (latitude, longitude) = (52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0);
+
+    // Apeldoorn, 2015-03-27: the events of the fixture in CET
+    let tm = time::at_utc(Timespec::new(1427457600, 0));
+    let daylight = ::calculate_daylight(tm, latitude, longitude).in_zone(Amsterdam);
+    assert_eq!(daylight.sunrise.to_rfc3339(), "2015-03-27T06:22:47+01:00");
+    assert_eq!(daylight.sunset.to_rfc3339(), "2015-03-27T19:00:07+01:00");
+
+    // the clock goes forward on 2024-03-31 at 02:00 and back on 2024-10-27
+    // at 03:00, before the morning twilight
+    let march = calculate_daylight_zoned_range(NaiveDate::from_ymd_opt(2024, 3, 29).unwrap(),
+                                               5,
+                                               latitude,
+                                               longitude,
+                                               Amsterdam)
+        .unwrap();
+    let offsets: Vec<i32> = march.iter().map(|&(_, day)| offset(day.sunrise)).collect();
+    assert_eq!(offsets, [3600, 3600, 7200, 7200, 7200]);
+    let (before, after) = (march[1].1, march[2].1);
+    assert_eq!(before.sunrise.to_rfc3339(), "2024-03-30T06:13:59+01:00");
+    assert_eq!(after.sunrise.to_rfc3339(), "2024-03-31T07:11:45+02:00");
+    assert!(after.sunrise.hour() == 7 && after.sunset.hour() == 20);
+
+    let october = calculate_daylight_zoned_range(NaiveDate::from_ymd_opt(2024, 10, 25).unwrap(),
+                                                 5,
+                                                 latitude,
+                                                 longitude,
+                                                 Amsterdam)
+        .unwrap();
+    let offsets: Vec<i32> = october.iter().map(|&(_, day)| offset(day.sunset)).collect();
+    assert_eq!(offsets, [7200, 7200, 3600, 3600, 3600]);
+    assert_eq!(october[2].1.noon.hour(), 12);
+    assert_eq!(october[1].1.noon.hour(), 13);
+
+    // every event of a year on its own local date; the calculation equals
+    // the one with the fixed offset of the noon
+    let year = calculate_daylight_zoned_range(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                                              366,
+                                              latitude,
+                                              longitude,
+                                              Amsterdam)
+        .unwrap();
+    for &(date, day) in &year {
+        for &event in &[day.twilight_morning, day.sunrise, day.noon, day.sunset,
+                        day.twilight_evening] {
+            assert_eq!(event.date_naive(), date);
+        }
+        let expected = calculate_daylight_local_with_offset(to_tm(date),
+                                                            latitude,
+                                                            longitude,
+                                                            offset(day.noon));
+        assert_eq!(day, expected.in_zone(Amsterdam));
+    }
+    let summer = year.iter().filter(|&&(_, day)| offset(day.noon) == 7200).count();
+    assert_eq!(summer, 210);
+
+    // Lord Howe Island moves its clock by 30 minutes, back on 2024-04-07 and
+    // forward on 2024-10-06, both at 02:00
+    let (latitude, longitude) = (-31.55, 159.08);
+    let april = calculate_daylight_zoned_range(NaiveDate::from_ymd_opt(2024, 4, 6).unwrap(),
+                                               2,
+                                               latitude,
+                                               longitude,
+                                               Lord_Howe)
+        .unwrap();
+    assert_eq!((offset(april[0].1.sunrise), offset(april[1].1.sunrise)),
+               (11 * 3600, 10 * 3600 + 1800));
+    assert!(april[1].1.sunrise.to_rfc3339().ends_with("+10:30"));
+    let october = calculate_daylight_zoned(NaiveDate::from_ymd_opt(2024, 10, 6).unwrap(),
+                                           latitude,
+                                           longitude,
+                                           Lord_Howe);
+    assert!(october.sunrise.to_rfc3339().ends_with("+11:00"));
+    assert_eq!(october.sunrise.day(), 6);
+
+    assert_eq!(calculate_daylight_zoned_range(NaiveDate::default(), 40_001, 0.0, 0.0, Amsterdam),
+               Err(DaylightError::TooManyDays(40_001)));
+    assert_eq!(calculate_daylight_zoned_range(NaiveDate::default(), 1, 91.0, 0.0, Amsterdam),
+               Err(DaylightError::LatitudeOutOfRange(91.0)));
+}
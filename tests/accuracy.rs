@@ -0,0 +1,201 @@
+//! Compares the calculated events with the published tables of the US Naval
+//! Observatory, listed in `tests/data/usno_reference.csv`.
+#![cfg(feature = "std")]
+
+extern crate daylight;
+extern crate time;
+
+use daylight::{calculate_daylight, calculate_daylight_with, Algorithm, Daylight, DaylightOptions,
+               EventKind};
+use time::{at_utc, strptime, Timespec};
+
+const REFERENCE: &str = include_str!("data/usno_reference.csv");
+
+/// Maximum deviation from the tables in seconds: two minutes, of which the
+/// tables themselves round to the minute
+const TOLERANCE: i64 = 120;
+/// Known deviation of the default algorithm from the tables in seconds.
+/// Unlike the refined calculation, it approximates the hour angle, which
+/// leaves the civil twilight up to 26 minutes off near the equator, and it
+/// takes the position of the sun at one instant for the whole day, which
+/// moves the events by up to 18 minutes close to the polar circles, where
+/// the sun crosses the horizon at a shallow angle (see
+/// `calculate_daylight_with_uncertainty`).
+const DEFAULT_DEVIATION: i64 = 27 * 60;
+
+/// The events of a row: sunrise and sunset, or the civil twilight
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Events {
+    Sun,
+    Civil,
+}
+
+impl Events {
+    fn kinds(self) -> [EventKind; 2] {
+        match self {
+            Events::Sun => [EventKind::Sunrise, EventKind::Sunset],
+            Events::Civil => [EventKind::TwilightMorning, EventKind::TwilightEvening],
+        }
+    }
+
+    fn names(self) -> [&'static str; 2] {
+        match self {
+            Events::Sun => ["sunrise", "sunset"],
+            Events::Civil => ["civil dawn", "civil dusk"],
+        }
+    }
+}
+
+struct Row {
+    line: usize,
+    location: String,
+    date: Timespec,
+    latitude: f64,
+    longitude: f64,
+    kind: Events,
+    events: [Option<Timespec>; 2],
+}
+
+fn parse_time(field: &str, line: usize) -> Option<Timespec> {
+    if field == "-" {
+        return None;
+    }
+    let tm = strptime(field, "%Y-%m-%dT%H:%M:%SZ")
+        .unwrap_or_else(|e| panic!("line {}: invalid time {:?}: {}", line, field, e));
+    Some(tm.to_timespec())
+}
+
+fn parse_number<T: std::str::FromStr>(field: &str, line: usize) -> T {
+    field
+        .parse()
+        .unwrap_or_else(|_| panic!("line {}: invalid number {:?}", line, field))
+}
+
+fn reference_rows() -> Vec<Row> {
+    let mut rows = Vec::new();
+    for (index, text) in REFERENCE.lines().enumerate() {
+        let line = index + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = text.split(',').collect();
+        assert_eq!(fields.len(), 7, "line {}: expected 7 fields", line);
+        let date = strptime(fields[0], "%Y-%m-%d")
+            .unwrap_or_else(|e| panic!("line {}: invalid date {:?}: {}", line, fields[0], e));
+        let longitude = parse_number::<f64>(fields[2], line);
+        let kind = match fields[3] {
+            "sun" => Events::Sun,
+            "civil" => Events::Civil,
+            other => panic!("line {}: invalid events {:?}", line, other),
+        };
+        rows.push(Row {
+            line,
+            location: fields[6].to_string(),
+            // evaluate at the mean solar noon of the reference date at the
+            // location, 4 minutes before 12:00 UTC per degree east
+            date: Timespec::new(date.to_timespec().sec + 12 * 3600 - (longitude * 240.0) as i64,
+                                0),
+            latitude: parse_number(fields[1], line),
+            longitude,
+            kind,
+            events: [parse_time(fields[4], line), parse_time(fields[5], line)],
+        });
+    }
+    rows
+}
+
+/// Returns the events of the row that occur on the calculated day
+fn events(row: &Row, daylight: &Daylight) -> [Option<Timespec>; 2] {
+    let event = |kind| daylight.events().find(|event| event.kind == kind).map(|event| event.time);
+    let kinds = row.kind.kinds();
+    [event(kinds[0]), event(kinds[1])]
+}
+
+/// Checks the events of the row, returning a description of each event that
+/// exceeds the tolerance or of which the occurrence differs.
+fn check_row(row: &Row, daylight: &Daylight, tolerance: i64) -> Vec<String> {
+    let actual = events(row, daylight);
+    let mut failures = Vec::new();
+    for (i, expected) in row.events.iter().enumerate() {
+        let context = format!("line {} ({}, {}): {}",
+                              row.line,
+                              row.location,
+                              at_utc(row.date).strftime("%Y-%m-%d").unwrap(),
+                              row.kind.names()[i]);
+        match (*expected, actual[i]) {
+            (Some(expected), Some(actual)) => {
+                let delta = actual.sec - expected.sec;
+                if delta.abs() > tolerance {
+                    failures.push(format!("{} expected {} got {} (off by {} s, tolerance {} s)",
+                                          context,
+                                          expected.sec,
+                                          actual.sec,
+                                          delta,
+                                          tolerance));
+                }
+            }
+            (None, None) => {}
+            (expected, actual) => {
+                failures.push(format!("{} expected {:?} got {:?}", context, expected, actual))
+            }
+        }
+    }
+    failures
+}
+
+#[test]
+fn reference_table_is_complete() {
+    let rows = reference_rows();
+    assert!(rows.len() >= 100, "only {} reference rows", rows.len());
+    for &kind in &[Events::Sun, Events::Civil] {
+        assert!(rows.iter().any(|row| row.kind == kind && row.events[0].is_some()));
+        assert!(rows.iter().any(|row| row.kind == kind && row.events[0].is_none()));
+    }
+    for row in &rows {
+        assert!(row.events[0].is_some() == row.events[1].is_some(), "line {}", row.line);
+    }
+}
+
+/// Checks every row with the calculation, asserting the tolerance
+fn check_reference(options: &DaylightOptions, tolerance: i64) {
+    let mut failures = Vec::new();
+    for row in reference_rows() {
+        let daylight = calculate_daylight_with(at_utc(row.date),
+                                               row.latitude,
+                                               row.longitude,
+                                               options);
+        failures.extend(check_row(&row, &daylight, tolerance));
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn refined_algorithm_matches_reference() {
+    check_reference(&DaylightOptions::default().refined(true), TOLERANCE);
+}
+
+#[test]
+fn noaa_algorithm_matches_reference() {
+    check_reference(&DaylightOptions::default().algorithm(Algorithm::Noaa), TOLERANCE);
+}
+
+/// The default algorithm does not meet `TOLERANCE`; this bounds its known
+/// deviation instead, and fails once the deviation is gone, so that the
+/// default algorithm is then checked like the others.
+#[test]
+fn default_algorithm_known_deviation() {
+    check_reference(&DaylightOptions::default(), DEFAULT_DEVIATION);
+
+    let mut worst = 0;
+    for row in reference_rows() {
+        let daylight = calculate_daylight(at_utc(row.date), row.latitude, row.longitude);
+        for (expected, actual) in row.events.iter().zip(events(&row, &daylight).iter()) {
+            if let (&Some(expected), &Some(actual)) = (expected, actual) {
+                worst = worst.max((actual.sec - expected.sec).abs());
+            }
+        }
+    }
+    println!("largest deviation of the default algorithm: {} s", worst);
+    assert!(worst > TOLERANCE, "the default algorithm is within the tolerance");
+}
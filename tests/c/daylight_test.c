@@ -0,0 +1,31 @@
+/* Calls the C interface with the Apeldoorn fixture; see tests/ffi.rs */
+#include <math.h>
+#include <stdio.h>
+
+#include "daylight.h"
+
+#define CHECK(condition)                                            \
+    if (!(condition)) {                                             \
+        fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #condition); \
+        return 1;                                                   \
+    }
+
+int main(void) {
+    CDaylight daylight;
+
+    /* Apeldoorn, 2015-03-27 12:00 UTC */
+    CHECK(daylight_calculate(1427457600, 52.0 + 13.0 / 60.0, 5.0 + 58.0 / 60.0, &daylight) ==
+          DAYLIGHT_OK);
+    CHECK(daylight.twilight_morning == 1427432130);
+    CHECK(daylight.sunrise == 1427433767);
+    CHECK(daylight.noon == 1427456487);
+    CHECK(daylight.sunset == 1427479207);
+    CHECK(daylight.twilight_evening == 1427480844);
+    CHECK(daylight.daylength == 45440);
+    CHECK(daylight.declination > 2.7 && daylight.declination < 2.8);
+
+    CHECK(daylight_calculate(1427457600, 91.0, 0.0, &daylight) == DAYLIGHT_ERROR_LATITUDE);
+    CHECK(daylight_calculate(1427457600, 0.0, NAN, &daylight) == DAYLIGHT_ERROR_LONGITUDE);
+    CHECK(daylight_calculate(1427457600, 0.0, 0.0, NULL) == DAYLIGHT_ERROR_NULL);
+    return 0;
+}
@@ -0,0 +1,79 @@
+//! Runs the `daylight` binary (enabled with the `cli` feature) like a user
+//! would, checking its output and exit status.
+#![cfg(feature = "cli")]
+
+extern crate serde_json;
+
+use std::process::{Command, Output};
+
+fn daylight(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_daylight"))
+        .args(args)
+        .output()
+        .expect("failed to run the daylight binary")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn json_output() {
+    let output = daylight(&["--lat", "52.22", "--lon", "5.97", "--date", "2015-03-27",
+                            "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["sunrise"], "2015-03-27T05:22:46Z");
+
+    // a range of days is an array
+    let output = daylight(&["--coords", "52.22, 5.97", "--date", "2015-03-27", "--days", "3",
+                            "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    assert_eq!(json[2]["noon"].as_str().unwrap().get(..10), Some("2015-03-29"));
+
+    // past 2038
+    let output = daylight(&["--coords", "52.22, 5.97", "--date", "2100-12-31", "--days", "2",
+                            "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json[1]["noon"].as_str().unwrap().get(..10), Some("2101-01-01"));
+}
+
+#[test]
+fn table_output() {
+    let output = daylight(&["--coords", "52°13'N 5°58'E", "--date", "2024-03-01", "--days",
+                            "2", "--offset", "+01:00"]);
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", text);
+    assert_eq!(lines[0], "52°13'0\"N 5°58'0\"E");
+    assert!(lines[2].starts_with("2024-03-01  06:5"), "{}", text);
+    assert!(lines[3].starts_with("2024-03-02"), "{}", text);
+
+    // on the far side of the globe
+    let output = daylight(&["--lat", "-33.87", "--lon", "151.21", "--date", "2024-01-01",
+                            "--offset", "+11:00"]);
+    assert!(stdout(&output).lines().nth(2).unwrap().starts_with("2024-01-01  05:"));
+}
+
+#[test]
+fn invalid_input() {
+    for args in &[vec!["--lat", "91", "--lon", "5.97"],
+                  vec!["--lat", "52.22"],
+                  vec!["--coords", "somewhere"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--date", "2024-02-30"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--offset", "2h"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--offset", "+-1:00"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--offset", "--0:30"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--offset", "+1:000"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--format", "xml"],
+                  vec!["--lat", "52.22", "--lon", "5.97", "--days", "0"]] {
+        let output = daylight(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(!output.stderr.is_empty(), "{:?}", args);
+    }
+}
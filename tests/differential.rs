@@ -0,0 +1,300 @@
+//! Differential test of the calculation against an independent
+//! implementation, for a few hundred random dates and locations from a fixed
+//! seed, so every run checks the same cases; the largest differences are
+//! printed (with `--nocapture`).
+//!
+//! The independent reference is the `sunrise` crate: the sunrise equation
+//! with its own series for the position of the sun, compared with the
+//! refined and the default calculation. The NOAA algorithm
+//! (`Algorithm::Noaa`) is compared as well, but is not independent: only its
+//! series for the position of the sun is its own, while it shares the
+//! refinement of the events, the depression of the sunrise, the
+//! trigonometry of the latitude and the rounding with this crate. Another
+//! crate can be compared by implementing `Reference` for it.
+#![cfg(feature = "std")]
+
+extern crate chrono;
+extern crate daylight;
+extern crate sunrise;
+extern crate time;
+
+use chrono::NaiveDate;
+use daylight::{calculate_daylight_with, Algorithm, Daylight, DaylightOptions, EventKind};
+use sunrise::{Coordinates, SolarDay, SolarEvent};
+use time::{at_utc, Timespec};
+
+/// Number of random cases
+const CASES: usize = 500;
+/// Seed of the random cases
+const SEED: u64 = 0x5eed_da71_1947;
+/// 1950-01-01 and 2050-01-01 in seconds since 1970
+const RANGE_START: i64 = -631_152_000;
+const RANGE_END: i64 = 2_524_608_000;
+/// Agreement of sunrise, sunset and noon of the refined calculation in
+/// seconds, at most 2 minutes; against `sunrise` the differences grow
+/// towards 1950 and 2050 (to about 1.5 minutes below 60 degrees latitude),
+/// as its ecliptic longitude of the sun drifts from 2000 by about 1.4
+/// degrees per century: it advances the perihelion relative to the stars
+/// instead of the equinox
+const TOLERANCE: i64 = 120;
+/// Known deviation of the default calculation in seconds, which does not
+/// meet `TOLERANCE`: its approximation of the hour angle leaves out the
+/// refraction at the equator, which shortens the day there by about 3
+/// minutes at either end, and it takes the position of the sun at one
+/// instant for the whole day, which moves the events by minutes at high
+/// latitudes (see `calculate_daylight_with_uncertainty`). The same defect
+/// is bounded against the published tables by
+/// `default_algorithm_known_deviation` in `tests/accuracy.rs`.
+const DEFAULT_DEVIATION: i64 = 7 * 60;
+/// Latitude in degrees beyond which the tolerance is widened, as the sun
+/// crosses the horizon at a shallow angle
+const SHALLOW_LATITUDE: f64 = 60.0;
+/// Day length in seconds from the polar day or night below which a day is
+/// a polar transition day, on which the events are not compared
+const TRANSITION: i64 = 2 * 3600;
+/// Number of the largest differences logged
+const WORST: usize = 5;
+
+/// Small deterministic generator (splitmix64)
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[low, high)`
+    fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Sunrise, noon and sunset of a day in seconds since 1970; `None` for an
+/// event that does not occur or is not calculated by the reference
+#[derive(Debug)]
+struct Events {
+    sunrise: Option<i64>,
+    noon: Option<i64>,
+    sunset: Option<i64>,
+    daylength: i64,
+}
+
+/// Adapter from an implementation to the events compared
+trait Reference {
+    fn name(&self) -> &'static str;
+    fn events(&self, secs: i64, latitude: f64, longitude: f64) -> Events;
+}
+
+fn to_events(daylight: &Daylight) -> Events {
+    let event = |kind| {
+        daylight.events().find(|event| event.kind == kind).map(|event| event.time.sec)
+    };
+    Events {
+        sunrise: event(EventKind::Sunrise),
+        noon: Some(daylight.noon.sec),
+        sunset: event(EventKind::Sunset),
+        daylength: daylight.daylength.num_seconds(),
+    }
+}
+
+struct Noaa;
+
+impl Reference for Noaa {
+    fn name(&self) -> &'static str {
+        "NOAA"
+    }
+
+    fn events(&self, secs: i64, latitude: f64, longitude: f64) -> Events {
+        let options = DaylightOptions::default().algorithm(Algorithm::Noaa);
+        to_events(&calculate_daylight_with(at_utc(Timespec::new(secs, 0)),
+                                           latitude,
+                                           longitude,
+                                           &options))
+    }
+}
+
+/// The `sunrise` crate, for the UTC date of the instant (the date of which
+/// the events are calculated); it has no solar noon, but its sunrise and
+/// sunset lie symmetric around it
+struct Sunrise;
+
+/// Seconds since 1970 of 12:00 UTC of the date
+fn midday(date: NaiveDate) -> i64 {
+    date.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp()
+}
+
+impl Sunrise {
+    /// Calculates the event with `sunrise`, evaluating the sun again at the
+    /// moment of the event: the crate takes the position of the sun at the
+    /// mean solar noon for both events, which is shifted by moving the
+    /// location along with the sun (a day per 360 degrees of longitude)
+    fn event(date: NaiveDate, latitude: f64, longitude: f64, event: SolarEvent) -> Option<i64> {
+        let at = |date, longitude| {
+            let coordinates = Coordinates::new(latitude, longitude).unwrap();
+            SolarDay::new(coordinates, date).event_time(event).map(|time| time.timestamp())
+        };
+        let first = at(date, longitude)?;
+        let shift = (first - midday(date)) as f64 / 86400.0 + longitude / 360.0;
+        let (mut date, mut longitude) = (date, longitude - 360.0 * shift);
+        if longitude < -180.0 {
+            date = date.succ_opt().unwrap();
+            longitude += 360.0;
+        } else if longitude > 180.0 {
+            date = date.pred_opt().unwrap();
+            longitude -= 360.0;
+        }
+        at(date, longitude).map(|time| time - (86400.0 * shift).round() as i64)
+    }
+}
+
+impl Reference for Sunrise {
+    fn name(&self) -> &'static str {
+        "sunrise"
+    }
+
+    fn events(&self, secs: i64, latitude: f64, longitude: f64) -> Events {
+        let tm = at_utc(Timespec::new(secs, 0));
+        let date = NaiveDate::from_ymd_opt(tm.tm_year + 1900,
+                                           tm.tm_mon as u32 + 1,
+                                           tm.tm_mday as u32)
+            .unwrap();
+        let sunrise = Sunrise::event(date, latitude, longitude, SolarEvent::Sunrise);
+        let sunset = Sunrise::event(date, latitude, longitude, SolarEvent::Sunset);
+        Events {
+            sunrise,
+            noon: sunrise.and_then(|sunrise| sunset.map(|sunset| (sunrise + sunset) / 2)),
+            sunset,
+            // without a sunrise and sunset the day is skipped either way
+            daylength: sunrise.and_then(|sunrise| sunset.map(|sunset| sunset - sunrise))
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Returns whether the day is within `TRANSITION` of the polar day or night
+fn near_polar(events: &Events) -> bool {
+    events.daylength < TRANSITION || events.daylength > 86400 - TRANSITION
+}
+
+/// Tolerance in seconds at the latitude
+fn tolerance(latitude: f64, tolerance: i64) -> i64 {
+    if latitude.abs() > SHALLOW_LATITUDE {
+        2 * tolerance
+    } else {
+        tolerance
+    }
+}
+
+struct Difference {
+    seconds: i64,
+    description: String,
+}
+
+/// Compares the calculation with the options to the reference, returning
+/// the failures, every difference from large to small and the number of
+/// skipped cases
+fn compare(options: &DaylightOptions,
+           reference: &dyn Reference,
+           base: i64)
+           -> (Vec<String>, Vec<Difference>, usize) {
+    let mut random = Random(SEED);
+    let mut failures = Vec::new();
+    let mut differences = Vec::new();
+    let mut skipped = 0;
+    for _ in 0..CASES {
+        let secs = random.range(RANGE_START as f64, RANGE_END as f64) as i64;
+        let latitude = random.range(-70.0, 70.0);
+        let longitude = random.range(-180.0, 180.0);
+        let case = format!("{} at {:.3}, {:.3}",
+                           at_utc(Timespec::new(secs, 0)).strftime("%Y-%m-%d %H:%M").unwrap(),
+                           latitude,
+                           longitude);
+        let actual = to_events(&calculate_daylight_with(at_utc(Timespec::new(secs, 0)),
+                                                        latitude,
+                                                        longitude,
+                                                        options));
+        let expected = reference.events(secs, latitude, longitude);
+        if near_polar(&actual) || near_polar(&expected) {
+            // the day the sun first stays up or down depends on details of
+            // either implementation
+            skipped += 1;
+            continue;
+        }
+        let pairs = [("sunrise", actual.sunrise, expected.sunrise),
+                     ("noon", actual.noon, expected.noon),
+                     ("sunset", actual.sunset, expected.sunset)];
+        for &(name, actual, expected) in &pairs {
+            match (actual, expected) {
+                (Some(actual), Some(expected)) => {
+                    let seconds = actual - expected;
+                    let description = format!("{} {}: {} s", case, name, seconds);
+                    if seconds.abs() > tolerance(latitude, base) {
+                        failures.push(description.clone());
+                    }
+                    differences.push(Difference {
+                        seconds: seconds.abs(),
+                        description,
+                    });
+                }
+                (None, None) => {}
+                _ => {
+                    failures.push(format!("{} {}: {:?} against {:?} of {}",
+                                          case,
+                                          name,
+                                          actual,
+                                          expected,
+                                          reference.name()))
+                }
+            }
+        }
+    }
+    differences.sort_by_key(|difference| -difference.seconds);
+    (failures, differences, skipped)
+}
+
+/// Runs the comparison, printing the largest differences
+fn check(options: &DaylightOptions, reference: &dyn Reference, base: i64) {
+    let (failures, differences, skipped) = compare(options, reference, base);
+    println!("{} cases against {}, {} polar transition days skipped; largest differences:",
+             CASES,
+             reference.name(),
+             skipped);
+    for difference in differences.iter().take(WORST) {
+        println!("  {}", difference.description);
+    }
+    assert!(skipped < CASES / 10, "{} cases skipped", skipped);
+    assert!(differences.len() >= 2 * CASES, "{} events compared", differences.len());
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn refined_against_sunrise() {
+    check(&DaylightOptions::default().refined(true), &Sunrise, TOLERANCE);
+}
+
+#[test]
+fn refined_against_noaa() {
+    check(&DaylightOptions::default().refined(true), &Noaa, TOLERANCE);
+}
+
+#[test]
+fn default_against_sunrise() {
+    check(&DaylightOptions::default(), &Sunrise, DEFAULT_DEVIATION);
+}
+
+#[test]
+fn random_cases_are_deterministic() {
+    let (mut first, mut second) = (Random(SEED), Random(SEED));
+    for _ in 0..100 {
+        assert_eq!(first.next(), second.next());
+    }
+    let mut random = Random(SEED);
+    for _ in 0..1000 {
+        let value = random.range(-70.0, 70.0);
+        assert!((-70.0..70.0).contains(&value));
+    }
+}
@@ -0,0 +1,51 @@
+//! Compiles a C program against the generated header and the static library
+//! (enabled with the `ffi` feature) and runs it; needs a C compiler `cc`.
+#![cfg(feature = "ffi")]
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn header_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/daylight.h"));
+    let checked_in = include_str!("../include/daylight.h");
+    assert!(generated == checked_in,
+            "include/daylight.h is out of date; update it with \
+             `DAYLIGHT_UPDATE_HEADER=1 cargo build --features ffi`");
+}
+
+#[test]
+fn c_program() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // the test runs from target/<profile>/deps; the static library is built
+    // in a target directory of its own, as the build directory is locked
+    let exe = env::current_exe().unwrap();
+    let profile_dir = exe.parent().unwrap().parent().unwrap();
+    let target_dir = profile_dir.join("ffi");
+    let status = Command::new(env!("CARGO"))
+        .args(["rustc", "--lib", "--features", "ffi", "--crate-type", "staticlib"])
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "building the static library failed");
+    let library = target_dir.join("debug").join("libdaylight.a");
+
+    let program = profile_dir.join("daylight_c_test");
+    let status = Command::new("cc")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests").join("c").join("daylight_test.c"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling the C program failed");
+
+    let status = Command::new(&program).status().unwrap();
+    assert!(status.success(), "the C program failed");
+}
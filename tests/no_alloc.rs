@@ -0,0 +1,56 @@
+//! Checks that `calculate_daylight_range_into` does not allocate, with an
+//! allocator that counts the allocations of the current thread.
+#![cfg(feature = "std")]
+
+extern crate daylight;
+extern crate time;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use daylight::{calculate_daylight_range, calculate_daylight_range_into, Daylight};
+use time::{at_utc, Timespec};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+#[test]
+fn range_into_does_not_allocate() {
+    // 2024, a leap year, in Apeldoorn
+    let start = at_utc(Timespec::new(1704110400, 0));
+    let before = allocations();
+    let expected = calculate_daylight_range(start, 366, 52.22, 5.97).unwrap();
+    // the allocator does count, like the vector of the other variant
+    assert!(allocations() > before);
+    let mut year = [expected[0].1; 366];
+
+    let before = allocations();
+    let result = calculate_daylight_range_into(start, 52.22, 5.97, &mut year);
+    let after = allocations();
+    assert_eq!(result, Ok(()));
+    assert_eq!(after - before, 0, "allocations");
+
+    let days: Vec<Daylight> = expected.iter().map(|&(_, daylight)| daylight).collect();
+    assert_eq!(&year[..], &days[..]);
+}
@@ -0,0 +1,114 @@
+//! Physical invariants of the calculation, checked for random locations and
+//! dates between 1900 and 2100. On failure proptest shrinks the input to a
+//! minimal reproducing (seconds since 1970, latitude, longitude).
+#![cfg(feature = "std")]
+
+extern crate daylight;
+extern crate proptest;
+extern crate time;
+
+use daylight::{calculate_daylight, calculate_daylight_with, next_sunrise, Daylight, DaylightOptions,
+               Horizon};
+use proptest::prelude::*;
+use time::{at_utc, Duration, Timespec};
+
+/// 1900-01-01 and 2100-01-01 in seconds since 1970
+const RANGE_START: i64 = -2_208_988_800;
+const RANGE_END: i64 = 4_102_444_800;
+
+/// Maximum magnitude of the equation of time (plus some margin)
+const EQUATION_OF_TIME: i64 = 20 * 60;
+
+fn daylight(secs: i64, latitude: f64, longitude: f64, refined: bool) -> Daylight {
+    let options = DaylightOptions::default().refined(refined);
+    calculate_daylight_with(at_utc(Timespec::new(secs, 0)), latitude, longitude, &options)
+}
+
+proptest! {
+    #[test]
+    fn events_are_ordered(secs in RANGE_START..RANGE_END,
+                          latitude in -90.0..90.0f64,
+                          longitude in -180.0..180.0f64,
+                          refined in any::<bool>()) {
+        let d = daylight(secs, latitude, longitude, refined);
+        prop_assert!(d.twilight_morning <= d.sunrise, "{:?}", d);
+        prop_assert!(d.sunrise <= d.noon, "{:?}", d);
+        prop_assert!(d.noon <= d.sunset, "{:?}", d);
+        prop_assert!(d.sunset <= d.twilight_evening, "{:?}", d);
+    }
+
+    #[test]
+    fn daylength_within_a_day(secs in RANGE_START..RANGE_END,
+                              latitude in -90.0..90.0f64,
+                              longitude in -180.0..180.0f64,
+                              refined in any::<bool>()) {
+        let d = daylight(secs, latitude, longitude, refined);
+        prop_assert!(d.daylength >= Duration::zero(), "{:?}", d);
+        prop_assert!(d.daylength <= Duration::days(1), "{:?}", d);
+    }
+
+    #[test]
+    fn declination_within_obliquity(secs in RANGE_START..RANGE_END,
+                                    latitude in -90.0..90.0f64,
+                                    longitude in -180.0..180.0f64,
+                                    refined in any::<bool>()) {
+        let d = daylight(secs, latitude, longitude, refined);
+        prop_assert!(d.declination.abs() < 23.5, "{:?}", d);
+    }
+
+    #[test]
+    fn noon_near_mean_noon(secs in RANGE_START..RANGE_END,
+                           latitude in -90.0..90.0f64,
+                           longitude in -180.0..180.0f64,
+                           refined in any::<bool>()) {
+        let d = daylight(secs, latitude, longitude, refined);
+        // mean solar noon at the longitude on the UTC date of the input
+        let midnight = secs - secs.rem_euclid(86_400);
+        let mean_noon = midnight + ((12.0 - longitude / 15.0) * 3600.0) as i64;
+        prop_assert!((d.noon.sec - mean_noon).abs() < EQUATION_OF_TIME,
+                     "noon {} mean noon {}: {:?}", d.noon.sec, mean_noon, d);
+    }
+
+    #[test]
+    fn opposite_latitudes_complement(secs in RANGE_START..RANGE_END,
+                                     latitude in -65.0..65.0f64,
+                                     longitude in -180.0..180.0f64) {
+        // for the centre of the sun at the true horizon, the nights at one
+        // latitude are as long as the days at the opposite latitude
+        let options = DaylightOptions::default().horizon(Horizon::Geometric);
+        let tm = at_utc(Timespec::new(secs, 0));
+        let north = calculate_daylight_with(tm, latitude, longitude, &options);
+        let south = calculate_daylight_with(tm, -latitude, longitude, &options);
+        let sum = north.daylength + south.daylength;
+        prop_assert!((sum - Duration::days(1)).num_seconds().abs() <= 120,
+                     "{:?} {:?}", north, south);
+    }
+
+    #[test]
+    fn sunrise_continuous(secs in RANGE_START..RANGE_END,
+                          latitude in -60.0..60.0f64,
+                          longitude in -180.0..180.0f64) {
+        // outside the polar circles sunrise moves by minutes per day
+        let today = calculate_daylight(at_utc(Timespec::new(secs, 0)), latitude, longitude);
+        let tomorrow = calculate_daylight(at_utc(Timespec::new(secs + 86_400, 0)),
+                                          latitude,
+                                          longitude);
+        let shift = tomorrow.sunrise - today.sunrise - Duration::days(1);
+        prop_assert!(shift.num_minutes().abs() < 30, "{:?} {:?}", today, tomorrow);
+    }
+
+    #[test]
+    fn next_sunrise_a_day_later(secs in RANGE_START..RANGE_END,
+                                latitude in -60.0..60.0f64,
+                                longitude in -180.0..180.0f64) {
+        // the sunrise after the next sunrise is the one of the following day
+        let after = Timespec::new(secs, 0);
+        let sunrise = next_sunrise(after, latitude, longitude).unwrap();
+        let following = next_sunrise(sunrise, latitude, longitude).unwrap();
+        prop_assert!(sunrise > after, "{:?} {:?}", after, sunrise);
+        prop_assert!(sunrise - after < Duration::days(1) + Duration::minutes(30),
+                     "{:?} {:?}", after, sunrise);
+        prop_assert!((following - sunrise - Duration::days(1)).num_minutes().abs() < 30,
+                     "{:?} {:?}", sunrise, following);
+    }
+}
@@ -0,0 +1,46 @@
+//! Runs the JavaScript interface (enabled with the `wasm` feature) under
+//! wasm32, like `wasm-pack test --node -- --features wasm`.
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+extern crate daylight;
+extern crate js_sys;
+extern crate wasm_bindgen;
+extern crate wasm_bindgen_test;
+
+use daylight::wasm::{calculate_daylight, solar_position};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(object, &JsValue::from_str(key)).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn tokyo() {
+    // 2015-03-27 12:00 UTC, the golden values of the native tests
+    let daylight = calculate_daylight(1427457600000.0, 35.41, 139.41);
+    assert_eq!(get(&daylight, "twilightMorning").as_f64(), Some(1427401348000.0));
+    assert_eq!(get(&daylight, "sunrise").as_f64(), Some(1427402244000.0));
+    assert_eq!(get(&daylight, "noon").as_f64(), Some(1427424461000.0));
+    assert_eq!(get(&daylight, "sunset").as_f64(), Some(1427446677000.0));
+    assert_eq!(get(&daylight, "twilightEvening").as_f64(), Some(1427447573000.0));
+    assert_eq!(get(&daylight, "daylength").as_f64(), Some(44434000.0));
+    assert!(get(&daylight, "polar").is_null());
+    let declination = get(&daylight, "declination").as_f64().unwrap();
+    assert!(declination > 2.777311 && declination < 2.777313);
+
+    let position = solar_position(1427424461000.0, 35.41, 139.41);
+    let altitude = get(&position, "altitude").as_f64().unwrap();
+    assert!((altitude - 57.22).abs() < 0.01, "{}", altitude);
+    let azimuth = get(&position, "azimuth").as_f64().unwrap();
+    assert!((azimuth - 180.0).abs() < 1.0, "{}", azimuth);
+}
+
+#[wasm_bindgen_test]
+fn polar_night() {
+    // Longyearbyen, 2015-12-21
+    let daylight = calculate_daylight(1450699200000.0, 78.22, 15.65);
+    assert_eq!(get(&daylight, "polar").as_string(), Some("night".to_string()));
+    assert!(get(&daylight, "sunrise").is_null() && get(&daylight, "sunset").is_null());
+    assert!(get(&daylight, "noon").as_f64().is_some());
+}